@@ -10,6 +10,7 @@ pub fn configure(cfg: &mut web::ServiceConfig) {
         web::scope("/api")
             // Status
             .route("/status", web::get().to(status))
+            .route("/health", web::get().to(health))
             // Users
             .route("/users", web::get().to(list_users))
             .route("/users", web::post().to(create_user))
@@ -69,6 +70,11 @@ fn err_response(e: grounddb::GroundDbError) -> HttpResponse {
                 "error": e.to_string()
             }))
         }
+        grounddb::GroundDbError::Conflict { .. } => {
+            HttpResponse::Conflict().json(serde_json::json!({
+                "error": e.to_string()
+            }))
+        }
         grounddb::GroundDbError::ReferentialIntegrity(_) => {
             HttpResponse::Conflict().json(serde_json::json!({
                 "error": e.to_string()
@@ -93,6 +99,11 @@ async fn status(state: web::Data<AppState>) -> HttpResponse {
     }
 }
 
+async fn health(state: web::Data<AppState>) -> HttpResponse {
+    let store = state.store.lock().unwrap();
+    ok_json(store.health())
+}
+
 // ── Generic CRUD handlers ───────────────────────────────────────────
 
 fn handle_list(state: &AppState, collection: &str) -> HttpResponse {
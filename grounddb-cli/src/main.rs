@@ -1,7 +1,7 @@
 use clap::{Parser, Subcommand, ValueEnum};
-use grounddb::Store;
+use grounddb::{Store, StoreOptions};
 use std::collections::HashMap;
-use std::process;
+use std::process::{self, ExitCode};
 
 /// GroundDB CLI — interact with a GroundDB data store from the command line
 #[derive(Parser)]
@@ -15,6 +15,10 @@ struct Cli {
     #[arg(long, default_value = "yaml")]
     format: OutputFormat,
 
+    /// Don't take the data directory's `_system.lock` (another process may be using it)
+    #[arg(long)]
+    no_lock: bool,
+
     #[command(subcommand)]
     command: Command,
 }
@@ -33,6 +37,9 @@ enum Command {
         collection: String,
         /// Document ID
         id: String,
+        /// Only output these fields (e.g. --fields name,email)
+        #[arg(long, value_delimiter = ',')]
+        fields: Vec<String>,
     },
 
     /// List documents in a collection
@@ -42,6 +49,9 @@ enum Command {
         /// Path segment filters (e.g. --filter status=published)
         #[arg(long = "filter", value_parser = parse_key_value)]
         filters: Vec<(String, String)>,
+        /// Only output these fields (e.g. --fields name,email)
+        #[arg(long, value_delimiter = ',')]
+        fields: Vec<String>,
     },
 
     /// Insert a new document
@@ -70,6 +80,23 @@ enum Command {
         fields: Vec<(String, String)>,
     },
 
+    /// Edit a document's body in $VISUAL / $EDITOR, keeping its front matter
+    Edit {
+        /// Collection name
+        collection: String,
+        /// Document ID
+        id: String,
+    },
+
+    /// Bulk-insert documents from NDJSON on stdin (one JSON object per line)
+    Import {
+        /// Collection name
+        collection: String,
+        /// Keep each record's id, created_at and modified_at (as written by export)
+        #[arg(long)]
+        preserve: bool,
+    },
+
     /// Delete a document
     Delete {
         /// Collection name
@@ -85,6 +112,10 @@ enum Command {
     View {
         /// View name
         name: String,
+        /// Compare against saved rows (a YAML or JSON file of `view` output)
+        /// and print the added, removed and changed rows
+        #[arg(long, value_name = "BASELINE")]
+        diff: Option<String>,
     },
 
     /// Execute a parameterized query
@@ -96,8 +127,28 @@ enum Command {
         params: Vec<(String, String)>,
     },
 
-    /// Check all documents against the schema
-    Validate,
+    /// Check all documents against the schema. Exits non-zero if any
+    /// document has errors (warnings alone don't fail)
+    Validate {
+        /// Also report refs pointing at documents that don't exist
+        #[arg(long)]
+        refs: bool,
+        /// Only lint this schema file; no data directory is opened. Exits
+        /// non-zero if it has problems
+        #[arg(long, value_name = "FILE")]
+        schema: Option<String>,
+        /// Fail on the first document that can't be parsed, naming its path,
+        /// instead of skipping it
+        #[arg(long)]
+        strict_parse: bool,
+    },
+
+    /// Report documents whose file path doesn't match the path template
+    Check {
+        /// Move mismatched files to their rendered path
+        #[arg(long)]
+        fix: bool,
+    },
 
     /// Show schema info, collection stats, and view health
     Status,
@@ -107,8 +158,26 @@ enum Command {
         /// Only rebuild a specific collection
         #[arg(long)]
         collection: Option<String>,
+        /// Show what would change without rebuilding
+        #[arg(long)]
+        dry_run: bool,
+        /// Only re-run view queries against the current index; no files are read
+        #[arg(long, conflicts_with_all = ["collection", "dry_run"])]
+        views_only: bool,
+    },
+
+    /// Remove materialized view files for views no longer in the schema
+    Prune,
+
+    /// Inspect the schema
+    Schema {
+        #[command(subcommand)]
+        action: SchemaAction,
     },
 
+    /// Rewrite all materialized view files from the current view data
+    Materialize,
+
     /// Apply pending schema migrations
     Migrate {
         /// Show what would change without applying
@@ -132,6 +201,12 @@ enum Command {
     },
 }
 
+#[derive(Subcommand)]
+enum SchemaAction {
+    /// Print collections, fields, types and views as JSON
+    Dump,
+}
+
 fn parse_key_value(s: &str) -> Result<(String, String), String> {
     let pos = s.find('=').ok_or_else(|| {
         format!("Invalid key=value pair: no '=' found in '{s}'")
@@ -139,28 +214,67 @@ fn parse_key_value(s: &str) -> Result<(String, String), String> {
     Ok((s[..pos].to_string(), s[pos + 1..].to_string()))
 }
 
-fn main() {
+fn main() -> ExitCode {
     let cli = Cli::parse();
 
-    if let Err(e) = run(cli) {
-        // Machine-readable error on stderr per spec §7.3
-        eprintln!("ERROR:{e}");
-        process::exit(1);
+    match run(cli) {
+        Ok(status) => status,
+        Err(e) => {
+            // Machine-readable error on stderr per spec §7.3
+            eprintln!("ERROR:{e}");
+            ExitCode::FAILURE
+        }
     }
 }
 
-fn run(cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
-    let store = Store::open(&cli.data_dir)?;
+/// Run one command. The store (and its lock) is dropped before this returns,
+/// so the caller can exit with the returned status.
+fn run(cli: Cli) -> Result<ExitCode, Box<dyn std::error::Error>> {
+    // Schema linting works before any data exists, so it runs without a store
+    if let Command::Validate { schema: Some(path), .. } = &cli.command {
+        let yaml = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read schema '{path}': {e}"))?;
+        let problems = grounddb::validate_schema_str(&yaml)?;
+        let ok = problems.is_empty();
+        print_output(&serde_json::json!({ "ok": ok, "problems": problems }), &cli.format);
+        return Ok(if ok { ExitCode::SUCCESS } else { ExitCode::FAILURE });
+    }
+
+    let options = StoreOptions {
+        no_lock: cli.no_lock,
+        strict_parse: matches!(cli.command, Command::Validate { strict_parse: true, .. }),
+        ..Default::default()
+    };
+    let store = Store::open_with_options(&cli.data_dir, options)?;
+    let mut status = ExitCode::SUCCESS;
 
     match cli.command {
-        Command::Get { collection, id } => {
-            let doc = store.get_dynamic(&collection, &id)?;
+        Command::Get {
+            collection,
+            id,
+            fields,
+        } => {
+            let doc = if fields.is_empty() {
+                store.get_dynamic(&collection, &id)?
+            } else {
+                let fields: Vec<&str> = fields.iter().map(|f| f.as_str()).collect();
+                store.get_dynamic_fields(&collection, &id, &fields)?
+            };
             print_output(&doc, &cli.format);
         }
 
-        Command::List { collection, filters } => {
+        Command::List {
+            collection,
+            filters,
+            fields,
+        } => {
             let filter_map: HashMap<String, String> = filters.into_iter().collect();
-            let docs = store.list_dynamic(&collection, &filter_map)?;
+            let docs = if fields.is_empty() {
+                store.list_dynamic(&collection, &filter_map)?
+            } else {
+                let fields: Vec<&str> = fields.iter().map(|f| f.as_str()).collect();
+                store.list_dynamic_fields(&collection, &filter_map, &fields)?
+            };
             print_output(&docs, &cli.format);
         }
 
@@ -176,6 +290,16 @@ fn run(cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
             print_output(&serde_json::json!({ "id": id }), &cli.format);
         }
 
+        Command::Import { collection, preserve } => {
+            let records = read_ndjson_stdin()?;
+            let ids = if preserve {
+                store.import_documents(&collection, records)?
+            } else {
+                store.bulk_insert(&collection, records)?
+            };
+            print_output(&serde_json::json!({ "ok": true, "inserted": ids.len() }), &cli.format);
+        }
+
         Command::Update {
             collection,
             id,
@@ -186,6 +310,14 @@ fn run(cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
             print_output(&serde_json::json!({ "ok": true, "id": id }), &cli.format);
         }
 
+        Command::Edit { collection, id } => {
+            let editor = std::env::var("VISUAL")
+                .or_else(|_| std::env::var("EDITOR"))
+                .unwrap_or_else(|_| "vi".to_string());
+            let result = edit_content(&store, &collection, &id, &editor)?;
+            print_output(&result, &cli.format);
+        }
+
         Command::Delete {
             collection,
             id,
@@ -208,8 +340,17 @@ fn run(cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
             }
         }
 
-        Command::View { name } => {
-            let result = store.view_dynamic(&name)?;
+        Command::View { name, diff } => {
+            let result = match diff {
+                Some(path) => {
+                    let raw = std::fs::read_to_string(&path)
+                        .map_err(|e| format!("Failed to read baseline '{path}': {e}"))?;
+                    let baseline: serde_json::Value = serde_yaml::from_str(&raw)
+                        .map_err(|e| format!("Invalid baseline '{path}': {e}"))?;
+                    store.view_diff(&name, &baseline)?
+                }
+                None => store.view_dynamic(&name)?,
+            };
             print_output(&result, &cli.format);
         }
 
@@ -219,8 +360,24 @@ fn run(cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
             print_output(&result, &cli.format);
         }
 
-        Command::Validate => {
-            let result = store.validate_all()?;
+        Command::Validate { refs, .. } => {
+            let result = if refs {
+                store.validate_all_with_refs()?
+            } else {
+                store.validate_all()?
+            };
+            print_output(&result, &cli.format);
+            if has_errors(&result) {
+                status = ExitCode::FAILURE;
+            }
+        }
+
+        Command::Check { fix } => {
+            let result = if fix {
+                store.reconcile_paths()?
+            } else {
+                store.check_consistency()?
+            };
             print_output(&result, &cli.format);
         }
 
@@ -229,9 +386,39 @@ fn run(cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
             print_output(&result, &cli.format);
         }
 
-        Command::Rebuild { collection } => {
-            store.rebuild(collection.as_deref())?;
-            print_output(&serde_json::json!({ "ok": true, "rebuilt": true }), &cli.format);
+        Command::Rebuild {
+            collection,
+            dry_run,
+            views_only,
+        } => {
+            if views_only {
+                store.rebuild_views_only()?;
+                print_output(&serde_json::json!({ "ok": true, "rebuilt": true }), &cli.format);
+            } else if dry_run {
+                let plan = store.rebuild_plan(collection.as_deref())?;
+                print_output(&plan, &cli.format);
+            } else {
+                store.rebuild(collection.as_deref())?;
+                print_output(&serde_json::json!({ "ok": true, "rebuilt": true }), &cli.format);
+            }
+        }
+
+        Command::Prune => {
+            let removed: Vec<String> = store
+                .prune_materialized()?
+                .iter()
+                .map(|p| p.display().to_string())
+                .collect();
+            print_output(&serde_json::json!({ "ok": true, "removed": removed }), &cli.format);
+        }
+
+        Command::Schema { action: SchemaAction::Dump } => {
+            print_output(&store.schema_json(), &cli.format);
+        }
+
+        Command::Materialize => {
+            store.materialize_all()?;
+            print_output(&serde_json::json!({ "ok": true }), &cli.format);
         }
 
         Command::Migrate { dry_run } => {
@@ -245,13 +432,37 @@ fn run(cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
             print_output(&docs, &cli.format);
         }
 
-        Command::Explain { name, params: _ } => {
-            let result = store.explain_view(&name)?;
+        Command::Explain { name, params } => {
+            let result = if params.is_empty() {
+                store.explain_view(&name)?
+            } else {
+                let params: HashMap<String, String> = params.into_iter().collect();
+                store.explain_query(&name, &params)?
+            };
             print_output(&result, &cli.format);
         }
     }
 
-    Ok(())
+    Ok(status)
+}
+
+/// Whether a `validate` report lists a document with errors or dangling refs,
+/// or a file that was skipped because it couldn't be read
+fn has_errors(report: &serde_json::Value) -> bool {
+    let collections: Vec<&serde_json::Value> = report
+        .as_object()
+        .into_iter()
+        .flat_map(|collections| collections.values())
+        .collect();
+    let skipped = collections
+        .iter()
+        .any(|collection| collection.get("skipped").is_some());
+    skipped
+        || collections
+            .iter()
+            .filter_map(|collection| collection["issues"].as_array())
+            .flatten()
+            .any(|issue| issue.get("errors").is_some() || issue.get("dangling_refs").is_some())
 }
 
 fn print_output(value: &serde_json::Value, format: &OutputFormat) {
@@ -277,6 +488,65 @@ fn fields_to_value(fields: &[(String, String)]) -> serde_json::Value {
     serde_json::Value::Object(map)
 }
 
+/// Open a document's body in `editor` and save it back if it changed. The
+/// editor command may include arguments (e.g. `code --wait`); the temp file
+/// path is appended. A non-zero exit aborts without writing.
+fn edit_content(
+    store: &Store,
+    collection: &str,
+    id: &str,
+    editor: &str,
+) -> Result<serde_json::Value, Box<dyn std::error::Error>> {
+    let col = store.collection(collection)?;
+    let doc = col.get(id)?;
+    // Drop the blank line separating the body from the front matter
+    let original = doc.content.unwrap_or_default();
+    let original = original.strip_prefix('\n').unwrap_or(&original);
+
+    let file = tempfile::Builder::new()
+        .prefix(&format!("{collection}-{id}-"))
+        .suffix(".md")
+        .tempfile()?;
+    std::fs::write(file.path(), original)?;
+
+    let mut args = editor.split_whitespace();
+    let program = args.next().ok_or("No editor set (use $VISUAL or $EDITOR)")?;
+    let status = process::Command::new(program)
+        .args(args)
+        .arg(file.path())
+        .status()
+        .map_err(|e| format!("Failed to run editor '{editor}': {e}"))?;
+    if !status.success() {
+        return Err(format!("Editor '{editor}' exited with {status}; document not updated").into());
+    }
+
+    let edited = std::fs::read_to_string(file.path())?;
+    let changed = edited != original;
+    if changed {
+        col.update_partial(
+            id,
+            serde_yaml::Value::Mapping(serde_yaml::Mapping::new()),
+            Some(&edited),
+        )?;
+    }
+    Ok(serde_json::json!({ "ok": true, "id": id, "changed": changed }))
+}
+
+fn read_ndjson_stdin() -> Result<Vec<serde_json::Value>, Box<dyn std::error::Error>> {
+    use std::io::BufRead;
+    let mut records = Vec::new();
+    for (i, line) in std::io::stdin().lock().lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let record = serde_json::from_str(&line)
+            .map_err(|e| format!("Invalid JSON on line {}: {e}", i + 1))?;
+        records.push(record);
+    }
+    Ok(records)
+}
+
 fn read_content(
     content_file: Option<String>,
     content_stdin: bool,
@@ -294,3 +564,130 @@ fn read_content(
         Ok(None)
     }
 }
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+    use tempfile::TempDir;
+
+    fn setup_store() -> (TempDir, Store) {
+        let tmp = TempDir::new().unwrap();
+        let schema = r#"
+collections:
+  posts:
+    path: "posts/{title}.md"
+    fields:
+      title: { type: string, required: true }
+      status: { type: string, default: draft }
+      related: { type: ref, target: posts }
+    content: true
+"#;
+        std::fs::write(tmp.path().join("schema.yaml"), schema).unwrap();
+        let store = Store::open(tmp.path().to_str().unwrap()).unwrap();
+        let data = serde_yaml::from_str("title: hello\nstatus: published").unwrap();
+        store
+            .collection("posts")
+            .unwrap()
+            .insert(data, Some("Old body\n"))
+            .unwrap();
+        (tmp, store)
+    }
+
+    /// Write an executable shell script to use as the editor.
+    fn fake_editor(dir: &TempDir, script: &str) -> String {
+        let path = dir.path().join("editor.sh");
+        std::fs::write(&path, format!("#!/bin/sh\n{script}\n")).unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        path.to_str().unwrap().to_string()
+    }
+
+    fn file_text(tmp: &TempDir) -> String {
+        std::fs::read_to_string(tmp.path().join("posts/hello.md")).unwrap()
+    }
+
+    #[test]
+    fn test_edit_replaces_body_and_keeps_front_matter() {
+        let (tmp, store) = setup_store();
+        let editor = fake_editor(&tmp, r#"grep -q "^Old body$" "$1" && printf 'New body\n' > "$1""#);
+
+        let result = edit_content(&store, "posts", "hello", &editor).unwrap();
+        assert_eq!(result["changed"], serde_json::json!(true));
+        assert_eq!(
+            file_text(&tmp),
+            "---\ntitle: hello\nstatus: published\n---\n\nNew body\n"
+        );
+    }
+
+    #[test]
+    fn test_edit_without_changes_or_failing_editor_writes_nothing() {
+        let (tmp, store) = setup_store();
+        let before = file_text(&tmp);
+
+        let unchanged = fake_editor(&tmp, "exit 0");
+        let result = edit_content(&store, "posts", "hello", &unchanged).unwrap();
+        assert_eq!(result["changed"], serde_json::json!(false));
+
+        let failing = fake_editor(&tmp, r#"printf 'Discarded\n' > "$1"; exit 1"#);
+        assert!(edit_content(&store, "posts", "hello", &failing).is_err());
+        assert_eq!(file_text(&tmp), before);
+    }
+
+    #[test]
+    fn test_validate_fails_only_on_errors() {
+        let (tmp, store) = setup_store();
+        assert!(!has_errors(&store.validate_all().unwrap()));
+        drop(store);
+
+        // Problems in a non-strict collection are only warnings
+        std::fs::write(tmp.path().join("posts/untitled.md"), "---\nstatus: draft\n---\n").unwrap();
+        let store = Store::open(tmp.path().to_str().unwrap()).unwrap();
+        assert!(!has_errors(&store.validate_all().unwrap()));
+        drop(store);
+
+        // Once strict, the missing required title is an error
+        let schema_path = tmp.path().join("schema.yaml");
+        let schema = std::fs::read_to_string(&schema_path).unwrap();
+        std::fs::write(&schema_path, format!("{schema}    strict: true\n")).unwrap();
+        let store = Store::open(tmp.path().to_str().unwrap()).unwrap();
+        assert!(has_errors(&store.validate_all().unwrap()));
+        drop(store);
+
+        // The command fails, and has released the store's lock by the time it returns
+        let data_dir = tmp.path().to_str().unwrap();
+        let cli = Cli::parse_from(["grounddb", "--data-dir", data_dir, "--format", "json", "validate"]);
+        assert_eq!(run(cli).unwrap(), ExitCode::FAILURE);
+        Store::open(data_dir).unwrap();
+    }
+
+    #[test]
+    fn test_validate_fails_on_skipped_files_and_dangling_refs() {
+        let (tmp, store) = setup_store();
+        drop(store);
+        let data_dir = tmp.path().to_str().unwrap();
+        let validate = |args: &[&str]| {
+            let mut argv = vec!["grounddb", "--data-dir", data_dir, "--format", "json", "validate"];
+            argv.extend(args);
+            run(Cli::parse_from(argv)).unwrap()
+        };
+
+        // A dangling ref only fails the run when refs are checked
+        std::fs::write(
+            tmp.path().join("posts/orphan.md"),
+            "---\ntitle: orphan\nrelated: missing\n---\n",
+        )
+        .unwrap();
+        assert_eq!(validate(&[]), ExitCode::SUCCESS);
+        assert_eq!(validate(&["--refs"]), ExitCode::FAILURE);
+        std::fs::remove_file(tmp.path().join("posts/orphan.md")).unwrap();
+        assert_eq!(validate(&["--refs"]), ExitCode::SUCCESS);
+
+        // A file that can't be parsed is skipped, which is a failure too
+        std::fs::write(tmp.path().join("posts/broken.md"), "---\ntitle: [\n---\n").unwrap();
+        assert_eq!(validate(&[]), ExitCode::FAILURE);
+        let store = Store::open(data_dir).unwrap();
+        let invalid = store.invalid_documents().unwrap();
+        assert_eq!(invalid.len(), 1, "{invalid:?}");
+        assert_eq!((invalid[0].0.as_str(), invalid[0].1.as_str()), ("posts", "broken"));
+    }
+}
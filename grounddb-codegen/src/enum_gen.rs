@@ -1,4 +1,5 @@
-use grounddb::schema::{CollectionDefinition, FieldDefinition, RefTarget, SchemaDefinition};
+use grounddb::schema::{CollectionDefinition, FieldDefinition, FieldType, RefTarget, SchemaDefinition};
+use heck::ToShoutySnakeCase;
 use proc_macro2::TokenStream;
 use quote::{format_ident, quote};
 
@@ -6,8 +7,9 @@ use crate::type_utils::{enum_type_name, enum_variant_ident, ref_enum_name};
 
 /// Generate all enum types from the schema.
 /// This includes:
-/// 1. Enums for fields with `enum` values (e.g., UserRole, PostStatus)
-/// 2. Polymorphic ref enums for multi-target ref fields (e.g., ParentRef)
+/// 1. Enums for string fields with `enum` values (e.g., UserRole, PostStatus)
+/// 2. Allowed-value constants for number/boolean enums (e.g., TASK_LEVEL_VALUES)
+/// 3. Polymorphic ref enums for multi-target ref fields (e.g., ParentRef)
 pub fn generate_enums(schema: &SchemaDefinition) -> TokenStream {
     let mut tokens = TokenStream::new();
 
@@ -35,11 +37,19 @@ fn generate_collection_enums(
     fields.sort_by(|(a, _), (b, _)| a.cmp(b));
 
     for (field_name, field_def) in &fields {
-        // Generate value enums for fields with enum values
-        if let Some(ref enum_values) = field_def.enum_values {
+        // Generate value enums for string fields with enum values, and a
+        // constant listing the allowed values for number/boolean enums
+        if let Some(enum_values) = field_def.string_enum_values() {
             let enum_tokens =
                 generate_value_enum(collection_name, field_name, enum_values, field_def);
             tokens.extend(enum_tokens);
+        } else if let Some(ref enum_values) = field_def.enum_values {
+            tokens.extend(generate_enum_values_const(
+                collection_name,
+                field_name,
+                enum_values,
+                field_def,
+            ));
         }
 
         // Generate polymorphic ref enums for multi-target refs
@@ -95,6 +105,38 @@ fn generate_value_enum(
     }
 }
 
+/// Generate the allowed values of a number or boolean enum field as a
+/// constant like `TASK_LEVEL_VALUES: &[f64]`.
+fn generate_enum_values_const(
+    collection_name: &str,
+    field_name: &str,
+    enum_values: &[String],
+    field_def: &FieldDefinition,
+) -> TokenStream {
+    let const_ident = format_ident!(
+        "{}_VALUES",
+        enum_type_name(collection_name, field_name).to_shouty_snake_case()
+    );
+    match field_def.field_type {
+        FieldType::Number => {
+            let values = enum_values
+                .iter()
+                .filter_map(|v| v.parse::<f64>().ok())
+                .map(proc_macro2::Literal::f64_unsuffixed);
+            quote! {
+                pub const #const_ident: &[f64] = &[#(#values),*];
+            }
+        }
+        FieldType::Boolean => {
+            let values = enum_values.iter().filter_map(|v| v.parse::<bool>().ok());
+            quote! {
+                pub const #const_ident: &[bool] = &[#(#values),*];
+            }
+        }
+        _ => TokenStream::new(),
+    }
+}
+
 /// Generate a polymorphic ref enum like ParentRef.
 fn generate_ref_enum(field_name: &str, targets: &[String]) -> TokenStream {
     let enum_name = ref_enum_name(field_name);
@@ -128,15 +170,20 @@ mod tests {
         let field = FieldDefinition {
             field_type: FieldType::String,
             required: false,
+            nullable: None,
             enum_values: Some(vec![
                 "admin".to_string(),
                 "member".to_string(),
                 "guest".to_string(),
             ]),
+            enum_case_insensitive: None,
             default: Some(serde_yaml::Value::String("member".to_string())),
             target: None,
             items: None,
             on_delete: None,
+            collation: None,
+            denormalize: None,
+            immutable: false,
         };
 
         let tokens = generate_value_enum("users", "role", field.enum_values.as_ref().unwrap(), &field);
@@ -149,6 +196,37 @@ mod tests {
         assert!(code.contains("impl Default for UserRole"));
     }
 
+    #[test]
+    fn test_number_enum_generates_values_const() {
+        let field = FieldDefinition {
+            field_type: FieldType::Number,
+            required: true,
+            nullable: None,
+            enum_values: Some(vec!["1".to_string(), "2".to_string(), "3".to_string()]),
+            enum_case_insensitive: None,
+            default: None,
+            target: None,
+            items: None,
+            on_delete: None,
+            collation: None,
+            denormalize: None,
+            immutable: false,
+        };
+        let mut collection: CollectionDefinition =
+            serde_yaml::from_str("path: \"tasks/{id}.md\"").unwrap();
+        collection.fields.insert("level".to_string(), field.clone());
+
+        let code = generate_collection_enums("tasks", &collection).to_string();
+        assert!(code.contains("TASK_LEVEL_VALUES"));
+        assert!(code.contains("& [f64]"));
+        assert!(code.contains("1.0 , 2.0 , 3.0"));
+        assert!(!code.contains("enum TaskLevel"));
+
+        // The struct field keeps its numeric type
+        let ty = crate::type_utils::field_base_type(&field, "tasks", "level", &[]).to_string();
+        assert_eq!(ty, "f64");
+    }
+
     #[test]
     fn test_generate_ref_enum() {
         let tokens = generate_ref_enum("parent", &["posts".to_string(), "comments".to_string()]);
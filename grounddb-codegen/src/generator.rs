@@ -73,11 +73,16 @@ mod tests {
             FieldDefinition {
                 field_type: FieldType::String,
                 required: true,
+                nullable: None,
                 enum_values: None,
+                enum_case_insensitive: None,
                 default: None,
                 target: None,
                 items: None,
                 on_delete: None,
+                collation: None,
+                denormalize: None,
+                immutable: false,
             },
         );
         address_fields.insert(
@@ -85,11 +90,16 @@ mod tests {
             FieldDefinition {
                 field_type: FieldType::String,
                 required: true,
+                nullable: None,
                 enum_values: None,
+                enum_case_insensitive: None,
                 default: None,
                 target: None,
                 items: None,
                 on_delete: None,
+                collation: None,
+                denormalize: None,
+                immutable: false,
             },
         );
         types.insert("address".to_string(), address_fields);
@@ -103,11 +113,16 @@ mod tests {
             FieldDefinition {
                 field_type: FieldType::String,
                 required: true,
+                nullable: None,
                 enum_values: None,
+                enum_case_insensitive: None,
                 default: None,
                 target: None,
                 items: None,
                 on_delete: None,
+                collation: None,
+                denormalize: None,
+                immutable: false,
             },
         );
         user_fields.insert(
@@ -115,11 +130,16 @@ mod tests {
             FieldDefinition {
                 field_type: FieldType::String,
                 required: true,
+                nullable: None,
                 enum_values: None,
+                enum_case_insensitive: None,
                 default: None,
                 target: None,
                 items: None,
                 on_delete: None,
+                collation: None,
+                denormalize: None,
+                immutable: false,
             },
         );
         user_fields.insert(
@@ -127,15 +147,20 @@ mod tests {
             FieldDefinition {
                 field_type: FieldType::String,
                 required: false,
+                nullable: None,
                 enum_values: Some(vec![
                     "admin".to_string(),
                     "member".to_string(),
                     "guest".to_string(),
                 ]),
+                enum_case_insensitive: None,
                 default: Some(serde_yaml::Value::String("member".to_string())),
                 target: None,
                 items: None,
                 on_delete: None,
+                collation: None,
+                denormalize: None,
+                immutable: false,
             },
         );
         collections.insert(
@@ -143,13 +168,20 @@ mod tests {
             CollectionDefinition {
                 path: "users/{name}.md".to_string(),
                 fields: user_fields,
-                content: false,
+                content: false.into(),
                 additional_properties: false,
                 strict: true,
                 readonly: false,
                 on_delete: None,
                 id: None,
                 records: None,
+                renamed_from: None,
+                content_template: None,
+                extract_content: None,
+                enum_case_insensitive: false,
+                layout: Default::default(),
+                compress: false,
+                enum_ordinals: false,
             },
         );
 
@@ -160,11 +192,16 @@ mod tests {
             FieldDefinition {
                 field_type: FieldType::String,
                 required: true,
+                nullable: None,
                 enum_values: None,
+                enum_case_insensitive: None,
                 default: None,
                 target: None,
                 items: None,
                 on_delete: None,
+                collation: None,
+                denormalize: None,
+                immutable: false,
             },
         );
         post_fields.insert(
@@ -172,11 +209,16 @@ mod tests {
             FieldDefinition {
                 field_type: FieldType::Ref,
                 required: true,
+                nullable: None,
                 enum_values: None,
+                enum_case_insensitive: None,
                 default: None,
                 target: Some(RefTarget::Single("users".to_string())),
                 items: None,
                 on_delete: None,
+                collation: None,
+                denormalize: None,
+                immutable: false,
             },
         );
         post_fields.insert(
@@ -184,15 +226,20 @@ mod tests {
             FieldDefinition {
                 field_type: FieldType::String,
                 required: false,
+                nullable: None,
                 enum_values: Some(vec![
                     "draft".to_string(),
                     "published".to_string(),
                     "archived".to_string(),
                 ]),
+                enum_case_insensitive: None,
                 default: Some(serde_yaml::Value::String("draft".to_string())),
                 target: None,
                 items: None,
                 on_delete: None,
+                collation: None,
+                denormalize: None,
+                immutable: false,
             },
         );
         collections.insert(
@@ -200,13 +247,20 @@ mod tests {
             CollectionDefinition {
                 path: "posts/{status}/{title}.md".to_string(),
                 fields: post_fields,
-                content: true,
+                content: true.into(),
                 additional_properties: false,
                 strict: true,
                 readonly: false,
                 on_delete: None,
                 id: None,
                 records: None,
+                renamed_from: None,
+                content_template: None,
+                extract_content: None,
+                enum_case_insensitive: false,
+                layout: Default::default(),
+                compress: false,
+                enum_ordinals: false,
             },
         );
 
@@ -220,6 +274,7 @@ mod tests {
                 materialize: true,
                 buffer: Some("2x".to_string()),
                 params: None,
+                renamed_from: None,
             },
         );
 
@@ -238,10 +293,12 @@ mod tests {
                 materialize: false,
                 buffer: None,
                 params: Some(post_comments_params),
+                renamed_from: None,
             },
         );
 
         SchemaDefinition {
+            defaults: Default::default(),
             types,
             collections,
             views,
@@ -29,9 +29,7 @@ pub fn generate_from_schema(
     output_path: &str,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let schema = grounddb::schema::parse_schema(Path::new(schema_path))?;
-    let tokens = generator::generate_all(&schema);
-    let formatted = generator::format_token_stream(&tokens);
-    std::fs::write(output_path, formatted)?;
+    std::fs::write(output_path, generate(&schema)?)?;
     Ok(())
 }
 
@@ -43,9 +41,24 @@ pub fn generate_from_schema_str(
     schema_yaml: &str,
 ) -> Result<String, Box<dyn std::error::Error>> {
     let schema = grounddb::schema::parse_schema_str(schema_yaml)?;
-    let tokens = generator::generate_all(&schema);
-    let formatted = generator::format_token_stream(&tokens);
-    Ok(formatted)
+    generate(&schema)
+}
+
+/// Generate formatted code for a parsed schema, or fail with every problem
+/// that would keep the generated code from compiling or running.
+fn generate(
+    schema: &grounddb::SchemaDefinition,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let errors = struct_gen::codegen_errors(schema);
+    if !errors.is_empty() {
+        return Err(format!(
+            "Cannot generate code for this schema:\n  - {}",
+            errors.join("\n  - ")
+        )
+        .into());
+    }
+    let tokens = generator::generate_all(schema);
+    Ok(generator::format_token_stream(&tokens))
 }
 
 #[cfg(test)]
@@ -58,15 +58,27 @@ pub fn generate_store_ext(schema: &SchemaDefinition) -> TokenStream {
         } else {
             // Static view
             let view_name_lit = *view_name;
+            let on_change_ident = format_ident!("on_{}_change", view_name);
 
             trait_methods.push(quote! {
                 fn #method_ident(&self) -> grounddb::Result<Vec<#row_struct>>;
+
+                fn #on_change_ident<F>(&self, callback: F) -> grounddb::SubscriptionId
+                where
+                    F: Fn(&[#row_struct]) + Send + 'static;
             });
 
             impl_methods.push(quote! {
                 fn #method_ident(&self) -> grounddb::Result<Vec<#row_struct>> {
                     self.store().read_view(#view_name_lit)
                 }
+
+                fn #on_change_ident<F>(&self, callback: F) -> grounddb::SubscriptionId
+                where
+                    F: Fn(&[#row_struct]) + Send + 'static,
+                {
+                    self.store().on_typed_view_change(#view_name_lit, callback)
+                }
             });
         }
     }
@@ -138,7 +150,7 @@ pub fn generate_store_ext(schema: &SchemaDefinition) -> TokenStream {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use grounddb::schema::{SchemaDefinition, CollectionDefinition};
+    use grounddb::schema::{SchemaDefinition, CollectionDefinition, ViewDefinition};
     use std::collections::HashMap;
 
     #[test]
@@ -149,17 +161,25 @@ mod tests {
             CollectionDefinition {
                 path: "users/{name}.md".to_string(),
                 fields: HashMap::new(),
-                content: false,
+                content: false.into(),
                 additional_properties: false,
                 strict: true,
                 readonly: false,
                 on_delete: None,
                 id: None,
                 records: None,
+                renamed_from: None,
+                content_template: None,
+                extract_content: None,
+                enum_case_insensitive: false,
+                layout: Default::default(),
+                compress: false,
+                enum_ordinals: false,
             },
         );
 
         let schema = SchemaDefinition {
+            defaults: Default::default(),
             types: HashMap::new(),
             collections,
             views: HashMap::new(),
@@ -172,4 +192,33 @@ mod tests {
         assert!(code.contains("fn users"));
         assert!(code.contains("TypedCollection"));
     }
+
+    #[test]
+    fn test_generate_typed_view_subscription() {
+        let mut views = HashMap::new();
+        views.insert(
+            "post_feed".to_string(),
+            ViewDefinition {
+                query: "SELECT p.title FROM posts p".to_string(),
+                view_type: None,
+                materialize: true,
+                buffer: None,
+                params: None,
+                renamed_from: None,
+            },
+        );
+
+        let schema = SchemaDefinition {
+            defaults: Default::default(),
+            types: HashMap::new(),
+            collections: HashMap::new(),
+            views,
+        };
+
+        let code = generate_store_ext(&schema).to_string();
+        assert!(syn::parse_file(&code).is_ok(), "Not valid Rust:\n{code}");
+        assert!(code.contains("fn on_post_feed_change"));
+        assert!(code.contains("Fn (& [PostFeedRow])"), "{code}");
+        assert!(code.contains("on_typed_view_change (\"post_feed\" , callback)"), "{code}");
+    }
 }
@@ -1,7 +1,9 @@
+use chrono::{DateTime, Datelike, NaiveDate, Utc};
 use grounddb::schema::{CollectionDefinition, FieldDefinition, FieldType, SchemaDefinition};
 use heck::ToPascalCase;
-use proc_macro2::TokenStream;
+use proc_macro2::{Literal, TokenStream};
 use quote::{format_ident, quote};
+use std::collections::{BTreeMap, HashMap};
 
 use crate::type_utils::{
     collection_struct_name, enum_type_name, field_to_rust_type, partial_struct_name,
@@ -35,6 +37,69 @@ pub fn generate_structs(schema: &SchemaDefinition) -> TokenStream {
     tokens
 }
 
+/// Problems in `schema` that would make the generated code fail to compile or
+/// panic: fields of one struct that map to the same Rust identifier, and date
+/// or datetime defaults that don't parse. Empty when code can be generated.
+pub fn codegen_errors(schema: &SchemaDefinition) -> Vec<String> {
+    let mut errors = Vec::new();
+
+    let mut types: Vec<_> = schema.types.iter().collect();
+    types.sort_by_key(|(name, _)| *name);
+    for (type_name, fields) in types {
+        check_fields(&format!("Type '{type_name}'"), fields, &mut errors);
+    }
+
+    let mut collections: Vec<_> = schema.collections.iter().collect();
+    collections.sort_by_key(|(name, _)| *name);
+    for (collection_name, collection_def) in collections {
+        check_fields(
+            &format!("Collection '{collection_name}'"),
+            &collection_def.fields,
+            &mut errors,
+        );
+    }
+
+    errors
+}
+
+fn check_fields(owner: &str, fields: &HashMap<String, FieldDefinition>, errors: &mut Vec<String>) {
+    let mut by_ident: BTreeMap<String, Vec<&str>> = BTreeMap::new();
+    for field_name in fields.keys() {
+        by_ident
+            .entry(safe_field_ident(field_name).to_string())
+            .or_default()
+            .push(field_name);
+    }
+    for (ident, mut names) in by_ident {
+        if names.len() > 1 {
+            names.sort();
+            errors.push(format!(
+                "{owner}: fields '{}' all generate the Rust field `{ident}`; rename all but one",
+                names.join("', '")
+            ));
+        }
+    }
+
+    let mut dated: Vec<_> = fields
+        .iter()
+        .filter(|(_, f)| matches!(f.field_type, FieldType::Date | FieldType::Datetime))
+        .filter(|(_, f)| f.default.as_ref().is_some_and(|d| !d.is_null()))
+        .collect();
+    dated.sort_by_key(|(name, _)| *name);
+    for (field_name, field_def) in dated {
+        if default_value_expr(field_def).is_none() {
+            let expected = match field_def.field_type {
+                FieldType::Date => "a YYYY-MM-DD date",
+                _ => "an RFC 3339 datetime",
+            };
+            errors.push(format!(
+                "{owner}: field '{field_name}' default {:?} is not {expected}",
+                field_def.default.as_ref().and_then(|d| d.as_str()).unwrap_or_default()
+            ));
+        }
+    }
+}
+
 /// Generate structs for reusable types defined in the `types:` section.
 fn generate_reusable_types(schema: &SchemaDefinition, known_types: &[String]) -> TokenStream {
     let mut tokens = TokenStream::new();
@@ -53,15 +118,18 @@ fn generate_reusable_types(schema: &SchemaDefinition, known_types: &[String]) ->
             .map(|(field_name, field_def)| {
                 let ident = safe_field_ident(field_name);
                 let ty = field_to_rust_type(field_def, type_name, field_name, known_types);
-                generate_field_with_attrs(&ident, &ty, field_def, type_name, field_name)
+                generate_field_with_attrs(&ident, &ty, field_def, type_name, &struct_name, field_name)
             })
             .collect();
 
+        let default_fns = generate_default_fns(&struct_name, type_name, &field_entries);
         tokens.extend(quote! {
             #[derive(Debug, Clone, Serialize, Deserialize)]
             pub struct #struct_name {
                 #(#field_tokens)*
             }
+
+            #default_fns
         });
     }
 
@@ -91,9 +159,17 @@ fn generate_collection_struct(
         .map(|(field_name, field_def)| {
             let ident = safe_field_ident(field_name);
             let ty = field_to_rust_type(field_def, collection_name, field_name, known_types);
-            generate_field_with_attrs(&ident, &ty, field_def, collection_name, field_name)
+            generate_field_with_attrs(
+                &ident,
+                &ty,
+                field_def,
+                collection_name,
+                &struct_ident,
+                field_name,
+            )
         })
         .collect();
+    let default_fns = generate_default_fns(&struct_ident, collection_name, &fields);
 
     quote! {
         #[doc = #doc_comment]
@@ -101,6 +177,8 @@ fn generate_collection_struct(
         pub struct #struct_ident {
             #(#field_tokens)*
         }
+
+        #default_fns
     }
 }
 
@@ -146,9 +224,10 @@ fn generate_field_with_attrs(
     ty: &TokenStream,
     field_def: &FieldDefinition,
     collection_name: &str,
+    struct_ident: &proc_macro2::Ident,
     field_name: &str,
 ) -> TokenStream {
-    let serde_attrs = generate_serde_attrs(field_def, collection_name, field_name);
+    let serde_attrs = generate_serde_attrs(field_def, collection_name, struct_ident, field_name);
 
     let rename_attr = if ident.to_string().starts_with("r#") {
         let original = field_name;
@@ -171,6 +250,7 @@ fn generate_field_with_attrs(
 fn generate_serde_attrs(
     field_def: &FieldDefinition,
     collection_name: &str,
+    struct_ident: &proc_macro2::Ident,
     field_name: &str,
 ) -> TokenStream {
     match &field_def.field_type {
@@ -180,25 +260,22 @@ fn generate_serde_attrs(
         }
         _ => {
             if let Some(ref _default_val) = field_def.default {
-                if field_def.enum_values.is_some() {
+                if field_def.string_enum_values().is_some() {
                     // Enum with default - use the enum's Default impl
                     let enum_name = enum_type_name(collection_name, field_name);
                     let default_fn = format!("{}::default", enum_name);
                     quote! { #[serde(default = #default_fn)] }
+                } else if default_value_expr(field_def).is_some() {
+                    // Non-enum with default - a function returning the schema's value
+                    let default_fn = format!("{struct_ident}::{}", default_fn_ident(field_name));
+                    quote! { #[serde(default = #default_fn)] }
                 } else {
-                    // Non-enum with default
+                    // A default with no Rust literal form falls back to the type's
                     quote! { #[serde(default)] }
                 }
             } else if !field_def.required {
-                // Optional fields without default need skip_serializing_if for Option
-                match &field_def.field_type {
-                    FieldType::Object => {
-                        quote! { #[serde(default, skip_serializing_if = "Option::is_none")] }
-                    }
-                    _ => {
-                        quote! { #[serde(default, skip_serializing_if = "Option::is_none")] }
-                    }
-                }
+                // Optional fields without default are Option: absent reads as None
+                quote! { #[serde(default, skip_serializing_if = "Option::is_none")] }
             } else {
                 quote! {}
             }
@@ -206,6 +283,82 @@ fn generate_serde_attrs(
     }
 }
 
+/// Name of the associated function returning a field's schema default. Unique
+/// within its struct, since `codegen_errors` rejects fields sharing an ident.
+fn default_fn_ident(field_name: &str) -> proc_macro2::Ident {
+    let ident = safe_field_ident(field_name).to_string();
+    format_ident!("default_{}", ident.trim_start_matches("r#"))
+}
+
+/// The schema default of a non-enum, non-list field as a Rust expression of
+/// the field's type, if it has one. Dates and datetimes are parsed here, so
+/// the generated code only rebuilds a known-valid value.
+fn default_value_expr(field_def: &FieldDefinition) -> Option<TokenStream> {
+    let default = field_def.default.as_ref()?;
+    let value = match (&field_def.field_type, default) {
+        (FieldType::Number, serde_yaml::Value::Number(n)) => {
+            let literal = Literal::f64_suffixed(n.as_f64()?);
+            quote! { #literal }
+        }
+        (FieldType::Boolean, serde_yaml::Value::Bool(b)) => quote! { #b },
+        (FieldType::String, serde_yaml::Value::String(s)) => quote! { #s.to_string() },
+        (FieldType::Date, serde_yaml::Value::String(s)) => {
+            let date = NaiveDate::parse_from_str(s, "%Y-%m-%d").ok()?;
+            let (year, month, day) = (date.year(), date.month(), date.day());
+            quote! { chrono::NaiveDate::from_ymd_opt(#year, #month, #day).unwrap_or_default() }
+        }
+        (FieldType::Datetime, serde_yaml::Value::String(s)) => {
+            let time = DateTime::parse_from_rfc3339(s).ok()?.with_timezone(&Utc);
+            let (secs, nanos) = (time.timestamp(), time.timestamp_subsec_nanos());
+            quote! {
+                chrono::TimeZone::timestamp_opt(&chrono::Utc, #secs, #nanos)
+                    .single()
+                    .unwrap_or_default()
+            }
+        }
+        _ => return None,
+    };
+    // Required-but-nullable fields are `Option`
+    if field_def.required && field_def.nullable == Some(true) {
+        Some(quote! { Some(#value) })
+    } else {
+        Some(value)
+    }
+}
+
+/// Generate the associated functions `#[serde(default = "...")]` names for
+/// fields whose schema default isn't the type's `Default`.
+fn generate_default_fns(
+    struct_ident: &proc_macro2::Ident,
+    owner: &str,
+    fields: &[(&String, &FieldDefinition)],
+) -> TokenStream {
+    let fns: Vec<_> = fields
+        .iter()
+        .filter_map(|(field_name, field_def)| {
+            if field_def.field_type == FieldType::List || field_def.string_enum_values().is_some() {
+                return None;
+            }
+            let value = default_value_expr(field_def)?;
+            let name = default_fn_ident(field_name);
+            let ty = field_to_rust_type(field_def, owner, field_name, &[]);
+            Some(quote! {
+                fn #name() -> #ty {
+                    #value
+                }
+            })
+        })
+        .collect();
+    if fns.is_empty() {
+        return TokenStream::new();
+    }
+    quote! {
+        impl #struct_ident {
+            #(#fns)*
+        }
+    }
+}
+
 /// Generate serde attribute for partial struct fields.
 fn generate_partial_serde_attr(field_name: &str) -> TokenStream {
     let ident_str = crate::type_utils::safe_field_ident(field_name).to_string();
@@ -225,37 +378,66 @@ fn generate_partial_serde_attr(field_name: &str) -> TokenStream {
 mod tests {
     use super::*;
     use grounddb::schema::{CollectionDefinition, FieldDefinition, FieldType};
+    use serde::{Deserialize, Serialize};
     use std::collections::HashMap;
 
     fn make_string_field(required: bool) -> FieldDefinition {
         FieldDefinition {
             field_type: FieldType::String,
             required,
+            nullable: None,
             enum_values: None,
+            enum_case_insensitive: None,
             default: None,
             target: None,
             items: None,
             on_delete: None,
+            collation: None,
+            denormalize: None,
+            immutable: false,
         }
     }
 
-    #[test]
-    fn test_generate_collection_struct() {
-        let mut fields = HashMap::new();
-        fields.insert("name".to_string(), make_string_field(true));
-        fields.insert("email".to_string(), make_string_field(true));
-
-        let collection = CollectionDefinition {
-            path: "users/{name}.md".to_string(),
+    fn make_collection(path: &str, fields: HashMap<String, FieldDefinition>) -> CollectionDefinition {
+        CollectionDefinition {
+            path: path.to_string(),
             fields,
-            content: false,
+            content: false.into(),
             additional_properties: false,
             strict: true,
             readonly: false,
             on_delete: None,
             id: None,
             records: None,
-        };
+            renamed_from: None,
+            content_template: None,
+            extract_content: None,
+            enum_case_insensitive: false,
+            layout: Default::default(),
+            compress: false,
+            enum_ordinals: false,
+        }
+    }
+
+    /// Assert that generated code, minus doc comments, is exactly `source`
+    /// (the items a `compiled!` block declared).
+    fn assert_generates(code: &str, source: &str) {
+        let mut generated: syn::File = syn::parse_str(code).unwrap();
+        for item in &mut generated.items {
+            if let syn::Item::Struct(item) = item {
+                item.attrs.retain(|attr| !attr.path().is_ident("doc"));
+            }
+        }
+        let expected: syn::File = syn::parse_str(source).unwrap();
+        assert_eq!(quote!(#generated).to_string(), quote!(#expected).to_string());
+    }
+
+    #[test]
+    fn test_generate_collection_struct() {
+        let mut fields = HashMap::new();
+        fields.insert("name".to_string(), make_string_field(true));
+        fields.insert("email".to_string(), make_string_field(true));
+        let collection = make_collection("users/{name}.md", fields);
 
         let tokens = generate_collection_struct("users", &collection, &[]);
         let code = tokens.to_string();
@@ -270,18 +452,7 @@ mod tests {
         let mut fields = HashMap::new();
         fields.insert("name".to_string(), make_string_field(true));
         fields.insert("email".to_string(), make_string_field(true));
-
-        let collection = CollectionDefinition {
-            path: "users/{name}.md".to_string(),
-            fields,
-            content: false,
-            additional_properties: false,
-            strict: true,
-            readonly: false,
-            on_delete: None,
-            id: None,
-            records: None,
-        };
+        let collection = make_collection("users/{name}.md", fields);
 
         let tokens = generate_partial_struct("users", &collection, &[]);
         let code = tokens.to_string();
@@ -289,4 +460,182 @@ mod tests {
         assert!(code.contains("pub struct UserPartial"));
         assert!(code.contains("Option < String >"));
     }
+
+    /// Declares items and keeps their source, so a test can check generated
+    /// tokens against code that actually compiles.
+    macro_rules! compiled {
+        ($source:ident, $($item:tt)*) => {
+            $($item)*
+            const $source: &str = stringify!($($item)*);
+        };
+    }
+
+    compiled! {
+        NOTE_SOURCE,
+        #[derive(Debug, Clone, Serialize, Deserialize)]
+        pub struct Note {
+            #[serde(default, skip_serializing_if = "Option::is_none")]
+            pub summary: Option<String>,
+            pub title: String,
+            #[serde(default = "Note::default_views")]
+            pub views: f64,
+        }
+
+        impl Note {
+            fn default_views() -> f64 {
+                3f64
+            }
+        }
+    }
+
+    compiled! {
+        EVENT_SOURCE,
+        #[derive(Debug, Clone, Serialize, Deserialize)]
+        pub struct Event {
+            #[serde(default = "Event::default_at")]
+            pub at: chrono::DateTime<chrono::Utc>,
+            #[serde(default = "Event::default_day")]
+            pub day: chrono::NaiveDate,
+        }
+
+        impl Event {
+            fn default_at() -> chrono::DateTime<chrono::Utc> {
+                chrono::TimeZone::timestamp_opt(&chrono::Utc, 1767268800i64, 0u32)
+                    .single()
+                    .unwrap_or_default()
+            }
+            fn default_day() -> chrono::NaiveDate {
+                chrono::NaiveDate::from_ymd_opt(2026i32, 1u32, 1u32).unwrap_or_default()
+            }
+        }
+    }
+
+    compiled! {
+        ACCOUNT_SOURCE,
+        #[derive(Debug, Clone, Serialize, Deserialize)]
+        pub struct Account {
+            #[serde(rename = "2fa-enabled")]
+            pub _2fa_enabled: bool,
+            #[serde(rename = "published-at")]
+            #[serde(default, skip_serializing_if = "Option::is_none")]
+            pub published_at: Option<String>,
+        }
+    }
+
+    #[test]
+    fn test_non_identifier_field_names_are_renamed() {
+        let enabled = FieldDefinition {
+            field_type: FieldType::Boolean,
+            ..make_string_field(true)
+        };
+        let fields = HashMap::from([
+            ("published-at".to_string(), make_string_field(false)),
+            ("2fa-enabled".to_string(), enabled),
+        ]);
+        let collection = make_collection("accounts/{id}.md", fields);
+
+        let code = generate_collection_struct("accounts", &collection, &[]).to_string();
+        assert!(code.contains("# [serde (rename = \"published-at\")]"), "{code}");
+        assert!(code.contains("pub published_at : Option < String >"), "{code}");
+        assert_generates(&code, ACCOUNT_SOURCE);
+
+        let yaml = "2fa-enabled: true\npublished-at: draft\n";
+        let account: Account = serde_yaml::from_str(yaml).unwrap();
+        assert!(account._2fa_enabled);
+        assert_eq!(account.published_at.as_deref(), Some("draft"));
+        assert_eq!(serde_yaml::to_string(&account).unwrap(), yaml);
+    }
+
+    #[test]
+    fn test_optional_fields_are_option_and_deserialize_when_missing() {
+        let mut views = FieldDefinition {
+            field_type: FieldType::Number,
+            ..make_string_field(false)
+        };
+        views.default = Some(serde_yaml::Value::from(3));
+        let fields = HashMap::from([
+            ("title".to_string(), make_string_field(true)),
+            ("summary".to_string(), make_string_field(false)),
+            ("views".to_string(), views),
+        ]);
+        let collection = make_collection("notes/{title}.md", fields);
+
+        let code = generate_collection_struct("notes", &collection, &[]).to_string();
+        assert!(code.contains("pub summary : Option < String >"), "{code}");
+        assert!(code.contains("pub title : String"), "{code}");
+        assert!(code.contains("pub views : f64"), "{code}");
+        assert_generates(&code, NOTE_SOURCE);
+
+        // A missing field takes the schema's default, not the type's
+        let note: Note = serde_yaml::from_str("title: Hello").unwrap();
+        assert_eq!(note.title, "Hello");
+        assert_eq!(note.summary, None);
+        assert_eq!(note.views, 3.0);
+        assert!(serde_yaml::from_str::<Note>("summary: No title").is_err());
+    }
+
+    #[test]
+    fn test_date_defaults_are_parsed_at_codegen_time() {
+        let field = |field_type, default: &str| FieldDefinition {
+            field_type,
+            default: Some(serde_yaml::Value::from(default)),
+            ..make_string_field(false)
+        };
+        let fields = HashMap::from([
+            ("day".to_string(), field(FieldType::Date, "2026-01-01")),
+            ("at".to_string(), field(FieldType::Datetime, "2026-01-01T12:00:00Z")),
+        ]);
+        let collection = make_collection("events/{id}.md", fields);
+
+        let code = generate_collection_struct("events", &collection, &[]).to_string();
+        assert!(!code.contains("unwrap ()"), "{code}");
+        assert_generates(&code, EVENT_SOURCE);
+        let event: Event = serde_yaml::from_str("{}").unwrap();
+        assert_eq!(event.day.to_string(), "2026-01-01");
+        assert_eq!(event.at.to_rfc3339(), "2026-01-01T12:00:00+00:00");
+
+        // A default that doesn't parse is a codegen error, not a runtime panic
+        let mut schema: SchemaDefinition =
+            serde_yaml::from_str("collections: {}").unwrap();
+        let fields = HashMap::from([
+            ("day".to_string(), field(FieldType::Date, "soon")),
+            ("at".to_string(), field(FieldType::Datetime, "2026-01-01")),
+        ]);
+        schema
+            .collections
+            .insert("events".to_string(), make_collection("events/{id}.md", fields));
+        assert_eq!(
+            codegen_errors(&schema),
+            [
+                "Collection 'events': field 'at' default \"2026-01-01\" is not an RFC 3339 datetime",
+                "Collection 'events': field 'day' default \"soon\" is not a YYYY-MM-DD date",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_fields_sharing_an_ident_are_a_codegen_error() {
+        let mut schema: SchemaDefinition =
+            serde_yaml::from_str("collections: {}").unwrap();
+        let fields = HashMap::from([
+            ("published-at".to_string(), make_string_field(false)),
+            ("published_at".to_string(), make_string_field(false)),
+            ("fooBar".to_string(), make_string_field(false)),
+            ("foo_bar".to_string(), make_string_field(false)),
+            ("title".to_string(), make_string_field(true)),
+        ]);
+        schema
+            .collections
+            .insert("posts".to_string(), make_collection("posts/{id}.md", fields));
+
+        assert_eq!(
+            codegen_errors(&schema),
+            [
+                "Collection 'posts': fields 'fooBar', 'foo_bar' all generate the Rust field \
+                 `foo_bar`; rename all but one",
+                "Collection 'posts': fields 'published-at', 'published_at' all generate the \
+                 Rust field `published_at`; rename all but one",
+            ]
+        );
+    }
 }
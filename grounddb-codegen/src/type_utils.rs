@@ -61,18 +61,16 @@ pub fn field_to_rust_type(
 ) -> TokenStream {
     let base_type = field_base_type(field, collection_name, field_name, known_types);
 
-    // Wrap in Option if not required and no default
+    // Wrap in Option if not required and no default. Lists default to an
+    // empty Vec instead; fields with a default get a serde default
     if !field.required && field.default.is_none() {
-        // Lists default to empty vec, objects default to empty value - don't wrap those
         match &field.field_type {
             FieldType::List => base_type,
-            FieldType::Object => {
-                quote! { Option<#base_type> }
-            }
-            _ => {
-                quote! { Option<#base_type> }
-            }
+            _ => quote! { Option<#base_type> },
         }
+    } else if field.required && field.nullable == Some(true) {
+        // Required-but-nullable: always present, possibly null
+        quote! { Option<#base_type> }
     } else {
         base_type
     }
@@ -85,8 +83,8 @@ pub fn field_base_type(
     field_name: &str,
     known_types: &[String],
 ) -> TokenStream {
-    // If field has enum values, use the generated enum type
-    if field.enum_values.is_some() {
+    // If a string field has enum values, use the generated enum type
+    if field.string_enum_values().is_some() {
         let name = enum_type_name(collection_name, field_name);
         let ident = format_ident!("{}", name);
         return quote! { #ident };
@@ -131,6 +129,7 @@ fn list_item_type(
             "date" => quote! { chrono::NaiveDate },
             "datetime" => quote! { chrono::DateTime<chrono::Utc> },
             "object" => quote! { serde_json::Value },
+            "ref" => quote! { String },
             other => {
                 if known_types.contains(&other.to_string()) {
                     let ident = format_ident!("{}", other.to_pascal_case());
@@ -157,6 +156,8 @@ fn list_item_type(
 /// Get the Rust type for a ref field.
 fn ref_rust_type(field: &FieldDefinition, field_name: &str) -> TokenStream {
     match &field.target {
+        // Denormalized refs are stored as `{ id, <copied fields> }`
+        Some(RefTarget::Single(_)) if field.denormalize.is_some() => quote! { serde_json::Value },
         Some(RefTarget::Single(_)) => quote! { String },
         Some(RefTarget::Multiple(_)) => {
             let name = ref_enum_name(field_name);
@@ -183,18 +184,28 @@ pub fn singularize(word: &str) -> String {
     }
 }
 
-/// Check if a field name is a Rust keyword and needs raw identifier syntax.
+/// Turn a YAML field name into a Rust field identifier: snake_case, with a
+/// leading `_` if it would start with a digit, and raw identifier syntax for
+/// keywords. `self`, `super` and `crate` can't be raw identifiers, so they get
+/// a trailing `_`. Callers emit `#[serde(rename)]` whenever the result differs
+/// from the YAML key (e.g. `published-at` becomes `published_at`).
 pub fn safe_field_ident(name: &str) -> proc_macro2::Ident {
-    match name {
-        "type" | "struct" | "enum" | "fn" | "let" | "mut" | "ref" | "self" | "super" | "crate"
-        | "mod" | "use" | "pub" | "impl" | "trait" | "for" | "loop" | "while" | "if" | "else"
-        | "match" | "return" | "break" | "continue" | "as" | "in" | "where" | "async"
-        | "await" | "dyn" | "move" | "static" | "const" | "unsafe" | "extern" | "true"
-        | "false" | "abstract" | "become" | "box" | "do" | "final" | "macro" | "override"
-        | "priv" | "typeof" | "unsized" | "virtual" | "yield" | "try" => {
-            format_ident!("r#{}", name)
-        }
-        _ => format_ident!("{}", name.to_snake_case()),
+    let mut snake = name.to_snake_case();
+    if snake.is_empty() {
+        snake.push_str("field");
+    } else if snake.starts_with(|c: char| c.is_ascii_digit()) {
+        snake.insert(0, '_');
+    }
+
+    match snake.as_str() {
+        "self" | "super" | "crate" => format_ident!("{}_", snake),
+        "type" | "struct" | "enum" | "fn" | "let" | "mut" | "ref" | "mod" | "use" | "pub"
+        | "impl" | "trait" | "for" | "loop" | "while" | "if" | "else" | "match" | "return"
+        | "break" | "continue" | "as" | "in" | "where" | "async" | "await" | "dyn" | "move"
+        | "static" | "const" | "unsafe" | "extern" | "true" | "false" | "abstract"
+        | "become" | "box" | "do" | "final" | "macro" | "override" | "priv" | "typeof"
+        | "unsized" | "virtual" | "yield" | "try" => format_ident!("r#{}", snake),
+        _ => format_ident!("{}", snake),
     }
 }
 
@@ -250,5 +261,11 @@ mod tests {
 
         let ident = safe_field_ident("name");
         assert_eq!(ident.to_string(), "name");
+
+        assert_eq!(safe_field_ident("published-at").to_string(), "published_at");
+        assert_eq!(safe_field_ident("2fa-enabled").to_string(), "_2fa_enabled");
+        assert_eq!(safe_field_ident("Type").to_string(), "r#type");
+        assert_eq!(safe_field_ident("self").to_string(), "self_");
+        assert_eq!(safe_field_ident("--").to_string(), "field");
     }
 }
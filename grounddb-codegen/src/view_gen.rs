@@ -269,7 +269,7 @@ fn resolve_column_type(
     // Map field type to Rust type (simplified for views - no Option wrapping)
     match &field_def.field_type {
         FieldType::String => {
-            if field_def.enum_values.is_some() {
+            if field_def.string_enum_values().is_some() {
                 let enum_name = crate::type_utils::enum_type_name(&collection_name, &col.column_name);
                 let ident = format_ident!("{}", enum_name);
                 quote! { #ident }
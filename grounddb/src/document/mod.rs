@@ -1,6 +1,9 @@
 use crate::error::{GroundDbError, Result};
 use chrono::{DateTime, Utc};
+use serde::de::{DeserializeSeed, Deserializer, MapAccess, SeqAccess, Visitor};
 use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::io::BufRead;
 use std::path::Path;
 
 /// A loaded document with implicit fields and typed data
@@ -16,26 +19,113 @@ pub struct Document<T> {
 /// The front matter separator used in Markdown documents
 const FRONT_MATTER_FENCE: &str = "---";
 
+/// The byte order mark some editors put at the start of UTF-8 files; skipped
+/// when reading
+const BOM: char = '\u{feff}';
+
+/// Front matter key that pins a document's `created_at`, since file creation
+/// times can't be set
+pub const CREATED_AT_KEY: &str = "created_at";
+
 /// Read a markdown document from disk.
 /// Parses YAML front matter and optional markdown body.
 /// The `id` is derived from the filename (without extension).
 pub fn read_document(path: &Path) -> Result<Document<serde_yaml::Value>> {
-    let raw = std::fs::read_to_string(path)?;
-    let metadata = std::fs::metadata(path)?;
+    let raw = read_text(path)?;
+    let (data, content) = parse_front_matter(&raw)?;
+    document_at(path, data, content)
+}
+
+/// Read a file as UTF-8 text. Invalid UTF-8 is a `Parse` error naming the
+/// file and the offset of the first bad byte.
+fn read_text(path: &Path) -> Result<String> {
+    String::from_utf8(std::fs::read(path)?).map_err(|e| invalid_utf8(path, e.utf8_error()))
+}
+
+fn invalid_utf8(path: &Path, error: std::str::Utf8Error) -> GroundDbError {
+    GroundDbError::Parse {
+        path: path.display().to_string(),
+        reason: format!("not valid UTF-8 (invalid byte at offset {})", error.valid_up_to()),
+    }
+}
+
+/// Read a document's file verbatim: the front matter block between the
+/// fences (empty if there is none), the body, and the whole file text. Nothing
+/// is reserialized, so formatting, comments and key order are kept as written.
+/// A leading byte order mark stays in the whole file text but, as when parsing,
+/// not in the front matter or body.
+pub fn read_raw(path: &Path) -> Result<(String, Option<String>, String)> {
+    let raw = read_text(path)?;
+    let (front_matter, body) = split_raw(&raw);
+    Ok((
+        front_matter.unwrap_or_default().to_string(),
+        body.map(str::to_string),
+        raw,
+    ))
+}
+
+/// Read only a document's front matter, stopping at the closing fence so a
+/// large body is never read. The returned document has no `content`.
+pub fn read_front_matter_only(path: &Path) -> Result<Document<serde_yaml::Value>> {
+    let file = std::fs::File::open(path)?;
+    let data = read_front_matter(std::io::BufReader::new(file)).map_err(|e| match e {
+        GroundDbError::Io(io) if io.kind() == std::io::ErrorKind::InvalidData => {
+            GroundDbError::Parse {
+                path: path.display().to_string(),
+                reason: "not valid UTF-8".to_string(),
+            }
+        }
+        other => other,
+    })?;
+    document_at(path, data, None)
+}
+
+/// Parse front matter from `reader` line by line, consuming nothing past the
+/// closing fence. Mirrors `parse_front_matter`: no opening fence means no data,
+/// and a missing closing fence makes the rest of the input YAML.
+fn read_front_matter(mut reader: impl BufRead) -> Result<serde_yaml::Value> {
+    let mut line = String::new();
+
+    // The opening fence, after any leading blank lines
+    let opening = loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            return Ok(serde_yaml::Value::Mapping(serde_yaml::Mapping::new()));
+        }
+        let trimmed = line.trim_start_matches(BOM).trim_start();
+        if !trimmed.is_empty() {
+            break trimmed.to_string();
+        }
+    };
+    let Some(rest) = opening.strip_prefix(FRONT_MATTER_FENCE) else {
+        return Ok(serde_yaml::Value::Mapping(serde_yaml::Mapping::new()));
+    };
+
+    let mut block = rest.trim_start_matches(['\r', '\n']).to_string();
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 || is_fence(&line) {
+            break;
+        }
+        block.push_str(&line);
+    }
+    parse_front_matter_block(&block)
+}
 
+/// Build a `Document` for the file at `path` from its parsed parts, with the id
+/// from the filename and timestamps from [`document_timestamps`].
+fn document_at(
+    path: &Path,
+    data: serde_yaml::Value,
+    content: Option<String>,
+) -> Result<Document<serde_yaml::Value>> {
     let id = path
         .file_stem()
         .and_then(|s| s.to_str())
         .ok_or_else(|| GroundDbError::Other(format!("Cannot extract ID from path: {path:?}")))?
         .to_string();
 
-    let created_at = metadata
-        .created()
-        .unwrap_or(metadata.modified()?)
-        .into();
-    let modified_at: DateTime<Utc> = metadata.modified()?.into();
-
-    let (data, content) = parse_front_matter(&raw)?;
+    let (created_at, modified_at) = document_timestamps(path, &data)?;
 
     Ok(Document {
         id,
@@ -46,55 +136,245 @@ pub fn read_document(path: &Path) -> Result<Document<serde_yaml::Value>> {
     })
 }
 
+/// A document's `created_at` and `modified_at`. `modified_at` is the file's
+/// mtime. `created_at` is the front matter's `created_at` when it holds an
+/// RFC 3339 timestamp (imported documents carry their original one), otherwise
+/// the file's creation time, falling back to its mtime.
+pub fn document_timestamps(
+    path: &Path,
+    data: &serde_yaml::Value,
+) -> Result<(DateTime<Utc>, DateTime<Utc>)> {
+    let metadata = std::fs::metadata(path)?;
+    let modified_at: DateTime<Utc> = metadata.modified()?.into();
+    let created_at = match front_matter_created_at(data) {
+        Some(created_at) => created_at,
+        None => metadata.created().unwrap_or(metadata.modified()?).into(),
+    };
+    Ok((created_at, modified_at))
+}
+
+/// The RFC 3339 `created_at` recorded in front matter, if any
+fn front_matter_created_at(data: &serde_yaml::Value) -> Option<DateTime<Utc>> {
+    let value = data.get(CREATED_AT_KEY)?.as_str()?;
+    DateTime::parse_from_rfc3339(value)
+        .ok()
+        .map(|t| t.with_timezone(&Utc))
+}
+
+/// Read a markdown document from disk like [`read_document`], but recover
+/// from front matter that strict YAML rejects: tabs used for indentation are
+/// replaced with spaces and duplicate keys keep their last value. Each repair
+/// is described in the returned warnings; a well-formed document has none.
+pub fn read_document_lenient(path: &Path) -> Result<(Document<serde_yaml::Value>, Vec<String>)> {
+    let raw = read_text(path)?;
+    let mut warnings = Vec::new();
+    let (data, content) =
+        split_front_matter(&raw, |block| parse_front_matter_block_lenient(block, &mut warnings))?;
+    Ok((document_at(path, data, content)?, warnings))
+}
+
 /// Parse a markdown string into front matter data and optional body content.
 pub fn parse_front_matter(raw: &str) -> Result<(serde_yaml::Value, Option<String>)> {
+    split_front_matter(raw, parse_front_matter_block)
+}
+
+/// Split a markdown string at its front matter fences, parsing the front
+/// matter with `parse_block`.
+fn split_front_matter(
+    raw: &str,
+    mut parse_block: impl FnMut(&str) -> Result<serde_yaml::Value>,
+) -> Result<(serde_yaml::Value, Option<String>)> {
+    let (block, body) = split_raw(raw);
+    let data = match block {
+        Some(block) => parse_block(block)?,
+        // No front matter -- treat entire content as body with empty data
+        None => serde_yaml::Value::Mapping(serde_yaml::Mapping::new()),
+    };
+    Ok((data, body.map(str::to_string)))
+}
+
+/// Split a file's text into its front matter block (between the fences, if
+/// any) and its non-blank body, both as slices of `raw`.
+fn split_raw(raw: &str) -> (Option<&str>, Option<&str>) {
+    let raw = raw.strip_prefix(BOM).unwrap_or(raw);
     let trimmed = raw.trim_start();
 
     if !trimmed.starts_with(FRONT_MATTER_FENCE) {
-        // No front matter -- treat entire content as body with empty data
-        return Ok((
-            serde_yaml::Value::Mapping(serde_yaml::Mapping::new()),
-            if raw.trim().is_empty() {
-                None
-            } else {
-                Some(raw.to_string())
-            },
-        ));
+        let body = if raw.trim().is_empty() { None } else { Some(raw) };
+        return (None, body);
     }
 
-    // Find the second --- fence
+    // Find the closing fence: the first line after the opening one that is
+    // exactly `---`. Later `---` lines (e.g. horizontal rules) are body text.
     let after_first = &trimmed[3..];
     let after_first = after_first.trim_start_matches(|c: char| c == '\r' || c == '\n');
 
-    if let Some(end_pos) = after_first.find("\n---") {
-        let yaml_str = &after_first[..end_pos];
-        let after_fence = &after_first[end_pos + 4..]; // skip "\n---"
-
-        // Skip any trailing newlines after the closing fence
-        let body = after_fence.strip_prefix('\r').unwrap_or(after_fence);
-        let body = body.strip_prefix('\n').unwrap_or(body);
-
-        let data: serde_yaml::Value = if yaml_str.trim().is_empty() {
-            serde_yaml::Value::Mapping(serde_yaml::Mapping::new())
-        } else {
-            serde_yaml::from_str(yaml_str)?
-        };
-
-        let content = if body.trim().is_empty() {
+    if let Some((fence_start, fence_end)) = find_fence_line(after_first) {
+        let body = &after_first[fence_end..];
+        let body = if body.trim().is_empty() {
             None
         } else {
-            Some(body.to_string())
+            Some(body)
         };
-
-        Ok((data, content))
+        (Some(&after_first[..fence_start]), body)
     } else {
         // Only one fence -- entire content after first --- is YAML (data-only doc)
-        let data: serde_yaml::Value = if after_first.trim().is_empty() {
-            serde_yaml::Value::Mapping(serde_yaml::Mapping::new())
-        } else {
-            serde_yaml::from_str(after_first)?
-        };
-        Ok((data, None))
+        (Some(after_first), None)
+    }
+}
+
+/// Whether `line` (with or without its line ending) is a front matter fence.
+fn is_fence(line: &str) -> bool {
+    line.trim_end() == FRONT_MATTER_FENCE
+}
+
+/// Byte range of the first fence line in `text`, including its line ending.
+fn find_fence_line(text: &str) -> Option<(usize, usize)> {
+    let mut offset = 0;
+    for line in text.split_inclusive('\n') {
+        if is_fence(line) {
+            return Some((offset, offset + line.len()));
+        }
+        offset += line.len();
+    }
+    None
+}
+
+/// Parse the text between the front matter fences. A block starting with `{`
+/// is parsed as JSON (as written by some tooling), anything else as YAML.
+fn parse_front_matter_block(block: &str) -> Result<serde_yaml::Value> {
+    let trimmed = block.trim();
+    if trimmed.is_empty() {
+        Ok(serde_yaml::Value::Mapping(serde_yaml::Mapping::new()))
+    } else if trimmed.starts_with('{') {
+        Ok(serde_json::from_str(trimmed)?)
+    } else {
+        Ok(serde_yaml::from_str(block)?)
+    }
+}
+
+/// Parse front matter like `parse_front_matter_block`, falling back to
+/// repairing tab indentation and duplicate keys when strict parsing fails.
+/// Repairs are appended to `warnings`; if the repaired block still doesn't
+/// parse, the original error is returned.
+fn parse_front_matter_block_lenient(
+    block: &str,
+    warnings: &mut Vec<String>,
+) -> Result<serde_yaml::Value> {
+    let error = match parse_front_matter_block(block) {
+        Ok(data) => return Ok(data),
+        Err(e) => e,
+    };
+
+    let normalized = replace_indent_tabs(block);
+    let repairs = RefCell::new(Vec::new());
+    if normalized != block {
+        repairs.borrow_mut().push("tabs in indentation replaced with spaces".to_string());
+    }
+    let seed = LastKeyWins { repairs: &repairs };
+    match seed.deserialize(serde_yaml::Deserializer::from_str(&normalized)) {
+        Ok(data) => {
+            warnings.extend(repairs.into_inner());
+            Ok(data)
+        }
+        Err(_) => Err(error),
+    }
+}
+
+/// Replace each tab in a line's leading whitespace, and each tab right after
+/// a `key:`, with spaces. YAML forbids tabs there.
+fn replace_indent_tabs(block: &str) -> String {
+    block
+        .split_inclusive('\n')
+        .map(|line| {
+            let body = line.trim_start_matches([' ', '\t']);
+            let indent = &line[..line.len() - body.len()];
+            format!("{}{}", indent.replace('\t', "  "), body.replace(":\t", ": "))
+        })
+        .collect()
+}
+
+/// Deserializes a YAML value, letting a repeated mapping key replace the
+/// earlier entry (strict parsing rejects it) and recording each one.
+#[derive(Clone, Copy)]
+struct LastKeyWins<'a> {
+    repairs: &'a RefCell<Vec<String>>,
+}
+
+impl<'de> DeserializeSeed<'de> for LastKeyWins<'_> {
+    type Value = serde_yaml::Value;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> std::result::Result<Self::Value, D::Error> {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de> Visitor<'de> for LastKeyWins<'_> {
+    type Value = serde_yaml::Value;
+
+    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str("a YAML value")
+    }
+
+    fn visit_bool<E>(self, v: bool) -> std::result::Result<Self::Value, E> {
+        Ok(v.into())
+    }
+
+    fn visit_i64<E>(self, v: i64) -> std::result::Result<Self::Value, E> {
+        Ok(v.into())
+    }
+
+    fn visit_u64<E>(self, v: u64) -> std::result::Result<Self::Value, E> {
+        Ok(v.into())
+    }
+
+    fn visit_f64<E>(self, v: f64) -> std::result::Result<Self::Value, E> {
+        Ok(v.into())
+    }
+
+    fn visit_str<E>(self, v: &str) -> std::result::Result<Self::Value, E> {
+        Ok(v.into())
+    }
+
+    fn visit_string<E>(self, v: String) -> std::result::Result<Self::Value, E> {
+        Ok(v.into())
+    }
+
+    fn visit_unit<E>(self) -> std::result::Result<Self::Value, E> {
+        Ok(serde_yaml::Value::Null)
+    }
+
+    fn visit_none<E>(self) -> std::result::Result<Self::Value, E> {
+        Ok(serde_yaml::Value::Null)
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> std::result::Result<Self::Value, D::Error> {
+        self.deserialize(deserializer)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> std::result::Result<Self::Value, A::Error> {
+        let mut items = Vec::new();
+        while let Some(item) = seq.next_element_seed(self)? {
+            items.push(item);
+        }
+        Ok(serde_yaml::Value::Sequence(items))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> std::result::Result<Self::Value, A::Error> {
+        let mut mapping = serde_yaml::Mapping::new();
+        while let Some(key) = map.next_key_seed(self)? {
+            let value = map.next_value_seed(self)?;
+            if mapping.insert(key.clone(), value).is_some() {
+                let name = match &key {
+                    serde_yaml::Value::String(name) => name.clone(),
+                    other => serde_yaml::to_string(other).unwrap_or_default().trim().to_string(),
+                };
+                self.repairs
+                    .borrow_mut()
+                    .push(format!("duplicate key '{name}', kept the last value"));
+            }
+        }
+        Ok(serde_yaml::Value::Mapping(mapping))
     }
 }
 
@@ -153,6 +433,59 @@ pub fn write_document(
     Ok(())
 }
 
+/// Write a document like [`write_document`], keeping the diff against the file
+/// currently at `previous` (usually `path` itself) small: front matter keys keep
+/// their on-disk order with new keys appended, and the body is separated by one
+/// blank line and ends with exactly one newline.
+pub fn write_document_stable(
+    path: &Path,
+    previous: &Path,
+    data: &serde_yaml::Value,
+    content: Option<&str>,
+) -> Result<()> {
+    let data = match read_document(previous) {
+        Ok(existing) => order_keys_like(data, &existing.data),
+        Err(_) => data.clone(),
+    };
+    let content = content.map(normalize_body);
+    write_document(path, &data, content.as_deref())
+}
+
+/// Reorder the top-level keys of `data` to follow `reference`; keys missing
+/// from `reference` keep their relative order after the known ones.
+fn order_keys_like(data: &serde_yaml::Value, reference: &serde_yaml::Value) -> serde_yaml::Value {
+    let (Some(map), Some(reference)) = (data.as_mapping(), reference.as_mapping()) else {
+        return data.clone();
+    };
+    let mut ordered = serde_yaml::Mapping::new();
+    for key in reference.keys() {
+        if let Some(value) = map.get(key) {
+            ordered.insert(key.clone(), value.clone());
+        }
+    }
+    for (key, value) in map {
+        if !ordered.contains_key(key) {
+            ordered.insert(key.clone(), value.clone());
+        }
+    }
+    serde_yaml::Value::Mapping(ordered)
+}
+
+/// Drop the blank separator line a parsed body starts with (serialization adds
+/// it back) and collapse trailing newlines to one.
+fn normalize_body(body: &str) -> String {
+    let body = body
+        .strip_prefix("\r\n")
+        .or_else(|| body.strip_prefix('\n'))
+        .unwrap_or(body);
+    let trimmed = body.trim_end_matches(['\r', '\n']);
+    if trimmed.is_empty() {
+        String::new()
+    } else {
+        format!("{trimmed}\n")
+    }
+}
+
 /// Delete a document from disk.
 pub fn delete_document(path: &Path) -> Result<()> {
     std::fs::remove_file(path)?;
@@ -192,6 +525,45 @@ pub fn move_document(from: &Path, to: &Path) -> Result<()> {
     Ok(())
 }
 
+/// Structure extracted from a Markdown body for indexing
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ContentStructure {
+    /// Text of the first level-1 heading
+    pub title: Option<String>,
+    /// Destinations of all links, in document order
+    pub links: Vec<String>,
+}
+
+/// Parse a Markdown body and extract its first H1 and outbound links.
+pub fn parse_content_structure(body: &str) -> ContentStructure {
+    use pulldown_cmark::{Event, HeadingLevel, Parser, Tag, TagEnd};
+
+    let mut structure = ContentStructure::default();
+    let mut in_h1 = false;
+    let mut heading = String::new();
+
+    for event in Parser::new(body) {
+        match event {
+            Event::Start(Tag::Heading { level: HeadingLevel::H1, .. })
+                if structure.title.is_none() =>
+            {
+                in_h1 = true;
+            }
+            Event::End(TagEnd::Heading(HeadingLevel::H1)) if in_h1 => {
+                in_h1 = false;
+                structure.title = Some(heading.trim().to_string());
+            }
+            Event::Text(text) | Event::Code(text) if in_h1 => heading.push_str(&text),
+            Event::Start(Tag::Link { dest_url, .. }) => {
+                structure.links.push(dest_url.to_string());
+            }
+            _ => {}
+        }
+    }
+
+    structure
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -212,6 +584,18 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_parse_content_structure() {
+        let body = "Intro with [a link](https://example.com).\n\n# The `Real` Title\n\n# Second\n\nSee [docs](../docs/guide.md).\n";
+        let structure = parse_content_structure(body);
+        assert_eq!(structure.title.as_deref(), Some("The Real Title"));
+        assert_eq!(
+            structure.links,
+            vec!["https://example.com".to_string(), "../docs/guide.md".to_string()]
+        );
+        assert_eq!(parse_content_structure("## Only H2\n"), ContentStructure::default());
+    }
+
     #[test]
     fn test_parse_content_document() {
         let raw = "---\ntitle: My Post\nstatus: draft\n---\n\n## Summary\n\nHello world.\n";
@@ -225,6 +609,148 @@ mod tests {
         assert!(body.contains("Hello world."));
     }
 
+    #[test]
+    fn test_read_json_front_matter() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("post.md");
+        let raw = "---\n{\n  \"title\": \"JSON Post\",\n  \"meta\": {\"views\": 3, \"draft\": false},\n  \"tags\": [\"a\", \"b\"]\n}\n---\n\nBody text.\n";
+        std::fs::write(&path, raw).unwrap();
+
+        let doc = read_document(&path).unwrap();
+        assert_eq!(doc.data["title"], serde_yaml::Value::String("JSON Post".into()));
+        assert_eq!(doc.data["meta"]["views"], serde_yaml::Value::Number(3.into()));
+        assert_eq!(doc.data["meta"]["draft"], serde_yaml::Value::Bool(false));
+        assert_eq!(
+            doc.data["tags"],
+            serde_yaml::Value::Sequence(vec!["a".into(), "b".into()])
+        );
+        assert_eq!(doc.content.as_deref(), Some("\nBody text.\n"));
+    }
+
+    #[test]
+    fn test_read_strips_utf8_bom() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("note.md");
+        std::fs::write(&path, "\u{feff}---\ntitle: Windows\n---\nBody.\n").unwrap();
+
+        let doc = read_document(&path).unwrap();
+        assert_eq!(doc.data["title"], serde_yaml::Value::String("Windows".into()));
+        assert_eq!(doc.content.as_deref(), Some("Body.\n"));
+        let front = read_front_matter_only(&path).unwrap();
+        assert_eq!(front.data, doc.data);
+
+        let (front_matter, body, raw) = read_raw(&path).unwrap();
+        assert_eq!(front_matter, "title: Windows\n");
+        assert_eq!(body.as_deref(), Some("Body.\n"));
+        assert_eq!(raw, "\u{feff}---\ntitle: Windows\n---\nBody.\n");
+    }
+
+    #[test]
+    fn test_read_invalid_utf8_names_the_file() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("latin1.md");
+        std::fs::write(&path, b"---\ntitle: caf\xe9\n---\n").unwrap();
+
+        for result in [read_document(&path), read_front_matter_only(&path)] {
+            match result {
+                Err(GroundDbError::Parse { path: p, reason }) => {
+                    assert!(p.ends_with("latin1.md"), "{p}");
+                    assert!(reason.contains("UTF-8"), "{reason}");
+                }
+                other => panic!("expected a parse error, got {:?}", other.map(|d| d.id)),
+            }
+        }
+    }
+
+    #[test]
+    fn test_read_lenient_repairs_duplicate_keys_and_tabs() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("post.md");
+        let raw = "---\ntitle: Draft\nmeta:\n\tviews: 3\ntitle: Final\n---\n\nBody.\n";
+        std::fs::write(&path, raw).unwrap();
+        assert!(read_document(&path).is_err());
+
+        let (doc, warnings) = read_document_lenient(&path).unwrap();
+        assert_eq!(doc.data["title"], serde_yaml::Value::String("Final".into()));
+        assert_eq!(doc.data["meta"]["views"], serde_yaml::Value::Number(3.into()));
+        assert_eq!(doc.content.as_deref(), Some("\nBody.\n"));
+        assert_eq!(
+            warnings,
+            [
+                "tabs in indentation replaced with spaces",
+                "duplicate key 'title', kept the last value",
+            ]
+        );
+
+        // Well-formed documents read the same with no warnings
+        std::fs::write(&path, "---\ntitle: Clean\n---\n").unwrap();
+        let (doc, warnings) = read_document_lenient(&path).unwrap();
+        assert_eq!(doc.data["title"], serde_yaml::Value::String("Clean".into()));
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn test_read_front_matter_only_matches_full_read() {
+        let tmp = TempDir::new().unwrap();
+        let samples = [
+            "---\ntitle: Post\ntags: [a, b]\n---\n\n# Heading\n\n---\n\nMore body.\n",
+            "\n\n---\r\nname: Crlf\r\n---\r\nBody\r\n",
+            "---\n{\"title\": \"JSON\"}\n---\nBody\n",
+            "---\nname: Data only\n",
+            "No front matter at all.\n",
+            "",
+        ];
+        for (i, raw) in samples.iter().enumerate() {
+            let path = tmp.path().join(format!("doc-{i}.md"));
+            std::fs::write(&path, raw).unwrap();
+
+            let full = read_document(&path).unwrap();
+            let front = read_front_matter_only(&path).unwrap();
+            assert_eq!(front.data, full.data, "{raw:?}");
+            assert_eq!(front.id, full.id);
+            assert_eq!(front.modified_at, full.modified_at);
+            assert!(front.content.is_none());
+        }
+    }
+
+    #[test]
+    fn test_read_front_matter_stops_at_closing_fence() {
+        // A 4 MB body: the front matter reader must not consume any of it
+        let front = "---\ntitle: Big\nstatus: draft\n---\n";
+        let raw = format!("{front}{}", "lorem ipsum dolor\n".repeat(4 * 1024 * 1024 / 18));
+        let mut reader = std::io::Cursor::new(raw.as_bytes());
+
+        let data = read_front_matter(&mut reader).unwrap();
+        assert_eq!(data["title"], serde_yaml::Value::String("Big".into()));
+        assert_eq!(reader.position() as usize, front.len());
+
+        let (full, _) = parse_front_matter(&raw).unwrap();
+        assert_eq!(full, data);
+    }
+
+    #[test]
+    fn test_horizontal_rules_in_body_round_trip() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("post.md");
+        let data: serde_yaml::Value = serde_yaml::from_str("title: Rules\nstatus: draft").unwrap();
+        let body = "Intro\n\n---\n\nMiddle\n---\n----\n--- not a fence\n\nEnd\n---\n";
+
+        write_document(&path, &data, Some(body)).unwrap();
+        let doc = read_document(&path).unwrap();
+        assert_eq!(doc.data, data);
+        assert_eq!(doc.content.as_deref().map(|c| c.trim_start_matches('\n')), Some(body));
+        assert_eq!(read_front_matter_only(&path).unwrap().data, data);
+    }
+
+    #[test]
+    fn test_empty_front_matter_before_horizontal_rule() {
+        let raw = "---\n---\n---\nBody\n";
+        let (data, content) = parse_front_matter(raw).unwrap();
+        assert_eq!(data, serde_yaml::Value::Mapping(serde_yaml::Mapping::new()));
+        assert_eq!(content.as_deref(), Some("---\nBody\n"));
+        assert_eq!(read_front_matter(raw.as_bytes()).unwrap(), data);
+    }
+
     #[test]
     fn test_parse_empty_body() {
         let raw = "---\nname: Test\n---\n";
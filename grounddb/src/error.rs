@@ -11,8 +11,24 @@ pub enum GroundDbError {
     #[error("Document not found: {collection}/{id}")]
     NotFound { collection: String, id: String },
 
-    #[error("Path conflict: {path}")]
-    PathConflict { path: String },
+    #[error("Path conflict: {path} (existing document '{existing_id}')")]
+    PathConflict { path: String, existing_id: String },
+
+    #[error("Conflict: {collection}/{id} was modified on disk since it was indexed: {reason}")]
+    Conflict {
+        collection: String,
+        id: String,
+        reason: String,
+    },
+
+    #[error("Failed to read document {path}: {reason}")]
+    Parse { path: String, reason: String },
+
+    #[error("Store is locked: {path} is held by {holder}")]
+    Locked { path: String, holder: String },
+
+    #[error("Subscription overflowed its capacity of {capacity} events")]
+    SubscriptionOverflow { capacity: usize },
 
     #[error("Referential integrity violation: {0}")]
     ReferentialIntegrity(String),
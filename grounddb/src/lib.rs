@@ -48,9 +48,11 @@ pub mod migration;
 pub mod watcher;
 pub mod error;
 pub mod util;
+mod trace;
 
 pub use error::{GroundDbError, Result};
-pub use schema::SchemaDefinition;
-pub use store::{Store, Collection, Batch, SubscriptionId, ChangeEvent};
+pub use schema::{SchemaDefinition, validate_schema_str};
+pub use store::{Store, Collection, Batch, SubscriptionId, SnapshotId, ChangeEvent, QueryBuilder, SortOrder, StoreOptions, ConflictPolicy, BoundedSubscription, OverflowPolicy};
 pub use document::Document;
+pub use validation::Validator;
 pub use view::ViewEngine;
@@ -1,11 +1,17 @@
-use crate::schema::SchemaDefinition;
-use std::collections::HashSet;
+use crate::schema::{SchemaDefinition, ViewDefinition};
+use std::collections::{HashMap, HashSet};
 
 /// Represents a single schema change detected between two schema versions.
 #[derive(Debug, Clone, PartialEq)]
 pub enum SchemaMigration {
     CollectionAdded { name: String },
     CollectionRemoved { name: String },
+    CollectionRenamed { from: String, to: String },
+    ViewRenamed { from: String, to: String },
+    ViewAdded { name: String },
+    ViewRemoved { name: String },
+    /// The view's query, type, params, buffer or materialization changed
+    ViewChanged { name: String },
     FieldAdded {
         collection: String,
         field: String,
@@ -46,6 +52,11 @@ impl SchemaMigration {
     pub fn is_safe(&self) -> bool {
         match self {
             SchemaMigration::CollectionAdded { .. } => true,
+            SchemaMigration::CollectionRenamed { .. } => true,
+            SchemaMigration::ViewRenamed { .. } => true,
+            SchemaMigration::ViewAdded { .. } => true,
+            SchemaMigration::ViewRemoved { .. } => true,
+            SchemaMigration::ViewChanged { .. } => true,
             SchemaMigration::EnumValueAdded { .. } => true,
             SchemaMigration::FieldAdded { has_default, required, .. } => {
                 // Safe if has a default or is not required
@@ -65,6 +76,21 @@ impl SchemaMigration {
             SchemaMigration::CollectionRemoved { name } => {
                 format!("Collection '{}' removed (data preserved)", name)
             }
+            SchemaMigration::CollectionRenamed { from, to } => {
+                format!("Collection '{}' renamed to '{}' (will move documents)", from, to)
+            }
+            SchemaMigration::ViewRenamed { from, to } => {
+                format!("View '{}' renamed to '{}' (will keep cached rows)", from, to)
+            }
+            SchemaMigration::ViewAdded { name } => {
+                format!("View '{}' added", name)
+            }
+            SchemaMigration::ViewRemoved { name } => {
+                format!("View '{}' removed (will drop cached rows)", name)
+            }
+            SchemaMigration::ViewChanged { name } => {
+                format!("View '{}' changed (will rebuild)", name)
+            }
             SchemaMigration::FieldAdded { collection, field, has_default, required } => {
                 let qualifier = if *required && !*has_default {
                     " (required, no default — ERROR)"
@@ -107,8 +133,54 @@ pub fn diff_schemas(old: &SchemaDefinition, new: &SchemaDefinition) -> Vec<Schem
     let old_names: HashSet<&String> = old.collections.keys().collect();
     let new_names: HashSet<&String> = new.collections.keys().collect();
 
+    // Collections renamed: a new collection whose `renamed_from` names a
+    // collection that existed before and is gone now. Maps new name -> old name.
+    let mut renames: HashMap<&String, &String> = HashMap::new();
+    for name in new_names.difference(&old_names) {
+        if let Some(from) = &new.collections[*name].renamed_from {
+            if old_names.contains(from) && !new_names.contains(from) {
+                renames.insert(*name, from);
+            }
+        }
+    }
+    let renamed_old: HashSet<&String> = renames.values().copied().collect();
+
+    // Renames come first so later field migrations see the new collection name
+    let mut rename_list: Vec<_> = renames.iter().collect();
+    rename_list.sort();
+    for (to, from) in &rename_list {
+        migrations.push(SchemaMigration::CollectionRenamed {
+            from: (**from).clone(),
+            to: (**to).clone(),
+        });
+    }
+
+    // Views renamed, like collections: the old view is gone and a new one
+    // names it in `renamed_from`
+    let mut view_renames: Vec<(&String, &String)> = new
+        .views
+        .iter()
+        .filter(|(name, _)| !old.views.contains_key(*name))
+        .filter_map(|(name, view)| {
+            let from = view.renamed_from.as_ref()?;
+            let gone = old.views.contains_key(from) && !new.views.contains_key(from);
+            gone.then_some((name, from))
+        })
+        .collect();
+    view_renames.sort();
+    for (to, from) in &view_renames {
+        migrations.push(SchemaMigration::ViewRenamed {
+            from: (*from).clone(),
+            to: (*to).clone(),
+        });
+    }
+    migrations.extend(diff_views(old, new, &view_renames));
+
     // Collections added
     for name in new_names.difference(&old_names) {
+        if renames.contains_key(*name) {
+            continue;
+        }
         migrations.push(SchemaMigration::CollectionAdded {
             name: (*name).clone(),
         });
@@ -116,20 +188,29 @@ pub fn diff_schemas(old: &SchemaDefinition, new: &SchemaDefinition) -> Vec<Schem
 
     // Collections removed
     for name in old_names.difference(&new_names) {
+        if renamed_old.contains(*name) {
+            continue;
+        }
         migrations.push(SchemaMigration::CollectionRemoved {
             name: (*name).clone(),
         });
     }
 
-    // Collections present in both — diff fields
-    for name in old_names.intersection(&new_names) {
-        let old_col = &old.collections[*name];
-        let new_col = &new.collections[*name];
+    // Collections present in both (or renamed) — diff fields
+    let mut pairs: Vec<(&String, &String)> = old_names
+        .intersection(&new_names)
+        .map(|name| (*name, *name))
+        .collect();
+    pairs.extend(rename_list.iter().map(|(to, from)| (**from, **to)));
+
+    for (old_name, name) in pairs {
+        let old_col = &old.collections[old_name];
+        let new_col = &new.collections[name];
 
-        // Path template changed
-        if old_col.path != new_col.path {
+        // Path template changed (a rename re-renders paths itself)
+        if old_name == name && old_col.path != new_col.path {
             migrations.push(SchemaMigration::PathTemplateChanged {
-                collection: (*name).clone(),
+                collection: name.clone(),
                 old_template: old_col.path.clone(),
                 new_template: new_col.path.clone(),
             });
@@ -207,6 +288,77 @@ pub fn diff_schemas(old: &SchemaDefinition, new: &SchemaDefinition) -> Vec<Schem
     migrations
 }
 
+/// Views added, removed or changed between two schema versions. `renames`
+/// pairs each renamed view's new name with its old one; those are neither
+/// added nor removed, but are compared across the rename.
+fn diff_views(
+    old: &SchemaDefinition,
+    new: &SchemaDefinition,
+    renames: &[(&String, &String)],
+) -> Vec<SchemaMigration> {
+    let mut migrations = Vec::new();
+
+    let mut added: Vec<&String> = new
+        .views
+        .keys()
+        .filter(|name| {
+            !old.views.contains_key(*name) && !renames.iter().any(|(to, _)| to == name)
+        })
+        .collect();
+    added.sort();
+    migrations.extend(
+        added
+            .into_iter()
+            .map(|name| SchemaMigration::ViewAdded { name: name.clone() }),
+    );
+
+    let mut removed: Vec<&String> = old
+        .views
+        .keys()
+        .filter(|name| {
+            !new.views.contains_key(*name) && !renames.iter().any(|(_, from)| from == name)
+        })
+        .collect();
+    removed.sort();
+    migrations.extend(
+        removed
+            .into_iter()
+            .map(|name| SchemaMigration::ViewRemoved { name: name.clone() }),
+    );
+
+    let mut pairs: Vec<(&String, &String)> = new
+        .views
+        .keys()
+        .filter(|name| old.views.contains_key(*name))
+        .map(|name| (name, name))
+        .collect();
+    pairs.extend(renames.iter().map(|(to, from)| (*from, *to)));
+    pairs.sort_by_key(|(_, name)| *name);
+    for (old_name, name) in pairs {
+        let (before, after) = (&old.views[old_name], &new.views[name]);
+        let params = |view: &ViewDefinition| {
+            let mut params: Vec<(String, String)> = view
+                .params
+                .iter()
+                .flatten()
+                .map(|(name, param)| (name.clone(), param.param_type.clone()))
+                .collect();
+            params.sort();
+            params
+        };
+        if before.query != after.query
+            || before.view_type != after.view_type
+            || before.materialize != after.materialize
+            || before.buffer != after.buffer
+            || params(before) != params(after)
+        {
+            migrations.push(SchemaMigration::ViewChanged { name: name.clone() });
+        }
+    }
+
+    migrations
+}
+
 /// Check if any migrations are unsafe (would block boot).
 pub fn has_unsafe_migrations(migrations: &[SchemaMigration]) -> Vec<&SchemaMigration> {
     migrations.iter().filter(|m| !m.is_safe()).collect()
@@ -444,4 +596,110 @@ collections:
         assert!(matches!(&diffs[0], SchemaMigration::FieldTypeChanged { .. }));
         assert!(!diffs[0].is_safe());
     }
+
+    #[test]
+    fn test_diff_view_renamed() {
+        let collections = r#"
+collections:
+  users:
+    path: "users/{name}.md"
+    fields:
+      name: { type: string, required: true }
+"#;
+        let old = parse_schema_str(&format!(
+            "{collections}views:\n  user_names:\n    query: SELECT name FROM users\n"
+        ))
+        .unwrap();
+        let new = parse_schema_str(&format!(
+            "{collections}views:\n  names:\n    renamed_from: user_names\n    query: SELECT name FROM users\n"
+        ))
+        .unwrap();
+        let diffs = diff_schemas(&old, &new);
+        assert_eq!(
+            diffs,
+            [SchemaMigration::ViewRenamed {
+                from: "user_names".into(),
+                to: "names".into(),
+            }]
+        );
+        assert!(diffs[0].is_safe());
+    }
+
+    #[test]
+    fn test_diff_views_added_removed_changed() {
+        let collections = r#"
+collections:
+  users:
+    path: "users/{name}.md"
+    fields:
+      name: { type: string, required: true }
+      role: { type: string }
+views:
+"#;
+        let old = parse_schema_str(&format!(
+            "{collections}  kept:\n    query: SELECT name FROM users\n  \
+             dropped:\n    query: SELECT role FROM users\n  \
+             edited:\n    query: SELECT name FROM users\n"
+        ))
+        .unwrap();
+        let new = parse_schema_str(&format!(
+            "{collections}  kept:\n    query: SELECT name FROM users\n  \
+             edited:\n    query: SELECT name, role FROM users\n  \
+             fresh:\n    query: SELECT role FROM users\n"
+        ))
+        .unwrap();
+        let diffs = diff_schemas(&old, &new);
+        assert_eq!(
+            diffs,
+            [
+                SchemaMigration::ViewAdded { name: "fresh".into() },
+                SchemaMigration::ViewRemoved { name: "dropped".into() },
+                SchemaMigration::ViewChanged { name: "edited".into() },
+            ]
+        );
+        assert!(diffs.iter().all(SchemaMigration::is_safe));
+
+        // Materializing an existing view changes it too
+        let mut materialized = new.clone();
+        materialized.views.get_mut("kept").unwrap().materialize = true;
+        assert_eq!(
+            diff_schemas(&new, &materialized),
+            [SchemaMigration::ViewChanged { name: "kept".into() }]
+        );
+    }
+
+    #[test]
+    fn test_diff_collection_renamed() {
+        let old = parse_schema_str(
+            r#"
+collections:
+  users:
+    path: "users/{name}.md"
+    fields:
+      name: { type: string, required: true }
+"#,
+        )
+        .unwrap();
+        let new = parse_schema_str(
+            r#"
+collections:
+  people:
+    path: "people/{name}.md"
+    renamed_from: users
+    fields:
+      name: { type: string, required: true }
+"#,
+        )
+        .unwrap();
+        let diffs = diff_schemas(&old, &new);
+        assert_eq!(diffs.len(), 1);
+        match &diffs[0] {
+            SchemaMigration::CollectionRenamed { from, to } => {
+                assert_eq!(from, "users");
+                assert_eq!(to, "people");
+            }
+            other => panic!("Expected CollectionRenamed, got {other:?}"),
+        }
+        assert!(diffs[0].is_safe());
+    }
 }
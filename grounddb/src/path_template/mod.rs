@@ -2,12 +2,55 @@ use crate::error::{GroundDbError, Result};
 use chrono::NaiveDate;
 
 use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+/// The longest file or directory name most filesystems accept, in bytes
+pub const DEFAULT_MAX_COMPONENT_BYTES: usize = 255;
 
 /// A parsed path template with segments for interpolation
 #[derive(Debug, Clone)]
 pub struct PathTemplate {
     pub raw: String,
     pub segments: Vec<PathSegment>,
+    /// Rendered file and directory names longer than this many bytes are
+    /// shortened by [`limit_component`]
+    pub max_component_bytes: usize,
+    /// Turns field values into path segments
+    pub slug: Arc<dyn SlugStrategy>,
+}
+
+/// How field values become path segments. The default, [`DefaultSlug`], is
+/// [`slugify`]; set another on a store with
+/// [`StoreOptions::slug_strategy`](crate::StoreOptions::slug_strategy) for
+/// domain-specific rules (keeping dots, custom transliteration, ...).
+/// Implemented for `Fn(&str) -> String` closures.
+///
+/// A slug must not contain `/`, and should be stable: changing the strategy of
+/// an existing store changes where its documents are expected to live.
+pub trait SlugStrategy: Send + Sync {
+    fn slugify(&self, input: &str) -> String;
+}
+
+impl<F: Fn(&str) -> String + Send + Sync> SlugStrategy for F {
+    fn slugify(&self, input: &str) -> String {
+        self(input)
+    }
+}
+
+impl std::fmt::Debug for dyn SlugStrategy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("SlugStrategy")
+    }
+}
+
+/// The built-in [`SlugStrategy`]: [`slugify`]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DefaultSlug;
+
+impl SlugStrategy for DefaultSlug {
+    fn slugify(&self, input: &str) -> String {
+        slugify(input)
+    }
 }
 
 /// A single segment of a path template
@@ -19,6 +62,9 @@ pub enum PathSegment {
     Field { name: String, format: Option<String> },
     /// A nested field reference for refs (e.g., {parent:type}, {parent:id}, {user:id})
     NestedField { parent: String, child: String },
+    /// A greedy field whose value spans several directories (e.g., {path*}).
+    /// Each `/`-separated part is slugified on its own, keeping the slashes.
+    Greedy { name: String },
 }
 
 impl PathTemplate {
@@ -29,6 +75,7 @@ impl PathTemplate {
     /// - Simple fields: {title}
     /// - Fields with date format: {date:YYYY-MM-DD}
     /// - Nested ref fields: {parent:type}, {parent:id}, {user:id}
+    /// - Greedy multi-directory fields: {path*}
     /// - The implicit {id} field
     pub fn parse(template: &str) -> Result<Self> {
         let mut segments = Vec::new();
@@ -71,9 +118,23 @@ impl PathTemplate {
         Ok(PathTemplate {
             raw: template.to_string(),
             segments,
+            max_component_bytes: DEFAULT_MAX_COMPONENT_BYTES,
+            slug: Arc::new(DefaultSlug),
         })
     }
 
+    /// Set how [`render`](Self::render) turns field values into path segments
+    pub fn with_slug_strategy(mut self, slug: Arc<dyn SlugStrategy>) -> Self {
+        self.slug = slug;
+        self
+    }
+
+    /// Set the longest file or directory name [`render`](Self::render) produces
+    pub fn with_max_component_bytes(mut self, max_bytes: usize) -> Self {
+        self.max_component_bytes = max_bytes;
+        self
+    }
+
     /// Render the template with the given front matter values and optional document id.
     /// Returns the complete file path.
     pub fn render(&self, fields: &serde_yaml::Value, id: Option<&str>) -> Result<String> {
@@ -82,31 +143,41 @@ impl PathTemplate {
         for segment in &self.segments {
             match segment {
                 PathSegment::Literal(s) => result.push_str(s),
-                PathSegment::Field { name, format } => {
-                    let raw_value = if name == "id" {
-                        if let Some(id) = id {
-                            serde_yaml::Value::String(id.to_string())
-                        } else {
-                            get_yaml_field(fields, name)?
-                        }
-                    } else if name == "created_at" || name == "modified_at" {
-                        // Implicit fields may be provided in the value map
-                        get_yaml_field(fields, name)?
-                    } else {
-                        get_yaml_field(fields, name)?
-                    };
-                    let rendered = format_value(&raw_value, format.as_deref())?;
-                    result.push_str(&slugify(&rendered));
-                }
-                PathSegment::NestedField { parent, child } => {
-                    let raw_value = get_nested_yaml_field(fields, parent, child)?;
-                    let rendered = value_to_string(&raw_value)?;
-                    result.push_str(&slugify(&rendered));
-                }
+                PathSegment::Greedy { .. } => result.push_str(&slugify_path_with(
+                    &segment_value(segment, fields, id)?,
+                    |part| self.slug.slugify(part),
+                )),
+                _ => result.push_str(&self.slug.slugify(&segment_value(segment, fields, id)?)),
             }
         }
 
-        Ok(result)
+        // Over-long names (e.g. from a very long title) are shortened
+        Ok(self.limit_components(&result))
+    }
+
+    /// Shorten each `/`-separated name in `path` to `max_component_bytes`
+    /// with [`limit_component`]
+    pub fn limit_components(&self, path: &str) -> String {
+        path.split('/')
+            .map(|part| limit_component(part, self.max_component_bytes))
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+
+    /// Render the template as plain text rather than a path: values are substituted
+    /// without slugifying, and fields missing from the document render as empty.
+    /// Used for collection `content_template`s.
+    pub fn render_text(&self, fields: &serde_yaml::Value, id: Option<&str>) -> String {
+        let mut result = String::new();
+
+        for segment in &self.segments {
+            match segment {
+                PathSegment::Literal(s) => result.push_str(s),
+                _ => result.push_str(&segment_value(segment, fields, id).unwrap_or_default()),
+            }
+        }
+
+        result
     }
 
     /// Returns the set of field names referenced in this template.
@@ -116,7 +187,7 @@ impl PathTemplate {
         let mut fields = HashSet::new();
         for segment in &self.segments {
             match segment {
-                PathSegment::Field { name, .. } => {
+                PathSegment::Field { name, .. } | PathSegment::Greedy { name } => {
                     fields.insert(name.clone());
                 }
                 PathSegment::NestedField { parent, .. } => {
@@ -131,7 +202,7 @@ impl PathTemplate {
     /// Returns true if the given field name is referenced in the path template
     pub fn references_field(&self, field_name: &str) -> bool {
         self.segments.iter().any(|s| match s {
-            PathSegment::Field { name, .. } => name == field_name,
+            PathSegment::Field { name, .. } | PathSegment::Greedy { name } => name == field_name,
             PathSegment::NestedField { parent, .. } => parent == field_name,
             PathSegment::Literal(_) => false,
         })
@@ -159,8 +230,9 @@ impl PathTemplate {
     /// by matching it against the template segments.
     ///
     /// Returns `None` if the path doesn't match the template structure.
-    /// Skips `NestedField` segments (consumes the text but doesn't include
-    /// them in the result map).
+    /// A `Greedy` field takes everything up to the last occurrence of the
+    /// literal that follows it, slashes included. Skips `NestedField` segments
+    /// (consumes the text but doesn't include them in the result map).
     pub fn extract(&self, path: &str) -> Option<HashMap<String, String>> {
         let mut fields = HashMap::new();
         let mut remaining = path;
@@ -184,6 +256,14 @@ impl PathTemplate {
                     remaining = &remaining[value.len()..];
                     // NestedField values are not stored
                 }
+                PathSegment::Greedy { name } => {
+                    let value = match self.next_delimiter(i) {
+                        Some(delim) => &remaining[..remaining.rfind(delim)?],
+                        None => remaining,
+                    };
+                    remaining = &remaining[value.len()..];
+                    fields.insert(name.clone(), value.to_string());
+                }
             }
         }
 
@@ -211,15 +291,7 @@ impl PathTemplate {
             }
         }
 
-        // Find the next literal delimiter after this field
-        let delimiter = self.segments[idx + 1..]
-            .iter()
-            .find_map(|s| match s {
-                PathSegment::Literal(lit) => Some(lit.as_str()),
-                _ => None,
-            });
-
-        if let Some(delim) = delimiter {
+        if let Some(delim) = self.next_delimiter(idx) {
             if let Some(pos) = remaining.find(delim) {
                 Some(remaining[..pos].to_string())
             } else {
@@ -230,6 +302,14 @@ impl PathTemplate {
             Some(remaining.to_string())
         }
     }
+
+    /// The first literal after segment `idx`, which ends that segment's value
+    fn next_delimiter(&self, idx: usize) -> Option<&str> {
+        self.segments[idx + 1..].iter().find_map(|s| match s {
+            PathSegment::Literal(lit) => Some(lit.as_str()),
+            _ => None,
+        })
+    }
 }
 
 /// Parse a field expression inside {}.
@@ -241,6 +321,7 @@ impl PathTemplate {
 /// - "parent:id" -> NestedField { parent: "parent", child: "id" }
 /// - "user:id" -> NestedField { parent: "user", child: "id" }
 /// - "created_at:YYYY-MM-DDTHHMM" -> Field { name: "created_at", format: Some(...) }
+/// - "path*" -> Greedy { name: "path" }
 ///
 /// The disambiguation rule: if the part after ":" looks like a date format
 /// (contains Y, M, D, H, or uppercase characters typical of format strings),
@@ -264,6 +345,10 @@ fn parse_field_expr(expr: &str) -> PathSegment {
                 child: right.to_string(),
             }
         }
+    } else if let Some(name) = expr.strip_suffix('*') {
+        PathSegment::Greedy {
+            name: name.to_string(),
+        }
     } else {
         PathSegment::Field {
             name: expr.to_string(),
@@ -272,6 +357,29 @@ fn parse_field_expr(expr: &str) -> PathSegment {
     }
 }
 
+/// Resolve the (unslugified) string value of a field segment
+fn segment_value(
+    segment: &PathSegment,
+    fields: &serde_yaml::Value,
+    id: Option<&str>,
+) -> Result<String> {
+    match segment {
+        PathSegment::Literal(s) => Ok(s.clone()),
+        PathSegment::Field { name, format } => {
+            let raw_value = match id {
+                Some(id) if name == "id" => serde_yaml::Value::String(id.to_string()),
+                _ => get_yaml_field(fields, name)?,
+            };
+            format_value(&raw_value, format.as_deref())
+        }
+        PathSegment::Greedy { name } => value_to_string(&get_yaml_field(fields, name)?),
+        PathSegment::NestedField { parent, child } => {
+            let raw_value = get_nested_yaml_field(fields, parent, child)?;
+            value_to_string(&raw_value)
+        }
+    }
+}
+
 /// Determine if a string looks like a date format specifier.
 /// Date formats contain characters like Y, M, D, H (uppercase) in sequences.
 /// Simple field names like "type", "id" are lowercase alpha only.
@@ -460,10 +568,65 @@ pub fn slugify(input: &str) -> String {
     slug::slugify(input)
 }
 
+/// Slugify a multi-directory value (a `Greedy` field) one `/`-separated part
+/// at a time. Parts that slugify to nothing, like `..` or the gap in `a//b`,
+/// are dropped, so the value can't climb out of or skip a directory.
+pub fn slugify_path(input: &str) -> String {
+    slugify_path_with(input, slugify)
+}
+
+/// [`slugify_path`] with another slug function
+fn slugify_path_with(input: &str, slugify: impl Fn(&str) -> String) -> String {
+    input
+        .split(['/', '\\'])
+        .map(slugify)
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Shorten a file or directory name longer than `max_bytes`: the stem is cut
+/// on a character boundary and followed by `-` and a hash of the whole name,
+/// so names sharing a long prefix stay distinct. A short extension is kept.
+/// Names within the limit are returned unchanged.
+pub fn limit_component(name: &str, max_bytes: usize) -> String {
+    if name.len() <= max_bytes {
+        return name.to_string();
+    }
+    let (stem, ext) = match name.rfind('.') {
+        Some(dot) if dot > 0 && name.len() - dot <= 16 => name.split_at(dot),
+        _ => (name, ""),
+    };
+    let hash = format!("{:08x}", fnv1a(name.as_bytes()) as u32);
+    let mut cut = max_bytes.saturating_sub(ext.len() + hash.len() + 1).min(stem.len());
+    while !stem.is_char_boundary(cut) {
+        cut -= 1;
+    }
+    format!("{}-{hash}{ext}", stem[..cut].trim_end_matches('-'))
+}
+
+/// 64-bit FNV-1a, a stable hash for shortened names
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &b| {
+        (hash ^ u64::from(b)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
 /// Resolve a path conflict by appending a suffix (-2, -3, etc.)
 pub fn resolve_suffix(base_path: &str, exists_fn: impl Fn(&str) -> bool) -> String {
-    if !exists_fn(base_path) {
-        return base_path.to_string();
+    match try_resolve_suffix(base_path, |path| Ok::<_, std::convert::Infallible>(exists_fn(path))) {
+        Ok(path) => path,
+        Err(never) => match never {},
+    }
+}
+
+/// [`resolve_suffix`] with a fallible existence check, stopping at its first error
+pub fn try_resolve_suffix<E>(
+    base_path: &str,
+    exists_fn: impl Fn(&str) -> std::result::Result<bool, E>,
+) -> std::result::Result<String, E> {
+    if !exists_fn(base_path)? {
+        return Ok(base_path.to_string());
     }
 
     // Split at the last dot (extension)
@@ -476,8 +639,8 @@ pub fn resolve_suffix(base_path: &str, exists_fn: impl Fn(&str) -> bool) -> Stri
     let mut counter = 2;
     loop {
         let candidate = format!("{stem}-{counter}{ext}");
-        if !exists_fn(&candidate) {
-            return candidate;
+        if !exists_fn(&candidate)? {
+            return Ok(candidate);
         }
         counter += 1;
     }
@@ -611,6 +774,14 @@ mod tests {
         assert_eq!(result, "comments/posts/my-post.md");
     }
 
+    #[test]
+    fn test_render_text() {
+        let t = PathTemplate::parse("# {title}\n\nWritten on {date} by {author}.").unwrap();
+        let data: Value = serde_yaml::from_str("title: Hello World\ndate: '2026-02-13'").unwrap();
+        let result = t.render_text(&data, None);
+        assert_eq!(result, "# Hello World\n\nWritten on 2026-02-13 by .");
+    }
+
     #[test]
     fn test_referenced_fields() {
         let t = PathTemplate::parse("posts/{status}/{date:YYYY-MM-DD}-{title}.md").unwrap();
@@ -648,6 +819,56 @@ mod tests {
         assert_eq!(slugify("special!@#chars"), "special-chars");
     }
 
+    #[test]
+    fn test_render_limits_long_names() {
+        let t = PathTemplate::parse("posts/{status}/{title}.md")
+            .unwrap()
+            .with_max_component_bytes(40);
+        let data: serde_yaml::Value = serde_yaml::from_str(&format!(
+            "title: {}\nstatus: draft",
+            "a very long title ".repeat(10)
+        ))
+        .unwrap();
+        let path = t.render(&data, None).unwrap();
+        let name = path.strip_prefix("posts/draft/").unwrap();
+        assert_eq!(name.len(), 40, "{name}");
+        assert!(name.starts_with("a-very-long-title-a-very-lon-"));
+        assert!(name.ends_with(".md"));
+        assert_eq!(t.render(&data, None).unwrap(), path);
+
+        // Titles differing only past the cut get different names
+        let other: serde_yaml::Value = serde_yaml::from_str(&format!(
+            "title: {} end\nstatus: draft",
+            "a very long title ".repeat(10)
+        ))
+        .unwrap();
+        assert_ne!(t.render(&other, None).unwrap(), path);
+        assert_eq!(limit_component("short.md", 40), "short.md");
+        assert!(limit_component(&"é".repeat(30), 21).len() <= 21);
+    }
+
+    #[test]
+    fn test_render_with_slug_strategy() {
+        struct KeepUnderscores;
+        impl SlugStrategy for KeepUnderscores {
+            fn slugify(&self, input: &str) -> String {
+                input
+                    .split('_')
+                    .map(slugify)
+                    .collect::<Vec<_>>()
+                    .join("_")
+            }
+        }
+
+        let data: serde_yaml::Value =
+            serde_yaml::from_str("title: My_Draft Post\nsection: Guides/Set_Up").unwrap();
+        let t = PathTemplate::parse("docs/{section*}/{title}.md").unwrap();
+        assert_eq!(t.render(&data, None).unwrap(), "docs/guides/set-up/my-draft-post.md");
+
+        let t = t.with_slug_strategy(Arc::new(KeepUnderscores));
+        assert_eq!(t.render(&data, None).unwrap(), "docs/guides/set_up/my_draft-post.md");
+    }
+
     #[test]
     fn test_resolve_suffix() {
         let existing = vec!["test.md".to_string(), "test-2.md".to_string()];
@@ -745,4 +966,36 @@ mod tests {
         assert_eq!(extracted.get("status").unwrap(), "published");
         assert_eq!(extracted.get("title").unwrap(), "quarterly-review");
     }
+
+    #[test]
+    fn test_greedy_segment_renders_nested_path() {
+        let t = PathTemplate::parse("wiki/{path*}.md").unwrap();
+        assert_eq!(t.segments[1], PathSegment::Greedy { name: "path".to_string() });
+        assert!(t.references_field("path"));
+        assert_eq!(t.base_directory(), "wiki/");
+
+        let data: Value =
+            serde_yaml::from_str("path: Guides/Getting Started/Setup/Install Notes").unwrap();
+        let rendered = t.render(&data, None).unwrap();
+        assert_eq!(rendered, "wiki/guides/getting-started/setup/install-notes.md");
+
+        let extracted = t.extract(&rendered).unwrap();
+        assert_eq!(
+            extracted.get("path").unwrap(),
+            "guides/getting-started/setup/install-notes"
+        );
+
+        // Empty and dot-only parts can't leave or skip a directory
+        let data: Value = serde_yaml::from_str("path: /a//../b/").unwrap();
+        assert_eq!(t.render(&data, None).unwrap(), "wiki/a/b.md");
+    }
+
+    #[test]
+    fn test_greedy_segment_before_other_fields() {
+        let t = PathTemplate::parse("docs/{section*}/{slug}.md").unwrap();
+        let fields = t.extract("docs/api/v2/auth/tokens/refresh.md").unwrap();
+        assert_eq!(fields.get("section").unwrap(), "api/v2/auth/tokens");
+        assert_eq!(fields.get("slug").unwrap(), "refresh");
+        assert!(t.extract("other/api/refresh.md").is_none());
+    }
 }
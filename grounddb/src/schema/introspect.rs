@@ -0,0 +1,189 @@
+//! A structured JSON description of a schema, for tools such as generic admin
+//! UIs that render forms and tables from GroundDB's own model.
+
+use super::types::*;
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+
+/// Describe `schema` as JSON: custom types, collections with their fields, and
+/// views with their result columns and parameters. Every field is listed with
+/// all of its options spelled out (`nullable` and `enum_case_insensitive`
+/// resolved, unset options `null`),
+/// so consumers don't need to know the YAML defaults.
+///
+/// A view whose SQL doesn't parse is listed with `columns: null`.
+pub fn schema_json(schema: &SchemaDefinition) -> Value {
+    let types: Map<String, Value> = schema
+        .types
+        .iter()
+        .map(|(name, fields)| (name.clone(), fields_json(fields, false)))
+        .collect();
+    let collections: Map<String, Value> = schema
+        .collections
+        .iter()
+        .map(|(name, collection)| (name.clone(), collection_json(collection)))
+        .collect();
+    let views: Map<String, Value> = schema
+        .views
+        .iter()
+        .map(|(name, view)| (name.clone(), view_json(name, view)))
+        .collect();
+
+    json!({
+        "types": types,
+        "collections": collections,
+        "views": views,
+    })
+}
+
+fn collection_json(collection: &CollectionDefinition) -> Value {
+    let case_insensitive = collection.enum_case_insensitive;
+    let records = collection.records.as_ref().map(|records| {
+        let variants: Map<String, Value> = records
+            .variants
+            .iter()
+            .map(|(name, variant)| (name.clone(), fields_json(&variant.fields, case_insensitive)))
+            .collect();
+        json!({
+            "by": records.by,
+            "base": fields_json(&records.base, case_insensitive),
+            "variants": variants,
+        })
+    });
+    let id = collection.id.as_ref();
+
+    json!({
+        "path": collection.path,
+        "id": {
+            "auto": id.and_then(|i| i.auto.as_ref()),
+            "on_conflict": collection.on_conflict(),
+        },
+        "content": collection.has_content(),
+        "content_format": collection.content_format().map(|f| f.as_str()),
+        "readonly": collection.readonly,
+        "layout": collection.layout,
+        "compress": collection.compress,
+        "enum_ordinals": collection.enum_ordinals,
+        "enum_case_insensitive": case_insensitive,
+        "fields": fields_json(&collection.fields, case_insensitive),
+        "records": records,
+    })
+}
+
+/// `case_insensitive` is the enclosing collection's `enum_case_insensitive`,
+/// which fields that don't set their own inherit.
+fn fields_json(fields: &HashMap<String, FieldDefinition>, case_insensitive: bool) -> Value {
+    Value::Object(
+        fields
+            .iter()
+            .map(|(name, field)| (name.clone(), field_json(field, case_insensitive)))
+            .collect(),
+    )
+}
+
+fn field_json(field: &FieldDefinition, case_insensitive: bool) -> Value {
+    let items = field.items.as_ref().map(|items| match items {
+        ItemType::Simple(name) => json!(name),
+        ItemType::Complex(def) => field_json(def, case_insensitive),
+    });
+    json!({
+        "type": field.field_type,
+        "required": field.required,
+        "nullable": field.is_nullable(),
+        "enum": field.enum_values,
+        "enum_case_insensitive": field.enum_case_insensitive.unwrap_or(case_insensitive),
+        "default": field.default,
+        "target": field.target,
+        "items": items,
+        "on_delete": field.on_delete,
+        "collation": field.collation,
+        "denormalize": field.denormalize,
+        "immutable": field.immutable,
+    })
+}
+
+fn view_json(name: &str, view: &ViewDefinition) -> Value {
+    let params: Map<String, Value> = view
+        .params
+        .iter()
+        .flatten()
+        .map(|(param, def)| (param.clone(), json!({ "type": def.param_type })))
+        .collect();
+    json!({
+        "type": view.view_type.clone().unwrap_or(ViewType::View),
+        "query": view.query,
+        "materialize": view.materialize,
+        "buffer": view.buffer,
+        "columns": crate::view::view_columns(name, view).ok(),
+        "params": params,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::parse_schema_str;
+
+    #[test]
+    fn test_schema_json_describes_fields_and_views() {
+        let schema = parse_schema_str(
+            r#"
+types:
+  address:
+    street: { type: string }
+
+collections:
+  users:
+    path: "users/{name}.md"
+    enum_case_insensitive: true
+    fields:
+      name: { type: string, required: true }
+      role: { type: string, enum: [admin, member], default: member }
+      tier: { type: string, enum: [free, pro], enum_case_insensitive: false }
+      home: { type: address }
+  posts:
+    path: "posts/{id}.md"
+    id: { auto: ulid }
+    content: true
+    fields:
+      title: { type: string, required: true }
+      author: { type: ref, target: users, on_delete: cascade }
+
+views:
+  user_posts:
+    type: query
+    query: |
+      SELECT p.title, u.name AS author
+      FROM posts p
+      JOIN users u ON p.author = u.id
+      WHERE u.id = :user_id
+    params:
+      user_id: { type: string }
+"#,
+        )
+        .unwrap();
+        let dump = schema_json(&schema);
+
+        let role = &dump["collections"]["users"]["fields"]["role"];
+        assert_eq!(role["type"], json!("string"));
+        assert_eq!(role["enum"], json!(["admin", "member"]));
+        assert_eq!(role["default"], json!("member"));
+        assert_eq!(role["nullable"], json!(true));
+        assert_eq!(role["enum_case_insensitive"], json!(true));
+        let tier = &dump["collections"]["users"]["fields"]["tier"];
+        assert_eq!(tier["enum_case_insensitive"], json!(false));
+        assert_eq!(dump["collections"]["users"]["fields"]["home"]["type"], json!("address"));
+        assert_eq!(dump["types"]["address"]["street"]["type"], json!("string"));
+
+        let author = &dump["collections"]["posts"]["fields"]["author"];
+        assert_eq!(author["target"], json!("users"));
+        assert_eq!(author["on_delete"], json!("cascade"));
+        assert_eq!(dump["collections"]["posts"]["id"]["auto"], json!("ulid"));
+        assert_eq!(dump["collections"]["posts"]["content_format"], json!("markdown"));
+
+        let view = &dump["views"]["user_posts"];
+        assert_eq!(view["type"], json!("query"));
+        assert_eq!(view["columns"], json!(["title", "author"]));
+        assert_eq!(view["params"], json!({ "user_id": { "type": "string" } }));
+    }
+}
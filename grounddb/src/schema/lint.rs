@@ -0,0 +1,166 @@
+//! Checking a schema on its own, before any data directory exists (e.g. in CI).
+
+use super::parser::{deserialize_schema, schema_errors, validate_path_enums};
+use super::types::*;
+use crate::error::Result;
+use crate::path_template::{PathSegment, PathTemplate};
+
+/// Check a schema YAML string without opening a store. Runs the structural
+/// checks [`parse_schema_str`](super::parse_schema_str) applies, plus checks
+/// that otherwise only fail once documents are written or views run: path
+/// templates use declared fields, enum values in paths slugify apart (with the
+/// built-in slug strategy), defaults fit their field, and views parse and read
+/// existing collections and columns.
+///
+/// Returns every problem found, empty for a clean schema. Only YAML that
+/// doesn't deserialize into a schema at all is an `Err`.
+pub fn validate_schema_str(content: &str) -> Result<Vec<String>> {
+    let schema = deserialize_schema(content)?;
+    let mut problems = schema_errors(&schema);
+
+    let mut collections: Vec<_> = schema.collections.iter().collect();
+    collections.sort_by_key(|(name, _)| *name);
+    for (name, collection) in collections {
+        check_path_fields(name, collection, &mut problems);
+        check_defaults(&schema, name, collection, &mut problems);
+    }
+
+    let mut views: Vec<_> = schema.views.iter().collect();
+    views.sort_by_key(|(name, _)| *name);
+    for (name, view) in views {
+        if let Err(e) = crate::view::check_view(name, view, &schema) {
+            problems.push(e.to_string());
+        }
+    }
+
+    Ok(problems)
+}
+
+/// Every field a path template renders must be declared or implicit
+fn check_path_fields(name: &str, collection: &CollectionDefinition, problems: &mut Vec<String>) {
+    let template = match PathTemplate::parse(&collection.path) {
+        Ok(template) => template,
+        Err(e) => {
+            problems.push(format!("Collection '{name}': invalid path template: {e}"));
+            return;
+        }
+    };
+    validate_path_enums(name, collection, &template, problems);
+    let declared = |field: &str| {
+        matches!(field, "id" | "created_at" | "modified_at")
+            || collection.fields.contains_key(field)
+            || collection
+                .records
+                .as_ref()
+                .is_some_and(|r| r.by == field || r.base.contains_key(field))
+    };
+
+    for segment in &template.segments {
+        let field = match segment {
+            PathSegment::Literal(_) => continue,
+            PathSegment::Field { name, .. } | PathSegment::Greedy { name } => name,
+            PathSegment::NestedField { parent, .. } => parent,
+        };
+        if !declared(field) {
+            problems.push(format!(
+                "Collection '{name}': path template uses '{{{field}}}', which is not a declared field"
+            ));
+        }
+    }
+}
+
+/// Every `default` must be a valid value for its own field
+fn check_defaults(
+    schema: &SchemaDefinition,
+    name: &str,
+    collection: &CollectionDefinition,
+    problems: &mut Vec<String>,
+) {
+    let mut fields: Vec<_> = collection.fields.iter().collect();
+    fields.sort_by_key(|(field_name, _)| *field_name);
+    for (field_name, field) in fields {
+        let Some(default) = field.default.as_ref().filter(|d| !d.is_null()) else {
+            continue;
+        };
+        for issue in
+            crate::validation::field_value_issues(schema, field_name, field, default, collection)
+        {
+            problems.push(format!("Collection '{name}': invalid default: {issue}"));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clean_schema_has_no_problems() {
+        let yaml = r#"
+collections:
+  users:
+    path: "users/{name}.md"
+    fields:
+      name: { type: string, required: true }
+      role: { type: string, enum: [admin, member], default: member }
+  posts:
+    path: "posts/{status}/{id}.md"
+    id: { auto: ulid }
+    fields:
+      title: { type: string, required: true }
+      status: { type: string, enum: [draft, published], default: draft }
+      author: { type: ref, target: users }
+
+views:
+  feed:
+    query: |
+      SELECT p.title, u.name AS author
+      FROM posts p
+      JOIN users u ON p.author = u.id
+"#;
+        assert_eq!(validate_schema_str(yaml).unwrap(), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_malformed_schema_reports_each_problem() {
+        let yaml = r#"
+collections:
+  users:
+    path: "users/{handle}.md"
+    fields:
+      name: { type: string }
+      role: { type: string, enum: [admin, member], default: owner }
+      age: { type: number, default: old }
+      team: { type: ref, target: teams }
+  tags:
+    path: "tags/{kind}/{id}.md"
+    fields:
+      kind: { type: string, enum: ["A b", "a-b"] }
+
+views:
+  names:
+    query: "SELECT u.nickname FROM users u"
+  missing:
+    query: "SELECT id FROM groups"
+"#;
+        let problems = validate_schema_str(yaml).unwrap();
+        let expected = [
+            "ref target 'teams' is not a defined collection",
+            "enum values 'A b' and 'a-b' both map to path segment 'a-b'",
+            "Collection 'users': path template uses '{handle}', which is not a declared field",
+            "Collection 'users': invalid default: Field 'role' value 'owner' is not in enum",
+            "Collection 'users': invalid default: Field 'age' expected number",
+            "View 'names': column 'u.nickname' is not a field of collection 'users'",
+            "View 'missing': referenced collection 'groups' not found in schema",
+        ];
+        for message in expected {
+            assert!(problems.iter().any(|p| p.contains(message)), "{message}\n{problems:#?}");
+        }
+        assert_eq!(problems.len(), expected.len(), "{problems:#?}");
+    }
+
+    #[test]
+    fn test_unparseable_yaml_is_an_error() {
+        assert!(validate_schema_str("collections: [").is_err());
+    }
+}
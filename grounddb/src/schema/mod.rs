@@ -1,5 +1,10 @@
+mod introspect;
+mod lint;
 mod parser;
 mod types;
 
+pub use introspect::schema_json;
+pub use lint::validate_schema_str;
 pub use parser::{parse_schema, parse_schema_str, hash_schema};
+pub(crate) use parser::{is_identifier, validate_path_enums};
 pub use types::*;
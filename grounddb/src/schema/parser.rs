@@ -1,5 +1,7 @@
 use crate::error::{GroundDbError, Result};
+use crate::path_template::{PathSegment, PathTemplate};
 use super::types::*;
+use std::collections::HashMap;
 use std::path::Path;
 
 /// Parse a schema.yaml file into a SchemaDefinition
@@ -10,32 +12,75 @@ pub fn parse_schema(path: &Path) -> Result<SchemaDefinition> {
 
 /// Parse a schema YAML string into a SchemaDefinition
 pub fn parse_schema_str(content: &str) -> Result<SchemaDefinition> {
-    let schema: SchemaDefinition = serde_yaml::from_str(content)
-        .map_err(|e| GroundDbError::Schema(format!("Failed to parse schema YAML: {e}")))?;
+    let schema = deserialize_schema(content)?;
     validate_schema(&schema)?;
     Ok(schema)
 }
 
+/// Deserialize schema YAML, filling the options a collection omits from the
+/// schema-root `defaults:`. An option set on the collection, even `false`,
+/// wins over the default.
+pub(super) fn deserialize_schema(content: &str) -> Result<SchemaDefinition> {
+    let parse_error = |e: serde_yaml::Error| {
+        GroundDbError::Schema(format!("Failed to parse schema YAML: {e}"))
+    };
+    let mut value: serde_yaml::Value = serde_yaml::from_str(content).map_err(parse_error)?;
+    let defaults: SchemaDefaults = match value.get("defaults") {
+        Some(defaults) => serde_yaml::from_value(defaults.clone()).map_err(parse_error)?,
+        None => SchemaDefaults::default(),
+    };
+    let inherited = [
+        ("strict", defaults.strict),
+        ("additional_properties", defaults.additional_properties),
+    ];
+    if let Some(collections) = value
+        .get_mut("collections")
+        .and_then(serde_yaml::Value::as_mapping_mut)
+    {
+        for collection in collections.values_mut() {
+            let Some(collection) = collection.as_mapping_mut() else {
+                continue;
+            };
+            for (key, default) in inherited {
+                if let Some(default) = default {
+                    if !collection.contains_key(key) {
+                        collection.insert(key.into(), default.into());
+                    }
+                }
+            }
+        }
+    }
+    serde_yaml::from_value(value).map_err(parse_error)
+}
+
 /// Validate the schema for semantic correctness after parsing
 fn validate_schema(schema: &SchemaDefinition) -> Result<()> {
+    let errors = schema_errors(schema);
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(GroundDbError::Schema(format!(
+            "Schema validation failed:\n  - {}",
+            errors.join("\n  - ")
+        )))
+    }
+}
+
+/// Every structural problem [`parse_schema_str`] rejects a schema for
+pub(super) fn schema_errors(schema: &SchemaDefinition) -> Vec<String> {
     let mut errors: Vec<String> = Vec::new();
 
     for (name, collection) in &schema.collections {
         validate_collection(schema, name, collection, &mut errors);
     }
 
+    validate_base_directories(schema, &mut errors);
+
     for (name, view) in &schema.views {
-        validate_view(name, view, &mut errors);
+        validate_view(schema, name, view, &mut errors);
     }
 
-    if errors.is_empty() {
-        Ok(())
-    } else {
-        Err(GroundDbError::Schema(format!(
-            "Schema validation failed:\n  - {}",
-            errors.join("\n  - ")
-        )))
-    }
+    errors
 }
 
 fn validate_collection(
@@ -44,20 +89,212 @@ fn validate_collection(
     collection: &CollectionDefinition,
     errors: &mut Vec<String>,
 ) {
+    // Names become SQL table and column names in views
+    if !is_identifier(name) {
+        errors.push(format!("Collection '{name}': {IDENTIFIER_RULE}"));
+    }
+
     // Path template must not be empty
     if collection.path.is_empty() {
         errors.push(format!("Collection '{name}': path template is empty"));
     }
 
+    // A rename source must no longer be defined, or its documents would be claimed twice
+    if let Some(from) = &collection.renamed_from {
+        if schema.collections.contains_key(from) {
+            errors.push(format!(
+                "Collection '{name}': renamed_from '{from}' is still defined in the schema"
+            ));
+        }
+    }
+
     // Validate each field definition
     for (field_name, field) in &collection.fields {
+        // Views quote other names (`"published-at"`, `$."published-at"`), but
+        // a JSON path label can't contain a double quote
+        if field_name.is_empty() || field_name.contains('"') {
+            errors.push(format!(
+                "Collection '{name}': field '{field_name}': {FIELD_NAME_RULE}"
+            ));
+        }
+        if let Some(implicit) = implicit_column(collection, field_name) {
+            errors.push(format!(
+                "Collection '{name}': field '{field_name}' collides with the implicit \
+                 {implicit}; rename the field"
+            ));
+        }
         validate_field(schema, name, field_name, field, errors);
     }
 
+    if let Some(template) = &collection.content_template {
+        if !collection.has_content() {
+            errors.push(format!(
+                "Collection '{name}': content_template requires content: true"
+            ));
+        }
+        if let Err(e) = PathTemplate::parse(template) {
+            errors.push(format!("Collection '{name}': invalid content_template: {e}"));
+        }
+    }
+
+    if collection.extract_content.is_some() && !collection.has_content() {
+        errors.push(format!(
+            "Collection '{name}': extract_content requires content: true"
+        ));
+    }
+
+    // Ordinal columns must not shadow declared fields
+    for (field_name, _) in collection.ordinal_fields() {
+        let column = format!("{field_name}_ordinal");
+        if collection.fields.contains_key(&column) {
+            errors.push(format!(
+                "Collection '{name}': field '{column}' collides with the ordinal of enum field '{field_name}'"
+            ));
+        }
+    }
+
+    // Enum variants' path segments depend on the store's slug strategy, so
+    // `validate_path_enums` runs when a store builds its templates
+    if let Ok(template) = PathTemplate::parse(&collection.path) {
+        validate_path_field_types(name, collection, &template, errors);
+    }
+
     // If strict is set, additional_properties should typically be false
     // (but this is a warning, not a hard error -- the user might know what they're doing)
 }
 
+/// What the implicit view column or document member `field_name` holds, if
+/// the collection has one by that name. A declared field of the same name
+/// would be emitted twice in view SQL.
+fn implicit_column(collection: &CollectionDefinition, field_name: &str) -> Option<&'static str> {
+    let extract = collection.extract_content.as_ref();
+    match field_name {
+        "id" => Some("document id"),
+        "created_at" => Some("creation time"),
+        "modified_at" => Some("modification time"),
+        "content_length" => Some("body length"),
+        // The body, in views and in the dynamic API's documents
+        "content" => Some("document body"),
+        "_title" if extract.is_some_and(|e| e.title_from_h1) => Some("extracted title"),
+        "_links" if extract.is_some_and(|e| e.links) => Some("extracted links"),
+        _ => None,
+    }
+}
+
+const IDENTIFIER_RULE: &str =
+    "name must be letters, digits and underscores, not starting with a digit";
+
+const FIELD_NAME_RULE: &str = "name must not be empty or contain double quotes";
+
+/// Whether `name` can be used unquoted as a SQL identifier (and a Rust one in
+/// generated code): `[A-Za-z_][A-Za-z0-9_]*`.
+pub(crate) fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Reject collections whose base directories overlap (equal, or one nested in
+/// the other), since scanning and watching would claim the same files for both.
+fn validate_base_directories(schema: &SchemaDefinition, errors: &mut Vec<String>) {
+    let mut bases: Vec<(&str, String)> = schema
+        .collections
+        .iter()
+        .filter_map(|(name, c)| {
+            PathTemplate::parse(&c.path)
+                .ok()
+                .map(|t| (name.as_str(), t.base_directory()))
+        })
+        .collect();
+    bases.sort();
+
+    for (i, (a, a_base)) in bases.iter().enumerate() {
+        for (b, b_base) in &bases[i + 1..] {
+            if a_base.starts_with(b_base.as_str()) || b_base.starts_with(a_base.as_str()) {
+                errors.push(format!(
+                    "Collections '{a}' and '{b}' have overlapping base directories ('{a_base}' and '{b_base}')"
+                ));
+            }
+        }
+    }
+}
+
+/// Reject path segments naming fields that can't be read back from the path:
+/// booleans (and nulls) render as text that `extract` can't map back to the
+/// original value, and lists or objects have no path form at all. Only
+/// string, number, date, datetime and ref fields may appear in a path.
+fn validate_path_field_types(
+    collection_name: &str,
+    collection: &CollectionDefinition,
+    template: &PathTemplate,
+    errors: &mut Vec<String>,
+) {
+    for segment in &template.segments {
+        let field_name = match segment {
+            PathSegment::Field { name, .. } | PathSegment::Greedy { name } => name,
+            _ => continue,
+        };
+        let Some(field) = collection.fields.get(field_name) else {
+            continue;
+        };
+        let type_name = match &field.field_type {
+            FieldType::String
+            | FieldType::Number
+            | FieldType::Date
+            | FieldType::Datetime
+            | FieldType::Ref => continue,
+            FieldType::Boolean => "boolean",
+            FieldType::List => "list",
+            FieldType::Object => "object",
+            FieldType::Custom(type_name) => type_name,
+        };
+        errors.push(format!(
+            "Collection '{collection_name}': field '{field_name}' is a {type_name} and can't \
+             be used in the path; only string, number, date, datetime and ref fields can"
+        ));
+    }
+}
+
+/// Reject enum fields used as path segments whose variants slugify to the
+/// same token under the template's slug strategy, since the path could not
+/// be reconciled back to one variant.
+pub(crate) fn validate_path_enums(
+    collection_name: &str,
+    collection: &CollectionDefinition,
+    template: &PathTemplate,
+    errors: &mut Vec<String>,
+) {
+    for segment in &template.segments {
+        let field_name = match segment {
+            PathSegment::Field { name, format: None } => name,
+            _ => continue,
+        };
+        let variants = match collection
+            .fields
+            .get(field_name)
+            .and_then(|f| f.enum_values.as_ref())
+        {
+            Some(v) => v,
+            None => continue,
+        };
+
+        let mut seen: HashMap<String, &str> = HashMap::new();
+        for variant in variants {
+            let slug = template.slug.slugify(variant);
+            if let Some(previous) = seen.get(&slug) {
+                errors.push(format!(
+                    "Collection '{collection_name}', field '{field_name}': enum values \
+                     '{previous}' and '{variant}' both map to path segment '{slug}'"
+                ));
+            } else {
+                seen.insert(slug, variant);
+            }
+        }
+    }
+}
+
 fn validate_field(
     schema: &SchemaDefinition,
     collection_name: &str,
@@ -88,10 +325,11 @@ fn validate_field(
             if field.items.is_none() {
                 // Not an error, but worth noting -- items defaults to any
             }
-            // If items is a ref, validate the target
-            if let Some(ItemType::Complex(item_def)) = &field.items {
-                if item_def.field_type == FieldType::Ref {
-                    if let Some(target) = &item_def.target {
+            // If items are refs, validate the target
+            if let Some(ref_def) = field.ref_definition() {
+                match &ref_def.target {
+                    None => errors.push(format!("{ctx}: list of refs requires a 'target'")),
+                    Some(target) => {
                         for t in target.targets() {
                             if !schema.collections.contains_key(t) {
                                 errors.push(format!(
@@ -114,9 +352,42 @@ fn validate_field(
         _ => {}
     }
 
-    // Enum values should only be set for string type
-    if field.enum_values.is_some() && field.field_type != FieldType::String {
-        errors.push(format!("{ctx}: enum values are only valid for string type"));
+    // Enum values must match the field's scalar type
+    if let Some(variants) = &field.enum_values {
+        match field.field_type {
+            FieldType::String => {}
+            FieldType::Number => {
+                for v in variants.iter().filter(|v| v.parse::<f64>().is_err()) {
+                    errors.push(format!("{ctx}: enum value '{v}' is not a number"));
+                }
+            }
+            FieldType::Boolean => {
+                for v in variants.iter().filter(|v| v.parse::<bool>().is_err()) {
+                    errors.push(format!("{ctx}: enum value '{v}' is not a boolean"));
+                }
+            }
+            _ => errors.push(format!(
+                "{ctx}: enum values are only valid for string, number and boolean types"
+            )),
+        }
+    }
+
+    // denormalize copies fields of a single target collection
+    if let Some(fields) = &field.denormalize {
+        match (&field.field_type, &field.target) {
+            (FieldType::Ref, Some(RefTarget::Single(target))) => {
+                if let Some(target_def) = schema.collections.get(target) {
+                    for f in fields.iter().filter(|f| !target_def.fields.contains_key(*f)) {
+                        errors.push(format!(
+                            "{ctx}: denormalized field '{f}' is not a field of '{target}'"
+                        ));
+                    }
+                }
+            }
+            _ => errors.push(format!(
+                "{ctx}: denormalize is only valid for ref fields with a single target"
+            )),
+        }
     }
 
     // on_delete is only valid for ref fields
@@ -129,10 +400,24 @@ fn validate_field(
 }
 
 fn validate_view(
+    schema: &SchemaDefinition,
     name: &str,
     view: &ViewDefinition,
     errors: &mut Vec<String>,
 ) {
+    if !is_identifier(name) {
+        errors.push(format!("View '{name}': {IDENTIFIER_RULE}"));
+    }
+
+    // A rename source must no longer be defined, or both would claim its cache
+    if let Some(from) = &view.renamed_from {
+        if schema.views.contains_key(from) {
+            errors.push(format!(
+                "View '{name}': renamed_from '{from}' is still defined in the schema"
+            ));
+        }
+    }
+
     if view.query.trim().is_empty() {
         errors.push(format!("View '{name}': query is empty"));
     }
@@ -144,6 +429,21 @@ fn validate_view(
         ));
     }
 
+    // Rows are keyed by column name (and become struct fields in codegen), so
+    // two columns with one name would collide. Unparseable SQL is reported
+    // when the view is built.
+    if let Ok(duplicates) = crate::view::duplicate_columns(name, view) {
+        for (column, sources) in duplicates {
+            let example = sources[1].replace('.', "_");
+            errors.push(format!(
+                "View '{name}': column '{column}' is selected more than once ({}); \
+                 give each an alias, e.g. `{} AS {example}`",
+                sources.join(", "),
+                sources[1]
+            ));
+        }
+    }
+
     // Buffer must be a valid multiplier like "2x"
     if let Some(buffer) = &view.buffer {
         if !buffer.ends_with('x') || buffer[..buffer.len() - 1].parse::<f64>().is_err() {
@@ -296,7 +596,7 @@ views:
 
         // Posts collection
         let posts = &schema.collections["posts"];
-        assert!(posts.content);
+        assert!(posts.has_content());
         assert_eq!(posts.on_conflict(), OnConflict::Suffix);
         assert!(posts.fields["author_id"].required);
         assert_eq!(posts.fields["author_id"].field_type, FieldType::Ref);
@@ -365,6 +665,155 @@ collections:
         assert!(err.contains("target"));
     }
 
+    #[test]
+    fn test_root_defaults_apply_unless_overridden() {
+        let yaml = r#"
+defaults:
+  strict: true
+  additional_properties: false
+
+collections:
+  users:
+    path: "users/{name}.md"
+  notes:
+    path: "notes/{title}.md"
+    strict: false
+  events:
+    path: "events/{name}.md"
+    additional_properties: true
+"#;
+        let schema = parse_schema_str(yaml).unwrap();
+        let flags = |name: &str| {
+            let c = &schema.collections[name];
+            (c.strict, c.additional_properties)
+        };
+        assert_eq!(flags("users"), (true, false));
+        assert_eq!(flags("notes"), (false, false));
+        assert_eq!(flags("events"), (true, true));
+
+        let yaml = "defaults: { strikt: true }\ncollections: {}\n";
+        let err = parse_schema_str(yaml).unwrap_err().to_string();
+        assert!(err.contains("strikt"), "{err}");
+    }
+
+    #[test]
+    fn test_names_must_be_identifiers() {
+        let yaml = r#"
+collections:
+  "bob's_posts":
+    path: "posts/{title}.md"
+    fields:
+      title: { type: string }
+      'say "hi"': { type: string }
+views:
+  "2nd-feed":
+    query: "SELECT title FROM posts"
+"#;
+        let err = parse_schema_str(yaml).unwrap_err().to_string();
+        assert!(err.contains("Collection 'bob's_posts': name must be letters"), "{err}");
+        assert!(err.contains("field 'say \"hi\"': name must not be empty"), "{err}");
+        assert!(err.contains("View '2nd-feed': name must be letters"), "{err}");
+
+        let yaml = r#"
+collections:
+  _drafts2:
+    path: "drafts/{title}.md"
+    fields:
+      title: { type: string }
+      published-at: { type: datetime }
+      first name: { type: string }
+"#;
+        assert!(parse_schema_str(yaml).is_ok());
+    }
+
+    #[test]
+    fn test_view_columns_with_one_name_need_aliases() {
+        let yaml = r#"
+collections:
+  users:
+    path: "users/{name}.md"
+    fields:
+      name: { type: string }
+  posts:
+    path: "posts/{title}.md"
+    fields:
+      title: { type: string }
+      author_id: { type: ref, target: users }
+views:
+  post_authors:
+    query: |
+      SELECT p.id, p.title, u.id FROM posts p JOIN users u ON p.author_id = u.id
+"#;
+        let err = parse_schema_str(yaml).unwrap_err().to_string();
+        assert!(
+            err.contains(
+                "View 'post_authors': column 'id' is selected more than once (p.id, u.id); \
+                 give each an alias, e.g. `u.id AS u_id`"
+            ),
+            "{err}"
+        );
+
+        let aliased = yaml.replace("u.id FROM", "u.id AS author FROM");
+        assert!(parse_schema_str(&aliased).is_ok());
+    }
+
+    #[test]
+    fn test_fields_must_not_shadow_implicit_columns() {
+        let yaml = r#"
+collections:
+  notes:
+    path: "notes/{title}.md"
+    fields:
+      title: { type: string }
+      content: { type: string }
+      created_at: { type: datetime }
+    content: true
+"#;
+        let err = parse_schema_str(yaml).unwrap_err().to_string();
+        assert!(
+            err.contains("Collection 'notes': field 'content' collides with the implicit document"),
+            "{err}"
+        );
+        assert!(err.contains("field 'created_at' collides with the implicit creation"), "{err}");
+
+        // Extracted columns are only reserved when extracted
+        let yaml = r#"
+collections:
+  notes:
+    path: "notes/{title}.md"
+    fields:
+      title: { type: string }
+      _links: { type: list }
+    content: true
+"#;
+        assert!(parse_schema_str(yaml).is_ok());
+        let extracting = format!("{yaml}    extract_content: {{ links: true }}\n");
+        assert!(parse_schema_str(&extracting).is_err());
+    }
+
+    #[test]
+    fn test_path_fields_must_round_trip() {
+        let yaml = r#"
+collections:
+  tasks:
+    path: "tasks/{done}/{title}.md"
+    fields:
+      title: { type: string, required: true }
+      done: { type: boolean }
+"#;
+        let err = parse_schema_str(yaml).unwrap_err().to_string();
+        assert!(
+            err.contains("Collection 'tasks': field 'done' is a boolean and can't be used in the path"),
+            "{err}"
+        );
+
+        let yaml = yaml.replace("{done}/{title}", "{priority}/{due:YYYY}/{title}").replace(
+            "done: { type: boolean }",
+            "priority: { type: number }\n      due: { type: date }",
+        );
+        assert!(parse_schema_str(&yaml).is_ok());
+    }
+
     #[test]
     fn test_empty_path() {
         let yaml = r#"
@@ -378,6 +827,74 @@ collections:
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_list_of_refs_needs_valid_target() {
+        let yaml = r#"
+collections:
+  users:
+    path: "users/{name}.md"
+    fields:
+      name: { type: string }
+  posts:
+    path: "posts/{title}.md"
+    fields:
+      title: { type: string }
+      contributors: { type: list, items: ref }
+      editors: { type: list, items: ref, target: editors }
+"#;
+        let err = parse_schema_str(yaml).unwrap_err().to_string();
+        assert!(err.contains("field 'contributors': list of refs requires a 'target'"), "{err}");
+        assert!(err.contains("list item ref target 'editors'"), "{err}");
+    }
+
+    #[test]
+    fn test_enum_values_match_field_type() {
+        let yaml = r#"
+collections:
+  tasks:
+    path: "tasks/{id}.md"
+    fields:
+      level: { type: number, enum: [1, 2, 3.5] }
+      done: { type: boolean, enum: [true] }
+"#;
+        let schema = parse_schema_str(yaml).unwrap();
+        let level = &schema.collections["tasks"].fields["level"];
+        assert_eq!(level.enum_values, Some(vec!["1".into(), "2".into(), "3.5".into()]));
+        assert_eq!(level.string_enum_values(), None);
+
+        let yaml = r#"
+collections:
+  tasks:
+    path: "tasks/{id}.md"
+    fields:
+      level: { type: number, enum: [1, high] }
+      tags: { type: list, enum: [a, b] }
+"#;
+        let err = parse_schema_str(yaml).unwrap_err().to_string();
+        assert!(err.contains("enum value 'high' is not a number"), "{err}");
+        assert!(err.contains("enum values are only valid for string, number and boolean types"), "{err}");
+    }
+
+    #[test]
+    fn test_denormalize_needs_target_fields() {
+        let yaml = r#"
+collections:
+  users:
+    path: "users/{id}.md"
+    fields:
+      name: { type: string }
+  posts:
+    path: "posts/{id}.md"
+    fields:
+      author: { type: ref, target: users, denormalize: [name, avatar] }
+      title: { type: string, denormalize: [name] }
+"#;
+        let err = parse_schema_str(yaml).unwrap_err().to_string();
+        assert!(err.contains("denormalized field 'avatar' is not a field of 'users'"), "{err}");
+        assert!(!err.contains("field 'name' is not"), "{err}");
+        assert!(err.contains("denormalize is only valid for ref fields with a single target"), "{err}");
+    }
+
     #[test]
     fn test_query_view_needs_params() {
         let yaml = r#"
@@ -397,6 +914,22 @@ views:
         assert!(err.contains("params"));
     }
 
+    #[test]
+    fn test_enum_ordinal_column_collision() {
+        let yaml = r#"
+collections:
+  tickets:
+    path: "tickets/{title}.md"
+    enum_ordinals: true
+    fields:
+      title: { type: string }
+      status: { type: string, enum: [open, done] }
+      status_ordinal: { type: number }
+"#;
+        let err = parse_schema_str(yaml).unwrap_err().to_string();
+        assert!(err.contains("'status_ordinal' collides with the ordinal of enum field 'status'"), "{err}");
+    }
+
     #[test]
     fn test_invalid_buffer_format() {
         let yaml = r#"
@@ -435,6 +968,29 @@ collections:
 "#;
         let schema = parse_schema_str(yaml).unwrap();
         assert_eq!(schema.collections.len(), 1);
-        assert!(schema.collections["notes"].content);
+        assert!(schema.collections["notes"].has_content());
+    }
+
+    #[test]
+    fn test_content_format() {
+        let yaml = r#"
+collections:
+  notes:
+    path: "notes/{title}.md"
+    content: true
+  pages:
+    path: "pages/{title}.md"
+    content: { format: plaintext }
+  tags:
+    path: "tags/{title}.md"
+"#;
+        let schema = parse_schema_str(yaml).unwrap();
+        assert_eq!(schema.collections["notes"].content_format(), Some(ContentFormat::Markdown));
+        assert_eq!(schema.collections["pages"].content_format(), Some(ContentFormat::Plaintext));
+        assert!(schema.collections["pages"].has_content());
+        assert_eq!(schema.collections["tags"].content_format(), None);
+
+        let yaml = "collections:\n  pages:\n    path: \"pages/{title}.md\"\n    content: { format: rtf }\n";
+        assert!(parse_schema_str(yaml).is_err());
     }
 }
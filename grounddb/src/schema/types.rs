@@ -4,6 +4,9 @@ use std::collections::HashMap;
 /// Top-level schema definition parsed from schema.yaml
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SchemaDefinition {
+    /// Collection options inherited by collections that don't set them
+    #[serde(default)]
+    pub defaults: SchemaDefaults,
     #[serde(default)]
     pub types: HashMap<String, HashMap<String, FieldDefinition>>,
     #[serde(default)]
@@ -12,6 +15,17 @@ pub struct SchemaDefinition {
     pub views: HashMap<String, ViewDefinition>,
 }
 
+/// Schema-root `defaults:`, applied to each collection that omits the option
+/// when the schema is parsed
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SchemaDefaults {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub strict: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub additional_properties: Option<bool>,
+}
+
 /// Definition of a single collection
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CollectionDefinition {
@@ -19,7 +33,7 @@ pub struct CollectionDefinition {
     #[serde(default)]
     pub fields: HashMap<String, FieldDefinition>,
     #[serde(default)]
-    pub content: bool,
+    pub content: ContentOption,
     #[serde(default)]
     pub additional_properties: bool,
     #[serde(default)]
@@ -33,6 +47,93 @@ pub struct CollectionDefinition {
     /// JSONL record definitions (for multi-record files)
     #[serde(default)]
     pub records: Option<RecordDefinition>,
+    /// Previous name of this collection, used to migrate its documents on rename
+    #[serde(default)]
+    pub renamed_from: Option<String>,
+    /// Body seeded on insert when no content is given, with `{field}` placeholders
+    #[serde(default)]
+    pub content_template: Option<String>,
+    /// Derived fields parsed from the Markdown body and stored in the index
+    #[serde(default)]
+    pub extract_content: Option<ContentExtraction>,
+    /// Default for fields' `enum_case_insensitive`
+    #[serde(default)]
+    pub enum_case_insensitive: bool,
+    /// Where files are stored: under the path template, or flat by id
+    #[serde(default)]
+    pub layout: Layout,
+    /// Store document data zstd-compressed in the index
+    #[serde(default)]
+    pub compress: bool,
+    /// Index each enum field's declaration-order position, exposed to views
+    /// as `<field>_ordinal`
+    #[serde(default)]
+    pub enum_ordinals: bool,
+}
+
+/// Physical layout of a collection's files
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Layout {
+    /// Files live at the rendered path template
+    #[default]
+    Nested,
+    /// Every file lives directly in the template's base directory as
+    /// `{id}.<ext>`; the template still derives ids for non-auto collections,
+    /// but field changes never move files
+    Flat,
+}
+
+/// A collection's `content:` option: `true` / `false`, or `{ format: ... }`,
+/// which implies a body and records its format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ContentOption {
+    Enabled(bool),
+    Format { format: ContentFormat },
+}
+
+impl Default for ContentOption {
+    fn default() -> Self {
+        ContentOption::Enabled(false)
+    }
+}
+
+impl From<bool> for ContentOption {
+    fn from(enabled: bool) -> Self {
+        ContentOption::Enabled(enabled)
+    }
+}
+
+/// The format of a collection's document bodies
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ContentFormat {
+    Markdown,
+    Plaintext,
+    Html,
+}
+
+impl ContentFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ContentFormat::Markdown => "markdown",
+            ContentFormat::Plaintext => "plaintext",
+            ContentFormat::Html => "html",
+        }
+    }
+}
+
+/// Which derived fields to extract from a collection's Markdown bodies.
+/// Extracted values are exposed to views as `_title` and `_links`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ContentExtraction {
+    /// Index the text of the first H1 as `_title`
+    #[serde(default)]
+    pub title_from_h1: bool,
+    /// Index link destinations as the JSON array `_links`
+    #[serde(default)]
+    pub links: bool,
 }
 
 /// Configuration for document ID generation and conflict handling
@@ -66,8 +167,18 @@ pub struct FieldDefinition {
     pub field_type: FieldType,
     #[serde(default)]
     pub required: bool,
-    #[serde(rename = "enum", default)]
+    /// Whether an explicit `null` is accepted. Defaults to `!required`, so a
+    /// required field must be non-null unless it opts in with `nullable: true`.
+    #[serde(default)]
+    pub nullable: Option<bool>,
+    /// Allowed values. Number and boolean enums are kept in their YAML text
+    /// form (e.g. `"1"`, `"true"`) and compared by value during validation.
+    #[serde(rename = "enum", default, deserialize_with = "deserialize_enum_values")]
     pub enum_values: Option<Vec<String>>,
+    /// Match enum values ignoring case and store the declared variant.
+    /// Falls back to the collection's `enum_case_insensitive`.
+    #[serde(default)]
+    pub enum_case_insensitive: Option<bool>,
     #[serde(default)]
     pub default: Option<serde_yaml::Value>,
     #[serde(default)]
@@ -76,6 +187,42 @@ pub struct FieldDefinition {
     pub items: Option<ItemType>,
     #[serde(default)]
     pub on_delete: Option<OnDeletePolicy>,
+    /// How values compare when the field is used as a sort key
+    #[serde(default)]
+    pub collation: Option<Collation>,
+    /// For `ref` fields: fields of the target document copied next to the id
+    /// (`{ id, name, ... }`) on write and refreshed when the target changes
+    #[serde(default)]
+    pub denormalize: Option<Vec<String>>,
+    /// Once set, `update` rejects changes to the value. Use on fields the path
+    /// template derives the id from, so an update can't silently change it
+    #[serde(default)]
+    pub immutable: bool,
+}
+
+/// Deserialize `enum:` variants, accepting numbers and booleans as well as strings
+fn deserialize_enum_values<'de, D>(deserializer: D) -> Result<Option<Vec<String>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    use serde::de::Error;
+
+    let values: Option<Vec<serde_yaml::Value>> = Option::deserialize(deserializer)?;
+    values
+        .map(|values| {
+            values
+                .into_iter()
+                .map(|value| match value {
+                    serde_yaml::Value::String(s) => Ok(s),
+                    serde_yaml::Value::Number(n) => Ok(n.to_string()),
+                    serde_yaml::Value::Bool(b) => Ok(b.to_string()),
+                    other => Err(D::Error::custom(format!(
+                        "enum values must be strings, numbers or booleans, got {other:?}"
+                    ))),
+                })
+                .collect()
+        })
+        .transpose()
 }
 
 /// Field type enumeration
@@ -118,9 +265,21 @@ pub enum OnDeletePolicy {
     Error,
     Cascade,
     Nullify,
+    /// Set the referencing field to its schema `default` (null if it has none)
+    SetDefault,
     Archive,
 }
 
+/// Sort collation for a field
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Collation {
+    /// Compare text ignoring ASCII case (SQLite `COLLATE NOCASE`)
+    Nocase,
+    /// Compare values as numbers, so "10" sorts after "9"
+    Numeric,
+}
+
 /// View definition
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ViewDefinition {
@@ -133,6 +292,9 @@ pub struct ViewDefinition {
     pub buffer: Option<String>,
     #[serde(default)]
     pub params: Option<HashMap<String, ParamDefinition>>,
+    /// Previous name of this view, used to carry its cached rows over on rename
+    #[serde(default)]
+    pub renamed_from: Option<String>,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -179,6 +341,45 @@ impl SchemaDefinition {
 }
 
 impl CollectionDefinition {
+    /// Whether documents have a body
+    pub fn has_content(&self) -> bool {
+        !matches!(self.content, ContentOption::Enabled(false))
+    }
+
+    /// The body format: as declared, `Markdown` for `content: true`, or
+    /// `None` when documents have no body
+    pub fn content_format(&self) -> Option<ContentFormat> {
+        match self.content {
+            ContentOption::Enabled(false) => None,
+            ContentOption::Enabled(true) => Some(ContentFormat::Markdown),
+            ContentOption::Format { format } => Some(format),
+        }
+    }
+
+    /// Enum fields whose ordinals are indexed (`enum_ordinals: true`), by name
+    pub fn ordinal_fields(&self) -> Vec<(&str, &FieldDefinition)> {
+        if !self.enum_ordinals {
+            return Vec::new();
+        }
+        let mut fields: Vec<_> = self
+            .fields
+            .iter()
+            .filter(|(_, f)| f.enum_values.is_some())
+            .map(|(name, f)| (name.as_str(), f))
+            .collect();
+        fields.sort_by_key(|(name, _)| *name);
+        fields
+    }
+
+    /// The derived fields to extract from bodies. Only Markdown bodies are
+    /// parsed, so this is `None` for other formats even if `extract_content`
+    /// is set.
+    pub fn content_extraction(&self) -> Option<&ContentExtraction> {
+        self.extract_content
+            .as_ref()
+            .filter(|_| self.content_format() == Some(ContentFormat::Markdown))
+    }
+
     /// Returns the file extension from the path template
     pub fn file_extension(&self) -> &str {
         if self.path.ends_with(".json") {
@@ -205,11 +406,84 @@ impl CollectionDefinition {
 }
 
 impl FieldDefinition {
-    /// Returns the effective on_delete policy for a ref field,
+    /// Whether this field accepts an explicit `null` value
+    pub fn is_nullable(&self) -> bool {
+        self.nullable.unwrap_or(!self.required)
+    }
+
+    /// Returns the declared enum variant matching `value`, ignoring case when
+    /// `enum_case_insensitive` (or the collection default) is set
+    pub fn enum_variant(&self, value: &str, collection_default: bool) -> Option<&str> {
+        let variants = self.enum_values.as_ref()?;
+        let case_insensitive = self.enum_case_insensitive.unwrap_or(collection_default);
+        variants
+            .iter()
+            .find(|v| *v == value || (case_insensitive && v.to_lowercase() == value.to_lowercase()))
+            .map(|v| v.as_str())
+    }
+
+    /// Whether `value` is one of the declared enum values, comparing numbers
+    /// and booleans by value. True when the field has no enum.
+    pub fn enum_allows(&self, value: &serde_yaml::Value, collection_default: bool) -> bool {
+        self.enum_values.is_none() || self.enum_ordinal(value, collection_default).is_some()
+    }
+
+    /// Position of `value` among the declared enum values, matched as in
+    /// [`FieldDefinition::enum_allows`]. `None` without an enum or a match.
+    pub fn enum_ordinal(&self, value: &serde_yaml::Value, collection_default: bool) -> Option<usize> {
+        let variants = self.enum_values.as_ref()?;
+        match value {
+            serde_yaml::Value::String(s) => {
+                let variant = self.enum_variant(s, collection_default)?;
+                variants.iter().position(|v| v == variant)
+            }
+            serde_yaml::Value::Number(n) => {
+                variants.iter().position(|v| v.parse::<f64>().ok() == n.as_f64())
+            }
+            serde_yaml::Value::Bool(b) => variants.iter().position(|v| v.parse::<bool>() == Ok(*b)),
+            _ => None,
+        }
+    }
+
+    /// Enum values of a string field, which codegen turns into a Rust enum.
+    /// Number and boolean enums keep their scalar type.
+    pub fn string_enum_values(&self) -> Option<&[String]> {
+        match self.field_type {
+            FieldType::String => self.enum_values.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// Returns the definition holding the ref settings for this field's values:
+    /// the field itself for `ref` and for `list` with `items: ref` (target set
+    /// on the list), or the item definition for `items: { type: ref, ... }`
+    pub fn ref_definition(&self) -> Option<&FieldDefinition> {
+        match (&self.field_type, &self.items) {
+            (FieldType::Ref, _) => Some(self),
+            (FieldType::List, Some(ItemType::Simple(s))) if s == "ref" => Some(self),
+            (FieldType::List, Some(ItemType::Complex(item))) if item.field_type == FieldType::Ref => {
+                Some(item)
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the ref target of a ref field or a list of refs
+    pub fn ref_target(&self) -> Option<&RefTarget> {
+        self.ref_definition()?.target.as_ref()
+    }
+
+    /// Whether this is a list whose items are refs
+    pub fn is_ref_list(&self) -> bool {
+        self.field_type == FieldType::List && self.ref_definition().is_some()
+    }
+
+    /// Returns the effective on_delete policy for a ref field (or list of refs),
     /// falling back to the provided collection-level default
     pub fn effective_on_delete(&self, collection_default: Option<&OnDeletePolicy>) -> OnDeletePolicy {
-        self.on_delete
-            .clone()
+        self.ref_definition()
+            .and_then(|def| def.on_delete.clone())
+            .or_else(|| self.on_delete.clone())
             .or_else(|| collection_default.cloned())
             .unwrap_or(OnDeletePolicy::Error)
     }
@@ -0,0 +1,852 @@
+//! Bulk writes with deferred side effects.
+//!
+//! Writing documents one at a time recomputes the collection's directory hash
+//! and rebuilds every affected view after each write. [`Store::bulk_insert`],
+//! [`Store::import_documents`], [`Collection::delete_where`],
+//! [`Collection::update_where`] and [`Collection::purge`] instead run all their writes in one
+//! transaction and perform that post-write work once at the end. Writes from
+//! other threads wait until a bulk write finishes, and its change events are
+//! only sent once it commits.
+
+use super::{ChangeEvent, Collection, Store};
+use crate::document::CREATED_AT_KEY;
+use crate::error::{GroundDbError, Result};
+use crate::util::json_to_yaml;
+use chrono::{DateTime, Utc};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::thread::{self, ThreadId};
+
+/// File changes made during a bulk write, so they can be undone on failure.
+/// Each path is journaled once, in the state it had before the bulk write
+/// first touched it.
+#[derive(Default)]
+struct FileJournal {
+    created: Vec<PathBuf>,
+    saved: Vec<(PathBuf, Vec<u8>)>,
+    seen: HashSet<PathBuf>,
+}
+
+impl FileJournal {
+    /// Remember `path` before it is written or deleted: its current bytes if
+    /// it exists, otherwise that it is about to be created.
+    fn record(&mut self, path: &Path) {
+        if !self.seen.insert(path.to_path_buf()) {
+            return;
+        }
+        match std::fs::read(path) {
+            Ok(bytes) => self.saved.push((path.to_path_buf(), bytes)),
+            Err(_) => self.created.push(path.to_path_buf()),
+        }
+    }
+
+    /// Remove created files and restore saved ones.
+    fn restore(&self) {
+        for path in &self.created {
+            let _ = std::fs::remove_file(path);
+        }
+        for (path, bytes) in self.saved.iter().rev() {
+            if let Some(parent) = path.parent() {
+                let _ = std::fs::create_dir_all(parent);
+            }
+            let _ = std::fs::write(path, bytes);
+        }
+    }
+}
+
+/// State of an in-progress bulk write (see `with_deferred_writes`).
+pub(super) struct WriteScope {
+    /// Thread running the bulk write
+    pub(super) owner: ThreadId,
+    journal: FileJournal,
+    /// Collections whose `post_write` runs once the transaction commits
+    collections: HashSet<String>,
+    /// Change events announced once the transaction commits
+    pub(super) events: Vec<(String, ChangeEvent)>,
+}
+
+impl WriteScope {
+    fn new() -> Self {
+        WriteScope {
+            owner: thread::current().id(),
+            journal: FileJournal::default(),
+            collections: HashSet::new(),
+            events: Vec::new(),
+        }
+    }
+}
+
+impl Store {
+    /// Insert many documents into `collection` in a single transaction, then
+    /// update the directory hash and rebuild affected views once.
+    ///
+    /// Each record is a JSON object of field values, e.g. one line of NDJSON;
+    /// a string `content` member becomes the document body. If any insert
+    /// fails, files written so far are removed, the index is rolled back and
+    /// the error is returned. Returns the new document IDs in input order.
+    pub fn bulk_insert<I>(&self, collection: &str, records: I) -> Result<Vec<String>>
+    where
+        I: IntoIterator<Item = serde_json::Value>,
+    {
+        let col = self.collection(collection)?;
+        self.with_deferred_writes(|| {
+            let mut ids = Vec::new();
+            for mut record in records {
+                let content = match record.as_object_mut().and_then(|m| m.remove("content")) {
+                    Some(serde_json::Value::String(body)) => Some(body),
+                    _ => None,
+                };
+                ids.push(col.insert(json_to_yaml(&record), content.as_deref())?);
+            }
+            Ok(ids)
+        })
+    }
+
+    /// Import exported documents into `collection`, keeping each one's `id`,
+    /// `created_at` and `modified_at`, in a single transaction.
+    ///
+    /// Records are shaped like `list_dynamic` output (as written by
+    /// `grounddb export`): the implicit fields, the document's fields and an
+    /// optional string `content` body. `created_at` is pinned in the file's
+    /// front matter and `modified_at` becomes the file's mtime, so both survive
+    /// a rescan. Denormalized ref copies are kept as exported. A record whose
+    /// id is already taken fails with `PathConflict`; if any import fails, all
+    /// are undone. Returns the imported ids in input order.
+    pub fn import_documents<I>(&self, collection: &str, records: I) -> Result<Vec<String>>
+    where
+        I: IntoIterator<Item = serde_json::Value>,
+    {
+        let col = self.collection(collection)?;
+        self.with_deferred_writes(|| {
+            let mut ids = Vec::new();
+            for record in records {
+                ids.push(col.import(record)?);
+            }
+            Ok(ids)
+        })
+    }
+
+    /// Delete every document in `collection`. See [`Collection::purge`].
+    pub fn purge_collection(&self, collection: &str) -> Result<usize> {
+        self.collection(collection)?.purge()
+    }
+
+    /// Run `f` inside one transaction with post-write work deferred until it
+    /// returns. Other threads' writes wait until it finishes (see `gate`), and
+    /// a bulk write nested in another joins the outer one. Every document file
+    /// written or deleted meanwhile -- including by cascades and ref rewrites
+    /// in other collections -- is journaled; on error those changes are undone,
+    /// the transaction is rolled back and no change events are sent. On success
+    /// each touched collection gets a single `post_write`, then subscribers and
+    /// hooks hear about every write.
+    pub(super) fn with_deferred_writes<T>(&self, f: impl FnOnce() -> Result<T>) -> Result<T> {
+        let gate = self.write_gate.enter_exclusive();
+        if self.write_scope.lock().unwrap().is_some() {
+            return f();
+        }
+
+        *self.write_scope.lock().unwrap() = Some(WriteScope::new());
+        if let Err(e) = self.db.begin_transaction() {
+            *self.write_scope.lock().unwrap() = None;
+            return Err(e);
+        }
+        let result = f();
+        let scope = self.write_scope.lock().unwrap().take().unwrap_or_else(WriteScope::new);
+
+        let value = match result {
+            Ok(value) => value,
+            Err(e) => {
+                scope.journal.restore();
+                self.db.rollback_transaction()?;
+                return Err(e);
+            }
+        };
+
+        self.db.commit_transaction()?;
+        let flushed = scope.collections.iter().try_for_each(|name| self.post_write(name));
+        self.pending_events
+            .lock()
+            .unwrap()
+            .entry(scope.owner)
+            .or_default()
+            .extend(scope.events);
+        drop(gate);
+        self.announce_pending();
+        flushed.map(|()| value)
+    }
+
+    /// Journal `path` if a bulk write is in progress on this thread, before
+    /// it is written or deleted.
+    pub(super) fn journal_file(&self, path: &Path) {
+        self.with_current_scope(|scope| scope.journal.record(path));
+    }
+
+    /// Record `collection` for a `post_write` at the end of the bulk write in
+    /// progress on this thread. Returns false when there is none.
+    pub(super) fn defer_post_write(&self, collection: &str) -> bool {
+        self.with_current_scope(|scope| scope.collections.insert(collection.to_string()))
+            .is_some()
+    }
+
+    /// Run `f` on the bulk write in progress on the current thread, if any.
+    fn with_current_scope<R>(&self, f: impl FnOnce(&mut WriteScope) -> R) -> Option<R> {
+        let mut scope = self.write_scope.lock().unwrap();
+        scope
+            .as_mut()
+            .filter(|scope| scope.owner == thread::current().id())
+            .map(f)
+    }
+}
+
+impl Collection<'_> {
+    /// Insert several documents, each a `(data, content)` pair as taken by
+    /// [`Collection::insert`], in one transaction with a single view rebuild.
+    /// If any insert fails, all are undone. Returns the new ids in order.
+    pub fn insert_many(
+        &self,
+        items: Vec<(serde_yaml::Value, Option<String>)>,
+    ) -> Result<Vec<String>> {
+        self.store.with_deferred_writes(|| {
+            let mut ids = Vec::with_capacity(items.len());
+            for (data, content) in items {
+                ids.push(self.insert(data, content.as_deref())?);
+            }
+            Ok(ids)
+        })
+    }
+
+    /// Delete every document whose fields equal all of `filters` (the same
+    /// `key=value` model as `list_dynamic`), in one transaction with a single
+    /// view rebuild. Referential integrity policies apply to each delete; if
+    /// any delete fails, all are undone. Returns the number deleted.
+    pub fn delete_where(&self, filters: &HashMap<String, String>) -> Result<usize> {
+        let ids = self.matching_ids(filters)?;
+        self.store.with_deferred_writes(|| {
+            for id in &ids {
+                self.delete(id)?;
+            }
+            Ok(ids.len())
+        })
+    }
+
+    /// Delete every document in the collection, files and index rows, in one
+    /// transaction with a single view rebuild. Referential integrity policies
+    /// apply to each delete as in [`Collection::delete`]; if any delete fails,
+    /// all are undone, along with the cascades and ref rewrites they caused in
+    /// other collections. Returns the number deleted.
+    pub fn purge(&self) -> Result<usize> {
+        self.purge_documents(true)
+    }
+
+    /// Like [`Collection::purge`], but without applying `on_delete` policies:
+    /// documents elsewhere that reference purged ones are left dangling.
+    /// Meant for resetting test fixtures.
+    pub fn purge_unchecked(&self) -> Result<usize> {
+        self.purge_documents(false)
+    }
+
+    fn purge_documents(&self, check_refs: bool) -> Result<usize> {
+        let ids = self.matching_ids(&HashMap::new())?;
+        self.store.with_deferred_writes(|| {
+            let mut deleted = 0;
+            for id in &ids {
+                // A cascade from an earlier delete may already have removed it
+                if self.store.db.get_document(&self.name, id)?.is_none() {
+                    continue;
+                }
+                self.store.with_document_lock(&self.name, id, || {
+                    self.delete_locked(id, check_refs)
+                })?;
+                deleted += 1;
+            }
+            Ok(deleted)
+        })
+    }
+
+    /// Merge `patch` into every document whose fields equal all of `filters`,
+    /// as [`Collection::update_partial`] does, in one transaction with a single
+    /// view rebuild. If any update fails, all are undone. Returns the number
+    /// updated.
+    pub fn update_where(
+        &self,
+        filters: &HashMap<String, String>,
+        patch: serde_yaml::Value,
+    ) -> Result<usize> {
+        let ids = self.matching_ids(filters)?;
+        self.store.with_deferred_writes(|| {
+            for id in &ids {
+                self.update_partial(id, patch.clone(), None)?;
+            }
+            Ok(ids.len())
+        })
+    }
+
+    /// Write one exported record with its original id and timestamps.
+    /// Returns the id.
+    fn import(&self, mut record: serde_json::Value) -> Result<String> {
+        if self.definition().readonly {
+            return Err(GroundDbError::Other(format!(
+                "Collection '{}' is readonly",
+                self.name
+            )));
+        }
+        let fields = record.as_object_mut().ok_or_else(|| {
+            GroundDbError::Other("Import records must be JSON objects".into())
+        })?;
+        let id = match fields.remove("id") {
+            Some(serde_json::Value::String(id)) if !id.is_empty() => id,
+            _ => return Err(GroundDbError::Other("Import record has no string 'id'".into())),
+        };
+        let created = import_timestamp(fields.remove("created_at"), "created_at", &id)?;
+        let modified = import_timestamp(fields.remove("modified_at"), "modified_at", &id)?;
+        let content = match fields.remove("content") {
+            Some(serde_json::Value::String(body)) => Some(body),
+            _ => None,
+        };
+
+        let mut data = json_to_yaml(&record);
+        let warnings = self.validate_and_prepare(&mut data)?;
+        self.log_warnings(&id, &warnings);
+        if let serde_yaml::Value::Mapping(map) = &mut data {
+            map.insert(CREATED_AT_KEY.into(), created.to_rfc3339().into());
+        }
+
+        // The filename stem is the id, even where the template alone would
+        // render another one (a suffixed id, or an auto id)
+        let rendered = self.template().render(&data, Some(&id))?;
+        let rel_path = with_file_stem(&rendered, &id);
+        self.store.check_path_limits(&rel_path)?;
+        let abs_path = self.store.root.join(&rel_path);
+        if abs_path.exists() || self.store.db.get_document(&self.name, &id)?.is_some() {
+            return Err(GroundDbError::PathConflict {
+                path: rel_path,
+                existing_id: id,
+            });
+        }
+
+        self.store
+            .write_document_file(&abs_path, &abs_path, &data, content.as_deref())?;
+        std::fs::File::options()
+            .write(true)
+            .open(&abs_path)?
+            .set_modified(modified.into())?;
+
+        self.store.index_document(
+            &id,
+            &self.name,
+            &rel_path,
+            &data,
+            Some(&created.to_rfc3339()),
+            Some(&modified.to_rfc3339()),
+            content.as_deref(),
+        )?;
+        self.store.post_write(&self.name)?;
+        self.store.committed(
+            &self.name,
+            ChangeEvent::Inserted {
+                id: id.clone(),
+                data: serde_json::to_value(&data)?,
+            },
+        );
+        Ok(id)
+    }
+
+    fn matching_ids(&self, filters: &HashMap<String, String>) -> Result<Vec<String>> {
+        let rows = self.store.query(&self.name).filters(filters).run()?;
+        Ok(rows
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|row| row["id"].as_str().map(str::to_string))
+            .collect())
+    }
+}
+
+/// Parse an imported record's RFC 3339 timestamp field
+fn import_timestamp(
+    value: Option<serde_json::Value>,
+    field: &str,
+    id: &str,
+) -> Result<DateTime<Utc>> {
+    value
+        .as_ref()
+        .and_then(|v| v.as_str())
+        .and_then(|v| DateTime::parse_from_rfc3339(v).ok())
+        .map(|t| t.with_timezone(&Utc))
+        .ok_or_else(|| {
+            GroundDbError::Other(format!(
+                "Import record '{id}' has no RFC 3339 '{field}'"
+            ))
+        })
+}
+
+/// Replace the filename stem of a relative path, keeping its extension
+pub(super) fn with_file_stem(rel_path: &str, stem: &str) -> String {
+    let path = Path::new(rel_path);
+    let file_name = match path.extension() {
+        Some(ext) => format!("{stem}.{}", ext.to_string_lossy()),
+        None => stem.to_string(),
+    };
+    path.with_file_name(file_name)
+        .to_string_lossy()
+        .replace('\\', "/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn setup_store() -> (TempDir, Store) {
+        let tmp = TempDir::new().unwrap();
+        let schema = r#"
+collections:
+  posts:
+    path: "posts/{status}/{title}.md"
+    fields:
+      title: { type: string, required: true }
+      status: { type: string, enum: [draft, published] }
+      rank: { type: number }
+    strict: true
+
+  events:
+    path: "events/{name}.md"
+    fields:
+      name: { type: string, required: true }
+      level: { type: number }
+    content: true
+
+views:
+  event_levels:
+    query: |
+      SELECT name, level
+      FROM events
+      ORDER BY level DESC
+
+  post_titles:
+    query: |
+      SELECT title, status
+      FROM posts
+      ORDER BY title
+"#;
+        std::fs::write(tmp.path().join("schema.yaml"), schema).unwrap();
+        let store = Store::open(tmp.path().to_str().unwrap()).unwrap();
+        (tmp, store)
+    }
+
+    #[test]
+    fn test_bulk_insert_rebuilds_views_once() {
+        let (_tmp, store) = setup_store();
+        let builds_before = store.view_stats()["event_levels"]["build_count"]
+            .as_u64()
+            .unwrap();
+
+        let records = (0..500).map(|i| {
+            serde_json::json!({ "name": format!("event-{i}"), "level": i, "content": "Logged.\n" })
+        });
+        let ids = store.bulk_insert("events", records).unwrap();
+        assert_eq!(ids.len(), 500);
+        assert_eq!(ids[0], "event-0");
+
+        let builds_after = store.view_stats()["event_levels"]["build_count"]
+            .as_u64()
+            .unwrap();
+        assert_eq!(builds_after, builds_before + 1);
+
+        let rows = store.view_dynamic("event_levels").unwrap();
+        assert_eq!(rows.as_array().unwrap().len(), 500);
+        assert_eq!(rows[0]["level"], serde_json::json!(499));
+        let doc = store.get_dynamic("events", "event-7").unwrap();
+        assert_eq!(doc["content"].as_str().map(str::trim), Some("Logged."));
+    }
+
+    #[test]
+    fn test_bulk_insert_rolls_back_on_error() {
+        let (tmp, store) = setup_store();
+        let records = vec![
+            serde_json::json!({ "name": "first" }),
+            serde_json::json!({ "level": 3 }),
+        ];
+        assert!(store.bulk_insert("events", records).is_err());
+
+        assert!(store.get_dynamic("events", "first").is_err());
+        assert!(!tmp.path().join("events/first.md").exists());
+
+        // Later single writes run their side effects immediately again
+        store
+            .insert_dynamic("events", serde_json::json!({ "name": "after" }), None)
+            .unwrap();
+        let rows = store.view_dynamic("event_levels").unwrap();
+        assert_eq!(rows.as_array().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_bulk_write_announces_events_only_after_commit() {
+        let (_tmp, store) = setup_store();
+        let seen = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        {
+            let seen = std::sync::Arc::clone(&seen);
+            store.on_after_write(move |collection, _| {
+                seen.lock().unwrap().push(collection.to_string());
+                Ok(())
+            });
+        }
+
+        let failing = vec![serde_json::json!({ "name": "first" }), serde_json::json!({})];
+        assert!(store.bulk_insert("events", failing).is_err());
+        assert!(seen.lock().unwrap().is_empty());
+
+        let records = vec![serde_json::json!({ "name": "a" }), serde_json::json!({ "name": "b" })];
+        store.bulk_insert("events", records).unwrap();
+        assert_eq!(*seen.lock().unwrap(), ["events", "events"]);
+    }
+
+    #[test]
+    fn test_other_threads_writes_survive_a_bulk_rollback() {
+        let (tmp, store) = setup_store();
+        let store = std::sync::Arc::new(store);
+        let writer = std::sync::Arc::new(std::sync::Mutex::new(None));
+        {
+            // Start a write on another thread while the bulk write is running
+            let store_ref = std::sync::Arc::downgrade(&store);
+            let writer = std::sync::Arc::clone(&writer);
+            store.add_validator(move |collection: &str, data: &serde_yaml::Value| {
+                if collection == "events" && data["name"].as_str() == Some("first") {
+                    let store = store_ref.upgrade().unwrap();
+                    *writer.lock().unwrap() = Some(std::thread::spawn(move || {
+                        let data = serde_yaml::from_str("title: other\nstatus: draft").unwrap();
+                        store.collection("posts").unwrap().insert(data, None).unwrap();
+                    }));
+                }
+                Vec::new()
+            });
+        }
+
+        let records = vec![serde_json::json!({ "name": "first" }), serde_json::json!({})];
+        assert!(store.bulk_insert("events", records).is_err());
+        writer.lock().unwrap().take().unwrap().join().unwrap();
+
+        assert!(store.get_dynamic("posts", "other").is_ok());
+        assert!(tmp.path().join("posts/draft/other.md").exists());
+        assert!(store.get_dynamic("events", "first").is_err());
+    }
+
+    fn insert_posts(store: &Store) {
+        let posts = store.collection("posts").unwrap();
+        for (title, status, rank) in [
+            ("one", "draft", 1),
+            ("two", "published", 2),
+            ("three", "draft", 3),
+            ("four", "draft", 4),
+        ] {
+            let data: serde_yaml::Value =
+                serde_yaml::from_str(&format!("title: {title}\nstatus: {status}\nrank: {rank}"))
+                    .unwrap();
+            posts.insert(data, None).unwrap();
+        }
+    }
+
+    fn builds(store: &Store, view: &str) -> u64 {
+        store.view_stats()[view]["build_count"].as_u64().unwrap()
+    }
+
+    #[test]
+    fn test_insert_many_rebuilds_views_once() {
+        let (tmp, store) = setup_store();
+        let events = store.collection("events").unwrap();
+        let before = builds(&store, "event_levels");
+
+        let items = (0..20)
+            .map(|i| {
+                let data = serde_yaml::from_str(&format!("name: user-{i}\nlevel: {i}")).unwrap();
+                (data, Some(format!("Event {i}.\n")))
+            })
+            .collect();
+        let ids = events.insert_many(items).unwrap();
+        assert_eq!(ids.len(), 20);
+        assert_eq!(ids[3], "user-3");
+        assert_eq!(builds(&store, "event_levels"), before + 1);
+        let rows = store.view_dynamic("event_levels").unwrap();
+        assert_eq!(rows.as_array().unwrap().len(), 20);
+
+        // A failing item undoes the earlier ones
+        let items = vec![
+            (serde_yaml::from_str("name: kept-out").unwrap(), None),
+            (serde_yaml::from_str("level: 1").unwrap(), None),
+        ];
+        assert!(events.insert_many(items).is_err());
+        assert!(!tmp.path().join("events/kept-out.md").exists());
+        assert!(events.get("kept-out").is_err());
+    }
+
+    #[test]
+    fn test_delete_where_removes_matches_with_one_rebuild() {
+        let (tmp, store) = setup_store();
+        insert_posts(&store);
+        let posts = store.collection("posts").unwrap();
+        let before = builds(&store, "post_titles");
+
+        let filters = HashMap::from([("status".to_string(), "draft".to_string())]);
+        assert_eq!(posts.delete_where(&filters).unwrap(), 3);
+        assert_eq!(builds(&store, "post_titles"), before + 1);
+
+        let rows = store.view_dynamic("post_titles").unwrap();
+        assert_eq!(rows, serde_json::json!([{ "title": "two", "status": "published" }]));
+        assert!(!tmp.path().join("posts/draft/one.md").exists());
+        assert_eq!(posts.delete_where(&filters).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_purge_empties_collection_with_one_rebuild() {
+        let (tmp, store) = setup_store();
+        insert_posts(&store);
+        let before = builds(&store, "post_titles");
+
+        assert_eq!(store.purge_collection("posts").unwrap(), 4);
+        assert_eq!(builds(&store, "post_titles"), before + 1);
+        assert_eq!(store.view_dynamic("post_titles").unwrap(), serde_json::json!([]));
+        assert!(store.list_dynamic("posts", &HashMap::new()).unwrap().as_array().unwrap().is_empty());
+        let posts_dir = tmp.path().join("posts");
+        assert!(!posts_dir.exists() || posts_dir.read_dir().unwrap().next().is_none());
+        assert_eq!(store.collection("posts").unwrap().purge().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_purge_respects_referential_integrity_unless_unchecked() {
+        let tmp = TempDir::new().unwrap();
+        let schema = r#"
+collections:
+  users:
+    path: "users/{name}.md"
+    fields:
+      name: { type: string, required: true }
+  posts:
+    path: "posts/{title}.md"
+    fields:
+      title: { type: string, required: true }
+      author: { type: ref, target: users, required: true, on_delete: error }
+"#;
+        std::fs::write(tmp.path().join("schema.yaml"), schema).unwrap();
+        let store = Store::open(tmp.path().to_str().unwrap()).unwrap();
+        let users = store.collection("users").unwrap();
+        for name in ["ann", "bo"] {
+            users.insert(serde_yaml::from_str(&format!("name: {name}")).unwrap(), None).unwrap();
+        }
+        let post = serde_yaml::from_str("title: hello\nauthor: bo").unwrap();
+        store.collection("posts").unwrap().insert(post, None).unwrap();
+
+        // bo is still referenced, so nothing is deleted
+        assert!(users.purge().is_err());
+        assert!(users.get("ann").is_ok());
+        assert!(tmp.path().join("users/ann.md").exists());
+
+        assert_eq!(users.purge_unchecked().unwrap(), 2);
+        assert!(users.get("bo").is_err());
+        assert!(store.get_dynamic("posts", "hello").is_ok());
+    }
+
+    fn setup_ref_store() -> (TempDir, Store) {
+        let tmp = TempDir::new().unwrap();
+        let schema = r#"
+collections:
+  users:
+    path: "users/{name}.md"
+    fields:
+      name: { type: string, required: true }
+  posts:
+    path: "posts/{title}.md"
+    fields:
+      title: { type: string, required: true }
+      author: { type: ref, target: users, required: true, on_delete: cascade }
+  notes:
+    path: "notes/{title}.md"
+    fields:
+      title: { type: string, required: true }
+      owner: { type: ref, target: users, on_delete: nullify }
+  badges:
+    path: "badges/{label}.md"
+    fields:
+      label: { type: string, required: true }
+      holder: { type: ref, target: users, required: true, on_delete: error }
+"#;
+        std::fs::write(tmp.path().join("schema.yaml"), schema).unwrap();
+        let store = Store::open(tmp.path().to_str().unwrap()).unwrap();
+        for (collection, data) in [
+            ("users", "name: ann"),
+            ("users", "name: bo"),
+            ("posts", "title: p1\nauthor: ann"),
+            ("notes", "title: n1\nowner: ann"),
+            ("badges", "label: gold\nholder: bo"),
+        ] {
+            let data = serde_yaml::from_str(data).unwrap();
+            store.collection(collection).unwrap().insert(data, None).unwrap();
+        }
+        (tmp, store)
+    }
+
+    #[test]
+    fn test_delete_where_rollback_restores_cascaded_files() {
+        let (tmp, store) = setup_ref_store();
+        let note_before = std::fs::read_to_string(tmp.path().join("notes/n1.md")).unwrap();
+
+        // ann's delete cascades to p1 and nullifies n1; bo's then fails
+        let users = store.collection("users").unwrap();
+        assert!(users.delete_where(&HashMap::new()).is_err());
+
+        assert!(users.get("ann").is_ok());
+        assert!(tmp.path().join("posts/p1.md").exists());
+        assert!(store.get_dynamic("posts", "p1").is_ok());
+        let note_after = std::fs::read_to_string(tmp.path().join("notes/n1.md")).unwrap();
+        assert_eq!(note_after, note_before);
+        let note = store.get_dynamic("notes", "n1").unwrap();
+        assert_eq!(note["owner"], serde_json::json!("ann"));
+    }
+
+    #[test]
+    fn test_purge_rollback_restores_cascaded_files() {
+        let (tmp, store) = setup_ref_store();
+        let users = store.collection("users").unwrap();
+        assert!(users.purge().is_err());
+
+        assert!(tmp.path().join("users/ann.md").exists());
+        assert!(store.get_dynamic("posts", "p1").is_ok());
+        let note = store.get_dynamic("notes", "n1").unwrap();
+        assert_eq!(note["owner"], serde_json::json!("ann"));
+
+        // Without policies nothing cascades, and both users go
+        assert_eq!(users.purge_unchecked().unwrap(), 2);
+        assert!(store.get_dynamic("posts", "p1").is_ok());
+    }
+
+    #[test]
+    fn test_update_where_moves_matches_and_rolls_back() {
+        let (tmp, store) = setup_store();
+        insert_posts(&store);
+        let posts = store.collection("posts").unwrap();
+        let drafts = HashMap::from([("status".to_string(), "draft".to_string())]);
+        let before = builds(&store, "post_titles");
+
+        let patch: serde_yaml::Value = serde_yaml::from_str("status: published").unwrap();
+        assert_eq!(posts.update_where(&drafts, patch).unwrap(), 3);
+        assert_eq!(builds(&store, "post_titles"), before + 1);
+        assert!(tmp.path().join("posts/published/three.md").exists());
+        assert!(!tmp.path().join("posts/draft/three.md").exists());
+        assert_eq!(posts.get("four").unwrap().data["rank"], serde_yaml::Value::from(4));
+
+        // An invalid patch leaves every document as it was
+        let published = HashMap::from([("status".to_string(), "published".to_string())]);
+        let bad: serde_yaml::Value = serde_yaml::from_str("status: archived").unwrap();
+        assert!(posts.update_where(&published, bad).is_err());
+        let rows = store.query("posts").filter("status", "published").run().unwrap();
+        assert_eq!(rows.as_array().unwrap().len(), 4);
+    }
+
+    #[test]
+    fn test_import_preserves_ids_and_timestamps() {
+        let schema = r#"
+collections:
+  notes:
+    path: "notes/{id}.md"
+    id: { auto: ulid }
+    fields:
+      title: { type: string, required: true }
+    additional_properties: false
+    strict: true
+    content: true
+"#;
+        let source_dir = TempDir::new().unwrap();
+        std::fs::write(source_dir.path().join("schema.yaml"), schema).unwrap();
+        let source = Store::open(source_dir.path().to_str().unwrap()).unwrap();
+        let notes = source.collection("notes").unwrap();
+        for title in ["first", "second"] {
+            let data = serde_yaml::from_str(&format!("title: {title}")).unwrap();
+            notes.insert(data, Some("Body.\n")).unwrap();
+        }
+        let exported = source.list_dynamic("notes", &HashMap::new()).unwrap();
+
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        let target_dir = TempDir::new().unwrap();
+        std::fs::write(target_dir.path().join("schema.yaml"), schema).unwrap();
+        let target = Store::open(target_dir.path().to_str().unwrap()).unwrap();
+        let records = exported.as_array().unwrap().clone();
+        let ids = target.import_documents("notes", records).unwrap();
+        assert_eq!(ids.len(), 2);
+
+        let same_identity = |store: &Store| {
+            let imported = store.list_dynamic("notes", &HashMap::new()).unwrap();
+            let pairs = exported.as_array().unwrap().iter().zip(imported.as_array().unwrap());
+            for (original, copy) in pairs {
+                for key in ["id", "created_at", "modified_at", "title"] {
+                    assert_eq!(original[key], copy[key], "{key}");
+                }
+            }
+        };
+        same_identity(&target);
+
+        // The timestamps live on disk, so a fresh index over the files agrees
+        drop(target);
+        std::fs::remove_file(target_dir.path().join("_system.db")).unwrap();
+        let reopened = Store::open(target_dir.path().to_str().unwrap()).unwrap();
+        same_identity(&reopened);
+
+        // Importing an id that already exists is a conflict
+        let again = exported.as_array().unwrap()[..1].to_vec();
+        assert!(matches!(
+            reopened.import_documents("notes", again),
+            Err(GroundDbError::PathConflict { .. })
+        ));
+    }
+
+    #[test]
+    fn test_imported_created_at_survives_touch_and_rewrites() {
+        let tmp = TempDir::new().unwrap();
+        let schema = r#"
+collections:
+  users:
+    path: "users/{name}.md"
+    fields:
+      name: { type: string, required: true }
+  notes:
+    path: "notes/{id}.md"
+    fields:
+      title: { type: string, required: true }
+      author: { type: ref, target: users, on_delete: nullify }
+"#;
+        std::fs::write(tmp.path().join("schema.yaml"), schema).unwrap();
+        let store = Store::open(tmp.path().to_str().unwrap()).unwrap();
+        store
+            .collection("users")
+            .unwrap()
+            .insert(serde_yaml::from_str("name: alice").unwrap(), None)
+            .unwrap();
+        let record = serde_json::json!({
+            "id": "old-note",
+            "created_at": "2020-01-01T00:00:00+00:00",
+            "modified_at": "2020-06-01T00:00:00+00:00",
+            "title": "Old",
+            "author": "alice",
+        });
+        store.import_documents("notes", vec![record]).unwrap();
+        // The indexed column, which views and `changes_since` read
+        let created_at = || {
+            let rows = store
+                .db
+                .query_documents_sql::<String>(
+                    "SELECT created_at FROM documents WHERE id = 'old-note'",
+                    &HashMap::new(),
+                )
+                .unwrap();
+            rows[0]["created_at"].as_str().unwrap().to_string()
+        };
+        assert!(created_at().starts_with("2020-01-01T00:00:00"), "{}", created_at());
+
+        store.collection("notes").unwrap().touch("old-note").unwrap();
+        assert!(created_at().starts_with("2020-01-01T00:00:00"), "{}", created_at());
+
+        // Deleting the author rewrites the note's ref field
+        store.collection("users").unwrap().delete("alice").unwrap();
+        let note = store.collection("notes").unwrap().get("old-note").unwrap();
+        assert_eq!(note.data["author"], serde_yaml::Value::Null);
+        assert!(created_at().starts_with("2020-01-01T00:00:00"), "{}", created_at());
+    }
+}
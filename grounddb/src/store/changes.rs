@@ -0,0 +1,99 @@
+//! Incremental change feeds for sync clients.
+//!
+//! [`Store::changes_since`] lists what happened to a collection after a point
+//! in time: documents written since then, plus tombstones for documents
+//! deleted since then. Tombstones are kept in the system database's
+//! `deletions` table, recorded whenever a document leaves the index.
+
+use super::query::row_to_json;
+use super::Store;
+use crate::error::{GroundDbError, Result};
+use crate::system_db::DATA_JSON;
+use std::collections::HashMap;
+
+impl Store {
+    /// Changes to `collection` after the RFC 3339 timestamp `since`, oldest
+    /// first. Written documents are shaped like `list_dynamic` output with
+    /// `"change": "upsert"`; deleted ones are `{ "change": "delete", id,
+    /// deleted_at }`. A client can pass the latest `modified_at`/`deleted_at`
+    /// it has seen as the next `since`.
+    pub fn changes_since(&self, collection: &str, since: &str) -> Result<serde_json::Value> {
+        self.collection(collection)?;
+        let since = chrono::DateTime::parse_from_rfc3339(since)
+            .map_err(|e| GroundDbError::Other(format!("Invalid timestamp '{since}': {e}")))?
+            .with_timezone(&chrono::Utc)
+            .to_rfc3339();
+
+        let params = HashMap::from([
+            (":collection".to_string(), collection.to_string()),
+            (":since".to_string(), since.clone()),
+        ]);
+        let sql = format!(
+            "SELECT id, created_at, modified_at, {DATA_JSON} AS data_json, content_text \
+             FROM documents WHERE collection = :collection AND modified_at > :since"
+        );
+        let mut changes: Vec<(String, serde_json::Value)> = Vec::new();
+        for row in self.db.query_documents_sql(&sql, &params)? {
+            let mut doc = row_to_json(row)?;
+            let at = doc["modified_at"].as_str().unwrap_or_default().to_string();
+            doc["change"] = "upsert".into();
+            changes.push((at, doc));
+        }
+        for (id, deleted_at) in self.db.deletions_since(collection, &since)? {
+            let tombstone = serde_json::json!({
+                "change": "delete",
+                "id": id,
+                "deleted_at": deleted_at,
+            });
+            changes.push((deleted_at, tombstone));
+        }
+
+        changes.sort_by(|(a, a_doc), (b, b_doc)| {
+            a.cmp(b).then_with(|| a_doc["id"].as_str().cmp(&b_doc["id"].as_str()))
+        });
+        Ok(serde_json::Value::Array(changes.into_iter().map(|(_, doc)| doc).collect()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::Store;
+    use std::time::Duration;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_changes_since_returns_writes_and_tombstones() {
+        let tmp = TempDir::new().unwrap();
+        let schema = r#"
+collections:
+  notes:
+    path: "notes/{title}.md"
+    fields:
+      title: { type: string, required: true }
+"#;
+        std::fs::write(tmp.path().join("schema.yaml"), schema).unwrap();
+        let store = Store::open(tmp.path().to_str().unwrap()).unwrap();
+        let notes = store.collection("notes").unwrap();
+        notes.insert(serde_yaml::from_str("title: kept").unwrap(), None).unwrap();
+        notes.insert(serde_yaml::from_str("title: doomed").unwrap(), None).unwrap();
+
+        std::thread::sleep(Duration::from_millis(50));
+        let since = chrono::Utc::now().to_rfc3339();
+        std::thread::sleep(Duration::from_millis(50));
+
+        notes.insert(serde_yaml::from_str("title: fresh").unwrap(), None).unwrap();
+        notes.delete("doomed").unwrap();
+
+        let changes = store.changes_since("notes", &since).unwrap();
+        let changes = changes.as_array().unwrap();
+        assert_eq!(changes.len(), 2, "{changes:#?}");
+        assert_eq!(changes[0]["change"], serde_json::json!("upsert"));
+        assert_eq!(changes[0]["id"], serde_json::json!("fresh"));
+        assert_eq!(changes[0]["title"], serde_json::json!("fresh"));
+        assert_eq!(changes[1]["change"], serde_json::json!("delete"));
+        assert_eq!(changes[1]["id"], serde_json::json!("doomed"));
+        assert!(changes[1]["deleted_at"].as_str().unwrap() > since.as_str());
+
+        assert!(store.changes_since("notes", "yesterday").is_err());
+    }
+}
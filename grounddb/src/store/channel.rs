@@ -0,0 +1,314 @@
+//! Channel-backed collection subscriptions.
+//!
+//! [`Store::subscribe_collection_bounded`] delivers change events into a
+//! bounded queue instead of running a callback, so a slow consumer can't grow
+//! memory without limit. What happens when the queue is full is chosen with
+//! [`OverflowPolicy`].
+
+use super::{ChangeEvent, Store, SubscriptionId, SubscriptionManager};
+use crate::error::{GroundDbError, Result};
+use std::collections::VecDeque;
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::Duration;
+
+/// What a bounded subscription does with a new event when its queue is full.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// Discard the oldest queued event to make room. Discards are counted in
+    /// [`BoundedSubscription::dropped`].
+    DropOldest,
+    /// Block the writer until the consumer makes room. Never receive on the
+    /// thread that writes to the store, or the write will wait forever.
+    Block,
+    /// Stop delivering: the new event and all later ones are discarded, and
+    /// once the queued events are drained every receive returns
+    /// [`GroundDbError::SubscriptionOverflow`].
+    Error,
+}
+
+struct QueueState {
+    events: VecDeque<ChangeEvent>,
+    dropped: u64,
+    overflowed: bool,
+    closed: bool,
+}
+
+/// The queue shared between the store's callback and the receiver.
+struct BoundedQueue {
+    capacity: usize,
+    policy: OverflowPolicy,
+    state: Mutex<QueueState>,
+    changed: Condvar,
+}
+
+impl BoundedQueue {
+    fn push(&self, event: ChangeEvent) {
+        let mut state = self.state.lock().unwrap();
+        if state.closed {
+            return;
+        }
+        if state.overflowed {
+            state.dropped += 1;
+            return;
+        }
+        if state.events.len() >= self.capacity {
+            match self.policy {
+                OverflowPolicy::DropOldest => {
+                    state.events.pop_front();
+                    state.dropped += 1;
+                }
+                OverflowPolicy::Block => {
+                    state = self
+                        .changed
+                        .wait_while(state, |s| s.events.len() >= self.capacity && !s.closed)
+                        .unwrap();
+                    if state.closed {
+                        return;
+                    }
+                }
+                OverflowPolicy::Error => {
+                    state.overflowed = true;
+                    state.dropped += 1;
+                    return;
+                }
+            }
+        }
+        state.events.push_back(event);
+        self.changed.notify_all();
+    }
+
+    /// Pop the next event, waiting up to `timeout` for one (`None` = don't wait).
+    fn pop(&self, timeout: Option<Duration>) -> Result<Option<ChangeEvent>> {
+        let mut state = self.state.lock().unwrap();
+        if let Some(timeout) = timeout {
+            state = self
+                .changed
+                .wait_timeout_while(state, timeout, |s| s.events.is_empty() && !s.overflowed)
+                .unwrap()
+                .0;
+        }
+        match state.events.pop_front() {
+            Some(event) => {
+                self.changed.notify_all();
+                Ok(Some(event))
+            }
+            None if state.overflowed => Err(GroundDbError::SubscriptionOverflow {
+                capacity: self.capacity,
+            }),
+            None => Ok(None),
+        }
+    }
+}
+
+/// A collection subscription whose events are read from a bounded queue.
+/// Created by [`Store::subscribe_collection_bounded`]; dropping it unsubscribes.
+pub struct BoundedSubscription {
+    id: SubscriptionId,
+    queue: Arc<BoundedQueue>,
+    subscriptions: Arc<SubscriptionManager>,
+}
+
+impl BoundedSubscription {
+    /// The id of the underlying subscription.
+    pub fn id(&self) -> SubscriptionId {
+        self.id
+    }
+
+    /// Take the next queued event without waiting.
+    pub fn try_recv(&self) -> Result<Option<ChangeEvent>> {
+        self.queue.pop(None)
+    }
+
+    /// Take the next event, waiting up to `timeout` for one to arrive.
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<Option<ChangeEvent>> {
+        self.queue.pop(Some(timeout))
+    }
+
+    /// Number of events currently queued.
+    pub fn len(&self) -> usize {
+        self.queue.state.lock().unwrap().events.len()
+    }
+
+    /// Whether no events are queued.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Events discarded because the queue was full (or, under
+    /// [`OverflowPolicy::Error`], after it overflowed).
+    pub fn dropped(&self) -> u64 {
+        self.queue.state.lock().unwrap().dropped
+    }
+}
+
+impl Drop for BoundedSubscription {
+    fn drop(&mut self) {
+        // Release a writer blocked on a full queue before taking the
+        // subscription lock it is holding
+        self.queue.state.lock().unwrap().closed = true;
+        self.queue.changed.notify_all();
+        self.subscriptions.remove(self.id);
+    }
+}
+
+impl Store {
+    /// Subscribe to changes on a collection, delivering events into a queue of
+    /// at most `capacity` events. `policy` decides what happens when the
+    /// consumer falls behind and the queue is full.
+    pub fn subscribe_collection_bounded(
+        &self,
+        collection: &str,
+        capacity: usize,
+        policy: OverflowPolicy,
+    ) -> Result<BoundedSubscription> {
+        self.collection(collection)?;
+        if capacity == 0 {
+            return Err(GroundDbError::Other(
+                "Bounded subscription capacity must be at least 1".into(),
+            ));
+        }
+
+        let queue = Arc::new(BoundedQueue {
+            capacity,
+            policy,
+            state: Mutex::new(QueueState {
+                events: VecDeque::with_capacity(capacity),
+                dropped: 0,
+                overflowed: false,
+                closed: false,
+            }),
+            changed: Condvar::new(),
+        });
+        let sink = Arc::clone(&queue);
+        let id = self
+            .subscriptions
+            .add_collection_sub(collection, Box::new(move |event| sink.push(event)));
+
+        Ok(BoundedSubscription {
+            id,
+            queue,
+            subscriptions: Arc::clone(&self.subscriptions),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+    use tempfile::TempDir;
+
+    fn setup_store() -> (TempDir, Arc<Store>) {
+        let tmp = TempDir::new().unwrap();
+        let schema = r#"
+collections:
+  notes:
+    path: "notes/{title}.md"
+    fields:
+      title: { type: string, required: true }
+"#;
+        std::fs::write(tmp.path().join("schema.yaml"), schema).unwrap();
+        let store = Store::open(tmp.path().to_str().unwrap()).unwrap();
+        (tmp, Arc::new(store))
+    }
+
+    fn insert(store: &Store, title: &str) {
+        let data = serde_yaml::from_str(&format!("title: {title}")).unwrap();
+        store.collection("notes").unwrap().insert(data, None).unwrap();
+    }
+
+    fn inserted_id(event: Option<ChangeEvent>) -> String {
+        match event {
+            Some(ChangeEvent::Inserted { id, .. }) => id,
+            other => panic!("expected an insert, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_drop_oldest_keeps_latest_events() {
+        let (_tmp, store) = setup_store();
+        let sub = store
+            .subscribe_collection_bounded("notes", 2, OverflowPolicy::DropOldest)
+            .unwrap();
+        for title in ["a", "b", "c"] {
+            insert(&store, title);
+        }
+
+        assert_eq!(sub.len(), 2);
+        assert_eq!(sub.dropped(), 1);
+        assert_eq!(inserted_id(sub.try_recv().unwrap()), "b");
+        assert_eq!(inserted_id(sub.try_recv().unwrap()), "c");
+        assert!(sub.try_recv().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_error_policy_reports_overflow() {
+        let (_tmp, store) = setup_store();
+        let sub = store
+            .subscribe_collection_bounded("notes", 2, OverflowPolicy::Error)
+            .unwrap();
+        for title in ["a", "b", "c", "d"] {
+            insert(&store, title);
+        }
+
+        // Events queued before the overflow are still delivered
+        assert_eq!(inserted_id(sub.try_recv().unwrap()), "a");
+        assert_eq!(inserted_id(sub.try_recv().unwrap()), "b");
+        assert!(matches!(
+            sub.try_recv(),
+            Err(GroundDbError::SubscriptionOverflow { capacity: 2 })
+        ));
+        // Later events are not delivered either
+        insert(&store, "e");
+        assert!(sub.try_recv().is_err());
+        assert_eq!(sub.dropped(), 3);
+    }
+
+    #[test]
+    fn test_block_policy_waits_for_consumer() {
+        let (_tmp, store) = setup_store();
+        let sub = store
+            .subscribe_collection_bounded("notes", 1, OverflowPolicy::Block)
+            .unwrap();
+        insert(&store, "a");
+
+        let writer = {
+            let store = Arc::clone(&store);
+            std::thread::spawn(move || {
+                let started = Instant::now();
+                insert(&store, "b");
+                started.elapsed()
+            })
+        };
+        std::thread::sleep(Duration::from_millis(100));
+        // The writer is still waiting for room
+        assert!(!writer.is_finished());
+        assert_eq!(inserted_id(sub.try_recv().unwrap()), "a");
+
+        let waited = writer.join().unwrap();
+        assert!(waited >= Duration::from_millis(100));
+        assert_eq!(
+            inserted_id(sub.recv_timeout(Duration::from_secs(1)).unwrap()),
+            "b"
+        );
+        assert_eq!(sub.dropped(), 0);
+    }
+
+    #[test]
+    fn test_dropping_subscription_releases_blocked_writer() {
+        let (_tmp, store) = setup_store();
+        let sub = store
+            .subscribe_collection_bounded("notes", 1, OverflowPolicy::Block)
+            .unwrap();
+        insert(&store, "a");
+
+        let writer = {
+            let store = Arc::clone(&store);
+            std::thread::spawn(move || insert(&store, "b"))
+        };
+        std::thread::sleep(Duration::from_millis(50));
+        drop(sub);
+        writer.join().unwrap();
+        assert!(store.subscribe_collection_bounded("notes", 0, OverflowPolicy::Block).is_err());
+    }
+}
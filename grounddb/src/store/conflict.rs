@@ -0,0 +1,227 @@
+//! Detecting and resolving external edits that race a programmatic update.
+//!
+//! The index records each document's file mtime as `modified_at`. If the file's
+//! current mtime differs when an update runs, something else wrote the file
+//! since it was indexed, and [`StoreOptions::conflict_policy`] decides what the
+//! update does about it.
+//!
+//! [`StoreOptions::conflict_policy`]: super::StoreOptions::conflict_policy
+
+use super::{Collection, ConflictPolicy};
+use crate::error::{GroundDbError, Result};
+use crate::system_db::DocumentRecord;
+
+impl Collection<'_> {
+    /// Apply the store's conflict policy to an update of `record` whose new
+    /// values are `data` and `content`. Under `Merge` both may be rewritten
+    /// to include external changes.
+    pub(super) fn resolve_conflict(
+        &self,
+        record: &DocumentRecord,
+        data: &mut serde_yaml::Value,
+        content: &mut Option<String>,
+    ) -> Result<()> {
+        let policy = self.store.options.conflict_policy;
+        if policy == ConflictPolicy::LastWriteWins {
+            return Ok(());
+        }
+
+        let path = self.store.root.join(&record.path);
+        if !path.exists() {
+            return Ok(());
+        }
+        let disk_modified: chrono::DateTime<chrono::Utc> =
+            std::fs::metadata(&path)?.modified()?.into();
+        let indexed_modified = self
+            .store
+            .db
+            .get_document_fields(&self.name, &record.id, &[])?
+            .and_then(|r| r.modified_at);
+        if indexed_modified.as_deref() == Some(disk_modified.to_rfc3339().as_str()) {
+            return Ok(());
+        }
+
+        let conflict = |reason: String| GroundDbError::Conflict {
+            collection: self.name.clone(),
+            id: record.id.clone(),
+            reason,
+        };
+        if policy == ConflictPolicy::Error {
+            return Err(conflict(format!(
+                "indexed modified_at {}, file modified_at {}",
+                indexed_modified.as_deref().unwrap_or("unknown"),
+                disk_modified.to_rfc3339()
+            )));
+        }
+
+        // Three-way merge against the indexed version
+        let base = record.parse_data()?;
+        let disk = self.store.read_document_file(&path)?;
+        let base_content = self.store.db.get_document_content(&self.name, &record.id)?;
+
+        let mut keys: Vec<serde_yaml::Value> = Vec::new();
+        for side in [&base, &disk.data, &*data] {
+            for key in side.as_mapping().into_iter().flat_map(|m| m.keys()) {
+                if !keys.contains(key) {
+                    keys.push(key.clone());
+                }
+            }
+        }
+        for key in keys {
+            let theirs = disk.data.get(&key).cloned();
+            match take_theirs(base.get(&key), data.get(&key), theirs.as_ref()) {
+                Ok(false) => {}
+                Ok(true) => {
+                    if let Some(map) = data.as_mapping_mut() {
+                        match theirs {
+                            Some(value) => map.insert(key, value),
+                            None => map.remove(&key),
+                        };
+                    }
+                }
+                Err(()) => {
+                    let field = key.as_str().unwrap_or("?").to_string();
+                    return Err(conflict(format!("field '{field}' changed on both sides")));
+                }
+            }
+        }
+
+        // Bodies are compared ignoring surrounding whitespace
+        match take_theirs(
+            trimmed(&base_content).as_ref(),
+            trimmed(content).as_ref(),
+            trimmed(&disk.content).as_ref(),
+        ) {
+            Ok(false) => {}
+            Ok(true) => *content = disk.content,
+            Err(()) => return Err(conflict("body changed on both sides".to_string())),
+        }
+        Ok(())
+    }
+}
+
+/// Three-way merge of one value: whether the external side should replace
+/// ours. An unchanged side yields to a changed one and identical changes
+/// agree; differing changes on both sides are a conflict.
+fn take_theirs<T: PartialEq>(
+    base: Option<&T>,
+    ours: Option<&T>,
+    theirs: Option<&T>,
+) -> std::result::Result<bool, ()> {
+    if ours == theirs || theirs == base {
+        Ok(false)
+    } else if ours == base {
+        Ok(true)
+    } else {
+        Err(())
+    }
+}
+
+fn trimmed(content: &Option<String>) -> Option<&str> {
+    content.as_deref().map(str::trim).filter(|t| !t.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{Store, StoreOptions};
+    use super::*;
+    use std::time::{Duration, SystemTime};
+    use tempfile::TempDir;
+
+    fn setup_store(policy: ConflictPolicy) -> (TempDir, Store) {
+        let tmp = TempDir::new().unwrap();
+        let schema = r#"
+collections:
+  notes:
+    path: "notes/{title}.md"
+    fields:
+      title: { type: string, required: true }
+      status: { type: string }
+      priority: { type: number }
+    content: true
+"#;
+        std::fs::write(tmp.path().join("schema.yaml"), schema).unwrap();
+        let options = StoreOptions {
+            conflict_policy: policy,
+            ..Default::default()
+        };
+        let store = Store::open_with_options(tmp.path().to_str().unwrap(), options).unwrap();
+        let data: serde_yaml::Value =
+            serde_yaml::from_str("title: plan\nstatus: open\npriority: 1").unwrap();
+        store
+            .collection("notes")
+            .unwrap()
+            .insert(data, Some("First draft.\n"))
+            .unwrap();
+        (tmp, store)
+    }
+
+    /// Edit the file behind the store's back, without the watcher running,
+    /// leaving the index stale.
+    fn external_edit(tmp: &TempDir, raw: &str) {
+        let path = tmp.path().join("notes/plan.md");
+        std::fs::write(&path, raw).unwrap();
+        let file = std::fs::File::options().write(true).open(&path).unwrap();
+        file.set_modified(SystemTime::now() + Duration::from_secs(5)).unwrap();
+    }
+
+    fn update_status(store: &Store, status: &str) -> Result<()> {
+        let patch: serde_yaml::Value = serde_yaml::from_str(&format!("status: {status}")).unwrap();
+        store
+            .collection("notes")
+            .unwrap()
+            .update_partial("plan", patch, None)
+    }
+
+    #[test]
+    fn test_conflict_error_rejects_stale_update() {
+        let (tmp, store) = setup_store(ConflictPolicy::Error);
+        // Our own writes never count as conflicts
+        update_status(&store, "active").unwrap();
+
+        external_edit(&tmp, "---\ntitle: plan\nstatus: active\npriority: 5\n---\nFirst draft.\n");
+        let err = update_status(&store, "done").unwrap_err();
+        assert!(matches!(err, GroundDbError::Conflict { ref id, .. } if id == "plan"), "{err}");
+
+        let raw = std::fs::read_to_string(tmp.path().join("notes/plan.md")).unwrap();
+        assert!(raw.contains("priority: 5") && raw.contains("status: active"));
+    }
+
+    #[test]
+    fn test_conflict_last_write_wins_overwrites() {
+        let (tmp, store) = setup_store(ConflictPolicy::LastWriteWins);
+        external_edit(&tmp, "---\ntitle: plan\nstatus: open\npriority: 5\n---\nFirst draft.\n");
+
+        let data: serde_yaml::Value =
+            serde_yaml::from_str("title: plan\nstatus: done\npriority: 1").unwrap();
+        store
+            .collection("notes")
+            .unwrap()
+            .update("plan", data, Some("First draft.\n"))
+            .unwrap();
+        let doc = store.collection("notes").unwrap().get("plan").unwrap();
+        assert_eq!(doc.data["priority"], serde_yaml::Value::from(1));
+    }
+
+    #[test]
+    fn test_conflict_merge_keeps_external_changes() {
+        let (tmp, store) = setup_store(ConflictPolicy::Merge);
+        external_edit(&tmp, "---\ntitle: plan\nstatus: open\npriority: 5\n---\nRevised.\n");
+
+        // A full update built from the stale indexed values only changes status
+        let data: serde_yaml::Value =
+            serde_yaml::from_str("title: plan\nstatus: done\npriority: 1").unwrap();
+        let notes = store.collection("notes").unwrap();
+        notes.update("plan", data, Some("First draft.\n")).unwrap();
+
+        let doc = notes.get("plan").unwrap();
+        assert_eq!(doc.data["status"], serde_yaml::Value::from("done"));
+        assert_eq!(doc.data["priority"], serde_yaml::Value::from(5));
+        assert_eq!(doc.content.as_deref().map(str::trim), Some("Revised."));
+
+        // Both sides changing the same field can't be merged
+        external_edit(&tmp, "---\ntitle: plan\nstatus: blocked\npriority: 5\n---\nRevised.\n");
+        let err = update_status(&store, "archived").unwrap_err();
+        assert!(err.to_string().contains("'status' changed on both sides"), "{err}");
+    }
+}
@@ -0,0 +1,223 @@
+//! Detecting documents whose file no longer sits where the path template says.
+//!
+//! A manual edit can change a field used in the collection's path template
+//! without moving the file. [`Store::check_consistency`] reports such files and
+//! [`Store::reconcile_paths`] moves them to their canonical rendered path. It
+//! also reports files left out of the index for exceeding `max_document_bytes`.
+
+use super::bulk::with_file_stem;
+use super::Store;
+use crate::error::Result;
+
+/// A document whose indexed path differs from its rendered template path.
+struct PathMismatch {
+    collection: String,
+    id: String,
+    path: String,
+    expected: String,
+}
+
+impl PathMismatch {
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "collection": self.collection,
+            "id": self.id,
+            "path": self.path,
+            "expected": self.expected,
+        })
+    }
+}
+
+impl Store {
+    /// Report documents whose file path doesn't match what the collection's
+    /// path template renders from their current data, and files not indexed
+    /// because they exceed `max_document_bytes`. Read-only; see
+    /// [`Store::reconcile_paths`] to move mismatched documents.
+    pub fn check_consistency(&self) -> Result<serde_json::Value> {
+        let mismatches = self.path_mismatches()?;
+        let oversized = self.oversized_files()?;
+        Ok(serde_json::json!({
+            "ok": mismatches.is_empty() && oversized.is_empty(),
+            "path_mismatches": mismatches.iter().map(PathMismatch::to_json).collect::<Vec<_>>(),
+            "oversized": oversized,
+        }))
+    }
+
+    /// Move every mismatched document (see [`Store::check_consistency`]) to its
+    /// rendered path. Files are renamed, not rewritten. A document whose target
+    /// path is already taken is left in place and reported under `conflicts`.
+    pub fn reconcile_paths(&self) -> Result<serde_json::Value> {
+        let mut moved = Vec::new();
+        let mut conflicts = Vec::new();
+
+        for mismatch in self.path_mismatches()? {
+            let from = self.root.join(&mismatch.path);
+            let to = self.root.join(&mismatch.expected);
+            if to.exists() {
+                conflicts.push(mismatch.to_json());
+                continue;
+            }
+            self.with_document_lock(&mismatch.collection, &mismatch.id, || {
+                if let Some(parent) = to.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+                std::fs::rename(&from, &to)?;
+                let doc = self.read_document_file(&to)?;
+                self.index_document(
+                    &mismatch.id,
+                    &mismatch.collection,
+                    &mismatch.expected,
+                    &doc.data,
+                    Some(&doc.created_at.to_rfc3339()),
+                    Some(&doc.modified_at.to_rfc3339()),
+                    doc.content.as_deref(),
+                )
+            })?;
+            self.post_write(&mismatch.collection)?;
+            moved.push(mismatch.to_json());
+        }
+
+        Ok(serde_json::json!({
+            "moved": moved,
+            "conflicts": conflicts,
+        }))
+    }
+
+    /// Skipped files still over `max_document_bytes`, by collection and path.
+    /// (Files skipped as unreadable are reported by `validate_all`.)
+    fn oversized_files(&self) -> Result<Vec<serde_json::Value>> {
+        let Some(max) = self.options.max_document_bytes else {
+            return Ok(Vec::new());
+        };
+        let skipped = self.skipped_documents.lock().unwrap().clone();
+        let mut collections: Vec<_> = skipped.into_iter().collect();
+        collections.sort();
+
+        let mut oversized = Vec::new();
+        for (collection, paths) in collections {
+            for path in paths {
+                let Ok(metadata) = std::fs::metadata(self.root.join(&path)) else {
+                    continue;
+                };
+                if metadata.len() > max {
+                    oversized.push(serde_json::json!({
+                        "collection": collection,
+                        "path": path,
+                        "bytes": metadata.len(),
+                    }));
+                }
+            }
+        }
+        Ok(oversized)
+    }
+
+    fn path_mismatches(&self) -> Result<Vec<PathMismatch>> {
+        let mut names: Vec<&String> = self.schema.collections.keys().collect();
+        names.sort();
+
+        let mut mismatches = Vec::new();
+        for name in names {
+            let template = &self.path_templates[name];
+            for record in self.db.list_documents(name)? {
+                let data = record.parse_data()?;
+                // Documents missing a path field can't be placed; validation reports them
+                let Ok(rendered) = template.render(&data, Some(&record.id)) else {
+                    continue;
+                };
+                // The filename stem is the id, which may carry a conflict suffix
+                // the template alone doesn't render (notes/intro-2.md)
+                let expected = with_file_stem(&rendered, &record.id);
+                if expected != record.path {
+                    mismatches.push(PathMismatch {
+                        collection: name.clone(),
+                        id: record.id,
+                        path: record.path,
+                        expected,
+                    });
+                }
+            }
+        }
+        Ok(mismatches)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn setup_store() -> (TempDir, Store) {
+        let tmp = TempDir::new().unwrap();
+        let schema = r#"
+collections:
+  tasks:
+    path: "tasks/{status}/{id}.md"
+    id: { auto: ulid }
+    fields:
+      title: { type: string, required: true }
+      status: { type: string, enum: [open, done], default: open }
+"#;
+        std::fs::write(tmp.path().join("schema.yaml"), schema).unwrap();
+        let store = Store::open(tmp.path().to_str().unwrap()).unwrap();
+        (tmp, store)
+    }
+
+    #[test]
+    fn test_path_mismatch_reported_and_reconciled() {
+        let (tmp, store) = setup_store();
+        let tasks = store.collection("tasks").unwrap();
+        let id = tasks
+            .insert(serde_yaml::from_str("title: Ship it").unwrap(), Some("Notes\n"))
+            .unwrap();
+        assert_eq!(store.check_consistency().unwrap()["ok"], serde_json::json!(true));
+
+        // Mark the task done by hand without moving the file, then re-index
+        let path = tmp.path().join(format!("tasks/open/{id}.md"));
+        let edited = std::fs::read_to_string(&path).unwrap().replace("status: open", "status: done");
+        std::fs::write(&path, &edited).unwrap();
+        store.rebuild(Some("tasks")).unwrap();
+
+        let report = store.check_consistency().unwrap();
+        assert_eq!(report["ok"], serde_json::json!(false));
+        assert_eq!(
+            report["path_mismatches"],
+            serde_json::json!([{
+                "collection": "tasks",
+                "id": id,
+                "path": format!("tasks/open/{id}.md"),
+                "expected": format!("tasks/done/{id}.md"),
+            }])
+        );
+
+        let result = store.reconcile_paths().unwrap();
+        assert_eq!(result["moved"].as_array().unwrap().len(), 1);
+        assert!(!path.exists());
+        let moved = tmp.path().join(format!("tasks/done/{id}.md"));
+        assert_eq!(std::fs::read_to_string(&moved).unwrap(), edited);
+        assert_eq!(tasks.get(&id).unwrap().data["status"], serde_yaml::Value::from("done"));
+        assert_eq!(store.check_consistency().unwrap()["ok"], serde_json::json!(true));
+    }
+
+    #[test]
+    fn test_suffixed_ids_are_not_mismatches() {
+        let tmp = TempDir::new().unwrap();
+        let schema = r#"
+collections:
+  notes:
+    path: "notes/{title}.md"
+    fields:
+      title: { type: string, required: true }
+    id: { on_conflict: suffix }
+"#;
+        std::fs::write(tmp.path().join("schema.yaml"), schema).unwrap();
+        let store = Store::open(tmp.path().to_str().unwrap()).unwrap();
+        let notes = store.collection("notes").unwrap();
+        notes.insert(serde_yaml::from_str("title: intro").unwrap(), None).unwrap();
+        let id = notes.insert(serde_yaml::from_str("title: intro").unwrap(), None).unwrap();
+        assert_eq!(id, "intro-2");
+
+        assert_eq!(store.check_consistency().unwrap()["ok"], serde_json::json!(true));
+        let result = store.reconcile_paths().unwrap();
+        assert_eq!(result["conflicts"], serde_json::json!([]));
+    }
+}
@@ -0,0 +1,148 @@
+//! Denormalized ref fields.
+//!
+//! A ref declared with `denormalize: [name, ...]` is stored as a mapping of the
+//! target's id plus copies of the listed fields, so readers of the referencing
+//! document don't need a second lookup. Copies are filled in on every write of
+//! the referencing document and refreshed when the target document changes.
+
+use super::{ref_value_id, Collection};
+use crate::error::Result;
+use crate::schema::RefTarget;
+use std::collections::HashSet;
+
+/// The stored form of a denormalized ref: `{ id, <fields> }`, copying the
+/// listed fields that `target` has set.
+fn denormalized_value(id: &str, fields: &[String], target: &serde_yaml::Value) -> serde_yaml::Value {
+    let mut mapping = serde_yaml::Mapping::new();
+    mapping.insert("id".into(), id.into());
+    for field in fields {
+        if let Some(value) = target.get(field.as_str()) {
+            mapping.insert(field.as_str().into(), value.clone());
+        }
+    }
+    serde_yaml::Value::Mapping(mapping)
+}
+
+impl Collection<'_> {
+    /// Expand every denormalized ref in `data` to `{ id, <fields> }` from the
+    /// current target document. A ref to a missing document keeps only its id.
+    pub(super) fn denormalize_refs(&self, data: &mut serde_yaml::Value) -> Result<()> {
+        for (field_name, field_def) in &self.definition().fields {
+            let (Some(fields), Some(RefTarget::Single(target))) =
+                (&field_def.denormalize, &field_def.target)
+            else {
+                continue;
+            };
+            let Some(id) = data.get(field_name.as_str()).and_then(ref_value_id) else {
+                continue;
+            };
+            let id = id.to_string();
+            let target_data = match self.store.db.get_document(target, &id)? {
+                Some(record) => record.parse_data()?,
+                None => serde_yaml::Value::Null,
+            };
+            if let Some(mapping) = data.as_mapping_mut() {
+                mapping.insert(
+                    field_name.as_str().into(),
+                    denormalized_value(&id, fields, &target_data),
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Refresh the denormalized copies of document `id` held by other
+    /// documents, after a write that changed `changed` fields to `data`.
+    pub(super) fn propagate_denormalized(
+        &self,
+        id: &str,
+        data: &serde_yaml::Value,
+        changed: &HashSet<String>,
+    ) -> Result<()> {
+        let mut sources: Vec<&String> = self.store.schema.collections.keys().collect();
+        sources.sort();
+
+        for source in sources {
+            let mut rewritten = false;
+            for (field_name, field_def) in &self.store.schema.collections[source].fields {
+                let (Some(fields), Some(RefTarget::Single(target))) =
+                    (&field_def.denormalize, &field_def.target)
+                else {
+                    continue;
+                };
+                if *target != self.name || !fields.iter().any(|f| changed.contains(f)) {
+                    continue;
+                }
+                let value = denormalized_value(id, fields, data);
+                for record in self.store.db.find_field_references(source, field_name, &self.name, id)? {
+                    self.store.rewrite_field(&record, field_name, |_| value.clone())?;
+                    rewritten = true;
+                }
+            }
+            if rewritten {
+                self.store.post_write(source)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::Store;
+    use tempfile::TempDir;
+
+    fn setup_store() -> (TempDir, Store) {
+        let tmp = TempDir::new().unwrap();
+        let schema = r#"
+collections:
+  users:
+    path: "users/{id}.md"
+    id: { auto: ulid }
+    fields:
+      name: { type: string, required: true }
+      email: { type: string }
+  posts:
+    path: "posts/{id}.md"
+    id: { auto: ulid }
+    fields:
+      title: { type: string, required: true }
+      author: { type: ref, target: users, denormalize: [name] }
+"#;
+        std::fs::write(tmp.path().join("schema.yaml"), schema).unwrap();
+        let store = Store::open(tmp.path().to_str().unwrap()).unwrap();
+        (tmp, store)
+    }
+
+    #[test]
+    fn test_denormalized_ref_written_and_refreshed() {
+        let (_tmp, store) = setup_store();
+        let users = store.collection("users").unwrap();
+        let posts = store.collection("posts").unwrap();
+        let user_id = users
+            .insert(serde_yaml::from_str("name: Ada\nemail: ada@example.com").unwrap(), None)
+            .unwrap();
+        let post_id = posts
+            .insert(
+                serde_yaml::from_str(&format!("title: Hello\nauthor: {user_id}")).unwrap(),
+                None,
+            )
+            .unwrap();
+
+        let expected: serde_yaml::Value =
+            serde_yaml::from_str(&format!("{{ id: {user_id}, name: Ada }}")).unwrap();
+        assert_eq!(posts.get(&post_id).unwrap().data["author"], expected);
+
+        // Renaming the user refreshes the copy held by the post
+        let mut user = users.get(&user_id).unwrap().data;
+        user["name"] = "Ada Lovelace".into();
+        users.update(&user_id, user, None).unwrap();
+        let expected: serde_yaml::Value =
+            serde_yaml::from_str(&format!("{{ id: {user_id}, name: Ada Lovelace }}")).unwrap();
+        assert_eq!(posts.get(&post_id).unwrap().data["author"], expected);
+        assert_eq!(
+            store.get_dynamic("posts", &post_id).unwrap()["author"]["name"],
+            serde_json::json!("Ada Lovelace")
+        );
+    }
+}
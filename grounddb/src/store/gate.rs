@@ -0,0 +1,133 @@
+//! Isolation between bulk writes and every other write.
+//!
+//! A bulk write (see `with_deferred_writes`) holds one SQLite transaction open
+//! across many documents. Without a gate, a write from another thread would
+//! run inside that transaction and be rolled back with it. [`WriteGate`] lets
+//! any number of single writes run at once, but a bulk write waits for them to
+//! finish and holds the others off until it commits or rolls back. Both modes
+//! are re-entrant on the thread that holds them, so a bulk write can run single
+//! writes (and a single write can cascade into others).
+
+use std::collections::HashMap;
+use std::sync::{Condvar, Mutex};
+use std::thread::{self, ThreadId};
+
+#[derive(Default)]
+pub(super) struct WriteGate {
+    state: Mutex<GateState>,
+    released: Condvar,
+}
+
+#[derive(Default)]
+struct GateState {
+    /// Thread running a bulk write, and how many times it has entered
+    exclusive: Option<(ThreadId, usize)>,
+    /// Threads running single writes, and how many times each has entered
+    shared: HashMap<ThreadId, usize>,
+}
+
+impl GateState {
+    fn holds(&self, thread: ThreadId) -> bool {
+        self.shared.contains_key(&thread)
+            || self.exclusive.is_some_and(|(owner, _)| owner == thread)
+    }
+}
+
+impl WriteGate {
+    /// Enter for a single write, waiting while another thread runs a bulk write.
+    pub(super) fn enter_shared(&self) -> GateGuard<'_> {
+        let me = thread::current().id();
+        let mut state = self.state.lock().unwrap();
+        while state.exclusive.is_some_and(|(owner, _)| owner != me) {
+            state = self.released.wait(state).unwrap();
+        }
+        *state.shared.entry(me).or_default() += 1;
+        GateGuard { gate: self, exclusive: false }
+    }
+
+    /// Enter for a bulk write, waiting until no other thread is writing.
+    pub(super) fn enter_exclusive(&self) -> GateGuard<'_> {
+        let me = thread::current().id();
+        let mut state = self.state.lock().unwrap();
+        loop {
+            match state.exclusive {
+                Some((owner, depth)) if owner == me => {
+                    state.exclusive = Some((me, depth + 1));
+                    break;
+                }
+                None if state.shared.keys().all(|&thread| thread == me) => {
+                    state.exclusive = Some((me, 1));
+                    break;
+                }
+                _ => state = self.released.wait(state).unwrap(),
+            }
+        }
+        GateGuard { gate: self, exclusive: true }
+    }
+
+    /// Whether the current thread is inside the gate.
+    pub(super) fn held_by_current_thread(&self) -> bool {
+        self.state.lock().unwrap().holds(thread::current().id())
+    }
+}
+
+/// Leaves the gate on drop.
+pub(super) struct GateGuard<'a> {
+    gate: &'a WriteGate,
+    exclusive: bool,
+}
+
+impl Drop for GateGuard<'_> {
+    fn drop(&mut self) {
+        let me = thread::current().id();
+        let mut state = self.gate.state.lock().unwrap();
+        if self.exclusive {
+            if let Some((_, depth)) = state.exclusive.as_mut() {
+                *depth -= 1;
+                if *depth == 0 {
+                    state.exclusive = None;
+                }
+            }
+        } else if let Some(depth) = state.shared.get_mut(&me) {
+            *depth -= 1;
+            if *depth == 0 {
+                state.shared.remove(&me);
+            }
+        }
+        drop(state);
+        self.gate.released.notify_all();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    #[test]
+    fn test_exclusive_waits_for_other_threads_and_is_reentrant() {
+        let gate = Arc::new(WriteGate::default());
+        let exclusive = gate.enter_exclusive();
+        {
+            // Re-entering on the owning thread doesn't block
+            let _nested = gate.enter_exclusive();
+            let _single = gate.enter_shared();
+        }
+        assert!(gate.held_by_current_thread());
+
+        let (tx, rx) = mpsc::channel();
+        let other = Arc::clone(&gate);
+        let handle = thread::spawn(move || {
+            let _single = other.enter_shared();
+            tx.send(()).unwrap();
+        });
+        assert!(rx.recv_timeout(Duration::from_millis(100)).is_err());
+
+        drop(exclusive);
+        assert!(!gate.held_by_current_thread());
+        rx.recv_timeout(Duration::from_secs(5)).unwrap();
+        handle.join().unwrap();
+    }
+}
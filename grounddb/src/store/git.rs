@@ -0,0 +1,243 @@
+//! Document history from git (the `git` feature).
+//!
+//! Data directories are plain files and often live in a git repository. This
+//! reads a document's past revisions by running the `git` command against the
+//! data directory, which may be the repository root or any directory inside
+//! it. Nothing here writes to the repository.
+
+use super::Collection;
+use crate::document::{self, Document};
+use crate::error::{GroundDbError, Result};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::path::Path;
+use std::process::Command;
+
+/// Separates the fields of one commit in `git log` output.
+const FIELD_SEP: char = '\u{1f}';
+
+/// Starts each commit in `git log` output.
+const RECORD_SEP: char = '\u{1e}';
+
+/// One commit that touched a document's file, as listed by
+/// [`Collection::history`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RevisionMeta {
+    /// Full commit hash
+    pub commit: String,
+    pub author: String,
+    /// Author time
+    pub time: DateTime<Utc>,
+    /// First line of the commit message
+    pub message: String,
+    /// The file's path at this commit, relative to the data directory.
+    /// Differs from the current path if the document has moved since.
+    pub path: String,
+}
+
+impl Collection<'_> {
+    /// Commits that touched the document's file, newest first, following it
+    /// across moves. Uncommitted changes are not listed. Fails if the data
+    /// directory isn't inside a git work tree.
+    pub fn history(&self, id: &str) -> Result<Vec<RevisionMeta>> {
+        let rel_path = self.indexed_path(id)?;
+        let format = format!("--format={RECORD_SEP}%H{FIELD_SEP}%an{FIELD_SEP}%aI{FIELD_SEP}%s");
+        let log = run_git(
+            &self.store.root,
+            &[
+                "log",
+                "--follow",
+                "--name-only",
+                "--relative",
+                &format,
+                "--",
+                &rel_path,
+            ],
+        )?;
+        log.split(RECORD_SEP)
+            .filter(|record| !record.trim().is_empty())
+            .map(|record| parse_revision(record, &rel_path))
+            .collect()
+    }
+
+    /// The document as it was at `revision` (any commit-ish git accepts,
+    /// e.g. a hash from [`Collection::history`] or `HEAD~2`). Its
+    /// `modified_at` is the commit's author time; `created_at` is the front
+    /// matter's `created_at` when pinned, otherwise the same time.
+    pub fn get_at(&self, id: &str, revision: &str) -> Result<Document<serde_yaml::Value>> {
+        let commit = run_git(
+            &self.store.root,
+            &["rev-parse", "--verify", &format!("{revision}^{{commit}}")],
+        )?
+        .trim()
+        .to_string();
+        let history = self.history(id)?;
+        // The newest change at or before the revision names the file's path then
+        let revision_meta = history
+            .iter()
+            .find(|r| r.commit == commit)
+            .or_else(|| {
+                history.iter().find(|r| {
+                    run_git(
+                        &self.store.root,
+                        &["merge-base", "--is-ancestor", &r.commit, &commit],
+                    )
+                    .is_ok()
+                })
+            })
+            .ok_or_else(|| GroundDbError::NotFound {
+                collection: self.name.clone(),
+                id: format!("{id}@{revision}"),
+            })?;
+
+        let raw = run_git(
+            &self.store.root,
+            &[
+                "show",
+                &format!("{}:./{}", revision_meta.commit, revision_meta.path),
+            ],
+        )?;
+        let (data, content) = document::parse_front_matter(&raw)?;
+        let created_at = data
+            .get(document::CREATED_AT_KEY)
+            .and_then(|v| v.as_str())
+            .and_then(|v| DateTime::parse_from_rfc3339(v).ok())
+            .map_or(revision_meta.time, |t| t.with_timezone(&Utc));
+        Ok(Document {
+            id: id.to_string(),
+            created_at,
+            modified_at: revision_meta.time,
+            data,
+            content,
+        })
+    }
+
+    /// The document's current path relative to the data directory
+    fn indexed_path(&self, id: &str) -> Result<String> {
+        self.store
+            .db
+            .get_document(&self.name, id)?
+            .map(|record| record.path)
+            .ok_or_else(|| GroundDbError::NotFound {
+                collection: self.name.clone(),
+                id: id.to_string(),
+            })
+    }
+}
+
+/// Parse one `git log` record: the header line, then the file name(s).
+fn parse_revision(record: &str, fallback_path: &str) -> Result<RevisionMeta> {
+    let mut lines = record.lines();
+    let header = lines.next().unwrap_or_default();
+    let fields: Vec<&str> = header.splitn(4, FIELD_SEP).collect();
+    let [commit, author, time, message] = fields[..] else {
+        return Err(GroundDbError::Other(format!(
+            "Unexpected git log output: {header}"
+        )));
+    };
+    let time = DateTime::parse_from_rfc3339(time)
+        .map_err(|e| GroundDbError::Other(format!("Bad commit time '{time}': {e}")))?
+        .with_timezone(&Utc);
+    let path = lines
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .unwrap_or(fallback_path)
+        .to_string();
+    Ok(RevisionMeta {
+        commit: commit.to_string(),
+        author: author.to_string(),
+        time,
+        message: message.to_string(),
+        path,
+    })
+}
+
+/// Run `git` in `dir`, returning stdout, or stderr as the error.
+fn run_git(dir: &Path, args: &[&str]) -> Result<String> {
+    let output = Command::new("git").arg("-C").arg(dir).args(args).output()?;
+    if !output.status.success() {
+        return Err(GroundDbError::Other(format!(
+            "git {} failed: {}",
+            args.first().copied().unwrap_or_default(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Store;
+    use std::path::Path;
+    use tempfile::TempDir;
+
+    fn git(dir: &Path, args: &[&str]) {
+        let status = std::process::Command::new("git")
+            .arg("-C")
+            .arg(dir)
+            .args([
+                "-c",
+                "user.name=Tester",
+                "-c",
+                "user.email=tester@example.com",
+            ])
+            .args(["-c", "commit.gpgsign=false"])
+            .args(args)
+            .status()
+            .unwrap();
+        assert!(status.success(), "git {args:?}");
+    }
+
+    #[test]
+    fn test_history_and_get_at() {
+        let tmp = TempDir::new().unwrap();
+        let schema = r#"
+collections:
+  posts:
+    path: "posts/{status}/{title}.md"
+    fields:
+      title: { type: string, required: true }
+      status: { type: string, enum: [draft, published] }
+    content: true
+"#;
+        std::fs::write(tmp.path().join("schema.yaml"), schema).unwrap();
+        std::fs::write(tmp.path().join(".gitignore"), "_system.*\n").unwrap();
+        git(tmp.path(), &["init", "-q"]);
+        let store = Store::open(tmp.path().to_str().unwrap()).unwrap();
+        let posts = store.collection("posts").unwrap();
+
+        let body = "Opening paragraph that stays the same.\n\nA second paragraph, also kept.\n\n";
+        let data = serde_yaml::from_str("title: hello\nstatus: draft").unwrap();
+        posts
+            .insert(data, Some(&format!("{body}First draft.\n")))
+            .unwrap();
+        git(tmp.path(), &["add", "-A"]);
+        git(tmp.path(), &["commit", "-q", "-m", "Draft hello"]);
+
+        // Publishing moves the file; history follows it
+        let patch = serde_yaml::from_str("status: published").unwrap();
+        posts
+            .update_partial("hello", patch, Some(&format!("{body}Final text.\n")))
+            .unwrap();
+        git(tmp.path(), &["add", "-A"]);
+        git(tmp.path(), &["commit", "-q", "-m", "Publish hello"]);
+
+        let history = posts.history("hello").unwrap();
+        let messages: Vec<&str> = history.iter().map(|r| r.message.as_str()).collect();
+        assert_eq!(messages, ["Publish hello", "Draft hello"]);
+        assert_eq!(history[0].author, "Tester");
+        assert_eq!(history[0].path, "posts/published/hello.md");
+        assert_eq!(history[1].path, "posts/draft/hello.md");
+
+        let old = posts.get_at("hello", &history[1].commit).unwrap();
+        assert_eq!(old.data["status"], serde_yaml::Value::from("draft"));
+        assert!(old.content.unwrap().ends_with("First draft.\n"));
+        assert_eq!(old.modified_at, history[1].time);
+        let previous = posts.get_at("hello", "HEAD~1").unwrap();
+        assert_eq!(previous.data, old.data);
+
+        let current = posts.get_at("hello", "HEAD").unwrap();
+        assert_eq!(current.data["status"], serde_yaml::Value::from("published"));
+        assert!(posts.get_at("hello", "no-such-rev").is_err());
+    }
+}
@@ -0,0 +1,138 @@
+//! Advisory lock on a data directory.
+//!
+//! Two processes opening the same store would both run boot, scan and write
+//! documents, rebuild views and update `_system.db`, racing on every file.
+//! [`StoreLock`] takes an OS advisory lock (`flock` / `LockFileEx`) on
+//! `_system.lock` when a store opens, writing the owner's pid into it, and
+//! holds it until the store is dropped; a second open fails with
+//! [`GroundDbError::Locked`] while the lock is held. The OS releases the lock
+//! when its holder exits, however it exits, so a crashed process never leaves
+//! a stale lock behind.
+
+use crate::error::{GroundDbError, Result};
+use fs2::FileExt;
+use std::fs::File;
+use std::io::{Read, Seek, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+pub(super) const LOCK_FILE: &str = "_system.lock";
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Held for the lifetime of an open store; releases the lock on drop.
+#[derive(Debug)]
+pub(crate) struct StoreLock {
+    file: File,
+}
+
+impl StoreLock {
+    /// Take the lock on `root`, waiting up to `timeout` for another holder to
+    /// release it.
+    pub(crate) fn acquire(root: &Path, timeout: Option<Duration>) -> Result<Self> {
+        let path = root.join(LOCK_FILE);
+        let deadline = Instant::now() + timeout.unwrap_or_default();
+        // The file itself is never removed: a process waiting on it must lock
+        // the same file a later opener would create
+        let mut file = File::options()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&path)?;
+
+        loop {
+            match file.try_lock_exclusive() {
+                Ok(()) => {
+                    file.set_len(0)?;
+                    file.rewind()?;
+                    writeln!(file, "{}", std::process::id())?;
+                    return Ok(StoreLock { file });
+                }
+                Err(e) if e.kind() == fs2::lock_contended_error().kind() => {}
+                Err(e) => return Err(e.into()),
+            }
+
+            if Instant::now() >= deadline {
+                let mut holder = String::new();
+                let _ = file.rewind().and_then(|_| file.read_to_string(&mut holder));
+                return Err(GroundDbError::Locked {
+                    path: path.display().to_string(),
+                    holder: holder
+                        .trim()
+                        .parse::<u32>()
+                        .map(|pid| format!("process {pid}"))
+                        .unwrap_or_else(|_| "another process".to_string()),
+                });
+            }
+            std::thread::sleep(POLL_INTERVAL);
+        }
+    }
+}
+
+impl Drop for StoreLock {
+    fn drop(&mut self) {
+        let _ = self.file.set_len(0);
+        let _ = FileExt::unlock(&self.file);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{Store, StoreOptions};
+    use super::*;
+    use tempfile::TempDir;
+
+    fn setup_dir() -> TempDir {
+        let tmp = TempDir::new().unwrap();
+        let schema = r#"
+collections:
+  notes:
+    path: "notes/{title}.md"
+    fields:
+      title: { type: string, required: true }
+"#;
+        std::fs::write(tmp.path().join("schema.yaml"), schema).unwrap();
+        tmp
+    }
+
+    #[test]
+    fn test_second_open_reports_lock() {
+        let tmp = setup_dir();
+        let path = tmp.path().to_str().unwrap();
+        let store = Store::open(path).unwrap();
+
+        let options = StoreOptions {
+            lock_timeout: Some(Duration::from_millis(120)),
+            ..Default::default()
+        };
+        let started = Instant::now();
+        match Store::open_with_options(path, options) {
+            Err(GroundDbError::Locked { holder, .. }) => {
+                assert_eq!(holder, format!("process {}", std::process::id()));
+            }
+            Err(other) => panic!("Expected Locked, got {other}"),
+            Ok(_) => panic!("Second open should be locked"),
+        }
+        assert!(started.elapsed() >= Duration::from_millis(120));
+
+        // Opting out of the lock still opens
+        let unlocked = StoreOptions {
+            no_lock: true,
+            ..Default::default()
+        };
+        drop(Store::open_with_options(path, unlocked).unwrap());
+
+        // Dropping the store releases the lock
+        drop(store);
+        Store::open(path).unwrap();
+    }
+
+    #[test]
+    fn test_lock_file_without_holder_is_free() {
+        let tmp = setup_dir();
+        // A pid far above any real one stands in for a crashed holder, whose
+        // OS lock went away with it
+        std::fs::write(tmp.path().join(LOCK_FILE), "4294967294\n").unwrap();
+        Store::open(tmp.path().to_str().unwrap()).unwrap();
+    }
+}
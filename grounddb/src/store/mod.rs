@@ -2,20 +2,46 @@ use crate::document::{self, Document};
 use crate::error::{GroundDbError, Result};
 use crate::path_template::{self, PathSegment, PathTemplate};
 use crate::schema::{
-    hash_schema, parse_schema, AutoIdStrategy, CollectionDefinition, FieldType, OnConflict,
+    hash_schema, parse_schema_str, AutoIdStrategy, CollectionDefinition, Layout, OnConflict,
     OnDeletePolicy, SchemaDefinition,
 };
-use crate::system_db::{compute_directory_hash, SystemDb};
+use crate::system_db::{compute_directory_hash, DocumentRecord, ProjectedRecord, SystemDb};
+use crate::trace::OpSpan;
 use crate::util::json_to_yaml as json_value_to_yaml;
 use crate::validation;
 use crate::migration;
-use crate::view::{self as view_engine, ViewEngine};
+use crate::view::{self as view_engine, ParsedView, ViewEngine, ViewStatus};
 use crate::watcher::{ChangeKind, FileWatcher, WatcherEvent};
 use serde::de::DeserializeOwned;
 use serde::Serialize;
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::path::{Path, PathBuf};
-use std::sync::{Arc, Mutex, atomic::{AtomicU64, Ordering}};
+use std::sync::{Arc, Mutex, RwLock, atomic::{AtomicU64, Ordering}};
+
+mod bulk;
+mod changes;
+mod channel;
+mod conflict;
+mod consistency;
+mod denormalize;
+mod gate;
+#[cfg(feature = "git")]
+mod git;
+mod lock;
+mod options;
+mod patch;
+mod query;
+mod reload;
+mod snapshot;
+pub use channel::{BoundedSubscription, OverflowPolicy};
+#[cfg(feature = "git")]
+pub use git::RevisionMeta;
+pub use options::{ConflictPolicy, StoreOptions};
+pub use query::{QueryBuilder, SortOrder};
+pub use snapshot::SnapshotId;
+
+/// Longest full path, in bytes, a document may be written to (Linux `PATH_MAX`)
+const MAX_PATH_BYTES: usize = 4096;
 
 /// Unique subscription identifier.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -111,6 +137,13 @@ impl SubscriptionManager {
     }
 }
 
+/// (collection, id) pair identifying a single document.
+type DocumentKey = (String, String);
+
+/// Runs synchronously after a write has committed (see `Store::on_after_write`).
+type AfterWriteHook = dyn Fn(&str, &ChangeEvent) -> Result<()> + Send + Sync;
+type ViewTransform = dyn Fn(&mut Vec<serde_json::Value>) + Send + Sync;
+
 /// The main entry point for GroundDB.
 /// Opens a data directory, parses the schema, manages the system database,
 /// and provides collection handles for CRUD operations.
@@ -122,34 +155,55 @@ pub struct Store {
     path_templates: HashMap<String, PathTemplate>,
     view_engine: ViewEngine,
     subscriptions: Arc<SubscriptionManager>,
+    options: StoreOptions,
+    /// Relative paths of files skipped for exceeding `max_document_bytes`, by collection.
+    skipped_documents: Mutex<HashMap<String, BTreeSet<String>>>,
+    /// While `Some`, a bulk write is in progress: file changes are journaled
+    /// and `post_write` only records the collection for a later flush (see
+    /// `bulk_insert`).
+    write_scope: Mutex<Option<bulk::WriteScope>>,
+    /// Keeps other threads' writes out of a bulk write's transaction.
+    write_gate: gate::WriteGate,
+    /// Change events of writes still inside the write gate, by thread; they
+    /// are announced once the thread leaves it.
+    pending_events: Mutex<HashMap<std::thread::ThreadId, Vec<(String, ChangeEvent)>>>,
+    /// Views waiting for a rebuild under `coalesce_view_rebuilds`.
+    coalesced_views: Mutex<CoalescedViews>,
+    /// When the boot lifecycle last completed
+    booted_at: chrono::DateTime<chrono::Utc>,
+    /// Custom rules run after built-in validation (see `add_validator`).
+    validators: RwLock<Vec<Arc<dyn validation::Validator>>>,
+    /// Hooks run after each committed write (see `on_after_write`).
+    after_write_hooks: RwLock<Vec<Arc<AfterWriteHook>>>,
+    /// Row transforms per view, run after its SQL (see `on_view_rows`).
+    view_transforms: RwLock<HashMap<String, Vec<Arc<ViewTransform>>>>,
+    /// Per-(collection, id) locks held across read-modify-write operations.
+    doc_locks: Mutex<HashMap<DocumentKey, Arc<Mutex<()>>>>,
     /// File watcher handle. None until `watch()` is called.
     _watcher: Mutex<Option<FileWatcher>>,
+    /// Advisory `_system.lock` on the data directory, released on drop.
+    /// Declared last so it outlives everything else the store holds.
+    _lock: Option<lock::StoreLock>,
+}
+
+/// Views marked dirty by writes while `coalesce_view_rebuilds` is set.
+#[derive(Default)]
+struct CoalescedViews {
+    dirty: BTreeSet<String>,
+    /// When dirty views were last rebuilt; `None` before the first time
+    last_flush: Option<std::time::Instant>,
 }
 
 impl Store {
     /// Open a GroundDB store at the given data directory path.
     /// Parses schema.yaml, opens/creates _system.db, and runs the boot lifecycle.
     pub fn open(path: &str) -> Result<Self> {
-        // Resolve to absolute path so file watcher events (which use absolute
-        // paths) can be matched back to collections via strip_prefix.
-        let root = {
-            let p = PathBuf::from(path);
-            if p.is_absolute() {
-                p
-            } else {
-                std::env::current_dir()
-                    .map_err(|e| GroundDbError::Other(format!(
-                        "Failed to resolve data directory: {e}"
-                    )))?
-                    .join(p)
-            }
-        };
-        if !root.exists() {
-            return Err(GroundDbError::Other(format!(
-                "Data directory does not exist: {}",
-                root.display()
-            )));
-        }
+        Self::open_with_options(path, StoreOptions::default())
+    }
+
+    /// Like [`Store::open`], but with explicit [`StoreOptions`].
+    pub fn open_with_options(path: &str, options: StoreOptions) -> Result<Self> {
+        let root = resolve_root(path)?;
 
         let schema_path = root.join("schema.yaml");
         if !schema_path.exists() {
@@ -160,21 +214,56 @@ impl Store {
         }
 
         let schema_yaml = std::fs::read_to_string(&schema_path)?;
-        let schema = parse_schema(&schema_path)?;
+        Self::open_with_db(root, schema_yaml, SystemDb::open, options)
+    }
+
+    /// Open a store at the given data directory using `schema_yaml` instead of
+    /// reading `schema.yaml` from disk. Documents and `_system.db` still live
+    /// in the data directory.
+    pub fn open_with_schema(path: &str, schema_yaml: &str) -> Result<Self> {
+        let root = resolve_root(path)?;
+        Self::open_with_db(root, schema_yaml.to_string(), SystemDb::open, StoreOptions::default())
+    }
 
-        let db_path = root.join("_system.db");
-        let db = SystemDb::open(&db_path)?;
+    /// Open a store using `schema_yaml` and an in-memory system database.
+    /// Only documents are written to the data directory; the index, view cache
+    /// and schema history are discarded when the store is dropped.
+    pub fn open_in_memory(path: &str, schema_yaml: &str) -> Result<Self> {
+        let root = resolve_root(path)?;
+        Self::open_with_db(
+            root,
+            schema_yaml.to_string(),
+            |_| SystemDb::open_in_memory(),
+            StoreOptions::default(),
+        )
+    }
+
+    /// Shared tail of the constructors: take the directory lock, then open the
+    /// system database at `_system.db` with `open_db` and boot.
+    fn open_with_db(
+        root: PathBuf,
+        schema_yaml: String,
+        open_db: impl FnOnce(&Path) -> Result<SystemDb>,
+        options: StoreOptions,
+    ) -> Result<Self> {
+        let schema = parse_schema_str(&schema_yaml)?;
+
+        let lock = if options.no_lock {
+            None
+        } else {
+            Some(lock::StoreLock::acquire(&root, options.lock_timeout)?)
+        };
+        let db = open_db(&root.join("_system.db"))?;
 
-        // Parse all path templates
-        let mut path_templates = HashMap::new();
-        for (name, collection) in &schema.collections {
-            let template = PathTemplate::parse(&collection.path)?;
-            path_templates.insert(name.clone(), template);
+        let mut options = options;
+        if options.case_insensitive_paths.is_none() {
+            options.case_insensitive_paths = Some(options::detect_case_insensitive(&root));
         }
 
+        let path_templates = storage_templates(&schema, &options)?;
         let view_engine = ViewEngine::new(&schema)?;
 
-        let store = Store {
+        let mut store = Store {
             root,
             schema,
             schema_yaml,
@@ -182,10 +271,23 @@ impl Store {
             path_templates,
             view_engine,
             subscriptions: Arc::new(SubscriptionManager::new()),
+            options,
+            skipped_documents: Mutex::new(HashMap::new()),
+            write_scope: Mutex::new(None),
+            write_gate: gate::WriteGate::default(),
+            pending_events: Mutex::new(HashMap::new()),
+            coalesced_views: Mutex::new(CoalescedViews::default()),
+            booted_at: chrono::Utc::now(),
+            validators: RwLock::new(Vec::new()),
+            after_write_hooks: RwLock::new(Vec::new()),
+            view_transforms: RwLock::new(HashMap::new()),
+            doc_locks: Mutex::new(HashMap::new()),
             _watcher: Mutex::new(None),
+            _lock: lock,
         };
 
         store.boot()?;
+        store.booted_at = chrono::Utc::now();
 
         // Load cached view data
         store.view_engine.load_from_db(&store.db)?;
@@ -196,6 +298,8 @@ impl Store {
     /// Boot lifecycle: check schema, scan collections, run migrations, rebuild views
     fn boot(&self) -> Result<()> {
         let current_hash = hash_schema(&self.schema_yaml);
+        self.sync_compressed_collections();
+        self.create_ordinal_indexes()?;
 
         // Check schema hash
         let last_hash = self.db.get_last_schema_hash()?;
@@ -213,16 +317,44 @@ impl Store {
             self.incremental_scan()?;
         }
 
-        // Rebuild all static views so they are fresh on startup
-        self.rebuild_all_static_views()?;
+        // Rebuild all static views so they are fresh on startup, unless the
+        // caller opted to trust the cached rows
+        if self.options.rebuild_views_on_boot {
+            self.rebuild_all_static_views(false)?;
+        } else {
+            self.rebuild_uncached_views()?;
+        }
+
+        Ok(())
+    }
+
+    /// Tell the index which collections store compressed data (`compress: true`).
+    fn sync_compressed_collections(&self) {
+        self.db.set_compressed_collections(
+            self.schema
+                .collections
+                .iter()
+                .filter(|(_, c)| c.compress)
+                .map(|(name, _)| name.clone()),
+        );
+    }
 
+    /// Index every enum ordinal (`enum_ordinals: true`) so views can filter
+    /// and sort on `<field>_ordinal` without reading each row's derived data.
+    fn create_ordinal_indexes(&self) -> Result<()> {
+        for (name, collection) in &self.schema.collections {
+            for (field_name, _) in collection.ordinal_fields() {
+                self.db.create_expression_index(
+                    &format!("idx_ordinal_{name}_{field_name}"),
+                    &view_engine::ordinal_sql(field_name),
+                )?;
+            }
+        }
         Ok(())
     }
 
     /// Run schema migration: diff old vs new schema and apply safe changes.
     fn run_schema_migration(&self, old_yaml: &str) -> Result<()> {
-        use crate::schema::parse_schema_str;
-
         let old_schema = match parse_schema_str(old_yaml) {
             Ok(s) => s,
             Err(e) => {
@@ -280,32 +412,32 @@ impl Store {
                                 let key = serde_yaml::Value::String(field.clone());
                                 if !mapping.contains_key(&key) {
                                     mapping.insert(key, default_val.clone());
-                                    let file_path = self.root.join(&record.path);
-                                    // Read existing document to preserve content and get timestamps
-                                    let existing_doc = document::read_document(&file_path)?;
-                                    document::write_document(&file_path, &data, existing_doc.content.as_deref())?;
-                                    // Read timestamps from the updated file
-                                    let meta = std::fs::metadata(&file_path)?;
-                                    let created: chrono::DateTime<chrono::Utc> = meta
-                                        .created()
-                                        .unwrap_or(meta.modified()?)
-                                        .into();
-                                    let modified: chrono::DateTime<chrono::Utc> = meta.modified()?.into();
-                                    self.db.upsert_document(
-                                        &record.id,
-                                        &record.collection,
-                                        &record.path,
-                                        &data,
-                                        Some(&created.to_rfc3339()),
-                                        Some(&modified.to_rfc3339()),
-                                        existing_doc.content.as_deref(),
-                                    )?;
+                                    self.rewrite_document_data(record, &data)?;
                                 }
                             }
                         }
                     }
                     self.db.record_migration(&m.describe())?;
                 }
+                migration::SchemaMigration::CollectionRenamed { from, to } => {
+                    self.move_collection_to(from, to)?;
+                    self.db.record_migration(&m.describe())?;
+                }
+                migration::SchemaMigration::ViewRenamed { from, to } => {
+                    self.rename_view(from, to)?;
+                    self.db.record_migration(&m.describe())?;
+                }
+                migration::SchemaMigration::ViewAdded { .. } => {
+                    // Built with the other uncached views once the scan is done
+                    self.db.record_migration(&m.describe())?;
+                }
+                migration::SchemaMigration::ViewRemoved { name }
+                | migration::SchemaMigration::ViewChanged { name } => {
+                    // A changed view's stale rows must not be served even when
+                    // boot skips rebuilding cached views
+                    self.db.delete_view(name)?;
+                    self.db.record_migration(&m.describe())?;
+                }
                 migration::SchemaMigration::EnumValueAdded { .. } => {
                     // No action needed, just record it
                     self.db.record_migration(&m.describe())?;
@@ -323,12 +455,274 @@ impl Store {
         Ok(())
     }
 
+    /// Carry view `from`'s cached rows and build metadata over to view `to`,
+    /// so a renamed view keeps serving its cache instead of starting empty.
+    /// The old name's materialized `views/` file is removed; `to`'s is written
+    /// when the view is next built or refreshed. Used to migrate renamed views.
+    pub fn rename_view(&self, from: &str, to: &str) -> Result<()> {
+        if !self.schema.views.contains_key(to) {
+            return Err(GroundDbError::Other(format!("View '{to}' not found in schema")));
+        }
+        self.db.rename_view(from, to)?;
+        let old_file = self.root.join("views").join(format!("{from}.yaml"));
+        if old_file.exists() {
+            std::fs::remove_file(old_file)?;
+        }
+        Ok(())
+    }
+
+    /// Move every indexed document of collection `from` into collection `to`.
+    /// Files are re-rendered with `to`'s path template and the index rows are
+    /// re-keyed under the new collection name. Used to migrate renamed collections.
+    pub fn move_collection_to(&self, from: &str, to: &str) -> Result<()> {
+        self.with_write_gate(|| self.move_collection_impl(from, to))
+    }
+
+    fn move_collection_impl(&self, from: &str, to: &str) -> Result<()> {
+        let template = self.path_templates.get(to).ok_or_else(|| {
+            GroundDbError::Other(format!("Collection '{to}' not found in schema"))
+        })?;
+
+        // Render every target path and check for conflicts before moving any
+        // file, so a conflict leaves the whole collection where it was
+        let mut planned = Vec::new();
+        let mut targets = HashSet::new();
+        for record in self.db.list_documents(from)? {
+            let old_abs_path = self.root.join(&record.path);
+            if !old_abs_path.exists() {
+                planned.push((record, None));
+                continue;
+            }
+
+            let existing = self.read_document_file(&old_abs_path)?;
+            let new_rel_path = template.render(&existing.data, Some(&record.id))?;
+            let taken = new_rel_path != record.path && self.root.join(&new_rel_path).exists();
+            if taken || !targets.insert(new_rel_path.clone()) {
+                return Err(GroundDbError::PathConflict {
+                    existing_id: id_from_path(&new_rel_path, &record.id),
+                    path: new_rel_path,
+                });
+            }
+            planned.push((record, Some(new_rel_path)));
+        }
+
+        for (record, new_rel_path) in planned {
+            let Some(new_rel_path) = new_rel_path else {
+                self.db.delete_document(from, &record.id)?;
+                continue;
+            };
+            let new_abs_path = self.root.join(&new_rel_path);
+            if new_rel_path != record.path {
+                document::move_document(&self.root.join(&record.path), &new_abs_path)?;
+            }
+
+            let doc = self.read_document_file(&new_abs_path)?;
+            self.db.delete_document(from, &record.id)?;
+            self.index_document(
+                &doc.id,
+                to,
+                &new_rel_path,
+                &doc.data,
+                Some(&doc.created_at.to_rfc3339()),
+                Some(&doc.modified_at.to_rfc3339()),
+                doc.content.as_deref(),
+            )?;
+        }
+
+        self.post_write(to)
+    }
+
+    /// Rename a field across every document in a collection, as a one-off data
+    /// operation independent of the schema. Returns the number of documents changed.
+    pub fn rename_field(&self, collection: &str, from: &str, to: &str) -> Result<usize> {
+        self.with_write_gate(|| self.rename_field_impl(collection, from, to, false))
+    }
+
+    /// Report how many documents `rename_field` would change, without writing.
+    pub fn rename_field_dry_run(&self, collection: &str, from: &str, to: &str) -> Result<usize> {
+        self.rename_field_impl(collection, from, to, true)
+    }
+
+    fn rename_field_impl(
+        &self,
+        collection: &str,
+        from: &str,
+        to: &str,
+        dry_run: bool,
+    ) -> Result<usize> {
+        let template = self.path_templates.get(collection).ok_or_else(|| {
+            GroundDbError::Other(format!("Collection '{collection}' not found in schema"))
+        })?;
+        if template.references_field(from) || template.references_field(to) {
+            return Err(GroundDbError::Validation(format!(
+                "Cannot rename '{from}' to '{to}' in '{collection}': field is used in the path template"
+            )));
+        }
+
+        let from_key = serde_yaml::Value::String(from.to_string());
+        let to_key = serde_yaml::Value::String(to.to_string());
+
+        // Plan every rewrite up front so a conflict leaves all documents untouched
+        let mut planned = Vec::new();
+        for record in self.db.list_documents(collection)? {
+            let mut data = record.parse_data()?;
+            let Some(mapping) = data.as_mapping_mut() else {
+                continue;
+            };
+            let Some(value) = mapping.remove(&from_key) else {
+                continue;
+            };
+            if mapping.contains_key(&to_key) {
+                return Err(GroundDbError::Validation(format!(
+                    "Cannot rename '{from}' to '{to}': document '{}' already has both fields",
+                    record.id
+                )));
+            }
+            mapping.insert(to_key.clone(), value);
+            planned.push((record, data));
+        }
+
+        if dry_run || planned.is_empty() {
+            return Ok(planned.len());
+        }
+
+        for (record, data) in &planned {
+            self.rewrite_document_data(record, data)?;
+        }
+        self.post_write(collection)?;
+        for (record, data) in &planned {
+            self.committed(
+                collection,
+                ChangeEvent::Updated {
+                    id: record.id.clone(),
+                    data: serde_json::to_value(data)?,
+                },
+            );
+        }
+
+        Ok(planned.len())
+    }
+
+    /// Rewrite an indexed document's front matter in place, preserving its body,
+    /// and refresh its index row.
+    fn rewrite_document_data(&self, record: &DocumentRecord, data: &serde_yaml::Value) -> Result<()> {
+        let file_path = self.root.join(&record.path);
+        // Read existing document to preserve content
+        let existing_doc = self.read_document_file(&file_path)?;
+        self.write_document_file(&file_path, &file_path, data, existing_doc.content.as_deref())?;
+        // Read timestamps from the updated file
+        let (created, modified) = document::document_timestamps(&file_path, data)?;
+        self.index_document(
+            &record.id,
+            &record.collection,
+            &record.path,
+            data,
+            Some(&created.to_rfc3339()),
+            Some(&modified.to_rfc3339()),
+            existing_doc.content.as_deref(),
+        )
+    }
+
+    /// Run `f` while holding the lock for one document, so concurrent writers to
+    /// the same (collection, id) can't interleave their read-modify-write.
+    fn with_document_lock<R>(
+        &self,
+        collection: &str,
+        id: &str,
+        f: impl FnOnce() -> Result<R>,
+    ) -> Result<R> {
+        self.with_write_gate(|| {
+            let key = (collection.to_string(), id.to_string());
+            let lock = self.doc_locks.lock().unwrap().entry(key.clone()).or_default().clone();
+
+            let result = {
+                let _guard = lock.lock().unwrap_or_else(|e| e.into_inner());
+                f()
+            };
+
+            // Drop the entry once no other writer is waiting on it
+            let mut locks = self.doc_locks.lock().unwrap();
+            if Arc::strong_count(&lock) == 2 {
+                locks.remove(&key);
+            }
+            result
+        })
+    }
+
+    /// Run the single write `f` inside the write gate (see `gate`), so it
+    /// can't land in another thread's bulk write, then announce its events.
+    fn with_write_gate<R>(&self, f: impl FnOnce() -> Result<R>) -> Result<R> {
+        let result = {
+            let _gate = self.write_gate.enter_shared();
+            f()
+        };
+        self.announce_pending();
+        result
+    }
+
+    /// Upsert a document into the index, along with any fields the collection
+    /// derives from its body (`extract_content`).
+    #[allow(clippy::too_many_arguments)]
+    fn index_document(
+        &self,
+        id: &str,
+        collection: &str,
+        path: &str,
+        data: &serde_yaml::Value,
+        created_at: Option<&str>,
+        modified_at: Option<&str>,
+        content: Option<&str>,
+    ) -> Result<()> {
+        self.db
+            .upsert_document(id, collection, path, data, created_at, modified_at, content)?;
+
+        let Some(col_def) = self.schema.collections.get(collection) else {
+            return Ok(());
+        };
+        let extract = col_def.content_extraction();
+        let ordinal_fields = col_def.ordinal_fields();
+        if extract.is_none() && ordinal_fields.is_empty() {
+            return Ok(());
+        }
+
+        let mut derived = serde_json::Map::new();
+        if let Some(extract) = extract {
+            let structure = document::parse_content_structure(content.unwrap_or(""));
+            if extract.title_from_h1 {
+                derived.insert("_title".into(), serde_json::json!(structure.title));
+            }
+            if extract.links {
+                derived.insert("_links".into(), serde_json::json!(structure.links));
+            }
+        }
+        for (field_name, field_def) in ordinal_fields {
+            let ordinal = data
+                .get(field_name)
+                .and_then(|v| field_def.enum_ordinal(v, col_def.enum_case_insensitive));
+            derived.insert(format!("{field_name}_ordinal"), serde_json::json!(ordinal));
+        }
+        self.db
+            .set_document_derived(collection, id, &serde_json::Value::Object(derived))?;
+
+        Ok(())
+    }
+
     /// Rebuild all non-query-template (static) views.
-    fn rebuild_all_static_views(&self) -> Result<()> {
+    ///
+    /// Unless `force` is set, a view whose recorded sources (schema and
+    /// collection directory hashes) match the current ones keeps its cached
+    /// rows instead of re-running its query; its materialized file is still
+    /// rewritten.
+    fn rebuild_all_static_views(&self, force: bool) -> Result<()> {
         let view_names: Vec<String> = self.schema.views.keys().cloned().collect();
         for name in &view_names {
             if let Some(parsed) = self.view_engine.get_view(name) {
-                if !parsed.is_query_template {
+                if parsed.is_query_template {
+                    continue;
+                }
+                if !force && self.view_is_fresh(parsed)? {
+                    self.view_engine.materialize_view(&self.root, name)?;
+                } else {
                     self.rebuild_view(name)?;
                 }
             }
@@ -336,6 +730,41 @@ impl Store {
         Ok(())
     }
 
+    /// Build only the static views with no cached rows in the system database,
+    /// e.g. on first open or after a view is added to the schema.
+    fn rebuild_uncached_views(&self) -> Result<()> {
+        for name in self.schema.views.keys() {
+            let Some(parsed) = self.view_engine.get_view(name) else {
+                continue;
+            };
+            if !parsed.is_query_template && self.db.get_view_data(name)?.is_none() {
+                self.rebuild_view(name)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether a static view's cache was built from the current schema and
+    /// collection contents. Loads the cached rows from the system database if
+    /// the view engine doesn't hold them yet.
+    fn view_is_fresh(&self, parsed: &ParsedView) -> Result<bool> {
+        let Some((_, recorded)) = self.db.get_view_metadata(&parsed.name)? else {
+            return Ok(false);
+        };
+        let recorded: Option<serde_json::Value> = serde_json::from_str(&recorded).ok();
+        if recorded.as_ref() != Some(&self.view_sources(parsed)?) {
+            return Ok(false);
+        }
+        if self.view_engine.get_view_data(&parsed.name).is_none() {
+            let Some(json_str) = self.db.get_view_data(&parsed.name)? else {
+                return Ok(false);
+            };
+            self.view_engine
+                .set_view_data(&parsed.name, serde_json::from_str(&json_str)?);
+        }
+        Ok(true)
+    }
+
     /// Full scan: read all documents in all collections, populate the index
     fn full_scan(&self) -> Result<()> {
         for (name, _collection) in &self.schema.collections {
@@ -346,20 +775,24 @@ impl Store {
 
     /// Incremental scan: only scan collections whose directory hash changed
     fn incremental_scan(&self) -> Result<()> {
-        for (name, _collection) in &self.schema.collections {
-            let stored_hash = self.db.get_directory_hash(name)?;
-            let current_hash = self.compute_collection_hash(name)?;
-
-            if stored_hash.as_deref() != Some(&current_hash) {
+        for name in self.schema.collections.keys() {
+            if self.has_drift(name)? {
                 self.scan_collection(name)?;
             }
         }
         Ok(())
     }
 
+    /// Whether a collection's files changed since its directory hash was recorded
+    fn has_drift(&self, name: &str) -> Result<bool> {
+        let stored_hash = self.db.get_directory_hash(name)?;
+        let current_hash = self.compute_collection_hash(name)?;
+        Ok(stored_hash.as_deref() != Some(&current_hash))
+    }
+
     /// Scan a single collection: read all files, update the document index
     fn scan_collection(&self, name: &str) -> Result<()> {
-        let collection = &self.schema.collections[name];
+        let span = OpSpan::scan(name);
         let template = &self.path_templates[name];
         let base_dir = self.root.join(template.base_directory());
 
@@ -368,41 +801,28 @@ impl Store {
             std::fs::create_dir_all(&base_dir)?;
             self.db
                 .set_directory_hash(name, &compute_directory_hash(&[]))?;
+            span.finish(0);
             return Ok(());
         }
 
-        // Find all matching files recursively
-        let ext = collection.file_extension();
-        let pattern = format!("{}/**/*.{}", base_dir.display(), ext);
-        let files: Vec<PathBuf> = glob::glob(&pattern)
-            .map_err(|e| GroundDbError::Other(format!("Glob error: {e}")))?
-            .filter_map(|r| r.ok())
-            .collect();
+        let files = self.collection_files(name)?;
+
+        let read_body = self.indexes_body(name);
 
         // Clear existing documents for this collection and re-index
         self.db.delete_collection_documents(name)?;
+        self.skipped_documents.lock().unwrap().remove(name);
 
         let mut entries = Vec::new();
         for file_path in &files {
-            let doc = document::read_document(file_path)?;
             let rel_path = file_path
                 .strip_prefix(&self.root)
                 .unwrap_or(file_path)
                 .to_string_lossy()
                 .replace('\\', "/");
 
-            let created_str = doc.created_at.to_rfc3339();
-            let modified_str = doc.modified_at.to_rfc3339();
-            self.db.upsert_document(
-                &doc.id,
-                name,
-                &rel_path,
-                &doc.data,
-                Some(&created_str),
-                Some(&modified_str),
-                doc.content.as_deref(),
-            )?;
-
+            // Skipped files still count towards the directory hash, which
+            // `compute_collection_hash` computes over every matching file
             let mtime = std::fs::metadata(file_path)?
                 .modified()?
                 .duration_since(std::time::UNIX_EPOCH)
@@ -416,31 +836,191 @@ impl Store {
                     .to_string(),
                 mtime,
             ));
+
+            if self.skip_if_oversized(name, file_path, &rel_path)? {
+                continue;
+            }
+            let read = self.read_scanned_document(file_path, read_body);
+            let doc = match read {
+                Ok(doc) => doc,
+                Err(e) => {
+                    self.unreadable_document(&rel_path, e)?;
+                    self.skipped_documents
+                        .lock()
+                        .unwrap()
+                        .entry(name.to_string())
+                        .or_default()
+                        .insert(rel_path);
+                    continue;
+                }
+            };
+
+            let created_str = doc.created_at.to_rfc3339();
+            let modified_str = doc.modified_at.to_rfc3339();
+            self.index_document(
+                &doc.id,
+                name,
+                &rel_path,
+                &doc.data,
+                Some(&created_str),
+                Some(&modified_str),
+                doc.content.as_deref(),
+            )?;
         }
 
         let hash = compute_directory_hash(&entries);
         self.db.set_directory_hash(name, &hash)?;
 
+        span.finish(files.len());
         Ok(())
     }
 
-    /// Compute the current directory hash for a collection
-    fn compute_collection_hash(&self, name: &str) -> Result<String> {
+    /// Read a document file, honoring `lenient_yaml`: when set, front matter
+    /// with tab indentation or duplicate keys is repaired and read, with a
+    /// warning logged for each repair.
+    fn read_document_file(&self, path: &Path) -> Result<Document<serde_yaml::Value>> {
+        if !self.options.lenient_yaml {
+            return document::read_document(path);
+        }
+        let (doc, warnings) = document::read_document_lenient(path)?;
+        for warning in warnings {
+            let rel_path = path.strip_prefix(&self.root).unwrap_or(path);
+            log::warn!("Document {}: {warning}", rel_path.display());
+        }
+        Ok(doc)
+    }
+
+    /// Read a document file for a scan, skipping the body unless `read_body`.
+    fn read_scanned_document(&self, path: &Path, read_body: bool) -> Result<Document<serde_yaml::Value>> {
+        if read_body {
+            self.read_document_file(path)
+        } else if self.options.lenient_yaml {
+            // The front-matter-only reader has no lenient mode
+            Ok(Document {
+                content: None,
+                ..self.read_document_file(path)?
+            })
+        } else {
+            document::read_front_matter_only(path)
+        }
+    }
+
+    /// Write a document file, honoring `git_friendly`: when set, key order
+    /// follows the file at `previous` (the document's current location).
+    fn write_document_file(
+        &self,
+        path: &Path,
+        previous: &Path,
+        data: &serde_yaml::Value,
+        content: Option<&str>,
+    ) -> Result<()> {
+        self.journal_file(path);
+        if self.options.git_friendly {
+            document::write_document_stable(path, previous, data, content)
+        } else {
+            document::write_document(path, data, content)
+        }
+    }
+
+    /// Delete a document file, journaling it first like `write_document_file`.
+    fn delete_document_file(&self, path: &Path) -> Result<()> {
+        self.journal_file(path);
+        document::delete_document(path)
+    }
+
+    /// Handle a document that failed to read: with `strict_parse` this is a
+    /// `Parse` error naming the file, otherwise it's logged for the caller to
+    /// skip.
+    fn unreadable_document(&self, rel_path: &str, error: GroundDbError) -> Result<()> {
+        if self.options.strict_parse {
+            let reason = match error {
+                GroundDbError::Parse { reason, .. } => reason,
+                other => other.to_string(),
+            };
+            return Err(GroundDbError::Parse {
+                path: rel_path.to_string(),
+                reason,
+            });
+        }
+        log::warn!("Failed to read document {rel_path}: {error}");
+        Ok(())
+    }
+
+    /// Check `path` against `max_document_bytes`. Oversized files are logged and
+    /// recorded as skipped instead of being read; returns whether to skip it.
+    fn skip_if_oversized(&self, collection: &str, path: &Path, rel_path: &str) -> Result<bool> {
+        let Some(max) = self.options.max_document_bytes else {
+            return Ok(false);
+        };
+        let size = std::fs::metadata(path)?.len();
+        let mut skipped = self.skipped_documents.lock().unwrap();
+        if size <= max {
+            if let Some(paths) = skipped.get_mut(collection) {
+                paths.remove(rel_path);
+            }
+            return Ok(false);
+        }
+
+        log::warn!(
+            "Skipping {rel_path}: {size} bytes exceeds max_document_bytes ({max})"
+        );
+        skipped
+            .entry(collection.to_string())
+            .or_default()
+            .insert(rel_path.to_string());
+        Ok(true)
+    }
+
+    /// Reject a document path the filesystem would refuse: a file or directory
+    /// name over `max_filename_bytes` (a conflict suffix can push a shortened
+    /// name past it) or a full path over [`MAX_PATH_BYTES`].
+    fn check_path_limits(&self, rel_path: &str) -> Result<()> {
+        let max = self.options.max_filename_bytes;
+        if let Some(part) = rel_path.split('/').find(|part| part.len() > max) {
+            return Err(GroundDbError::Validation(format!(
+                "Path component '{part}' is {} bytes, over max_filename_bytes ({max})",
+                part.len()
+            )));
+        }
+        let full_len = self.root.join(rel_path).as_os_str().len();
+        if full_len > MAX_PATH_BYTES {
+            return Err(GroundDbError::Validation(format!(
+                "Path '{rel_path}' is {full_len} bytes with the data directory, over the {MAX_PATH_BYTES}-byte limit"
+            )));
+        }
+        Ok(())
+    }
+
+    /// Whether scans index document bodies for a collection: only when it has
+    /// them (and exposes them to views) or derives fields from them. Other
+    /// collections are scanned reading front matter only.
+    fn indexes_body(&self, name: &str) -> bool {
+        let collection = &self.schema.collections[name];
+        collection.has_content() || collection.content_extraction().is_some()
+    }
+
+    /// All files under a collection's base directory with its extension,
+    /// searched recursively. Empty if the directory doesn't exist.
+    fn collection_files(&self, name: &str) -> Result<Vec<PathBuf>> {
         let collection = &self.schema.collections[name];
         let template = &self.path_templates[name];
         let base_dir = self.root.join(template.base_directory());
 
         if !base_dir.exists() {
-            return Ok(compute_directory_hash(&[]));
+            return Ok(Vec::new());
         }
 
         let ext = collection.file_extension();
         let pattern = format!("{}/**/*.{}", base_dir.display(), ext);
-        let files: Vec<PathBuf> = glob::glob(&pattern)
+        Ok(glob::glob(&pattern)
             .map_err(|e| GroundDbError::Other(format!("Glob error: {e}")))?
             .filter_map(|r| r.ok())
-            .collect();
+            .collect())
+    }
 
+    /// Compute the current directory hash for a collection
+    fn compute_collection_hash(&self, name: &str) -> Result<String> {
+        let files = self.collection_files(name)?;
         let mut entries = Vec::new();
         for file_path in &files {
             let mtime = std::fs::metadata(&file_path)?
@@ -479,6 +1059,29 @@ impl Store {
         &self.schema
     }
 
+    /// The schema as structured JSON (see [`crate::schema::schema_json`]).
+    pub fn schema_json(&self) -> serde_json::Value {
+        crate::schema::schema_json(&self.schema)
+    }
+
+    /// Parse a past schema from `schema_history`, by version number or hash.
+    pub fn schema_at(&self, version_or_hash: &str) -> Result<SchemaDefinition> {
+        let yaml = self.db.get_schema_yaml(version_or_hash)?.ok_or_else(|| {
+            GroundDbError::Schema(format!(
+                "Schema version '{version_or_hash}' not found in history"
+            ))
+        })?;
+        parse_schema_str(&yaml)
+    }
+
+    /// The changes between two recorded schema versions (by version or hash),
+    /// as computed by `migration::diff_schemas`.
+    pub fn schema_diff(&self, from: &str, to: &str) -> Result<Vec<migration::SchemaMigration>> {
+        let old = self.schema_at(from)?;
+        let new = self.schema_at(to)?;
+        Ok(migration::diff_schemas(&old, &new))
+    }
+
     /// Get the root data directory path
     pub fn root(&self) -> &Path {
         &self.root
@@ -501,7 +1104,7 @@ impl Store {
             })?;
 
         let file_path = self.root.join(&record.path);
-        let raw_doc = document::read_document(&file_path)?;
+        let raw_doc = self.read_document_file(&file_path)?;
         let data: T = serde_yaml::from_value(raw_doc.data)?;
 
         Ok(Document {
@@ -524,7 +1127,7 @@ impl Store {
         for record in records {
             let file_path = self.root.join(&record.path);
             if file_path.exists() {
-                if let Ok(raw_doc) = document::read_document(&file_path) {
+                if let Ok(raw_doc) = self.read_document_file(&file_path) {
                     if let Ok(data) = serde_yaml::from_value(raw_doc.data) {
                         docs.push(Document {
                             id: raw_doc.id,
@@ -541,6 +1144,26 @@ impl Store {
         Ok(docs)
     }
 
+    /// Typed documents whose `field` equals `value`, in id order. The filter
+    /// runs on the index as in [`Store::query`] (numbers and booleans compare
+    /// by their string form), so only matching files are read. Unlike
+    /// [`Store::list_documents`], a matching file that can't be read or
+    /// deserialized into `T` is an error.
+    pub fn find_documents<T: DeserializeOwned>(
+        &self,
+        collection_name: &str,
+        field: &str,
+        value: &str,
+    ) -> Result<Vec<Document<T>>> {
+        let rows = self.query(collection_name).filter(field, value).run()?;
+        rows.as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|row| row["id"].as_str())
+            .map(|id| self.get_document(collection_name, id))
+            .collect()
+    }
+
     /// Insert a new typed document. Returns the generated ID.
     pub fn insert_document<T: Serialize>(
         &self,
@@ -586,6 +1209,26 @@ impl Store {
         Ok(rows)
     }
 
+    /// Read a static view as an iterator of typed rows, deserializing each row
+    /// only when it is reached. Unlike [`Store::read_view`], no `Vec<T>` of the
+    /// whole view is built and the cached rows are shared, not copied, so a
+    /// large view costs one row's worth of memory beyond the cache. The
+    /// iterator reads the rows as they were when it was created.
+    pub fn read_view_stream<T: DeserializeOwned>(
+        &self,
+        view_name: &str,
+    ) -> Result<impl Iterator<Item = Result<T>>> {
+        self.flush_view_rebuilds()?;
+        let rows = match self.view_engine.view_rows(view_name) {
+            Some(rows) => rows,
+            None => match self.view_dynamic(view_name)? {
+                serde_json::Value::Array(rows) => Arc::new(rows),
+                _ => Arc::default(),
+            },
+        };
+        Ok((0..rows.len()).map(move |i| Ok(T::deserialize(&rows[i])?)))
+    }
+
     /// Execute a parameterized view/query, returning typed rows.
     pub fn query_view<T: DeserializeOwned, P: Serialize>(
         &self,
@@ -613,27 +1256,76 @@ impl Store {
         doc_to_json(&doc)
     }
 
+    /// Get a document by its file path, either relative to the data directory
+    /// or absolute. The collection is determined from the path.
+    pub fn get_by_path(&self, path: &str) -> Result<serde_json::Value> {
+        let abs_path = self.root.join(path);
+        let rel_path = abs_path
+            .strip_prefix(&self.root)
+            .map_err(|_| {
+                GroundDbError::Other(format!("Path '{path}' is outside the data directory"))
+            })?
+            .to_string_lossy()
+            .replace('\\', "/");
+        let collection = self.collection_for_path(&abs_path).ok_or_else(|| {
+            GroundDbError::Other(format!("Path '{path}' does not belong to any collection"))
+        })?;
+        let record = self
+            .db
+            .get_by_path(&collection, &rel_path)?
+            .ok_or_else(|| GroundDbError::NotFound {
+                collection: collection.clone(),
+                id: rel_path.clone(),
+            })?;
+        self.get_dynamic(&collection, &record.id)
+    }
+
     /// List all documents in a collection, optionally filtered by field values.
-    /// Filter keys match against document data fields.
+    /// Filter keys match against document data fields. Served from the index via
+    /// [`Store::query`].
     pub fn list_dynamic(
         &self,
         collection: &str,
         filters: &HashMap<String, String>,
     ) -> Result<serde_json::Value> {
-        let col = self.collection(collection)?;
-        let docs = col.list()?;
-        let items: Vec<serde_json::Value> = docs
-            .iter()
-            .filter_map(|doc| doc_to_json(doc).ok())
-            .filter(|json| {
-                filters.iter().all(|(key, value)| {
-                    match json.get(key) {
-                        Some(serde_json::Value::String(s)) => s == value,
-                        Some(serde_json::Value::Number(n)) => &n.to_string() == value,
-                        Some(serde_json::Value::Bool(b)) => &b.to_string() == value,
-                        _ => false,
-                    }
-                })
+        self.query(collection).filters(filters).run()
+    }
+
+    /// Get a single document with only the requested fields (plus `id`).
+    pub fn get_dynamic_fields(
+        &self,
+        collection: &str,
+        id: &str,
+        fields: &[&str],
+    ) -> Result<serde_json::Value> {
+        self.collection(collection)?.get_fields(id, fields)
+    }
+
+    /// List documents with only the requested fields (plus `id`), optionally
+    /// filtered by field values. Filter keys need not be among the requested fields.
+    pub fn list_dynamic_fields(
+        &self,
+        collection: &str,
+        filters: &HashMap<String, String>,
+        fields: &[&str],
+    ) -> Result<serde_json::Value> {
+        let col = self.collection(collection)?;
+        let mut wanted: Vec<&str> = fields.to_vec();
+        for key in filters.keys() {
+            if !wanted.contains(&key.as_str()) {
+                wanted.push(key);
+            }
+        }
+
+        let items: Vec<serde_json::Value> = col
+            .list_fields(&wanted)?
+            .into_iter()
+            .filter(|json| matches_filters(json, filters))
+            .map(|mut json| {
+                if let serde_json::Value::Object(obj) = &mut json {
+                    obj.retain(|k, _| k == "id" || fields.contains(&k.as_str()));
+                }
+                json
             })
             .collect();
         Ok(serde_json::Value::Array(items))
@@ -682,8 +1374,45 @@ impl Store {
         col.delete(id)
     }
 
+    /// Attach a sidecar metadata value to a document under `key`, replacing
+    /// any previous value. Metadata lives only in `_system.db`: it is not
+    /// validated against the schema, never written to the document's file,
+    /// and kept across rescans. Deleting the document through the store
+    /// drops its metadata.
+    pub fn set_meta(
+        &self,
+        collection: &str,
+        id: &str,
+        key: &str,
+        value: serde_json::Value,
+    ) -> Result<()> {
+        self.collection(collection)?;
+        if self.db.get_document(collection, id)?.is_none() {
+            return Err(GroundDbError::NotFound {
+                collection: collection.to_string(),
+                id: id.to_string(),
+            });
+        }
+        let json = serde_json::to_string(&value)?;
+        self.with_write_gate(|| self.db.set_document_meta(collection, id, key, &json))
+    }
+
+    /// Read a document's sidecar metadata value for `key`, if set.
+    pub fn get_meta(
+        &self,
+        collection: &str,
+        id: &str,
+        key: &str,
+    ) -> Result<Option<serde_json::Value>> {
+        self.db
+            .get_document_meta(collection, id, key)?
+            .map(|json| serde_json::from_str(&json).map_err(Into::into))
+            .transpose()
+    }
+
     /// Read a static view by name.
     pub fn view_dynamic(&self, name: &str) -> Result<serde_json::Value> {
+        self.flush_view_rebuilds()?;
         // Check view exists
         if !self.schema.views.contains_key(name) {
             return Err(GroundDbError::NotFound {
@@ -707,6 +1436,21 @@ impl Store {
         Ok(serde_json::Value::Array(vec![]))
     }
 
+    /// Compare a static view's current rows against `baseline`, a list of rows
+    /// as returned by [`Store::view_dynamic`] (e.g. saved earlier). Rows are
+    /// matched by their `id` column when every row has one, otherwise by
+    /// their whole content. Returns `{ added, removed, changed }`: rows only
+    /// in the current output, rows only in the baseline, and for rows whose
+    /// id is in both with different values, `{ id, before, after }`.
+    pub fn view_diff(&self, name: &str, baseline: &serde_json::Value) -> Result<serde_json::Value> {
+        let baseline = baseline.as_array().ok_or_else(|| {
+            GroundDbError::Validation(format!("Baseline for view '{name}' must be a list of rows"))
+        })?;
+        let current = self.view_dynamic(name)?;
+        let current = current.as_array().map(Vec::as_slice).unwrap_or_default();
+        Ok(diff_rows(baseline, current))
+    }
+
     /// Execute a parameterized query/view with the given parameters.
     pub fn query_dynamic(
         &self,
@@ -730,15 +1474,15 @@ impl Store {
         let rewritten = view_engine::rewrite_view_sql(&parsed, &self.schema)?;
 
         // Execute with named parameter bindings
-        let results = self.db.query_documents_sql(&rewritten.sql, params)?;
+        let params = self.bind_params(name, params)?;
+        let mut results = self.db.query_documents_sql(&rewritten.sql, &params)?;
+        self.transform_view_rows(name, &mut results);
 
         Ok(serde_json::Value::Array(results))
     }
 
     /// Show pending schema migrations (dry-run or apply).
     pub fn migrate(&self, dry_run: bool) -> Result<serde_json::Value> {
-        use crate::schema::parse_schema_str;
-
         let old_yaml = self.db.get_last_schema_yaml()?;
         if old_yaml.is_none() {
             return Ok(serde_json::json!({
@@ -818,9 +1562,133 @@ impl Store {
         }))
     }
 
+    /// Explain a query template run with `params`: the rewritten SQL, the same
+    /// SQL with each parameter inlined as a literal (for pasting into a SQLite
+    /// shell), and SQLite's `EXPLAIN QUERY PLAN` for the bound query, one
+    /// step per line. Every parameter the query uses must be given.
+    pub fn explain_query(
+        &self,
+        name: &str,
+        params: &HashMap<String, String>,
+    ) -> Result<serde_json::Value> {
+        let parsed = self
+            .view_engine
+            .get_view(name)
+            .ok_or_else(|| GroundDbError::NotFound {
+                collection: "views".to_string(),
+                id: name.to_string(),
+            })?;
+        let rewritten = view_engine::rewrite_view_sql(parsed, &self.schema)?;
+
+        let mut bound = HashMap::new();
+        for param in &rewritten.param_names {
+            let value = params.get(param).ok_or_else(|| {
+                GroundDbError::Validation(format!(
+                    "Query '{name}' needs parameter '{param}'"
+                ))
+            })?;
+            bound.insert(param.clone(), value.clone());
+        }
+
+        let typed = self.bind_params(name, &bound)?;
+        let plan = self
+            .db
+            .query_documents_sql(&format!("EXPLAIN QUERY PLAN {}", rewritten.sql), &typed)?;
+        let plan: Vec<&str> = plan.iter().filter_map(|step| step["detail"].as_str()).collect();
+
+        Ok(serde_json::json!({
+            "view": name,
+            "params": bound,
+            "rewritten_sql": rewritten.sql,
+            "bound_sql": inline_params(&rewritten.sql, &typed),
+            "plan": plan,
+        }))
+    }
+
+    /// Convert a query's parameters to SQL values by the types the view
+    /// declares for them, so comparisons like `p.date >= :since` compare
+    /// numbers and dates rather than text. Undeclared parameters bind as text.
+    fn bind_params(
+        &self,
+        name: &str,
+        params: &HashMap<String, String>,
+    ) -> Result<HashMap<String, rusqlite::types::Value>> {
+        let declared = self.schema.views.get(name).and_then(|v| v.params.as_ref());
+        params
+            .iter()
+            .map(|(param, value)| {
+                let param_type = declared
+                    .and_then(|d| d.get(param.trim_start_matches(':')))
+                    .map(|d| d.param_type.as_str());
+                let bound = bind_param(param_type, value).ok_or_else(|| {
+                    GroundDbError::Validation(format!(
+                        "Query '{name}' parameter '{param}' expected a {}, got '{value}'",
+                        param_type.unwrap_or("string")
+                    ))
+                })?;
+                Ok((param.clone(), bound))
+            })
+            .collect()
+    }
+
     /// Validate all documents in all collections against the schema.
     /// Returns a report of validation results.
     pub fn validate_all(&self) -> Result<serde_json::Value> {
+        self.validate_documents(false)
+    }
+
+    /// Like `validate_all`, but also resolves every ref field against the index
+    /// and reports dangling references under `dangling_refs` per document.
+    pub fn validate_all_with_refs(&self) -> Result<serde_json::Value> {
+        self.validate_documents(true)
+    }
+
+    /// Documents that currently fail validation against the schema or a
+    /// registered validator, as `(collection, id, errors)` sorted by
+    /// collection and id. Files skipped while indexing (unparseable or over
+    /// `max_document_bytes`) are included, with the id taken from the file
+    /// name. Unlike [`Store::validate_all`], documents with only warnings are
+    /// left out.
+    pub fn invalid_documents(&self) -> Result<Vec<(String, String, Vec<String>)>> {
+        let mut invalid = Vec::new();
+        for (name, collection_def) in &self.schema.collections {
+            for doc in self.collection(name)?.list()? {
+                let errors = self.validate_data(name, collection_def, &doc.data).errors;
+                if !errors.is_empty() {
+                    invalid.push((name.clone(), doc.id, errors));
+                }
+            }
+        }
+        for name in self.schema.collections.keys() {
+            for path in self.skipped_files(name)? {
+                invalid.push((
+                    name.clone(),
+                    id_from_path(&path, &path),
+                    vec![format!("{path} was skipped while indexing and could not be validated")],
+                ));
+            }
+        }
+        invalid.sort();
+        Ok(invalid)
+    }
+
+    /// Check a document's data against its collection's schema and the
+    /// registered validators
+    fn validate_data(
+        &self,
+        name: &str,
+        collection_def: &CollectionDefinition,
+        data: &serde_yaml::Value,
+    ) -> validation::ValidationResult {
+        let mut vr = validation::validate_document(&self.schema, collection_def, data);
+        let validators = self.validators.read().unwrap().clone();
+        for validator in &validators {
+            vr.errors.extend(validator.validate(name, data));
+        }
+        vr
+    }
+
+    fn validate_documents(&self, check_refs: bool) -> Result<serde_json::Value> {
         let mut results = serde_json::Map::new();
 
         for (name, collection_def) in &self.schema.collections {
@@ -829,8 +1697,13 @@ impl Store {
             let mut col_results = Vec::new();
 
             for doc in &docs {
-                let vr = validation::validate_document(&self.schema, collection_def, &doc.data);
-                if !vr.is_ok() || vr.has_warnings() {
+                let vr = self.validate_data(name, collection_def, &doc.data);
+                let dangling = if check_refs {
+                    self.dangling_refs(collection_def, &doc.data)?
+                } else {
+                    Vec::new()
+                };
+                if !vr.is_ok() || vr.has_warnings() || !dangling.is_empty() {
                     let mut entry = serde_json::Map::new();
                     entry.insert("id".into(), serde_json::Value::String(doc.id.clone()));
                     if !vr.errors.is_empty() {
@@ -849,22 +1722,237 @@ impl Store {
                             ),
                         );
                     }
+                    if !dangling.is_empty() {
+                        entry.insert(
+                            "dangling_refs".into(),
+                            serde_json::Value::Array(
+                                dangling.into_iter().map(serde_json::Value::String).collect(),
+                            ),
+                        );
+                    }
                     col_results.push(serde_json::Value::Object(entry));
                 }
             }
 
-            results.insert(
-                name.clone(),
-                serde_json::json!({
-                    "total": docs.len(),
-                    "issues": col_results,
-                }),
-            );
+            let mut col_summary = serde_json::json!({
+                "total": docs.len(),
+                "issues": col_results,
+            });
+            let skipped = self.skipped_files(name)?;
+            if !skipped.is_empty() {
+                col_summary["skipped"] = serde_json::json!(skipped);
+            }
+            results.insert(name.clone(), col_summary);
         }
 
         Ok(serde_json::Value::Object(results))
     }
 
+    /// Relative paths of a collection's files that aren't in the index because
+    /// they were skipped while scanning. Besides the ones recorded this session,
+    /// this finds files skipped by an earlier process, which a reopen with an
+    /// unchanged directory hash doesn't rescan.
+    fn skipped_files(&self, name: &str) -> Result<BTreeSet<String>> {
+        let mut skipped = self
+            .skipped_documents
+            .lock()
+            .unwrap()
+            .get(name)
+            .cloned()
+            .unwrap_or_default();
+        let indexed: HashSet<String> = self
+            .db
+            .list_documents(name)?
+            .into_iter()
+            .map(|record| record.path)
+            .collect();
+        for file_path in self.collection_files(name)? {
+            let rel_path = file_path
+                .strip_prefix(&self.root)
+                .unwrap_or(&file_path)
+                .to_string_lossy()
+                .replace('\\', "/");
+            if !indexed.contains(&rel_path) {
+                skipped.insert(rel_path);
+            }
+        }
+        Ok(skipped)
+    }
+
+    /// Resolve each ref field of a document against the index and describe
+    /// the ones that point at documents which don't exist.
+    fn dangling_refs(
+        &self,
+        collection_def: &CollectionDefinition,
+        data: &serde_yaml::Value,
+    ) -> Result<Vec<String>> {
+        let mut dangling = Vec::new();
+
+        for (field_name, field_def) in &collection_def.fields {
+            let Some(target) = field_def.ref_target() else {
+                continue;
+            };
+            let targets = target.targets();
+
+            // A list of refs is checked element by element
+            let values: Vec<&serde_yaml::Value> = match data.get(field_name.as_str()) {
+                Some(value) if field_def.is_ref_list() => {
+                    value.as_sequence().map(|items| items.iter().collect()).unwrap_or_default()
+                }
+                Some(value) => vec![value],
+                None => continue,
+            };
+
+            for value in values {
+                // Polymorphic refs may name their target collection via `type`
+                let (ref_id, ref_type) = match value {
+                    serde_yaml::Value::String(s) => (s.as_str(), None),
+                    serde_yaml::Value::Mapping(m) => {
+                        let Some(id) = m.get("id").and_then(|v| v.as_str()) else {
+                            continue;
+                        };
+                        (id, m.get("type").and_then(|v| v.as_str()))
+                    }
+                    _ => continue,
+                };
+
+                let candidates: Vec<&str> = match ref_type {
+                    Some(ty) => targets.iter().copied().filter(|t| *t == ty).collect(),
+                    None => targets.clone(),
+                };
+                let mut found = false;
+                for candidate in &candidates {
+                    if self.db.get_document(candidate, ref_id)?.is_some() {
+                        found = true;
+                        break;
+                    }
+                }
+                if !found {
+                    dangling.push(format!(
+                        "Field '{field_name}' references missing document '{ref_id}' in {}",
+                        candidates.join(" | ")
+                    ));
+                }
+            }
+        }
+
+        Ok(dangling)
+    }
+
+    /// Register a custom validator. It runs on every insert and update after
+    /// the built-in checks, and its messages are reported by `validate_all`.
+    pub fn add_validator(&self, validator: impl validation::Validator + 'static) {
+        self.validators.write().unwrap().push(Arc::new(validator));
+    }
+
+    /// Register a hook run synchronously after every insert, update and delete
+    /// made through the store (typed or dynamic), once the file, index and
+    /// views are updated and subscribers notified. A hook can't fail the
+    /// write: an error it returns is logged and later hooks still run.
+    pub fn on_after_write(
+        &self,
+        hook: impl Fn(&str, &ChangeEvent) -> Result<()> + Send + Sync + 'static,
+    ) {
+        self.after_write_hooks.write().unwrap().push(Arc::new(hook));
+    }
+
+    /// Register a transform over a view's rows, run in registration order
+    /// right after the view's SQL and before the rows are cached, materialized
+    /// or sent to subscribers (and on each `query_dynamic` call for query
+    /// templates). Use it for derived columns that are awkward in SQL. A static
+    /// view is rebuilt immediately so its cached rows include the transform.
+    pub fn on_view_rows(
+        &self,
+        view_name: &str,
+        transform: impl Fn(&mut Vec<serde_json::Value>) + Send + Sync + 'static,
+    ) -> Result<()> {
+        let parsed = self
+            .view_engine
+            .get_view(view_name)
+            .ok_or_else(|| GroundDbError::NotFound {
+                collection: "views".to_string(),
+                id: view_name.to_string(),
+            })?;
+        let is_query_template = parsed.is_query_template;
+        self.view_transforms
+            .write()
+            .unwrap()
+            .entry(view_name.to_string())
+            .or_default()
+            .push(Arc::new(transform));
+        if !is_query_template {
+            self.rebuild_view(view_name)?;
+        }
+        Ok(())
+    }
+
+    /// Run the transforms registered for `view_name` over its rows.
+    fn transform_view_rows(&self, view_name: &str, rows: &mut Vec<serde_json::Value>) {
+        let transforms = self
+            .view_transforms
+            .read()
+            .unwrap()
+            .get(view_name)
+            .cloned()
+            .unwrap_or_default();
+        for transform in transforms {
+            transform(rows);
+        }
+    }
+
+    /// Announce a committed write once it is final: inside a bulk write, when
+    /// its transaction commits (never, if it rolls back); inside any other
+    /// write, when that write leaves the write gate, so hooks don't run while
+    /// the store holds its write locks.
+    fn committed(&self, collection: &str, event: ChangeEvent) {
+        let me = std::thread::current().id();
+        if let Some(scope) = self.write_scope.lock().unwrap().as_mut() {
+            if scope.owner == me {
+                scope.events.push((collection.to_string(), event));
+                return;
+            }
+        }
+        if self.write_gate.held_by_current_thread() {
+            self.pending_events
+                .lock()
+                .unwrap()
+                .entry(me)
+                .or_default()
+                .push((collection.to_string(), event));
+            return;
+        }
+        self.announce(collection, event);
+    }
+
+    /// Announce the events this thread queued, once it is outside the write gate.
+    fn announce_pending(&self) {
+        if self.write_gate.held_by_current_thread() {
+            return;
+        }
+        let events = self
+            .pending_events
+            .lock()
+            .unwrap()
+            .remove(&std::thread::current().id())
+            .unwrap_or_default();
+        for (collection, event) in events {
+            self.announce(&collection, event);
+        }
+    }
+
+    /// Notify collection subscribers, then run the after-write hooks. The
+    /// write already happened, so a failing hook is logged rather than
+    /// failing it.
+    fn announce(&self, collection: &str, event: ChangeEvent) {
+        self.subscriptions.notify_collection(collection, event.clone());
+        let hooks = self.after_write_hooks.read().unwrap().clone();
+        for hook in &hooks {
+            if let Err(e) = hook(collection, &event) {
+                log::warn!("After-write hook failed for {collection}: {e}");
+            }
+        }
+    }
+
     /// Get status information: schema hash, collection stats, view health.
     pub fn status(&self) -> Result<serde_json::Value> {
         let schema_hash = hash_schema(&self.schema_yaml);
@@ -882,14 +1970,133 @@ impl Store {
             "schema_hash": schema_hash,
             "collections": collections,
             "views": self.schema.views.keys().collect::<Vec<_>>(),
+            "view_stats": self.view_stats(),
         }))
     }
 
+    /// Summarize store health for monitoring: schema hash, whether the watcher
+    /// is running, whether the system database answers, how many collections
+    /// have drifted from their indexed directory hash, how many static views
+    /// have an empty cache, and when the store last booted.
+    pub fn health(&self) -> serde_json::Value {
+        let db_reachable = self.db.ping().is_ok();
+
+        let mut drifted = Vec::new();
+        for name in self.schema.collections.keys() {
+            // An unreadable collection counts as drifted
+            if self.has_drift(name).unwrap_or(true) {
+                drifted.push(name.clone());
+            }
+        }
+        drifted.sort();
+
+        let empty_views = self
+            .schema
+            .views
+            .keys()
+            .filter(|name| {
+                self.view_engine
+                    .get_view(name)
+                    .is_some_and(|v| !v.is_query_template)
+            })
+            .filter(|name| {
+                self.view_engine
+                    .get_view_data(name)
+                    .map_or(true, |rows| rows.is_empty())
+            })
+            .count();
+
+        serde_json::json!({
+            "ok": db_reachable && drifted.is_empty(),
+            "schema_hash": hash_schema(&self.schema_yaml),
+            "watcher_running": self._watcher.lock().unwrap().is_some(),
+            "db_reachable": db_reachable,
+            "drifted_collections": drifted.len(),
+            "drifted": drifted,
+            "empty_view_caches": empty_views,
+            "booted_at": self.booted_at.to_rfc3339(),
+        })
+    }
+
+    /// Per-view rebuild counters since the store was opened: build count,
+    /// last build duration (ms) and last row count.
+    pub fn view_stats(&self) -> serde_json::Value {
+        let mut stats = serde_json::Map::new();
+        for name in self.schema.views.keys() {
+            let s = self.view_engine.view_stats(name);
+            stats.insert(
+                name.clone(),
+                serde_json::json!({
+                    "build_count": s.build_count,
+                    "last_build_ms": s.last_build_duration.as_secs_f64() * 1000.0,
+                    "last_row_count": s.last_row_count,
+                }),
+            );
+        }
+        serde_json::Value::Object(stats)
+    }
+
+    /// Every view in the schema, sorted by name, with its configuration and
+    /// whether its cache is fresh. Useful for dashboards that show which views
+    /// exist and when they were last rebuilt.
+    pub fn views(&self) -> Result<Vec<ViewStatus>> {
+        self.flush_view_rebuilds()?;
+        let mut names: Vec<&String> = self.schema.views.keys().collect();
+        names.sort();
+
+        let mut statuses = Vec::with_capacity(names.len());
+        for name in names {
+            let Some(parsed) = self.view_engine.get_view(name) else {
+                continue;
+            };
+            let mut collections: Vec<String> =
+                parsed.referenced_collections().into_iter().collect();
+            collections.sort();
+
+            let (cached_rows, stale) = if parsed.is_query_template {
+                (None, false)
+            } else {
+                let cached_rows = self.view_engine.get_view_data(name).map(|rows| rows.len());
+                let mut stale = cached_rows.is_none();
+                for collection in &collections {
+                    // An unreadable collection counts as drifted, as in `health`
+                    stale |= self.has_drift(collection).unwrap_or(true);
+                }
+                (cached_rows, stale)
+            };
+
+            statuses.push(ViewStatus {
+                name: name.clone(),
+                materialize: parsed.materialize,
+                buffer_multiplier: parsed.buffer_multiplier,
+                is_query_template: parsed.is_query_template,
+                collections,
+                cached_rows,
+                last_built: self.db.get_view_metadata(name)?.map(|(built, _)| built),
+                stale,
+            });
+        }
+        Ok(statuses)
+    }
+
+    /// Delete `views/` files that have no corresponding materialized view in the
+    /// current schema. Returns the paths that were removed.
+    pub fn prune_materialized(&self) -> Result<Vec<PathBuf>> {
+        self.view_engine.prune_materialized(&self.root)
+    }
+
+    /// Rewrite every materialized view's `views/` file from its cached rows,
+    /// without re-scanning documents or re-running view queries.
+    pub fn materialize_all(&self) -> Result<()> {
+        self.view_engine.materialize_views(&self.root)
+    }
+
     /// Create a batch for all-or-nothing execution of multiple write operations.
     pub fn batch(&self) -> Batch<'_> {
         Batch {
             store: self,
             ops: Vec::new(),
+            check_refs: false,
         }
     }
 
@@ -911,9 +2118,134 @@ impl Store {
             }
             None => {
                 self.full_scan()?;
-                self.rebuild_all_static_views()
+                // An explicit rebuild re-runs every view, fresh or not
+                self.rebuild_all_static_views(true)
+            }
+        }
+    }
+
+    /// Re-run every static view's query against the current index, without
+    /// re-reading any files. For refreshing view caches (e.g. after clearing
+    /// `view_data`) when the index itself is known to be correct.
+    pub fn rebuild_views_only(&self) -> Result<()> {
+        self.rebuild_all_static_views(true)
+    }
+
+    /// Preview what `rebuild(collection)` would do without touching the index
+    /// or views: per collection, how many files would be re-read and which
+    /// index rows would be added, removed or changed; plus the views that
+    /// would rebuild.
+    pub fn rebuild_plan(&self, collection: Option<&str>) -> Result<serde_json::Value> {
+        let (names, mut views): (Vec<String>, Vec<String>) = match collection {
+            Some(name) => {
+                self.collection(name)?;
+                let views = self
+                    .view_engine
+                    .affected_views(name)
+                    .into_iter()
+                    .filter(|v| {
+                        self.view_engine
+                            .get_view(v)
+                            .is_some_and(|p| !p.is_query_template)
+                    })
+                    .map(|v| v.to_string())
+                    .collect();
+                (vec![name.to_string()], views)
+            }
+            None => {
+                let mut names: Vec<String> = self.schema.collections.keys().cloned().collect();
+                names.sort();
+                let views = self
+                    .schema
+                    .views
+                    .keys()
+                    .filter(|v| {
+                        self.view_engine
+                            .get_view(v)
+                            .is_some_and(|p| !p.is_query_template)
+                    })
+                    .cloned()
+                    .collect();
+                (names, views)
+            }
+        };
+        views.sort();
+
+        let mut collections = serde_json::Map::new();
+        let mut total_files = 0;
+        for name in &names {
+            let plan = self.plan_collection_scan(name)?;
+            total_files += plan["files"].as_u64().unwrap_or(0);
+            collections.insert(name.clone(), plan);
+        }
+
+        Ok(serde_json::json!({
+            "files": total_files,
+            "collections": collections,
+            "views": views,
+        }))
+    }
+
+    /// Read-only counterpart of `scan_collection`: compare the files on disk
+    /// against the indexed rows and report the differences by document id.
+    fn plan_collection_scan(&self, name: &str) -> Result<serde_json::Value> {
+        let files = self.collection_files(name)?;
+        let max_bytes = self.options.max_document_bytes;
+        let read_body = self.indexes_body(name);
+
+        let mut scanned: HashMap<String, (String, serde_json::Value, Option<String>)> =
+            HashMap::new();
+        let mut skipped = Vec::new();
+        for file_path in &files {
+            let rel_path = file_path
+                .strip_prefix(&self.root)
+                .unwrap_or(file_path)
+                .to_string_lossy()
+                .replace('\\', "/");
+            let oversized = max_bytes
+                .is_some_and(|max| std::fs::metadata(file_path).is_ok_and(|m| m.len() > max));
+            if oversized {
+                skipped.push(rel_path);
+                continue;
+            }
+            // Like the scan, an unreadable file is skipped (or fails under
+            // `strict_parse`)
+            let doc = match self.read_scanned_document(file_path, read_body) {
+                Ok(doc) => doc,
+                Err(e) => {
+                    self.unreadable_document(&rel_path, e)?;
+                    skipped.push(rel_path);
+                    continue;
+                }
+            };
+            let data = serde_json::to_value(&doc.data)?;
+            scanned.insert(doc.id, (rel_path, data, doc.content));
+        }
+
+        let mut removed = Vec::new();
+        let mut changed = Vec::new();
+        for record in self.db.list_documents(name)? {
+            let Some((path, data, content)) = scanned.remove(&record.id) else {
+                removed.push(record.id);
+                continue;
+            };
+            let indexed: serde_json::Value = serde_json::from_str(&record.data_json)?;
+            let indexed_content = self.db.get_document_content(name, &record.id)?;
+            if record.path != path || indexed != data || indexed_content != content {
+                changed.push(record.id);
             }
         }
+        let mut added: Vec<String> = scanned.into_keys().collect();
+        added.sort();
+        skipped.sort();
+
+        Ok(serde_json::json!({
+            "files": files.len() - skipped.len(),
+            "added": added,
+            "removed": removed,
+            "changed": changed,
+            "skipped": skipped,
+        }))
     }
 
     // ── Subscription API ────────────────────────────────────────────
@@ -927,6 +2259,26 @@ impl Store {
         self.subscriptions.add_view_sub(view_name, callback)
     }
 
+    /// Like [`Store::on_view_change`], but rows are deserialized into `T` (e.g. a
+    /// generated view row struct) before the callback runs. Updates whose rows
+    /// don't deserialize into `T` are skipped.
+    pub fn on_typed_view_change<T, F>(&self, view_name: &str, callback: F) -> SubscriptionId
+    where
+        T: DeserializeOwned,
+        F: Fn(&[T]) + Send + 'static,
+    {
+        self.on_view_change(
+            view_name,
+            Box::new(move |rows| {
+                let typed: std::result::Result<Vec<T>, _> =
+                    rows.iter().cloned().map(serde_json::from_value).collect();
+                if let Ok(typed) = typed {
+                    callback(&typed);
+                }
+            }),
+        )
+    }
+
     /// Subscribe to changes on a specific collection. Callback fires on insert/update/delete.
     pub fn on_collection_change(
         &self,
@@ -968,6 +2320,11 @@ impl Store {
     /// (e.g. on a timer or after receiving a notification) to apply
     /// external file changes to the index and views.
     pub fn process_watcher_events(&self) -> Result<()> {
+        self.with_write_gate(|| self.process_watcher_events_impl())
+    }
+
+    fn process_watcher_events_impl(&self) -> Result<()> {
+        self.flush_due_view_rebuilds()?;
         let guard = self._watcher.lock().unwrap();
         let watcher = match guard.as_ref() {
             Some(w) => w,
@@ -1017,13 +2374,16 @@ impl Store {
         let rel = path.strip_prefix(&self.root).ok()?;
         let rel_str = rel.to_string_lossy().replace('\\', "/");
 
-        for (name, template) in &self.path_templates {
-            let base = template.base_directory();
-            if rel_str.starts_with(&base) {
-                return Some(name.clone());
-            }
-        }
-        None
+        // Base directories can't overlap (checked at schema parse), but prefer the
+        // most specific match so the result never depends on HashMap order
+        self.path_templates
+            .iter()
+            .map(|(name, template)| (name, template.base_directory()))
+            .filter(|(_, base)| rel_str.starts_with(base.as_str()))
+            .max_by(|(a_name, a_base), (b_name, b_base)| {
+                a_base.len().cmp(&b_base.len()).then_with(|| b_name.cmp(a_name))
+            })
+            .map(|(name, _)| name.clone())
     }
 
     /// Process a single file watcher event: update the document index.
@@ -1041,8 +2401,21 @@ impl Store {
 
         match event.kind {
             ChangeKind::Created | ChangeKind::Modified => {
+                if event.path.exists()
+                    && self.skip_if_oversized(collection_name, &event.path, &rel_path)?
+                {
+                    // A file that grew past the limit is no longer indexed
+                    if let Some(record) = self.db.get_by_path(collection_name, &rel_path)? {
+                        self.db.delete_document(collection_name, &record.id)?;
+                        self.subscriptions.notify_collection(
+                            collection_name,
+                            ChangeEvent::Deleted { id: record.id },
+                        );
+                    }
+                    return Ok(());
+                }
                 if event.path.exists() {
-                    let mut doc = document::read_document(&event.path)?;
+                    let mut doc = self.read_document_file(&event.path)?;
 
                     // Reconcile path-extracted values with YAML front matter.
                     // When a file is moved between directories, the path may
@@ -1073,7 +2446,7 @@ impl Store {
                                     .as_mapping()
                                     .and_then(|m| m.get(serde_yaml::Value::String(field_name.clone())))
                                     .and_then(|v| v.as_str())
-                                    .map(path_template::slugify);
+                                    .map(|v| template.slug.slugify(v));
 
                                 if current_slug.as_deref() == Some(path_value) {
                                     continue; // already matches
@@ -1086,7 +2459,7 @@ impl Store {
                                     .and_then(|c| c.fields.get(field_name))
                                     .and_then(|f| f.enum_values.as_ref())
                                     .and_then(|variants| {
-                                        variants.iter().find(|v| path_template::slugify(v) == *path_value)
+                                        variants.iter().find(|v| template.slug.slugify(v) == *path_value)
                                     })
                                     .cloned()
                                     .unwrap_or_else(|| path_value.clone());
@@ -1101,18 +2474,22 @@ impl Store {
                             }
 
                             if changed {
-                                document::write_document(
+                                self.write_document_file(
+                                    &event.path,
                                     &event.path,
                                     &doc.data,
                                     doc.content.as_deref(),
                                 )?;
+                                // Index the rewritten file's mtime, so the next
+                                // update doesn't mistake our write for an external edit
+                                doc.modified_at = std::fs::metadata(&event.path)?.modified()?.into();
                             }
                         }
                     }
 
                     let created_str = doc.created_at.to_rfc3339();
                     let modified_str = doc.modified_at.to_rfc3339();
-                    self.db.upsert_document(
+                    self.index_document(
                         &doc.id,
                         collection_name,
                         &rel_path,
@@ -1179,29 +2556,145 @@ impl Store {
     /// Called after any write (insert/update/delete) to a collection.
     /// Updates the directory hash and rebuilds affected views.
     fn post_write(&self, collection_name: &str) -> Result<()> {
+        self.post_write_fields(collection_name, None)
+    }
+
+    /// Like `post_write`, but when `changed_fields` is given (an in-place update)
+    /// only views that reference one of those fields are rebuilt.
+    fn post_write_fields(
+        &self,
+        collection_name: &str,
+        changed_fields: Option<&HashSet<String>>,
+    ) -> Result<()> {
+        if self.defer_post_write(collection_name) {
+            return Ok(());
+        }
+
         // Update directory hash for this collection
         let hash = self.compute_collection_hash(collection_name)?;
         self.db.set_directory_hash(collection_name, &hash)?;
 
         // Rebuild affected static views
-        let affected = self.view_engine.affected_views(collection_name);
+        let affected = match changed_fields {
+            Some(fields) => self
+                .view_engine
+                .affected_views_for_fields(collection_name, fields),
+            None => self.view_engine.affected_views(collection_name),
+        };
+        // Only non-query-template (static) views are rebuilt
+        let affected: Vec<&str> = affected
+            .into_iter()
+            .filter(|name| {
+                self.view_engine
+                    .get_view(name)
+                    .is_some_and(|parsed| !parsed.is_query_template)
+            })
+            .collect();
+        if self.options.coalesce_view_rebuilds.is_some() {
+            self.coalesced_views
+                .lock()
+                .unwrap()
+                .dirty
+                .extend(affected.into_iter().map(str::to_string));
+            return self.flush_due_view_rebuilds();
+        }
         for view_name in affected {
-            if let Some(parsed) = self.view_engine.get_view(view_name) {
-                // Only rebuild non-query-template (static) views
-                if !parsed.is_query_template {
-                    self.rebuild_view(view_name)?;
-                }
-            }
+            self.rebuild_view(view_name)?;
         }
 
         Ok(())
     }
 
-    /// Rebuild a single static view by executing rewritten SQL against the documents table.
-    fn rebuild_view(&self, view_name: &str) -> Result<()> {
-        let parsed = match self.view_engine.get_view(view_name) {
-            Some(p) => p.clone(),
-            None => return Ok(()),
+    /// Rebuild the views that writes marked dirty under
+    /// `coalesce_view_rebuilds`. Reading a view does this first, so reads are
+    /// never stale; call it directly to bring materialized files and
+    /// `on_view_change` subscribers up to date after a burst of writes.
+    pub fn flush_view_rebuilds(&self) -> Result<()> {
+        let dirty = {
+            let mut pending = self.coalesced_views.lock().unwrap();
+            if pending.dirty.is_empty() {
+                return Ok(());
+            }
+            pending.last_flush = Some(std::time::Instant::now());
+            std::mem::take(&mut pending.dirty)
+        };
+        for view_name in &dirty {
+            self.rebuild_view(view_name)?;
+        }
+        Ok(())
+    }
+
+    /// Flush coalesced view rebuilds if the window has passed since the last
+    /// flush.
+    fn flush_due_view_rebuilds(&self) -> Result<()> {
+        let Some(window) = self.options.coalesce_view_rebuilds else {
+            return Ok(());
+        };
+        let due = self
+            .coalesced_views
+            .lock()
+            .unwrap()
+            .last_flush
+            .map_or(true, |at| at.elapsed() >= window);
+        if due {
+            self.flush_view_rebuilds()?;
+        }
+        Ok(())
+    }
+
+    /// Start a background thread giving `coalesce_view_rebuilds` a trailing
+    /// edge: when the window after the last rebuild closes, views that writes
+    /// marked dirty during it are rebuilt (updating materialized files and
+    /// notifying `on_view_change` subscribers) without waiting for a read,
+    /// another write or `process_watcher_events`. The thread exits once the
+    /// store is dropped. Does nothing without `coalesce_view_rebuilds`.
+    pub fn spawn_view_flusher(self: &Arc<Self>) -> Option<std::thread::JoinHandle<()>> {
+        let window = self.options.coalesce_view_rebuilds?;
+        let store = Arc::downgrade(self);
+        Some(std::thread::spawn(move || loop {
+            let Some(store) = store.upgrade() else {
+                return;
+            };
+            if let Err(e) = store.with_write_gate(|| store.flush_due_view_rebuilds()) {
+                log::warn!("Coalesced view rebuild failed: {e}");
+            }
+            // Sleep until the current window closes, or a whole window when
+            // nothing is waiting
+            let wait = {
+                let pending = store.coalesced_views.lock().unwrap();
+                match pending.last_flush {
+                    Some(at) if !pending.dirty.is_empty() => window.saturating_sub(at.elapsed()),
+                    _ => window,
+                }
+            };
+            drop(store);
+            std::thread::sleep(wait.max(std::time::Duration::from_millis(10)));
+        }))
+    }
+
+    /// Set one field of an indexed document, in its file and in the index,
+    /// without validation or view updates. `replacement` gets the field's
+    /// current value. Starts from the file rather than the index row, so
+    /// earlier rewrites of other fields in the same document are kept.
+    fn rewrite_field(
+        &self,
+        record: &DocumentRecord,
+        field: &str,
+        replacement: impl FnOnce(Option<&serde_yaml::Value>) -> serde_yaml::Value,
+    ) -> Result<()> {
+        let mut data = self.read_document_file(&self.root.join(&record.path))?.data;
+        let value = replacement(data.get(field));
+        if let Some(mapping) = data.as_mapping_mut() {
+            mapping.insert(serde_yaml::Value::String(field.to_string()), value);
+        }
+        self.rewrite_document_data(record, &data)
+    }
+
+    /// Rebuild a single static view by executing rewritten SQL against the documents table.
+    fn rebuild_view(&self, view_name: &str) -> Result<()> {
+        let parsed = match self.view_engine.get_view(view_name) {
+            Some(p) => p.clone(),
+            None => return Ok(()),
         };
 
         // Rewrite the view SQL into CTE-wrapped form
@@ -1219,13 +2712,20 @@ impl Store {
         };
 
         // Execute against the documents table
-        let empty_params = HashMap::new();
-        let rows = self.db.query_documents_sql(&exec_sql, &empty_params)?;
+        let span = OpSpan::view_rebuild(view_name);
+        let started = std::time::Instant::now();
+        let empty_params: HashMap<String, String> = HashMap::new();
+        let mut rows = self.db.query_documents_sql(&exec_sql, &empty_params)?;
+        self.transform_view_rows(view_name, &mut rows);
+        self.view_engine
+            .record_build(view_name, started.elapsed(), rows.len());
+        span.finish(rows.len());
 
         // Update in-memory cache and persist to DB
         let json_str = serde_json::to_string(&rows)?;
         self.db.set_view_data(view_name, &json_str)?;
         self.view_engine.set_view_data(view_name, rows.clone());
+        self.record_view_metadata(&parsed)?;
 
         // Notify view subscribers
         self.subscriptions.notify_view(view_name, &rows);
@@ -1237,6 +2737,32 @@ impl Store {
 
         Ok(())
     }
+
+    /// Record when a view was rebuilt and the sources it was built from.
+    fn record_view_metadata(&self, parsed: &ParsedView) -> Result<()> {
+        self.db.set_view_metadata(
+            &parsed.name,
+            &chrono::Utc::now().to_rfc3339(),
+            &self.view_sources(parsed)?.to_string(),
+        )
+    }
+
+    /// What a view's rows depend on: its definition (the rewritten SQL, which
+    /// covers the view's query and the fields of every collection it reads,
+    /// plus the buffer) and the indexed directory hash of each collection it
+    /// reads. Editing one view in the schema leaves the others' sources alone.
+    fn view_sources(&self, parsed: &ParsedView) -> Result<serde_json::Value> {
+        let rewritten = view_engine::rewrite_view_sql(parsed, &self.schema)?;
+        let mut hashes = serde_json::Map::new();
+        for collection in parsed.referenced_collections() {
+            let hash = self.db.get_directory_hash(&collection)?;
+            hashes.insert(collection, hash.into());
+        }
+        Ok(serde_json::json!({
+            "definition": hash_schema(&format!("{}\n{}", rewritten.sql, parsed.buffer_multiplier)),
+            "collections": hashes,
+        }))
+    }
 }
 
 // ── Batch Operations ───────────────────────────────────────────
@@ -1264,6 +2790,7 @@ enum BatchOp {
 pub struct Batch<'a> {
     store: &'a Store,
     ops: Vec<BatchOp>,
+    check_refs: bool,
 }
 
 /// A scoped handle for queuing batch writes to a specific collection.
@@ -1281,81 +2808,73 @@ impl<'a> Batch<'a> {
         }
     }
 
-    /// Execute all queued operations atomically.
-    /// If any operation fails, all file changes in this batch are rolled back:
-    /// created files are removed, and updated/deleted files are restored.
+    /// Check every ref of the documents this batch inserts or updates once all
+    /// operations have run, and fail (rolling back) if any points at a
+    /// document that doesn't exist. Refs are resolved against the batch's own
+    /// writes as well as committed documents, so a document and the documents
+    /// it references can be queued in any order.
+    pub fn check_refs(&mut self) -> &mut Self {
+        self.check_refs = true;
+        self
+    }
+
+    /// Execute all queued operations atomically, as one bulk write (views are
+    /// rebuilt and subscribers notified once it commits). If any operation
+    /// fails, all file changes in this batch are rolled back: created files
+    /// are removed, and updated/deleted files are restored.
     pub fn execute(self) -> Result<Vec<String>> {
-        // Track file changes for rollback
-        let mut created_files: Vec<PathBuf> = Vec::new();
-        // (path, original_content) for files that were modified or deleted
-        let mut saved_files: Vec<(PathBuf, Vec<u8>)> = Vec::new();
-        let mut results: Vec<String> = Vec::new();
-
-        // Begin a DB transaction
-        self.store.db.begin_transaction()?;
-
-        for op in &self.ops {
-            let res = match op {
-                BatchOp::Insert { collection, data, content } => {
-                    self.store
-                        .insert_dynamic(collection, data.clone(), content.as_deref())
-                        .map(|id| {
-                            results.push(id.clone());
-                            // Track the file that was created
-                            if let Ok(Some(record)) = self.store.db.get_document(collection, &id) {
-                                created_files.push(self.store.root.join(&record.path));
-                            }
-                        })
-                }
-                BatchOp::Update { collection, id, data } => {
-                    // Save old file content before updating
-                    if let Ok(Some(record)) = self.store.db.get_document(collection, id) {
-                        let file_path = self.store.root.join(&record.path);
-                        if let Ok(content) = std::fs::read(&file_path) {
-                            saved_files.push((file_path, content));
-                        }
+        self.store.with_deferred_writes(|| {
+            let mut results: Vec<String> = Vec::new();
+            // (collection, id) of inserted and updated documents, for `check_refs`
+            let mut written: Vec<(&str, String)> = Vec::new();
+
+            for op in &self.ops {
+                match op {
+                    BatchOp::Insert { collection, data, content } => {
+                        let id = self.store.insert_dynamic(
+                            collection,
+                            data.clone(),
+                            content.as_deref(),
+                        )?;
+                        results.push(id.clone());
+                        written.push((collection, id));
                     }
-                    self.store
-                        .update_dynamic(collection, id, data.clone())
-                        .map(|_| {
-                            results.push(id.clone());
-                        })
-                }
-                BatchOp::Delete { collection, id } => {
-                    // Save old file content before deleting
-                    if let Ok(Some(record)) = self.store.db.get_document(collection, id) {
-                        let file_path = self.store.root.join(&record.path);
-                        if let Ok(content) = std::fs::read(&file_path) {
-                            saved_files.push((file_path, content));
-                        }
+                    BatchOp::Update { collection, id, data } => {
+                        self.store.update_dynamic(collection, id, data.clone())?;
+                        results.push(id.clone());
+                        written.push((collection, id.clone()));
                     }
-                    self.store
-                        .delete_dynamic(collection, id)
-                        .map(|_| {
-                            results.push(id.clone());
-                        })
-                }
-            };
-
-            if let Err(e) = res {
-                // Roll back: remove files created during this batch
-                for path in &created_files {
-                    let _ = std::fs::remove_file(path);
-                }
-                // Restore files that were modified or deleted
-                for (path, content) in &saved_files {
-                    if let Some(parent) = path.parent() {
-                        let _ = std::fs::create_dir_all(parent);
+                    BatchOp::Delete { collection, id } => {
+                        self.store.delete_dynamic(collection, id)?;
+                        results.push(id.clone());
                     }
-                    let _ = std::fs::write(path, content);
                 }
-                self.store.db.rollback_transaction()?;
-                return Err(e);
             }
-        }
 
-        self.store.db.commit_transaction()?;
-        Ok(results)
+            if self.check_refs {
+                // The index already holds the batch's writes inside the transaction
+                self.find_dangling_refs(&written)?;
+            }
+            Ok(results)
+        })
+    }
+
+    /// Fail with the first document in `written` (that still exists) holding
+    /// a ref to a missing document.
+    fn find_dangling_refs(&self, written: &[(&str, String)]) -> Result<()> {
+        for (collection, id) in written {
+            let Some(record) = self.store.db.get_document(collection, id)? else {
+                continue;
+            };
+            let definition = &self.store.schema.collections[*collection];
+            let dangling = self.store.dangling_refs(definition, &record.parse_data()?)?;
+            if let Some(problem) = dangling.first() {
+                return Err(GroundDbError::ReferentialIntegrity(format!(
+                    "{collection}/{id}: {problem}"
+                )));
+            }
+        }
+        Ok(())
     }
 }
 
@@ -1406,6 +2925,22 @@ impl<'a> Collection<'a> {
         &self.store.path_templates[&self.name]
     }
 
+    /// Describe the collection as the schema defines it: its path template,
+    /// whether documents have a body and in which format, and its fields.
+    pub fn info(&self) -> serde_json::Value {
+        let definition = self.definition();
+        let mut fields: Vec<&String> = definition.fields.keys().collect();
+        fields.sort();
+        serde_json::json!({
+            "name": self.name,
+            "path": definition.path,
+            "content": definition.has_content(),
+            "content_format": definition.content_format().map(|f| f.as_str()),
+            "readonly": definition.readonly,
+            "fields": fields,
+        })
+    }
+
     /// Get a document by ID
     pub fn get(&self, id: &str) -> Result<Document<serde_yaml::Value>> {
         let record = self
@@ -1418,22 +2953,71 @@ impl<'a> Collection<'a> {
             })?;
 
         let file_path = self.store.root.join(&record.path);
-        document::read_document(&file_path)
+        self.store.read_document_file(&file_path)
+    }
+
+    /// A document's file exactly as it is on disk, for editors and diff tools:
+    /// `(front matter, body, full file)`. The front matter is the YAML between
+    /// the fences; use [`Collection::get`] for the parsed form.
+    pub fn get_raw(&self, id: &str) -> Result<(String, Option<String>, String)> {
+        let record = self
+            .store
+            .db
+            .get_document(&self.name, id)?
+            .ok_or_else(|| GroundDbError::NotFound {
+                collection: self.name.clone(),
+                id: id.to_string(),
+            })?;
+        document::read_raw(&self.store.root.join(&record.path))
     }
 
     /// List all documents in this collection
     pub fn list(&self) -> Result<Vec<Document<serde_yaml::Value>>> {
         let records = self.store.db.list_documents(&self.name)?;
+        self.read_records(&records)
+    }
+
+    /// Backlinks: documents in `from_collection` whose ref field `field` points
+    /// at the document `id` in this collection (e.g. all posts whose `author_id`
+    /// is a given user).
+    pub fn referencing(
+        &self,
+        id: &str,
+        from_collection: &str,
+        field: &str,
+    ) -> Result<Vec<Document<serde_yaml::Value>>> {
+        let source = self.store.collection(from_collection)?;
+        let targets_us = source
+            .definition()
+            .fields
+            .get(field)
+            .and_then(|f| f.ref_target())
+            .is_some_and(|t| t.targets().contains(&self.name.as_str()));
+        if !targets_us {
+            return Err(GroundDbError::Schema(format!(
+                "Field '{field}' of collection '{from_collection}' is not a ref to '{}'",
+                self.name
+            )));
+        }
+
+        let records =
+            self.store
+                .db
+                .find_field_references(from_collection, field, &self.name, id)?;
+        source.read_records(&records)
+    }
+
+    /// Read the files behind index records, skipping any that are missing.
+    /// Unreadable files are skipped too, or fail the read with `strict_parse`.
+    fn read_records(&self, records: &[DocumentRecord]) -> Result<Vec<Document<serde_yaml::Value>>> {
         let mut docs = Vec::new();
 
-        for record in &records {
+        for record in records {
             let file_path = self.store.root.join(&record.path);
             if file_path.exists() {
-                match document::read_document(&file_path) {
+                match self.store.read_document_file(&file_path) {
                     Ok(doc) => docs.push(doc),
-                    Err(e) => {
-                        log::warn!("Failed to read document {}: {}", record.path, e);
-                    }
+                    Err(e) => self.store.unreadable_document(&record.path, e)?,
                 }
             }
         }
@@ -1441,12 +3025,82 @@ impl<'a> Collection<'a> {
         Ok(docs)
     }
 
-    /// Insert a new document. Returns the document ID.
-    pub fn insert(
+    /// Get a document by ID with only the requested fields (plus `id`).
+    /// Data fields come from the index; the file is only read for `content`.
+    pub fn get_fields(&self, id: &str, fields: &[&str]) -> Result<serde_json::Value> {
+        let record = self
+            .store
+            .db
+            .get_document_fields(&self.name, id, fields)?
+            .ok_or_else(|| GroundDbError::NotFound {
+                collection: self.name.clone(),
+                id: id.to_string(),
+            })?;
+        self.project(record, fields)
+    }
+
+    /// List all documents with only the requested fields (plus `id`).
+    /// Data fields come from the index; files are only read for `content`.
+    pub fn list_fields(&self, fields: &[&str]) -> Result<Vec<serde_json::Value>> {
+        let records = self.store.db.list_document_fields(&self.name, fields)?;
+        let mut items = Vec::new();
+
+        for record in records {
+            let path = record.path.clone();
+            match self.project(record, fields) {
+                Ok(item) => items.push(item),
+                Err(e) => self.store.unreadable_document(&path, e)?,
+            }
+        }
+
+        Ok(items)
+    }
+
+    /// Build the JSON object for a projected record, in the requested field order
+    fn project(&self, mut record: ProjectedRecord, fields: &[&str]) -> Result<serde_json::Value> {
+        let mut obj = serde_json::Map::new();
+        obj.insert("id".into(), serde_json::Value::String(record.id.clone()));
+
+        for &field in fields {
+            let value = match field {
+                "created_at" => record.created_at.take().map(serde_json::Value::String),
+                "modified_at" => record.modified_at.take().map(serde_json::Value::String),
+                "content" => {
+                    let doc = self.store.read_document_file(&self.store.root.join(&record.path))?;
+                    doc.content.map(serde_json::Value::String)
+                }
+                _ => record.fields.remove(field),
+            };
+            if let Some(value) = value {
+                obj.insert(field.to_string(), value);
+            }
+        }
+
+        Ok(serde_json::Value::Object(obj))
+    }
+
+    /// Insert a new document. Returns the document ID. Validation warnings
+    /// (non-strict collections) are logged; use [`Collection::insert_with_report`]
+    /// to get them back.
+    pub fn insert(&self, data: serde_yaml::Value, content: Option<&str>) -> Result<String> {
+        self.insert_with_report(data, content).map(|(id, _)| id)
+    }
+
+    /// Like [`Collection::insert`], but also returns the validation warnings
+    /// the document produced (e.g. unknown fields on a non-strict collection).
+    pub fn insert_with_report(
+        &self,
+        data: serde_yaml::Value,
+        content: Option<&str>,
+    ) -> Result<(String, Vec<String>)> {
+        self.store.with_write_gate(|| self.insert_impl(data, content))
+    }
+
+    fn insert_impl(
         &self,
         mut data: serde_yaml::Value,
         content: Option<&str>,
-    ) -> Result<String> {
+    ) -> Result<(String, Vec<String>)> {
         let definition = self.definition();
 
         if definition.readonly {
@@ -1457,83 +3111,72 @@ impl<'a> Collection<'a> {
         }
 
         // Apply defaults and validate
-        validation::validate_and_prepare(&self.store.schema, definition, &mut data)?;
+        let warnings = self.validate_and_prepare(&mut data)?;
+        self.denormalize_refs(&mut data)?;
 
         // Generate or determine ID
         let id = self.determine_id(&data)?;
 
+        // Seed the body from the collection's content template if none was given
+        let seeded;
+        let content = match (content, &definition.content_template) {
+            (None, Some(content_template)) if definition.has_content() => {
+                seeded = PathTemplate::parse(content_template)?.render_text(&data, Some(&id));
+                Some(seeded.as_str())
+            }
+            _ => content,
+        };
+
         // Compute target path
         let template = self.template();
-        let rel_path = template.render(&data, Some(&id))?;
-        let abs_path = self.store.root.join(&rel_path);
-
-        // Check for path conflict
-        if abs_path.exists() {
+        let mut rel_path = template.render(&data, Some(&id))?;
+        let mut id = id;
+
+        // Check for a conflict with an existing file, or with an indexed document
+        // that has the same id under a different path (or, on a case-insensitive
+        // filesystem, a path differing only in case)
+        let case_insensitive = self.store.options.case_insensitive_paths == Some(true);
+        let taken = |path: &str| -> Result<bool> {
+            Ok(self.store.root.join(path).exists()
+                || self
+                    .store
+                    .db
+                    .get_document(&self.name, &id_from_path(path, ""))?
+                    .is_some()
+                || (case_insensitive
+                    && self.store.db.get_by_path_nocase(&self.name, path)?.is_some()))
+        };
+        if taken(&rel_path)? {
             match definition.on_conflict() {
                 OnConflict::Error => {
-                    return Err(GroundDbError::PathConflict { path: rel_path });
+                    return Err(GroundDbError::PathConflict {
+                        existing_id: id_from_path(&rel_path, &id),
+                        path: rel_path,
+                    });
                 }
                 OnConflict::Suffix => {
-                    let resolved = path_template::resolve_suffix(&rel_path, |p| {
-                        self.store.root.join(p).exists()
-                    });
-                    let abs_resolved = self.store.root.join(&resolved);
-
-                    // Write the file
-                    document::write_document(&abs_resolved, &data, content)?;
-
-                    // Extract ID from the resolved filename
-                    let resolved_id = Path::new(&resolved)
-                        .file_stem()
-                        .and_then(|s| s.to_str())
-                        .unwrap_or(&id)
-                        .to_string();
-
-                    // Read timestamps from the newly written file
-                    let meta = std::fs::metadata(&abs_resolved)?;
-                    let created: chrono::DateTime<chrono::Utc> = meta
-                        .created()
-                        .unwrap_or(meta.modified()?)
-                        .into();
-                    let modified: chrono::DateTime<chrono::Utc> = meta.modified()?.into();
-
-                    // Update the index
-                    self.store.db.upsert_document(
-                        &resolved_id,
-                        &self.name,
-                        &resolved,
-                        &data,
-                        Some(&created.to_rfc3339()),
-                        Some(&modified.to_rfc3339()),
-                        content,
-                    )?;
-
-                    self.store.post_write(&self.name)?;
-                    self.store.subscriptions.notify_collection(
-                        &self.name,
-                        ChangeEvent::Inserted {
-                            id: resolved_id.clone(),
-                            data: serde_json::to_value(&data)?,
-                        },
-                    );
-                    return Ok(resolved_id);
+                    // The suffixed filename stem is the new document's id. A
+                    // suffix pushing a shortened name over the limit shortens
+                    // it again, with a new hash
+                    let limit = |path: &str| template.limit_components(path);
+                    rel_path = limit(&path_template::try_resolve_suffix(&rel_path, |path| {
+                        taken(&limit(path))
+                    })?);
+                    id = id_from_path(&rel_path, &id);
                 }
             }
         }
+        self.store.check_path_limits(&rel_path)?;
+        let abs_path = self.store.root.join(&rel_path);
 
         // Write the file
-        document::write_document(&abs_path, &data, content)?;
+        self.store.write_document_file(&abs_path, &abs_path, &data, content)?;
 
         // Read timestamps from the newly written file
-        let meta = std::fs::metadata(&abs_path)?;
-        let created: chrono::DateTime<chrono::Utc> = meta
-            .created()
-            .unwrap_or(meta.modified()?)
-            .into();
-        let modified: chrono::DateTime<chrono::Utc> = meta.modified()?.into();
+        let (created, modified) = document::document_timestamps(&abs_path, &data)?;
 
         // Update the index
-        self.store.db.upsert_document(
+        self.store.index_document(
             &id,
             &self.name,
             &rel_path,
@@ -1544,23 +3187,93 @@ impl<'a> Collection<'a> {
         )?;
 
         self.store.post_write(&self.name)?;
-        self.store.subscriptions.notify_collection(
+        self.store.committed(
             &self.name,
             ChangeEvent::Inserted {
                 id: id.clone(),
                 data: serde_json::to_value(&data)?,
             },
         );
-        Ok(id)
+        self.log_warnings(&id, &warnings);
+        Ok((id, warnings))
+    }
+
+    /// Apply defaults and run built-in and registered validation on `data`.
+    fn validate_and_prepare(&self, data: &mut serde_yaml::Value) -> Result<Vec<String>> {
+        let validators = self.store.validators.read().unwrap().clone();
+        validation::validate_and_prepare_with(
+            &self.store.schema,
+            &self.name,
+            self.definition(),
+            data,
+            &validators,
+        )
+    }
+
+    /// Log validation warnings that a write accepted.
+    fn log_warnings(&self, id: &str, warnings: &[String]) {
+        for warning in warnings {
+            log::warn!("{}/{id}: {warning}", self.name);
+        }
+    }
+
+    /// Reject an update that changes an `immutable` field which already has a value.
+    fn check_immutable(
+        &self,
+        id: &str,
+        old: &serde_yaml::Value,
+        new: &serde_yaml::Value,
+    ) -> Result<()> {
+        let mut fields: Vec<&String> = self
+            .definition()
+            .fields
+            .iter()
+            .filter(|(_, def)| def.immutable)
+            .map(|(name, _)| name)
+            .collect();
+        fields.sort();
+        for field in fields {
+            let Some(current) = old.get(field.as_str()).filter(|v| !v.is_null()) else {
+                continue;
+            };
+            if new.get(field.as_str()) != Some(current) {
+                return Err(GroundDbError::Validation(format!(
+                    "Field '{field}' of {}/{id} is immutable and can't be changed by update",
+                    self.name
+                )));
+            }
+        }
+        Ok(())
     }
 
     /// Update an existing document. Handles file movement if path-relevant fields changed.
     pub fn update(
         &self,
         id: &str,
-        mut data: serde_yaml::Value,
+        data: serde_yaml::Value,
         content: Option<&str>,
     ) -> Result<()> {
+        self.update_with_report(id, data, content).map(|_| ())
+    }
+
+    /// Like [`Collection::update`], but returns the validation warnings the
+    /// new data produced.
+    pub fn update_with_report(
+        &self,
+        id: &str,
+        data: serde_yaml::Value,
+        content: Option<&str>,
+    ) -> Result<Vec<String>> {
+        self.store
+            .with_document_lock(&self.name, id, || self.update_locked(id, data, content))
+    }
+
+    fn update_locked(
+        &self,
+        id: &str,
+        mut data: serde_yaml::Value,
+        content: Option<&str>,
+    ) -> Result<Vec<String>> {
         let definition = self.definition();
 
         if definition.readonly {
@@ -1580,38 +3293,53 @@ impl<'a> Collection<'a> {
                 id: id.to_string(),
             })?;
 
+        // Reconcile with an external edit the index hasn't seen yet
+        let mut content = content.map(str::to_string);
+        self.resolve_conflict(&record, &mut data, &mut content)?;
+        let content = content.as_deref();
+
         // Apply defaults and validate
-        validation::validate_and_prepare(&self.store.schema, definition, &mut data)?;
+        let warnings = self.validate_and_prepare(&mut data)?;
+        self.log_warnings(id, &warnings);
+        self.denormalize_refs(&mut data)?;
+        let old_data = record.parse_data()?;
+        self.check_immutable(id, &old_data, &data)?;
+        // Keep a pinned creation time across rewrites
+        if let (Some(created), serde_yaml::Value::Mapping(map)) =
+            (old_data.get(document::CREATED_AT_KEY), &mut data)
+        {
+            map.entry(document::CREATED_AT_KEY.into())
+                .or_insert_with(|| created.clone());
+        }
+        let changed = changed_fields(&old_data, &data);
 
         // Compute new path
         let template = self.template();
         let new_rel_path = template.render(&data, Some(id))?;
+        self.store.check_path_limits(&new_rel_path)?;
         let old_abs_path = self.store.root.join(&record.path);
         let new_abs_path = self.store.root.join(&new_rel_path);
 
         if record.path != new_rel_path {
             // Path changed -- file needs to move
             // Write to new location first
-            document::write_document(&new_abs_path, &data, content)?;
+            self.store
+                .write_document_file(&new_abs_path, &old_abs_path, &data, content)?;
             // Delete old file
             if old_abs_path.exists() {
-                document::delete_document(&old_abs_path)?;
+                self.store.delete_document_file(&old_abs_path)?;
             }
         } else {
             // Same path -- just update the file
-            document::write_document(&new_abs_path, &data, content)?;
+            self.store
+                .write_document_file(&new_abs_path, &new_abs_path, &data, content)?;
         }
 
         // Read timestamps from the written file
-        let meta = std::fs::metadata(&new_abs_path)?;
-        let created: chrono::DateTime<chrono::Utc> = meta
-            .created()
-            .unwrap_or(meta.modified()?)
-            .into();
-        let modified: chrono::DateTime<chrono::Utc> = meta.modified()?.into();
+        let (created, modified) = document::document_timestamps(&new_abs_path, &data)?;
 
         // Update the index
-        self.store.db.upsert_document(
+        self.store.index_document(
             id,
             &self.name,
             &new_rel_path,
@@ -1621,15 +3349,16 @@ impl<'a> Collection<'a> {
             content,
         )?;
 
-        self.store.post_write(&self.name)?;
-        self.store.subscriptions.notify_collection(
+        self.store.post_write_fields(&self.name, Some(&changed))?;
+        self.propagate_denormalized(id, &data, &changed)?;
+        self.store.committed(
             &self.name,
             ChangeEvent::Updated {
                 id: id.to_string(),
                 data: serde_json::to_value(&data)?,
             },
         );
-        Ok(())
+        Ok(warnings)
     }
 
     /// Partially update a document. Merges the given partial data into the existing
@@ -1639,6 +3368,17 @@ impl<'a> Collection<'a> {
         id: &str,
         partial: serde_yaml::Value,
         content: Option<&str>,
+    ) -> Result<()> {
+        self.store.with_document_lock(&self.name, id, || {
+            self.update_partial_locked(id, partial, content)
+        })
+    }
+
+    fn update_partial_locked(
+        &self,
+        id: &str,
+        partial: serde_yaml::Value,
+        content: Option<&str>,
     ) -> Result<()> {
         // Read existing document
         let existing = self.get(id)?;
@@ -1658,11 +3398,86 @@ impl<'a> Collection<'a> {
         // Use the existing content if no new content was provided
         let effective_content = content.or(existing.content.as_deref());
 
-        self.update(id, merged, effective_content)
+        self.update_locked(id, merged, effective_content).map(|_| ())
+    }
+
+    /// Replace only a document's Markdown body. The front matter is written
+    /// back as it is, `modified_at` is bumped and an `Updated` event fires.
+    /// Fails for collections without `content: true`.
+    pub fn update_content(&self, id: &str, content: &str) -> Result<()> {
+        if !self.definition().has_content() {
+            return Err(GroundDbError::Validation(format!(
+                "Collection '{}' has no content to update",
+                self.name
+            )));
+        }
+        self.store.with_document_lock(&self.name, id, || {
+            let existing = self.get(id)?;
+            self.update_locked(id, existing.data, Some(content)).map(|_| ())
+        })
+    }
+
+    /// Bump a document's `modified_at` without changing its data or body: the
+    /// file's mtime is set to now, the document is re-indexed, views that read
+    /// `modified_at` are rebuilt and an `Updated` event fires.
+    pub fn touch(&self, id: &str) -> Result<()> {
+        self.store.with_document_lock(&self.name, id, || {
+            if self.definition().readonly {
+                return Err(GroundDbError::Other(format!(
+                    "Collection '{}' is readonly",
+                    self.name
+                )));
+            }
+
+            let record = self
+                .store
+                .db
+                .get_document(&self.name, id)?
+                .ok_or_else(|| GroundDbError::NotFound {
+                    collection: self.name.clone(),
+                    id: id.to_string(),
+                })?;
+            let abs_path = self.store.root.join(&record.path);
+            let doc = self.store.read_document_file(&abs_path)?;
+
+            std::fs::OpenOptions::new()
+                .write(true)
+                .open(&abs_path)?
+                .set_modified(std::time::SystemTime::now())?;
+            let (created, modified) = document::document_timestamps(&abs_path, &doc.data)?;
+
+            self.store.index_document(
+                id,
+                &self.name,
+                &record.path,
+                &doc.data,
+                Some(&created.to_rfc3339()),
+                Some(&modified.to_rfc3339()),
+                doc.content.as_deref(),
+            )?;
+
+            let changed = HashSet::from(["modified_at".to_string()]);
+            self.store.post_write_fields(&self.name, Some(&changed))?;
+            self.store.committed(
+                &self.name,
+                ChangeEvent::Updated {
+                    id: id.to_string(),
+                    data: serde_json::to_value(&doc.data)?,
+                },
+            );
+            Ok(())
+        })
     }
 
     /// Delete a document by ID. Enforces referential integrity.
     pub fn delete(&self, id: &str) -> Result<()> {
+        self.store
+            .with_document_lock(&self.name, id, || self.delete_locked(id, true))
+    }
+
+    /// Delete a document, applying `on_delete` policies to the documents that
+    /// reference it only when `check_refs` is set.
+    fn delete_locked(&self, id: &str, check_refs: bool) -> Result<()> {
         let definition = self.definition();
 
         if definition.readonly {
@@ -1683,19 +3498,22 @@ impl<'a> Collection<'a> {
             })?;
 
         // Check referential integrity
-        self.check_referential_integrity(id)?;
+        if check_refs {
+            self.check_referential_integrity(id)?;
+        }
 
         // Delete the file
         let abs_path = self.store.root.join(&record.path);
         if abs_path.exists() {
-            document::delete_document(&abs_path)?;
+            self.store.delete_document_file(&abs_path)?;
         }
 
         // Remove from index
         self.store.db.delete_document(&self.name, id)?;
+        self.store.db.delete_document_meta(&self.name, id)?;
 
         self.store.post_write(&self.name)?;
-        self.store.subscriptions.notify_collection(
+        self.store.committed(
             &self.name,
             ChangeEvent::Deleted {
                 id: id.to_string(),
@@ -1717,92 +3535,77 @@ impl<'a> Collection<'a> {
         for ref_doc in &refs {
             if let Some(ref_collection) = self.store.schema.collections.get(&ref_doc.collection) {
                 for (field_name, field_def) in &ref_collection.fields {
-                    if field_def.field_type == FieldType::Ref {
-                        if let Some(target) = &field_def.target {
-                            if target.targets().contains(&self.name.as_str()) {
-                                // This field references our collection
-                                let policy = field_def
-                                    .effective_on_delete(ref_collection.on_delete.as_ref());
-
-                                // Check if this document actually references us
-                                let data = ref_doc.parse_data()?;
-                                if let Some(val) = data.get(field_name) {
-                                    let ref_id = match val {
-                                        serde_yaml::Value::String(s) => Some(s.as_str()),
-                                        serde_yaml::Value::Mapping(m) => m
-                                            .get(&serde_yaml::Value::String("id".into()))
-                                            .and_then(|v| v.as_str()),
-                                        _ => None,
-                                    };
-
-                                    if ref_id == Some(id) {
-                                        match policy {
-                                            OnDeletePolicy::Error => {
-                                                return Err(GroundDbError::ReferentialIntegrity(
-                                                    format!(
-                                                        "Cannot delete {}/{}: referenced by {}/{} (field '{}')",
-                                                        self.name, id, ref_doc.collection, ref_doc.id, field_name
-                                                    ),
-                                                ));
-                                            }
-                                            OnDeletePolicy::Cascade => {
-                                                // Delete the referencing document
-                                                let ref_col =
-                                                    self.store.collection(&ref_doc.collection)?;
-                                                ref_col.delete(&ref_doc.id)?;
-                                            }
-                                            OnDeletePolicy::Nullify => {
-                                                // Set the reference field to null
-                                                let mut data = ref_doc.parse_data()?;
-                                                if let Some(mapping) = data.as_mapping_mut() {
-                                                    mapping.insert(
-                                                        serde_yaml::Value::String(
-                                                            field_name.clone(),
-                                                        ),
-                                                        serde_yaml::Value::Null,
-                                                    );
+                    if let Some(target) = field_def.ref_target() {
+                        if target.targets().contains(&self.name.as_str()) {
+                            // This field references our collection
+                            let policy = field_def
+                                .effective_on_delete(ref_collection.on_delete.as_ref());
+
+                            // Check if this document actually references us
+                            let data = ref_doc.parse_data()?;
+                            if let Some(val) = data.get(field_name) {
+                                let references_us = if field_def.is_ref_list() {
+                                    val.as_sequence().is_some_and(|items| {
+                                        items.iter().any(|item| ref_value_id(item) == Some(id))
+                                    })
+                                } else {
+                                    ref_value_id(val) == Some(id)
+                                };
+
+                                if references_us {
+                                    match policy {
+                                        OnDeletePolicy::Error => {
+                                            return Err(GroundDbError::ReferentialIntegrity(
+                                                format!(
+                                                    "Cannot delete {}/{}: referenced by {}/{} (field '{}')",
+                                                    self.name, id, ref_doc.collection, ref_doc.id, field_name
+                                                ),
+                                            ));
+                                        }
+                                        OnDeletePolicy::Cascade => {
+                                            // Delete the referencing document
+                                            let ref_col =
+                                                self.store.collection(&ref_doc.collection)?;
+                                            ref_col.delete(&ref_doc.id)?;
+                                        }
+                                        OnDeletePolicy::Nullify | OnDeletePolicy::SetDefault => {
+                                            self.store.rewrite_field(ref_doc, field_name, |current| {
+                                                if field_def.is_ref_list() {
+                                                    // Drop the deleted document from the list
+                                                    let mut items = current
+                                                        .and_then(|v| v.as_sequence())
+                                                        .cloned()
+                                                        .unwrap_or_default();
+                                                    items.retain(|item| ref_value_id(item) != Some(id));
+                                                    serde_yaml::Value::Sequence(items)
+                                                } else if policy == OnDeletePolicy::SetDefault {
+                                                    // Set the reference field to its default
+                                                    field_def
+                                                        .default
+                                                        .clone()
+                                                        .unwrap_or(serde_yaml::Value::Null)
+                                                } else {
+                                                    // Set the reference field to null
+                                                    serde_yaml::Value::Null
                                                 }
-                                                let file_path =
-                                                    self.store.root.join(&ref_doc.path);
-                                                // Read the existing document to preserve content
-                                                let existing_doc = document::read_document(&file_path)?;
-                                                document::write_document(
-                                                    &file_path, &data, existing_doc.content.as_deref(),
-                                                )?;
-                                                // Read timestamps from the updated file
-                                                let meta = std::fs::metadata(&file_path)?;
-                                                let created: chrono::DateTime<chrono::Utc> = meta
-                                                    .created()
-                                                    .unwrap_or(meta.modified()?)
-                                                    .into();
-                                                let modified: chrono::DateTime<chrono::Utc> = meta.modified()?.into();
-                                                self.store.db.upsert_document(
-                                                    &ref_doc.id,
+                                            })?;
+                                        }
+                                        OnDeletePolicy::Archive => {
+                                            // Move to _archive/ subdirectory
+                                            let old_path =
+                                                self.store.root.join(&ref_doc.path);
+                                            let archive_path = self
+                                                .store
+                                                .root
+                                                .join("_archive")
+                                                .join(&ref_doc.path);
+                                            document::move_document(&old_path, &archive_path)?;
+                                            self.store
+                                                .db
+                                                .delete_document(
                                                     &ref_doc.collection,
-                                                    &ref_doc.path,
-                                                    &data,
-                                                    Some(&created.to_rfc3339()),
-                                                    Some(&modified.to_rfc3339()),
-                                                    existing_doc.content.as_deref(),
+                                                    &ref_doc.id,
                                                 )?;
-                                            }
-                                            OnDeletePolicy::Archive => {
-                                                // Move to _archive/ subdirectory
-                                                let old_path =
-                                                    self.store.root.join(&ref_doc.path);
-                                                let archive_path = self
-                                                    .store
-                                                    .root
-                                                    .join("_archive")
-                                                    .join(&ref_doc.path);
-                                                document::move_document(&old_path, &archive_path)?;
-                                                self.store
-                                                    .db
-                                                    .delete_document(
-                                                        &ref_doc.collection,
-                                                        &ref_doc.id,
-                                                    )?;
-                                            }
                                         }
                                     }
                                 }
@@ -1829,8 +3632,16 @@ impl<'a> Collection<'a> {
             });
         }
 
-        // For path-based IDs, render the template and extract the filename stem
-        let template = self.template();
+        // For path-based IDs, render the template and extract the filename stem.
+        // Flat collections store files by id, so use the declared template
+        let declared;
+        let template = if definition.layout == Layout::Flat {
+            declared =
+                configure_template(PathTemplate::parse(&definition.path)?, &self.store.options);
+            &declared
+        } else {
+            self.template()
+        };
         let rendered = template.render(data, None)?;
         let id = Path::new(&rendered)
             .file_stem()
@@ -1846,6 +3657,109 @@ impl<'a> Collection<'a> {
     }
 }
 
+/// Parse each collection's path template into the template its files are
+/// stored under (for `layout: flat`, `<base dir>/{id}.<ext>`), rendering
+/// with the options' filename limit and slug strategy. Rejects enum fields
+/// whose values that strategy maps to the same path segment.
+fn storage_templates(
+    schema: &SchemaDefinition,
+    options: &StoreOptions,
+) -> Result<HashMap<String, PathTemplate>> {
+    let mut path_templates = HashMap::new();
+    let mut errors = Vec::new();
+    for (name, collection) in &schema.collections {
+        let mut template = PathTemplate::parse(&collection.path)?;
+        if collection.layout == Layout::Flat {
+            template = PathTemplate::parse(&format!(
+                "{}{{id}}.{}",
+                template.base_directory(),
+                collection.file_extension()
+            ))?;
+        }
+        let template = configure_template(template, options);
+        crate::schema::validate_path_enums(name, collection, &template, &mut errors);
+        path_templates.insert(name.clone(), template);
+    }
+    if !errors.is_empty() {
+        return Err(GroundDbError::Schema(format!(
+            "Schema validation failed:\n  - {}",
+            errors.join("\n  - ")
+        )));
+    }
+    Ok(path_templates)
+}
+
+/// Apply the store options that affect rendering to a parsed path template
+fn configure_template(template: PathTemplate, options: &StoreOptions) -> PathTemplate {
+    template
+        .with_max_component_bytes(options.max_filename_bytes)
+        .with_slug_strategy(options.slug_strategy.clone())
+}
+
+/// The id a ref value points at: a plain string, or the `id` of a
+/// polymorphic `{ type, id }` mapping.
+fn ref_value_id(value: &serde_yaml::Value) -> Option<&str> {
+    match value {
+        serde_yaml::Value::String(s) => Some(s.as_str()),
+        serde_yaml::Value::Mapping(m) => m.get("id").and_then(|v| v.as_str()),
+        _ => None,
+    }
+}
+
+/// The document id a relative path maps to (its filename stem), or `fallback`
+/// if the path has none.
+fn id_from_path(rel_path: &str, fallback: &str) -> String {
+    Path::new(rel_path)
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or(fallback)
+        .to_string()
+}
+
+/// Fields that differ between two versions of a document's data, plus the
+/// implicit fields every rewrite may touch (`modified_at` and the body).
+fn changed_fields(old: &serde_yaml::Value, new: &serde_yaml::Value) -> HashSet<String> {
+    let mut changed: HashSet<String> = ["modified_at", "content", "content_length", "_title", "_links"]
+        .iter()
+        .map(|f| f.to_string())
+        .collect();
+    let empty = serde_yaml::Mapping::new();
+    let old_map = old.as_mapping().unwrap_or(&empty);
+    let new_map = new.as_mapping().unwrap_or(&empty);
+    for key in old_map.keys().chain(new_map.keys()) {
+        if old_map.get(key) != new_map.get(key) {
+            if let Some(name) = key.as_str() {
+                changed.insert(name.to_string());
+                changed.insert(format!("{name}_ordinal"));
+            }
+        }
+    }
+    changed
+}
+
+/// Resolve a data directory to an absolute path and check that it exists.
+/// Absolute paths let file watcher events (which use absolute paths) be
+/// matched back to collections via strip_prefix.
+fn resolve_root(path: &str) -> Result<PathBuf> {
+    let p = PathBuf::from(path);
+    let root = if p.is_absolute() {
+        p
+    } else {
+        std::env::current_dir()
+            .map_err(|e| GroundDbError::Other(format!(
+                "Failed to resolve data directory: {e}"
+            )))?
+            .join(p)
+    };
+    if !root.exists() {
+        return Err(GroundDbError::Other(format!(
+            "Data directory does not exist: {}",
+            root.display()
+        )));
+    }
+    Ok(root)
+}
+
 /// Convert a Document to a JSON value for the dynamic API
 fn doc_to_json(doc: &Document<serde_yaml::Value>) -> Result<serde_json::Value> {
     let data_json = serde_json::to_value(&doc.data)?;
@@ -1876,6 +3790,131 @@ fn doc_to_json(doc: &Document<serde_yaml::Value>) -> Result<serde_json::Value> {
 }
 
 
+/// Check whether a dynamic-API document matches all `key=value` filters
+fn matches_filters(json: &serde_json::Value, filters: &HashMap<String, String>) -> bool {
+    filters.iter().all(|(key, value)| match json.get(key) {
+        Some(serde_json::Value::String(s)) => s == value,
+        Some(serde_json::Value::Number(n)) => &n.to_string() == value,
+        Some(serde_json::Value::Bool(b)) => &b.to_string() == value,
+        _ => false,
+    })
+}
+
+/// Diff two lists of view rows, keyed by `id` when every row has one.
+fn diff_rows(before: &[serde_json::Value], after: &[serde_json::Value]) -> serde_json::Value {
+    let keyed = before.iter().chain(after).all(|row| row.get("id").is_some());
+    let key = |row: &serde_json::Value| {
+        if keyed {
+            row["id"].to_string()
+        } else {
+            row.to_string()
+        }
+    };
+    let old: HashMap<String, &serde_json::Value> = before.iter().map(|row| (key(row), row)).collect();
+    let new: HashMap<String, &serde_json::Value> = after.iter().map(|row| (key(row), row)).collect();
+
+    let added: Vec<_> = after.iter().filter(|row| !old.contains_key(&key(row))).collect();
+    let removed: Vec<_> = before.iter().filter(|row| !new.contains_key(&key(row))).collect();
+    let changed: Vec<_> = after
+        .iter()
+        .filter_map(|row| {
+            let previous = old.get(&key(row))?;
+            (*previous != row).then(|| {
+                serde_json::json!({ "id": row["id"], "before": previous, "after": row })
+            })
+        })
+        .collect();
+    serde_json::json!({ "added": added, "removed": removed, "changed": changed })
+}
+
+/// A query parameter as the SQL value its declared type binds to: `number`
+/// as an integer or real, `boolean` as 1 or 0 (how SQLite's JSON functions
+/// return booleans), `date` as `YYYY-MM-DD` and `datetime` as RFC 3339 in UTC,
+/// so they order chronologically as text. Anything else binds as text.
+/// `None` if the value doesn't parse as its type.
+fn bind_param(param_type: Option<&str>, value: &str) -> Option<rusqlite::types::Value> {
+    use chrono::{DateTime, NaiveDate, NaiveDateTime, SecondsFormat, Utc};
+    use rusqlite::types::Value;
+
+    let value = value.trim();
+    let parse_date = |value: &str| {
+        ["%Y-%m-%d", "%Y/%m/%d"]
+            .iter()
+            .find_map(|format| NaiveDate::parse_from_str(value, format).ok())
+    };
+    let parse_datetime = |value: &str| {
+        DateTime::parse_from_rfc3339(value)
+            .map(|t| t.with_timezone(&Utc))
+            .ok()
+            .or_else(|| {
+                ["%Y-%m-%dT%H:%M:%S", "%Y-%m-%d %H:%M:%S"]
+                    .iter()
+                    .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+                    .map(|t| t.and_utc())
+            })
+    };
+    match param_type {
+        Some("number") => match value.parse::<i64>() {
+            Ok(n) => Some(Value::Integer(n)),
+            Err(_) => value.parse::<f64>().ok().filter(|f| f.is_finite()).map(Value::Real),
+        },
+        Some("boolean") => match value {
+            "true" => Some(Value::Integer(1)),
+            "false" => Some(Value::Integer(0)),
+            _ => None,
+        },
+        Some("date") => parse_date(value)
+            .or_else(|| parse_datetime(value).map(|t| t.date_naive()))
+            .map(|d| Value::Text(d.format("%Y-%m-%d").to_string())),
+        Some("datetime") => parse_datetime(value)
+            .or_else(|| parse_date(value).map(|d| d.and_time(Default::default()).and_utc()))
+            // `Z` rather than `+00:00`, as stored datetimes are written, so
+            // text comparison at the same instant is equal
+            .map(|t| Value::Text(t.to_rfc3339_opts(SecondsFormat::AutoSi, true))),
+        _ => Some(Value::Text(value.to_string())),
+    }
+}
+
+/// Replace each `:name` parameter in `sql` that has a value in `params` with
+/// that value as a SQL literal: numbers bare, text quoted. Text inside string
+/// literals is left alone.
+fn inline_params(sql: &str, params: &HashMap<String, rusqlite::types::Value>) -> String {
+    let mut out = String::with_capacity(sql.len());
+    let mut chars = sql.char_indices().peekable();
+    let mut in_string = false;
+
+    while let Some((i, c)) = chars.next() {
+        if c == '\'' {
+            in_string = !in_string;
+        }
+        if c != ':' || in_string {
+            out.push(c);
+            continue;
+        }
+        let start = i + 1;
+        let mut end = start;
+        while let Some(&(j, next)) = chars.peek() {
+            if !(next.is_alphanumeric() || next == '_') {
+                break;
+            }
+            end = j + next.len_utf8();
+            chars.next();
+        }
+        match params.get(&sql[start..end]) {
+            Some(rusqlite::types::Value::Integer(n)) => out.push_str(&n.to_string()),
+            Some(rusqlite::types::Value::Real(f)) => out.push_str(&f.to_string()),
+            Some(rusqlite::types::Value::Text(value)) => {
+                out.push('\'');
+                out.push_str(&value.replace('\'', "''"));
+                out.push('\'');
+            }
+            Some(_) => out.push_str("NULL"),
+            None => out.push_str(&sql[i..end]),
+        }
+    }
+    out
+}
+
 /// Strip a trailing LIMIT clause from SQL. Used to replace the user's LIMIT with
 /// a buffer-extended LIMIT for buffered views.
 ///
@@ -1991,7 +4030,46 @@ collections:
     }
 
     #[test]
-    fn test_insert_and_get_user() {
+    fn test_open_with_schema_string() {
+        let tmp = TempDir::new().unwrap();
+        let schema = r#"
+collections:
+  users:
+    path: "users/{name}.md"
+    fields:
+      name: { type: string, required: true }
+      email: { type: string, required: true }
+"#;
+        let root = tmp.path().to_str().unwrap();
+        let data: serde_yaml::Value =
+            serde_yaml::from_str("name: Alice\nemail: alice@test.com").unwrap();
+
+        {
+            let store = Store::open_with_schema(root, schema).unwrap();
+            let users = store.collection("users").unwrap();
+            users.insert(data.clone(), None).unwrap();
+            let doc = users.get("alice").unwrap();
+            assert_eq!(doc.data["email"], serde_yaml::Value::String("alice@test.com".into()));
+        }
+        assert!(!tmp.path().join("schema.yaml").exists());
+        assert!(tmp.path().join("_system.db").exists());
+
+        // In-memory index: existing files are scanned on open, no db file is written
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path().to_str().unwrap();
+        let store = Store::open_in_memory(root, schema).unwrap();
+        let users = store.collection("users").unwrap();
+        users.insert(data, None).unwrap();
+        assert_eq!(users.get("alice").unwrap().id, "alice");
+        drop(store);
+        assert!(!tmp.path().join("_system.db").exists());
+
+        let store = Store::open_in_memory(root, schema).unwrap();
+        assert_eq!(store.collection("users").unwrap().list().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_insert_and_get_user() {
         let (_tmp, store) = setup_test_store();
         let users = store.collection("users").unwrap();
 
@@ -2060,6 +4138,140 @@ collections:
         assert!(doc.content.unwrap().contains("This is my post."));
     }
 
+    #[test]
+    fn test_insert_seeds_content_from_template() {
+        let tmp = TempDir::new().unwrap();
+        let schema = r##"
+collections:
+  notes:
+    path: "notes/{title}.md"
+    content: true
+    content_template: "# {title}\n\nWritten on {date}.\n"
+    fields:
+      title: { type: string, required: true }
+      date: { type: date }
+"##;
+        std::fs::write(tmp.path().join("schema.yaml"), schema).unwrap();
+        let store = Store::open(tmp.path().to_str().unwrap()).unwrap();
+        let notes = store.collection("notes").unwrap();
+
+        let data: serde_yaml::Value =
+            serde_yaml::from_str("title: Hello World\ndate: '2026-02-13'").unwrap();
+        let id = notes.insert(data, None).unwrap();
+        let content = notes.get(&id).unwrap().content.unwrap();
+        assert!(content.contains("# Hello World"), "Unexpected body: {content}");
+        assert!(content.contains("Written on 2026-02-13."), "Unexpected body: {content}");
+
+        // Explicit content wins over the template
+        let data: serde_yaml::Value = serde_yaml::from_str("title: Other").unwrap();
+        let id = notes.insert(data, Some("Custom body")).unwrap();
+        let content = notes.get(&id).unwrap().content.unwrap();
+        assert!(!content.contains("Written on"));
+    }
+
+    #[test]
+    fn test_extract_content_indexes_title_and_links() {
+        let tmp = TempDir::new().unwrap();
+        let schema = r#"
+collections:
+  notes:
+    path: "notes/{slug}.md"
+    content: true
+    extract_content: { title_from_h1: true, links: true }
+    fields:
+      slug: { type: string, required: true }
+
+views:
+  note_titles:
+    query: |
+      SELECT id, _title, _links
+      FROM notes
+      ORDER BY id
+"#;
+        std::fs::write(tmp.path().join("schema.yaml"), schema).unwrap();
+        let store = Store::open(tmp.path().to_str().unwrap()).unwrap();
+
+        let data: serde_yaml::Value = serde_yaml::from_str("slug: first").unwrap();
+        store
+            .collection("notes")
+            .unwrap()
+            .insert(
+                data,
+                Some("# Getting Started\n\nRead [the guide](guide.md) and [more](https://example.com)."),
+            )
+            .unwrap();
+
+        let result = store.view_dynamic("note_titles").unwrap();
+        let rows = result.as_array().unwrap();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0]["_title"], "Getting Started");
+        let links: Vec<String> = serde_json::from_str(rows[0]["_links"].as_str().unwrap()).unwrap();
+        assert_eq!(links, vec!["guide.md", "https://example.com"]);
+
+        // Derived fields are index-only and never written to the file
+        let doc = store.collection("notes").unwrap().get("first").unwrap();
+        assert!(doc.data.get("_title").is_none());
+    }
+
+    #[test]
+    fn test_content_format_exposed_and_gates_extraction() {
+        let tmp = TempDir::new().unwrap();
+        let schema = r#"
+collections:
+  notes:
+    path: "notes/{slug}.md"
+    content: { format: markdown }
+    extract_content: { title_from_h1: true }
+    fields:
+      slug: { type: string, required: true }
+  snippets:
+    path: "snippets/{slug}.md"
+    content: { format: html }
+    extract_content: { title_from_h1: true }
+    fields:
+      slug: { type: string, required: true }
+  tags:
+    path: "tags/{slug}.md"
+    fields:
+      slug: { type: string, required: true }
+
+views:
+  titles:
+    query: |
+      SELECT id, _title FROM notes
+      UNION ALL
+      SELECT id, _title FROM snippets
+      ORDER BY id
+"#;
+        std::fs::write(tmp.path().join("schema.yaml"), schema).unwrap();
+        let store = Store::open(tmp.path().to_str().unwrap()).unwrap();
+
+        let info = store.collection("snippets").unwrap().info();
+        assert_eq!(info["content"], serde_json::json!(true));
+        assert_eq!(info["content_format"], serde_json::json!("html"));
+        assert_eq!(store.collection("notes").unwrap().info()["content_format"], serde_json::json!("markdown"));
+        let info = store.collection("tags").unwrap().info();
+        assert_eq!(info["content"], serde_json::json!(false));
+        assert!(info["content_format"].is_null());
+
+        // A heading only becomes `_title` in the Markdown collection
+        for collection in ["notes", "snippets"] {
+            let data = serde_yaml::from_str(&format!("slug: {collection}-1")).unwrap();
+            store
+                .collection(collection)
+                .unwrap()
+                .insert(data, Some("# Heading\n\nText"))
+                .unwrap();
+        }
+        assert_eq!(
+            store.view_dynamic("titles").unwrap(),
+            serde_json::json!([
+                { "id": "notes-1", "_title": "Heading" },
+                { "id": "snippets-1", "_title": null },
+            ])
+        );
+    }
+
     #[test]
     fn test_update_causes_file_movement() {
         let (tmp, store) = setup_test_store();
@@ -2096,6 +4308,39 @@ collections:
         assert!(published_path.exists(), "Published file should exist");
     }
 
+    #[test]
+    fn test_update_rejects_immutable_id_field() {
+        let tmp = TempDir::new().unwrap();
+        let schema = r#"
+collections:
+  users:
+    path: "users/{name}.md"
+    fields:
+      name: { type: string, required: true, immutable: true }
+      email: { type: string }
+"#;
+        std::fs::write(tmp.path().join("schema.yaml"), schema).unwrap();
+        let store = Store::open(tmp.path().to_str().unwrap()).unwrap();
+        let users = store.collection("users").unwrap();
+        let id = users
+            .insert(serde_yaml::from_str("name: alice\nemail: a@test.com").unwrap(), None)
+            .unwrap();
+
+        let err = users
+            .update(&id, serde_yaml::from_str("name: alicia\nemail: a@test.com").unwrap(), None)
+            .unwrap_err();
+        assert!(matches!(err, GroundDbError::Validation(_)), "{err}");
+        assert!(err.to_string().contains("Field 'name' of users/alice is immutable"), "{err}");
+        assert!(tmp.path().join("users/alice.md").exists());
+        assert!(!tmp.path().join("users/alicia.md").exists());
+
+        // Other fields still update
+        users
+            .update(&id, serde_yaml::from_str("name: alice\nemail: new@test.com").unwrap(), None)
+            .unwrap();
+        assert_eq!(users.get(&id).unwrap().data["email"], serde_yaml::Value::from("new@test.com"));
+    }
+
     #[test]
     fn test_delete_user() {
         let (_tmp, store) = setup_test_store();
@@ -2137,6 +4382,149 @@ collections:
         assert_eq!(post_list.len(), 0);
     }
 
+    #[test]
+    fn test_on_delete_set_default() {
+        let tmp = TempDir::new().unwrap();
+        let schema = r#"
+collections:
+  users:
+    path: "users/{name}.md"
+    fields:
+      name: { type: string, required: true }
+  posts:
+    path: "posts/{title}.md"
+    fields:
+      title: { type: string, required: true }
+      author_id: { type: ref, target: users, default: ghost, on_delete: set_default }
+      editor_id: { type: ref, target: users, on_delete: set_default }
+    content: true
+"#;
+        std::fs::write(tmp.path().join("schema.yaml"), schema).unwrap();
+        let store = Store::open(tmp.path().to_str().unwrap()).unwrap();
+
+        let users = store.collection("users").unwrap();
+        users.insert(serde_yaml::from_str("name: Ghost").unwrap(), None).unwrap();
+        users.insert(serde_yaml::from_str("name: Alice").unwrap(), None).unwrap();
+        let posts = store.collection("posts").unwrap();
+        posts
+            .insert(
+                serde_yaml::from_str("title: Hello\nauthor_id: alice\neditor_id: alice").unwrap(),
+                Some("Body\n"),
+            )
+            .unwrap();
+
+        users.delete("alice").unwrap();
+
+        let post = posts.get("hello").unwrap();
+        assert_eq!(post.data["author_id"], serde_yaml::Value::String("ghost".into()));
+        // Without a declared default the field falls back to null
+        assert_eq!(post.data["editor_id"], serde_yaml::Value::Null);
+        assert_eq!(post.content.as_deref().map(str::trim), Some("Body"));
+
+        let backlinks = users.referencing("ghost", "posts", "author_id").unwrap();
+        assert_eq!(backlinks.len(), 1);
+    }
+
+    #[test]
+    fn test_list_of_refs_integrity() {
+        let tmp = TempDir::new().unwrap();
+        let schema = r#"
+collections:
+  users:
+    path: "users/{name}.md"
+    fields:
+      name: { type: string, required: true }
+  posts:
+    path: "posts/{title}.md"
+    fields:
+      title: { type: string, required: true }
+      contributors: { type: list, items: ref, target: users, on_delete: nullify }
+      reviewers: { type: list, items: { type: ref, target: users, on_delete: error } }
+    strict: true
+"#;
+        std::fs::write(tmp.path().join("schema.yaml"), schema).unwrap();
+        let store = Store::open(tmp.path().to_str().unwrap()).unwrap();
+
+        let users = store.collection("users").unwrap();
+        for name in ["Alice", "Bob", "Carol"] {
+            users
+                .insert(serde_yaml::from_str(&format!("name: {name}")).unwrap(), None)
+                .unwrap();
+        }
+        let posts = store.collection("posts").unwrap();
+        posts
+            .insert(
+                serde_yaml::from_str(
+                    "title: Hello\ncontributors: [alice, bob]\nreviewers: [carol]",
+                )
+                .unwrap(),
+                None,
+            )
+            .unwrap();
+        assert!(posts
+            .insert(serde_yaml::from_str("title: Bad\ncontributors: [alice, 7]").unwrap(), None)
+            .is_err());
+
+        let backlinks = users.referencing("bob", "posts", "contributors").unwrap();
+        assert_eq!(backlinks.len(), 1);
+
+        // nullify removes the deleted user from the list
+        users.delete("alice").unwrap();
+        let post = posts.get("hello").unwrap();
+        assert_eq!(post.data["contributors"], serde_yaml::from_str::<serde_yaml::Value>("[bob]").unwrap());
+
+        // error policy on list items blocks the delete
+        let err = users.delete("carol").unwrap_err();
+        assert!(err.to_string().contains("field 'reviewers'"), "{err}");
+
+        // A stale list element is reported as dangling
+        let path = tmp.path().join("posts/hello.md");
+        let raw = std::fs::read_to_string(&path).unwrap().replace("- bob", "- bob\n- zed");
+        std::fs::write(&path, raw).unwrap();
+        store.rebuild(Some("posts")).unwrap();
+        let report = store.validate_all_with_refs().unwrap();
+        let issues = report["posts"]["issues"].as_array().unwrap();
+        let dangling = issues[0]["dangling_refs"].as_array().unwrap();
+        assert_eq!(dangling.len(), 1);
+        assert!(dangling[0].as_str().unwrap().contains("'zed'"));
+    }
+
+    #[test]
+    fn test_nullified_required_nullable_ref_resaves() {
+        let tmp = TempDir::new().unwrap();
+        let schema = r#"
+collections:
+  users:
+    path: "users/{name}.md"
+    fields:
+      name: { type: string, required: true }
+  posts:
+    path: "posts/{title}.md"
+    fields:
+      title: { type: string, required: true }
+      author_id: { type: ref, target: users, required: true, nullable: true, on_delete: nullify }
+    strict: true
+"#;
+        std::fs::write(tmp.path().join("schema.yaml"), schema).unwrap();
+        let store = Store::open(tmp.path().to_str().unwrap()).unwrap();
+
+        let users = store.collection("users").unwrap();
+        users.insert(serde_yaml::from_str("name: Alice").unwrap(), None).unwrap();
+        let posts = store.collection("posts").unwrap();
+        posts
+            .insert(serde_yaml::from_str("title: Hello\nauthor_id: alice").unwrap(), None)
+            .unwrap();
+
+        users.delete("alice").unwrap();
+        let post = posts.get("hello").unwrap();
+        assert_eq!(post.data["author_id"], serde_yaml::Value::Null);
+
+        // Re-saving the nullified document passes validation
+        let partial: serde_yaml::Value = serde_yaml::from_str("title: Hello").unwrap();
+        posts.update_partial("hello", partial, None).unwrap();
+        assert_eq!(posts.get("hello").unwrap().data["author_id"], serde_yaml::Value::Null);
+    }
+
     #[test]
     fn test_auto_id_generation() {
         let (_tmp, store) = setup_test_store();
@@ -2192,80 +4580,1140 @@ collections:
     }
 
     #[test]
-    fn test_collection_not_found() {
-        let (_tmp, store) = setup_test_store();
-        let result = store.collection("nonexistent");
-        assert!(result.is_err());
-    }
-
-    #[test]
-    fn test_dynamic_api() {
-        let (_tmp, store) = setup_test_store();
-
-        // Insert via dynamic API
-        let data = serde_json::json!({
-            "name": "Alice",
-            "email": "alice@test.com"
-        });
-        let id = store.insert_dynamic("users", data, None).unwrap();
-        assert_eq!(id, "alice");
-
-        // Get via dynamic API
-        let doc = store.get_dynamic("users", "alice").unwrap();
-        assert_eq!(doc["id"], "alice");
-        assert_eq!(doc["name"], "Alice");
-        assert_eq!(doc["email"], "alice@test.com");
-        assert!(doc["created_at"].is_string());
+    fn test_write_reports_validation_warnings() {
+        let tmp = TempDir::new().unwrap();
+        let schema = r#"
+collections:
+  events:
+    path: "events/{title}.md"
+    fields:
+      title: { type: string, required: true }
+      severity: { type: number }
+    additional_properties: false
+    strict: false
+"#;
+        std::fs::write(tmp.path().join("schema.yaml"), schema).unwrap();
+        let store = Store::open(tmp.path().to_str().unwrap()).unwrap();
+        let events = store.collection("events").unwrap();
 
-        // List via dynamic API
-        let list = store
-            .list_dynamic("users", &HashMap::new())
+        let (id, warnings) = events
+            .insert_with_report(
+                serde_yaml::from_str("title: outage\nseverity: high\nextra: 1").unwrap(),
+                None,
+            )
             .unwrap();
-        assert_eq!(list.as_array().unwrap().len(), 1);
+        assert_eq!(id, "outage");
+        assert_eq!(warnings.len(), 2, "{warnings:?}");
+        assert!(warnings.iter().any(|w| w.contains("severity")));
+        assert!(warnings.iter().any(|w| w.contains("extra")));
 
-        // Delete via dynamic API
-        store.delete_dynamic("users", "alice").unwrap();
-        let list = store
-            .list_dynamic("users", &HashMap::new())
+        let warnings = events
+            .update_with_report(&id, serde_yaml::from_str("title: outage\nseverity: 2").unwrap(), None)
             .unwrap();
-        assert_eq!(list.as_array().unwrap().len(), 0);
+        assert!(warnings.is_empty(), "{warnings:?}");
     }
 
-    #[test]
-    fn test_status() {
-        let (_tmp, store) = setup_test_store();
-        let status = store.status().unwrap();
-        assert!(status["schema_hash"].is_string());
-        assert!(status["collections"].is_object());
+    struct EndAfterStart;
+
+    impl validation::Validator for EndAfterStart {
+        fn validate(&self, collection: &str, data: &serde_yaml::Value) -> Vec<String> {
+            let (Some(start), Some(end)) = (
+                data.get("start").and_then(|v| v.as_str()),
+                data.get("end").and_then(|v| v.as_str()),
+            ) else {
+                return Vec::new();
+            };
+            if collection == "trips" && end < start {
+                vec![format!("end ({end}) is before start ({start})")]
+            } else {
+                Vec::new()
+            }
+        }
     }
 
     #[test]
-    fn test_validate_all() {
-        let (_tmp, store) = setup_test_store();
-
-        let data: serde_yaml::Value =
-            serde_yaml::from_str("name: Alice\nemail: alice@test.com").unwrap();
-        store.collection("users").unwrap().insert(data, None).unwrap();
-
+    fn test_custom_validator_blocks_write() {
+        let tmp = TempDir::new().unwrap();
+        let schema = r#"
+collections:
+  trips:
+    path: "trips/{name}.md"
+    fields:
+      name: { type: string, required: true }
+      start: { type: date, required: true }
+      end: { type: date, required: true }
+    strict: false
+"#;
+        std::fs::write(tmp.path().join("schema.yaml"), schema).unwrap();
+        let store = Store::open(tmp.path().to_str().unwrap()).unwrap();
+        store.add_validator(EndAfterStart);
+        let trips = store.collection("trips").unwrap();
+
+        let err = trips
+            .insert(
+                serde_yaml::from_str("name: lisbon\nstart: 2024-05-10\nend: 2024-05-01").unwrap(),
+                None,
+            )
+            .unwrap_err();
+        assert!(matches!(err, GroundDbError::Validation(_)));
+        assert!(err.to_string().contains("end (2024-05-01) is before start (2024-05-10)"));
+        assert!(!tmp.path().join("trips/lisbon.md").exists());
+
+        let id = trips
+            .insert(
+                serde_yaml::from_str("name: lisbon\nstart: 2024-05-01\nend: 2024-05-10").unwrap(),
+                None,
+            )
+            .unwrap();
+        assert!(trips
+            .update(
+                &id,
+                serde_yaml::from_str("name: lisbon\nstart: 2024-05-20\nend: 2024-05-10").unwrap(),
+                None,
+            )
+            .is_err());
+
+        // Closures work too, and validate_all reports their messages
+        store.add_validator(|_: &str, data: &serde_yaml::Value| {
+            if data.get("name").and_then(|v| v.as_str()) == Some("lisbon") {
+                vec!["no more lisbon".to_string()]
+            } else {
+                Vec::new()
+            }
+        });
         let report = store.validate_all().unwrap();
-        assert!(report["users"]["total"].as_u64().unwrap() >= 1);
+        assert_eq!(report["trips"]["issues"][0]["errors"][0], serde_json::json!("no more lisbon"));
     }
 
     #[test]
-    fn test_update_partial() {
-        let (_tmp, store) = setup_test_store();
-        let users = store.collection("users").unwrap();
-
-        let data: serde_yaml::Value =
-            serde_yaml::from_str("name: Alice\nemail: alice@test.com\nrole: member").unwrap();
-        users.insert(data, None).unwrap();
-
-        // Partially update just the email
-        let partial: serde_yaml::Value =
-            serde_yaml::from_str("email: alice@newdomain.com").unwrap();
-        users.update_partial("alice", partial, None).unwrap();
+    fn test_hooks_run_for_typed_writes() {
+        #[derive(serde::Serialize)]
+        struct Trip {
+            name: String,
+            start: String,
+            end: String,
+        }
 
-        let doc = users.get("alice").unwrap();
+        let tmp = TempDir::new().unwrap();
+        let schema = r#"
+collections:
+  trips:
+    path: "trips/{name}.md"
+    fields:
+      name: { type: string, required: true }
+      start: { type: date, required: true }
+      end: { type: date, required: true }
+"#;
+        std::fs::write(tmp.path().join("schema.yaml"), schema).unwrap();
+        let store = Store::open(tmp.path().to_str().unwrap()).unwrap();
+        store.add_validator(EndAfterStart);
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&seen);
+        store.on_after_write(move |collection, event| {
+            sink.lock().unwrap().push((collection.to_string(), event.clone()));
+            Ok(())
+        });
+
+        let trip = |start: &str, end: &str| Trip {
+            name: "lisbon".into(),
+            start: start.into(),
+            end: end.into(),
+        };
+        let err = store
+            .insert_document("trips", &trip("2024-05-10", "2024-05-01"), None)
+            .unwrap_err();
+        assert!(err.to_string().contains("is before start"));
+        assert!(seen.lock().unwrap().is_empty());
+
+        let id = store
+            .insert_document("trips", &trip("2024-05-01", "2024-05-10"), None)
+            .unwrap();
+        assert!(store.update_document("trips", &id, &trip("2024-05-20", "2024-05-10")).is_err());
+        store.update_document("trips", &id, &trip("2024-05-02", "2024-05-10")).unwrap();
+        store.delete_document("trips", &id).unwrap();
+
+        let seen = seen.lock().unwrap();
+        let kinds: Vec<&str> = seen
+            .iter()
+            .map(|(collection, event)| {
+                assert_eq!(collection, "trips");
+                match event {
+                    ChangeEvent::Inserted { .. } => "inserted",
+                    ChangeEvent::Updated { .. } => "updated",
+                    ChangeEvent::Deleted { .. } => "deleted",
+                }
+            })
+            .collect();
+        assert_eq!(kinds, ["inserted", "updated", "deleted"]);
+        assert!(matches!(
+            &seen[0].1,
+            ChangeEvent::Inserted { data, .. } if data["start"] == "2024-05-01"
+        ));
+    }
+
+    #[test]
+    fn test_after_write_hook_error_does_not_fail_the_write() {
+        let tmp = TempDir::new().unwrap();
+        let schema = r#"
+collections:
+  notes:
+    path: "notes/{title}.md"
+    fields:
+      title: { type: string, required: true }
+"#;
+        std::fs::write(tmp.path().join("schema.yaml"), schema).unwrap();
+        let store = Arc::new(Store::open(tmp.path().to_str().unwrap()).unwrap());
+        let committed = Arc::new(Mutex::new(false));
+        let later_ran = Arc::new(Mutex::new(false));
+        {
+            let store_ref = Arc::downgrade(&store);
+            let committed = Arc::clone(&committed);
+            store.on_after_write(move |collection, event| {
+                let ChangeEvent::Inserted { id, .. } = event else {
+                    return Ok(());
+                };
+                // The hook sees the committed document
+                let store = store_ref.upgrade().unwrap();
+                *committed.lock().unwrap() = store.get_dynamic(collection, id).is_ok();
+                Err(GroundDbError::Other("audit log unavailable".into()))
+            });
+            let later_ran = Arc::clone(&later_ran);
+            store.on_after_write(move |_, _| {
+                *later_ran.lock().unwrap() = true;
+                Ok(())
+            });
+        }
+
+        let id = store
+            .insert_dynamic("notes", serde_json::json!({ "title": "hi" }), None)
+            .unwrap();
+        assert_eq!(id, "hi");
+        assert!(*committed.lock().unwrap());
+        assert!(*later_ran.lock().unwrap());
+        assert!(tmp.path().join("notes/hi.md").exists());
+    }
+
+    #[test]
+    fn test_flat_layout_stores_files_by_id() {
+        let tmp = TempDir::new().unwrap();
+        let schema = r#"
+collections:
+  posts:
+    path: "posts/{status}/{title}.md"
+    layout: flat
+    fields:
+      title: { type: string, required: true }
+      status: { type: string, enum: [draft, published], default: draft }
+"#;
+        std::fs::write(tmp.path().join("schema.yaml"), schema).unwrap();
+        let store = Store::open(tmp.path().to_str().unwrap()).unwrap();
+        let posts = store.collection("posts").unwrap();
+
+        // The declared template still derives the id
+        let id = posts
+            .insert(serde_yaml::from_str("title: Hello World").unwrap(), None)
+            .unwrap();
+        assert_eq!(id, "hello-world");
+        let path = tmp.path().join("posts/hello-world.md");
+        assert!(path.exists());
+        assert!(!tmp.path().join("posts/draft").exists());
+
+        // Changing a template field doesn't move the file
+        posts
+            .update(
+                &id,
+                serde_yaml::from_str("title: Hello World\nstatus: published").unwrap(),
+                None,
+            )
+            .unwrap();
+        assert!(path.exists());
+        assert!(!tmp.path().join("posts/published").exists());
+        assert_eq!(store.check_consistency().unwrap()["ok"], serde_json::json!(true));
+
+        // A rescan finds the file under the same id
+        drop(posts);
+        drop(store);
+        let store = Store::open(tmp.path().to_str().unwrap()).unwrap();
+        let filters = HashMap::from([("status".to_string(), "published".to_string())]);
+        let listed = store.list_dynamic("posts", &filters).unwrap();
+        assert_eq!(listed.as_array().unwrap().len(), 1);
+        assert_eq!(listed[0]["id"], serde_json::json!("hello-world"));
+    }
+
+    #[test]
+    fn test_case_insensitive_paths_conflict() {
+        let open = |tmp: &TempDir, case_insensitive: bool| {
+            let options = StoreOptions {
+                case_insensitive_paths: Some(case_insensitive),
+                ..Default::default()
+            };
+            Store::open_with_options(tmp.path().to_str().unwrap(), options).unwrap()
+        };
+        let setup = || {
+            let tmp = TempDir::new().unwrap();
+            let schema = r#"
+collections:
+  notes:
+    path: "notes/{title}.md"
+    fields:
+      title: { type: string, required: true }
+"#;
+            std::fs::write(tmp.path().join("schema.yaml"), schema).unwrap();
+            // A hand-written file whose name isn't lowercase
+            std::fs::create_dir_all(tmp.path().join("notes")).unwrap();
+            std::fs::write(tmp.path().join("notes/Hello.md"), "---\ntitle: Hello\n---\n").unwrap();
+            tmp
+        };
+
+        let tmp = setup();
+        let store = open(&tmp, true);
+        let err = store
+            .collection("notes")
+            .unwrap()
+            .insert(serde_yaml::from_str("title: hello").unwrap(), None)
+            .unwrap_err();
+        assert!(matches!(err, GroundDbError::PathConflict { .. }), "{err}");
+        assert!(!tmp.path().join("notes/hello.md").exists());
+
+        // On a case-sensitive filesystem the two names are distinct files
+        let tmp = setup();
+        if options::detect_case_insensitive(tmp.path()) {
+            return;
+        }
+        let store = open(&tmp, false);
+        let id = store
+            .collection("notes")
+            .unwrap()
+            .insert(serde_yaml::from_str("title: hello").unwrap(), None)
+            .unwrap();
+        assert_eq!(id, "hello");
+    }
+
+    #[test]
+    fn test_compressed_collection_reads() {
+        let tmp = TempDir::new().unwrap();
+        let schema = r#"
+collections:
+  logs:
+    path: "logs/{id}.md"
+    id: { auto: ulid }
+    compress: true
+    fields:
+      level: { type: string, required: true }
+      payload: { type: string }
+
+views:
+  errors:
+    query: "SELECT level, payload FROM logs WHERE level = 'error'"
+    materialize: false
+"#;
+        std::fs::write(tmp.path().join("schema.yaml"), schema).unwrap();
+        let store = Store::open(tmp.path().to_str().unwrap()).unwrap();
+        let logs = store.collection("logs").unwrap();
+        let payload = "x".repeat(10_000);
+        let data: serde_yaml::Value =
+            serde_yaml::from_str(&format!("level: error\npayload: {payload}")).unwrap();
+        let id = logs.insert(data.clone(), None).unwrap();
+        logs.insert(serde_yaml::from_str("level: info").unwrap(), None).unwrap();
+
+        assert_eq!(store.db.get_document("logs", &id).unwrap().unwrap().parse_data().unwrap(), data);
+        assert_eq!(
+            store.view_dynamic("errors").unwrap(),
+            serde_json::json!([{ "level": "error", "payload": payload }])
+        );
+        let found = store.query("logs").filter("level", "error").run().unwrap();
+        assert_eq!(found.as_array().unwrap().len(), 1);
+        assert_eq!(found[0]["id"], serde_json::json!(id));
+    }
+
+    #[test]
+    fn test_insert_conflict_policies_report_ids() {
+        let tmp = TempDir::new().unwrap();
+        let schema = r#"
+collections:
+  pages:
+    path: "pages/{section}/{title}.md"
+    fields:
+      title: { type: string, required: true }
+      section: { type: string, required: true }
+  notes:
+    path: "notes/{section}/{title}.md"
+    id: { on_conflict: suffix }
+    fields:
+      title: { type: string, required: true }
+      section: { type: string, required: true }
+"#;
+        std::fs::write(tmp.path().join("schema.yaml"), schema).unwrap();
+        let store = Store::open(tmp.path().to_str().unwrap()).unwrap();
+
+        // error: same id in another directory is rejected, naming the existing id
+        let pages = store.collection("pages").unwrap();
+        pages
+            .insert(serde_yaml::from_str("title: Intro\nsection: guide").unwrap(), None)
+            .unwrap();
+        let err = pages
+            .insert(serde_yaml::from_str("title: Intro\nsection: blog").unwrap(), None)
+            .unwrap_err();
+        match err {
+            GroundDbError::PathConflict { existing_id, path } => {
+                assert_eq!(existing_id, "intro");
+                assert_eq!(path, "pages/blog/intro.md");
+            }
+            other => panic!("Expected PathConflict, got {other}"),
+        }
+        assert_eq!(pages.get("intro").unwrap().data["section"], serde_yaml::Value::from("guide"));
+
+        // suffix: the returned id is the suffixed filename stem and can be read back
+        let notes = store.collection("notes").unwrap();
+        let first = notes
+            .insert(serde_yaml::from_str("title: Intro\nsection: guide").unwrap(), None)
+            .unwrap();
+        let second = notes
+            .insert(serde_yaml::from_str("title: Intro\nsection: blog").unwrap(), None)
+            .unwrap();
+        let third = notes
+            .insert(serde_yaml::from_str("title: Intro\nsection: guide").unwrap(), None)
+            .unwrap();
+        assert_eq!((first.as_str(), second.as_str(), third.as_str()), ("intro", "intro-2", "intro-3"));
+        assert_eq!(notes.get("intro-2").unwrap().data["section"], serde_yaml::Value::from("blog"));
+        assert!(tmp.path().join("notes/blog/intro-2.md").exists());
+        assert_eq!(notes.get("intro").unwrap().data["section"], serde_yaml::Value::from("guide"));
+
+        // A rescan derives the same ids from the files
+        store.rebuild(None).unwrap();
+        assert_eq!(notes.get("intro-3").unwrap().data["section"], serde_yaml::Value::from("guide"));
+    }
+
+    #[test]
+    fn test_collection_not_found() {
+        let (_tmp, store) = setup_test_store();
+        let result = store.collection("nonexistent");
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_open_rejects_colliding_path_enum() {
+        let tmp = TempDir::new().unwrap();
+        let schema = r#"
+collections:
+  tasks:
+    path: "tasks/{status}/{title}.md"
+    fields:
+      title: { type: string, required: true }
+      status: { type: string, enum: ["In Progress", "in-progress"] }
+"#;
+        std::fs::write(tmp.path().join("schema.yaml"), schema).unwrap();
+
+        let err = Store::open(tmp.path().to_str().unwrap())
+            .err()
+            .expect("Store::open should reject colliding enum slugs");
+        let msg = err.to_string();
+        assert!(msg.contains("in-progress"), "Unexpected error: {msg}");
+        assert!(msg.contains("status"), "Unexpected error: {msg}");
+    }
+
+    #[test]
+    fn test_open_rejects_overlapping_base_directories() {
+        let tmp = TempDir::new().unwrap();
+        let schema = r#"
+collections:
+  posts:
+    path: "posts/{title}.md"
+    fields:
+      title: { type: string, required: true }
+  archived_posts:
+    path: "posts/archive/{title}.md"
+    fields:
+      title: { type: string, required: true }
+"#;
+        std::fs::write(tmp.path().join("schema.yaml"), schema).unwrap();
+
+        let err = Store::open(tmp.path().to_str().unwrap())
+            .err()
+            .expect("Store::open should reject overlapping base directories");
+        let msg = err.to_string();
+        assert!(msg.contains("overlapping base directories"), "Unexpected error: {msg}");
+        assert!(msg.contains("'archived_posts' and 'posts'"), "Unexpected error: {msg}");
+    }
+
+    #[test]
+    fn test_open_rejects_view_selecting_undeclared_column() {
+        let tmp = TempDir::new().unwrap();
+        let schema = r#"
+collections:
+  posts:
+    path: "posts/{title}.md"
+    fields:
+      title: { type: string, required: true }
+    content: true
+
+views:
+  post_titles:
+    query: |
+      SELECT p.id, p.title, p.content, p.subtitle
+      FROM posts p
+"#;
+        std::fs::write(tmp.path().join("schema.yaml"), schema).unwrap();
+
+        let err = Store::open(tmp.path().to_str().unwrap())
+            .err()
+            .expect("Store::open should reject a view selecting an undeclared column");
+        let msg = err.to_string();
+        assert!(msg.contains("post_titles"), "Unexpected error: {msg}");
+        assert!(msg.contains("p.subtitle"), "Unexpected error: {msg}");
+    }
+
+    #[test]
+    fn test_scan_skips_oversized_documents() {
+        let tmp = TempDir::new().unwrap();
+        let schema = r#"
+collections:
+  notes:
+    path: "notes/{title}.md"
+    fields:
+      title: { type: string, required: true }
+    content: true
+"#;
+        std::fs::write(tmp.path().join("schema.yaml"), schema).unwrap();
+        let notes_dir = tmp.path().join("notes");
+        std::fs::create_dir_all(&notes_dir).unwrap();
+        std::fs::write(notes_dir.join("small.md"), "---\ntitle: small\n---\nShort.\n").unwrap();
+        let huge = format!("---\ntitle: huge\n---\n{}\n", "x".repeat(4096));
+        std::fs::write(notes_dir.join("huge.md"), huge).unwrap();
+        std::fs::write(notes_dir.join("tiny.md"), "---\ntitle: tiny\n---\n").unwrap();
+
+        let options = StoreOptions {
+            max_document_bytes: Some(1024),
+            ..Default::default()
+        };
+        let store = Store::open_with_options(tmp.path().to_str().unwrap(), options).unwrap();
+
+        let notes = store.collection("notes").unwrap();
+        let mut ids: Vec<String> = notes.list().unwrap().into_iter().map(|d| d.id).collect();
+        ids.sort();
+        assert_eq!(ids, vec!["small", "tiny"]);
+        assert!(notes.get("huge").is_err());
+
+        let report = store.validate_all().unwrap();
+        assert_eq!(report["notes"]["skipped"], serde_json::json!(["notes/huge.md"]));
+
+        // A file growing past the limit drops out of the index
+        let grown = format!("---\ntitle: small\n---\n{}\n", "x".repeat(4096));
+        std::fs::write(notes_dir.join("small.md"), grown).unwrap();
+        let event = WatcherEvent {
+            path: notes_dir.join("small.md"),
+            kind: ChangeKind::Modified,
+        };
+        store.process_single_watcher_event("notes", &event).unwrap();
+        assert!(notes.get("small").is_err());
+
+        let report = store.check_consistency().unwrap();
+        assert_eq!(report["ok"], serde_json::json!(false));
+        let oversized: Vec<&str> = report["oversized"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|entry| entry["path"].as_str().unwrap())
+            .collect();
+        assert_eq!(oversized, ["notes/huge.md", "notes/small.md"]);
+    }
+
+    #[test]
+    fn test_strict_parse_fails_on_malformed_document() {
+        let tmp = TempDir::new().unwrap();
+        let schema = r#"
+collections:
+  notes:
+    path: "notes/{title}.md"
+    fields:
+      title: { type: string, required: true }
+"#;
+        std::fs::write(tmp.path().join("schema.yaml"), schema).unwrap();
+        let notes_dir = tmp.path().join("notes");
+        std::fs::create_dir_all(&notes_dir).unwrap();
+        std::fs::write(notes_dir.join("good.md"), "---\ntitle: good\n---\n").unwrap();
+        std::fs::write(notes_dir.join("bad.md"), "---\ntitle: [unclosed\n---\n").unwrap();
+        let strict = StoreOptions {
+            strict_parse: true,
+            ..Default::default()
+        };
+
+        // Lenient (default): the scan skips the file and reports it
+        let store = Store::open(tmp.path().to_str().unwrap()).unwrap();
+        let ids: Vec<String> = store
+            .collection("notes")
+            .unwrap()
+            .list()
+            .unwrap()
+            .into_iter()
+            .map(|d| d.id)
+            .collect();
+        assert_eq!(ids, vec!["good"]);
+        let report = store.validate_all().unwrap();
+        assert_eq!(report["notes"]["skipped"], serde_json::json!(["notes/bad.md"]));
+        drop(store);
+
+        // Strict: opening fails, naming the file
+        std::fs::remove_file(tmp.path().join("_system.db")).unwrap();
+        match Store::open_with_options(tmp.path().to_str().unwrap(), strict.clone()) {
+            Err(GroundDbError::Parse { path, .. }) => assert_eq!(path, "notes/bad.md"),
+            other => panic!("expected a parse error, got {:?}", other.map(|_| ())),
+        }
+
+        // Strict reads fail on an indexed document that became unparseable
+        std::fs::remove_file(notes_dir.join("bad.md")).unwrap();
+        let store = Store::open_with_options(tmp.path().to_str().unwrap(), strict).unwrap();
+        std::fs::write(notes_dir.join("good.md"), "---\ntitle: [unclosed\n---\n").unwrap();
+        let err = store.collection("notes").unwrap().list().unwrap_err();
+        assert!(err.to_string().contains("notes/good.md"), "{err}");
+    }
+
+    #[test]
+    fn test_lenient_yaml_indexes_duplicate_keys() {
+        let tmp = TempDir::new().unwrap();
+        let schema = r#"
+collections:
+  notes:
+    path: "notes/{slug}.md"
+    fields:
+      slug: { type: string, required: true }
+      status: { type: string }
+"#;
+        std::fs::write(tmp.path().join("schema.yaml"), schema).unwrap();
+        let notes_dir = tmp.path().join("notes");
+        std::fs::create_dir_all(&notes_dir).unwrap();
+        std::fs::write(
+            notes_dir.join("dup.md"),
+            "---\nslug: dup\nstatus: draft\nstatus: done\n---\n",
+        )
+        .unwrap();
+
+        let store = Store::open(tmp.path().to_str().unwrap()).unwrap();
+        assert!(store.collection("notes").unwrap().get("dup").is_err());
+        drop(store);
+
+        std::fs::remove_file(tmp.path().join("_system.db")).unwrap();
+        let options = StoreOptions {
+            lenient_yaml: true,
+            ..Default::default()
+        };
+        let store = Store::open_with_options(tmp.path().to_str().unwrap(), options).unwrap();
+        let doc = store.collection("notes").unwrap().get("dup").unwrap();
+        assert_eq!(doc.data["status"], serde_yaml::Value::String("done".into()));
+        let report = store.validate_all().unwrap();
+        assert!(report["notes"].get("skipped").is_none(), "{report}");
+    }
+
+    #[test]
+    fn test_git_friendly_update_only_touches_changed_line() {
+        let tmp = TempDir::new().unwrap();
+        let schema = r#"
+collections:
+  notes:
+    path: "notes/{title}.md"
+    fields:
+      title: { type: string, required: true }
+      status: { type: string }
+      rank: { type: number }
+    content: true
+"#;
+        std::fs::write(tmp.path().join("schema.yaml"), schema).unwrap();
+        let notes_dir = tmp.path().join("notes");
+        std::fs::create_dir_all(&notes_dir).unwrap();
+        let path = notes_dir.join("hello.md");
+        let before = "---\nstatus: draft\nrank: 1\ntitle: hello\n---\n\nSome body.\n";
+        std::fs::write(&path, before).unwrap();
+
+        let options = StoreOptions {
+            git_friendly: true,
+            ..Default::default()
+        };
+        let store = Store::open_with_options(tmp.path().to_str().unwrap(), options).unwrap();
+        let notes = store.collection("notes").unwrap();
+
+        // Caller supplies keys in a different order than the file
+        let data: serde_yaml::Value =
+            serde_yaml::from_str("title: hello\nrank: 2\nstatus: draft").unwrap();
+        let content = notes.get("hello").unwrap().content;
+        notes.update("hello", data, content.as_deref()).unwrap();
+
+        let after = std::fs::read_to_string(&path).unwrap();
+        let before_lines: Vec<&str> = before.lines().collect();
+        let after_lines: Vec<&str> = after.lines().collect();
+        assert_eq!(before_lines.len(), after_lines.len(), "after:\n{after}");
+        let changed: Vec<(&str, &str)> = before_lines
+            .into_iter()
+            .zip(after_lines)
+            .filter(|(b, a)| b != a)
+            .collect();
+        assert_eq!(changed, vec![("rank: 1", "rank: 2")]);
+        assert!(after.ends_with("Some body.\n") && !after.ends_with("\n\n"));
+    }
+
+    #[test]
+    fn test_schema_history_diff() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().to_str().unwrap();
+        let v1 = r#"
+collections:
+  users:
+    path: "users/{name}.md"
+    fields:
+      name: { type: string, required: true }
+"#;
+        let v2 = r#"
+collections:
+  users:
+    path: "users/{name}.md"
+    fields:
+      name: { type: string, required: true }
+      email: { type: string }
+  posts:
+    path: "posts/{title}.md"
+    fields:
+      title: { type: string, required: true }
+"#;
+        drop(Store::open_with_schema(path, v1).unwrap());
+        let store = Store::open_with_schema(path, v2).unwrap();
+
+        let (hash1, hash2) = (hash_schema(v1), hash_schema(v2));
+        let expected = migration::diff_schemas(
+            &parse_schema_str(v1).unwrap(),
+            &parse_schema_str(v2).unwrap(),
+        );
+        assert!(!expected.is_empty());
+        assert_eq!(store.schema_diff(&hash1, &hash2).unwrap(), expected);
+        assert_eq!(store.schema_diff("1", "2").unwrap(), expected);
+
+        assert!(!store.schema_at(&hash1).unwrap().collections.contains_key("posts"));
+        assert!(store.schema_at("nope").is_err());
+    }
+
+    #[test]
+    fn test_health_on_fresh_store() {
+        let (tmp, store) = setup_store_with_views();
+        let health = store.health();
+
+        assert_eq!(health["ok"], serde_json::json!(true));
+        assert_eq!(health["db_reachable"], serde_json::json!(true));
+        assert_eq!(health["watcher_running"], serde_json::json!(false));
+        assert_eq!(health["drifted_collections"], serde_json::json!(0));
+        assert_eq!(
+            health["schema_hash"].as_str(),
+            store.status().unwrap()["schema_hash"].as_str()
+        );
+        // No documents yet, so every static view cache is empty
+        let static_views = store
+            .schema()
+            .views
+            .values()
+            .filter(|v| v.view_type != Some(crate::schema::ViewType::Query))
+            .count();
+        assert_eq!(health["empty_view_caches"], serde_json::json!(static_views));
+        let booted_at = health["booted_at"].as_str().unwrap();
+        assert!(chrono::DateTime::parse_from_rfc3339(booted_at).is_ok());
+
+        // A file dropped in behind the store's back shows up as drift
+        std::fs::write(
+            tmp.path().join("users/zed.md"),
+            "---\nname: Zed\nemail: zed@test.com\n---\n",
+        )
+        .unwrap();
+        let health = store.health();
+        assert_eq!(health["drifted"], serde_json::json!(["users"]));
+        assert_eq!(health["ok"], serde_json::json!(false));
+    }
+
+    #[test]
+    fn test_rebuild_plan_is_read_only() {
+        let (tmp, store) = setup_store_with_views();
+        let users = store.collection("users").unwrap();
+        let alice = users
+            .insert(serde_yaml::from_str("name: Alice\nemail: alice@test.com").unwrap(), None)
+            .unwrap();
+        std::fs::write(
+            tmp.path().join("users/zed.md"),
+            "---\nname: Zed\nemail: zed@test.com\n---\n",
+        )
+        .unwrap();
+
+        let plan = store.rebuild_plan(Some("users")).unwrap();
+        assert_eq!(plan["files"], serde_json::json!(2));
+        assert_eq!(plan["collections"]["users"]["added"], serde_json::json!(["zed"]));
+        assert_eq!(plan["collections"]["users"]["removed"], serde_json::json!([]));
+        assert_eq!(plan["collections"]["users"]["changed"], serde_json::json!([]));
+        assert_eq!(plan["views"], serde_json::json!(["post_feed", "user_lookup"]));
+        // Nothing was indexed
+        assert!(users.get("zed").is_err());
+
+        std::fs::remove_file(tmp.path().join(format!("users/{alice}.md"))).unwrap();
+        let plan = store.rebuild_plan(None).unwrap();
+        assert_eq!(plan["collections"]["users"]["removed"], serde_json::json!([alice]));
+        assert_eq!(plan["collections"]["posts"]["files"], serde_json::json!(0));
+        assert_eq!(plan["views"], serde_json::json!(["all_posts", "post_feed", "user_lookup"]));
+
+        store.rebuild(Some("users")).unwrap();
+        let plan = store.rebuild_plan(Some("users")).unwrap();
+        assert_eq!(plan["collections"]["users"]["added"], serde_json::json!([]));
+        assert_eq!(plan["collections"]["users"]["removed"], serde_json::json!([]));
+        assert!(store.rebuild_plan(Some("missing")).is_err());
+
+        // A file the rebuild would skip is reported as skipped, not an error
+        std::fs::write(tmp.path().join("users/broken.md"), "---\nname: [unclosed\n---\n").unwrap();
+        let plan = store.rebuild_plan(Some("users")).unwrap();
+        assert_eq!(plan["collections"]["users"]["skipped"], serde_json::json!(["users/broken.md"]));
+        assert_eq!(plan["collections"]["users"]["added"], serde_json::json!([]));
+        store.rebuild(Some("users")).unwrap();
+    }
+
+    #[test]
+    fn test_collection_rename_moves_documents() {
+        let tmp = TempDir::new().unwrap();
+        let old_schema = r#"
+collections:
+  users:
+    path: "users/{name}.md"
+    fields:
+      name: { type: string, required: true }
+      email: { type: string, required: true }
+"#;
+        std::fs::write(tmp.path().join("schema.yaml"), old_schema).unwrap();
+        {
+            let store = Store::open(tmp.path().to_str().unwrap()).unwrap();
+            let data: serde_yaml::Value =
+                serde_yaml::from_str("name: Alice\nemail: alice@test.com").unwrap();
+            store.collection("users").unwrap().insert(data, None).unwrap();
+        }
+
+        let new_schema = r#"
+collections:
+  people:
+    path: "people/{name}.md"
+    renamed_from: users
+    fields:
+      name: { type: string, required: true }
+      email: { type: string, required: true }
+"#;
+        std::fs::write(tmp.path().join("schema.yaml"), new_schema).unwrap();
+        let store = Store::open(tmp.path().to_str().unwrap()).unwrap();
+
+        let doc = store.collection("people").unwrap().get("alice").unwrap();
+        assert_eq!(doc.data["email"], serde_yaml::Value::String("alice@test.com".into()));
+        assert!(tmp.path().join("people/alice.md").exists());
+        assert!(!tmp.path().join("users/alice.md").exists());
+        assert!(store.db.list_documents("users").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_collection_rename_conflict_moves_nothing() {
+        let tmp = TempDir::new().unwrap();
+        let old_schema = r#"
+collections:
+  users:
+    path: "users/{name}.md"
+    fields:
+      name: { type: string, required: true }
+"#;
+        std::fs::write(tmp.path().join("schema.yaml"), old_schema).unwrap();
+        {
+            let store = Store::open(tmp.path().to_str().unwrap()).unwrap();
+            let users = store.collection("users").unwrap();
+            for name in ["Alice", "Zed"] {
+                let data: serde_yaml::Value = serde_yaml::from_str(&format!("name: {name}")).unwrap();
+                users.insert(data, None).unwrap();
+            }
+        }
+
+        // Zed's new path is already taken, so the rename fails before moving Alice
+        std::fs::create_dir_all(tmp.path().join("people")).unwrap();
+        std::fs::write(tmp.path().join("people/zed.md"), "---\nname: Zed\n---\n").unwrap();
+        let new_schema = old_schema.replace("  users:\n    path: \"users/", "  people:\n    renamed_from: users\n    path: \"people/");
+        std::fs::write(tmp.path().join("schema.yaml"), new_schema).unwrap();
+        let err = Store::open(tmp.path().to_str().unwrap()).err().unwrap();
+        assert!(matches!(err, GroundDbError::PathConflict { .. }), "{err}");
+
+        assert!(tmp.path().join("users/alice.md").exists());
+        assert!(tmp.path().join("users/zed.md").exists());
+        assert!(!tmp.path().join("people/alice.md").exists());
+    }
+
+    #[test]
+    fn test_view_rename_keeps_cache() {
+        let (tmp, store) = setup_store_with_views();
+        seed_view_data(&store);
+        let rows = store.view_dynamic("post_feed").unwrap();
+        let (built, _) = store.db.get_view_metadata("post_feed").unwrap().unwrap();
+        drop(store);
+
+        let schema = std::fs::read_to_string(tmp.path().join("schema.yaml")).unwrap();
+        let renamed = schema.replace("  post_feed:\n", "  feed:\n    renamed_from: post_feed\n");
+        assert_ne!(renamed, schema);
+        std::fs::write(tmp.path().join("schema.yaml"), renamed).unwrap();
+        let options = StoreOptions {
+            rebuild_views_on_boot: false,
+            ..Default::default()
+        };
+        let store = Store::open_with_options(tmp.path().to_str().unwrap(), options).unwrap();
+
+        // The cache moved with the view rather than being rebuilt
+        assert_eq!(store.view_dynamic("feed").unwrap(), rows);
+        let (moved_built, _) = store.db.get_view_metadata("feed").unwrap().unwrap();
+        assert_eq!(moved_built, built);
+        assert!(store.db.get_view_data("post_feed").unwrap().is_none());
+        assert!(!tmp.path().join("views/post_feed.yaml").exists());
+    }
+
+    #[test]
+    fn test_view_migrations_update_cache() {
+        let (tmp, store) = setup_store_with_views();
+        seed_view_data(&store);
+        store.view_dynamic("user_lookup").unwrap();
+        drop(store);
+
+        let schema = std::fs::read_to_string(tmp.path().join("schema.yaml")).unwrap();
+        let (collections, _) = schema.split_once("views:").unwrap();
+        let changed = format!(
+            r#"{collections}views:
+  all_posts:
+    query: |
+      SELECT id, title
+      FROM posts
+      WHERE status = 'published'
+  user_names:
+    query: |
+      SELECT name FROM users ORDER BY name
+"#
+        );
+        std::fs::write(tmp.path().join("schema.yaml"), changed).unwrap();
+        let options = StoreOptions {
+            rebuild_views_on_boot: false,
+            ..Default::default()
+        };
+        let store = Store::open_with_options(tmp.path().to_str().unwrap(), options).unwrap();
+
+        // Removed views' caches are dropped
+        for removed in ["post_feed", "user_lookup"] {
+            assert!(store.db.get_view_data(removed).unwrap().is_none());
+            assert!(store.db.get_view_metadata(removed).unwrap().is_none());
+        }
+        // A changed view is rebuilt rather than served from its stale cache
+        let rows = store.view_dynamic("all_posts").unwrap();
+        let rows = rows.as_array().unwrap();
+        assert!(!rows.is_empty());
+        assert!(rows.iter().all(|row| row.get("status").is_none()));
+        // An added view is built
+        let names = store.view_dynamic("user_names").unwrap();
+        assert_eq!(names, serde_json::json!([{ "name": "Alice" }, { "name": "Bob" }]));
+    }
+
+    #[test]
+    fn test_rename_field() {
+        let (_tmp, store) = setup_test_store();
+        let events = store.collection("events").unwrap();
+        for kind in ["signup", "login"] {
+            let data: serde_yaml::Value =
+                serde_yaml::from_str(&format!("type: {kind}\nsource: web")).unwrap();
+            events.insert(data, None).unwrap();
+        }
+        let data: serde_yaml::Value = serde_yaml::from_str("type: logout").unwrap();
+        events.insert(data, None).unwrap();
+
+        assert_eq!(store.rename_field_dry_run("events", "source", "channel").unwrap(), 2);
+        let untouched = events.list().unwrap();
+        assert_eq!(untouched.iter().filter(|d| d.data.get("source").is_some()).count(), 2);
+
+        assert_eq!(store.rename_field("events", "source", "channel").unwrap(), 2);
+        for doc in events.list().unwrap() {
+            assert!(doc.data.get("source").is_none());
+            if doc.data["type"] != serde_yaml::Value::String("logout".into()) {
+                assert_eq!(doc.data["channel"], serde_yaml::Value::String("web".into()));
+            }
+        }
+
+        // The index is re-keyed too
+        let filters = HashMap::from([("channel".to_string(), "web".to_string())]);
+        let rows = store.list_dynamic("events", &filters).unwrap();
+        assert_eq!(rows.as_array().unwrap().len(), 2);
+
+        // Path fields can't be renamed
+        assert!(store.rename_field("users", "name", "full_name").is_err());
+    }
+
+    #[test]
+    fn test_dynamic_api() {
+        let (_tmp, store) = setup_test_store();
+
+        // Insert via dynamic API
+        let data = serde_json::json!({
+            "name": "Alice",
+            "email": "alice@test.com"
+        });
+        let id = store.insert_dynamic("users", data, None).unwrap();
+        assert_eq!(id, "alice");
+
+        // Get via dynamic API
+        let doc = store.get_dynamic("users", "alice").unwrap();
+        assert_eq!(doc["id"], "alice");
+        assert_eq!(doc["name"], "Alice");
+        assert_eq!(doc["email"], "alice@test.com");
+        assert!(doc["created_at"].is_string());
+
+        // List via dynamic API
+        let list = store
+            .list_dynamic("users", &HashMap::new())
+            .unwrap();
+        assert_eq!(list.as_array().unwrap().len(), 1);
+
+        // Delete via dynamic API
+        store.delete_dynamic("users", "alice").unwrap();
+        let list = store
+            .list_dynamic("users", &HashMap::new())
+            .unwrap();
+        assert_eq!(list.as_array().unwrap().len(), 0);
+    }
+
+    #[test]
+    fn test_status() {
+        let (_tmp, store) = setup_test_store();
+        let status = store.status().unwrap();
+        assert!(status["schema_hash"].is_string());
+        assert!(status["collections"].is_object());
+    }
+
+    #[test]
+    fn test_validate_all() {
+        let (_tmp, store) = setup_test_store();
+
+        let data: serde_yaml::Value =
+            serde_yaml::from_str("name: Alice\nemail: alice@test.com").unwrap();
+        store.collection("users").unwrap().insert(data, None).unwrap();
+
+        let report = store.validate_all().unwrap();
+        assert!(report["users"]["total"].as_u64().unwrap() >= 1);
+    }
+
+    #[test]
+    fn test_invalid_documents_lists_only_errors() {
+        let tmp = TempDir::new().unwrap();
+        let schema = r#"
+collections:
+  users:
+    path: "users/{name}.md"
+    fields:
+      name: { type: string, required: true }
+      email: { type: string, required: true }
+      role: { type: string, enum: [admin, member] }
+    strict: true
+  notes:
+    path: "notes/{title}.md"
+    fields:
+      title: { type: string, required: true }
+      priority: { type: number }
+"#;
+        std::fs::write(tmp.path().join("schema.yaml"), schema).unwrap();
+        let files = [
+            ("users/alice.md", "name: alice\nemail: alice@test.com\nrole: admin"),
+            ("users/bob.md", "name: bob"),
+            ("users/carol.md", "name: carol\nemail: carol@test.com\nrole: owner"),
+            // Not strict, so a wrong type is only a warning
+            ("notes/todo.md", "title: todo\npriority: high"),
+        ];
+        for (path, front_matter) in files {
+            let path = tmp.path().join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, format!("---\n{front_matter}\n---\n")).unwrap();
+        }
+        let store = Store::open(tmp.path().to_str().unwrap()).unwrap();
+
+        let invalid = store.invalid_documents().unwrap();
+        let ids: Vec<(&str, &str)> = invalid
+            .iter()
+            .map(|(collection, id, _)| (collection.as_str(), id.as_str()))
+            .collect();
+        assert_eq!(ids, [("users", "bob"), ("users", "carol")]);
+        assert!(invalid[0].2.iter().any(|e| e.contains("email")), "{invalid:?}");
+        assert!(invalid[1].2.iter().any(|e| e.contains("owner")), "{invalid:?}");
+
+        let report = store.validate_all().unwrap();
+        assert_eq!(report["notes"]["issues"].as_array().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_validate_all_with_refs_reports_dangling() {
+        let (tmp, store) = setup_test_store();
+
+        let user: serde_yaml::Value =
+            serde_yaml::from_str("name: Bob\nemail: bob@test.com").unwrap();
+        store.collection("users").unwrap().insert(user, None).unwrap();
+        let post: serde_yaml::Value = serde_yaml::from_str(
+            "title: Orphan\nauthor_id: bob\ndate: 2026-01-15\nstatus: draft",
+        )
+        .unwrap();
+        let post_id = store.collection("posts").unwrap().insert(post, None).unwrap();
+
+        // Delete the user out from under the post, bypassing on_delete
+        std::fs::remove_file(tmp.path().join("users/bob.md")).unwrap();
+        store.db.delete_document("users", "bob").unwrap();
+
+        let report = store.validate_all().unwrap();
+        assert!(report["posts"]["issues"].as_array().unwrap().is_empty());
+
+        let report = store.validate_all_with_refs().unwrap();
+        let issues = report["posts"]["issues"].as_array().unwrap();
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0]["id"], post_id.as_str());
+        let dangling = issues[0]["dangling_refs"].as_array().unwrap();
+        assert_eq!(dangling.len(), 1);
+        let msg = dangling[0].as_str().unwrap();
+        assert!(msg.contains("author_id") && msg.contains("bob"), "Unexpected: {msg}");
+    }
+
+    #[test]
+    fn test_referencing_finds_backlinks() {
+        let (_tmp, store) = setup_test_store();
+        let users = store.collection("users").unwrap();
+        let posts = store.collection("posts").unwrap();
+        for name in ["alice", "alicia"] {
+            let data: serde_yaml::Value =
+                serde_yaml::from_str(&format!("name: {name}\nemail: {name}@test.com")).unwrap();
+            users.insert(data, None).unwrap();
+        }
+        for (title, author) in [("First", "alice"), ("Second", "alicia"), ("Third", "alice")] {
+            let data: serde_yaml::Value = serde_yaml::from_str(&format!(
+                "title: {title}\nauthor_id: {author}\ndate: '2026-02-13'"
+            ))
+            .unwrap();
+            posts.insert(data, None).unwrap();
+        }
+
+        let mut titles: Vec<String> = users
+            .referencing("alice", "posts", "author_id")
+            .unwrap()
+            .iter()
+            .map(|d| d.data["title"].as_str().unwrap().to_string())
+            .collect();
+        titles.sort();
+        assert_eq!(titles, vec!["First", "Third"]);
+
+        assert!(users.referencing("nobody", "posts", "author_id").unwrap().is_empty());
+        assert!(users.referencing("alice", "posts", "title").is_err());
+    }
+
+    #[test]
+    fn test_update_partial() {
+        let (_tmp, store) = setup_test_store();
+        let users = store.collection("users").unwrap();
+
+        let data: serde_yaml::Value =
+            serde_yaml::from_str("name: Alice\nemail: alice@test.com\nrole: member").unwrap();
+        users.insert(data, None).unwrap();
+
+        // Partially update just the email
+        let partial: serde_yaml::Value =
+            serde_yaml::from_str("email: alice@newdomain.com").unwrap();
+        users.update_partial("alice", partial, None).unwrap();
+
+        let doc = users.get("alice").unwrap();
         assert_eq!(
             doc.data["email"],
             serde_yaml::Value::String("alice@newdomain.com".into())
@@ -2282,6 +5730,45 @@ collections:
         );
     }
 
+    #[test]
+    fn test_concurrent_partial_updates_lose_nothing() {
+        let tmp = TempDir::new().unwrap();
+        let fields: String = (0..8)
+            .map(|i| format!("      f{i}: {{ type: number }}\n"))
+            .collect();
+        let schema = format!(
+            "collections:\n  items:\n    path: \"items/{{name}}.md\"\n    fields:\n      name: {{ type: string, required: true }}\n{fields}"
+        );
+        std::fs::write(tmp.path().join("schema.yaml"), schema).unwrap();
+        let store = Store::open(tmp.path().to_str().unwrap()).unwrap();
+        let items = store.collection("items").unwrap();
+        items
+            .insert(serde_yaml::from_str("name: shared").unwrap(), None)
+            .unwrap();
+
+        std::thread::scope(|scope| {
+            for i in 0..8 {
+                let store = &store;
+                scope.spawn(move || {
+                    let items = store.collection("items").unwrap();
+                    let partial: serde_yaml::Value =
+                        serde_yaml::from_str(&format!("f{i}: {i}")).unwrap();
+                    items.update_partial("shared", partial, None).unwrap();
+                });
+            }
+        });
+
+        let doc = items.get("shared").unwrap();
+        for i in 0..8 {
+            assert_eq!(
+                doc.data[format!("f{i}").as_str()],
+                serde_yaml::Value::Number(i.into()),
+                "update to f{i} was lost"
+            );
+        }
+        assert!(store.doc_locks.lock().unwrap().is_empty());
+    }
+
     #[test]
     fn test_directory_hash_updated_on_write() {
         let (_tmp, store) = setup_test_store();
@@ -2353,6 +5840,46 @@ collections:
         assert_eq!(all[0].id, "alice");
     }
 
+    #[test]
+    fn test_batch_check_refs_sees_staged_documents() {
+        let (_tmp, store) = setup_test_store();
+
+        // The post is queued before the author it references
+        let mut batch = store.batch();
+        batch.check_refs();
+        batch.collection("posts").insert(
+            serde_json::json!({
+                "title": "Hello", "author_id": "dana", "date": "2026-02-13", "status": "draft"
+            }),
+            None,
+        );
+        batch.collection("users").insert(
+            serde_json::json!({ "name": "Dana", "email": "d@test.com" }),
+            None,
+        );
+        let results = batch.execute().unwrap();
+        assert_eq!(results, vec!["2026-02-13-hello".to_string(), "dana".to_string()]);
+
+        // A ref nothing in the batch or the store satisfies fails the whole batch
+        let mut batch = store.batch();
+        batch.check_refs();
+        batch.collection("users").insert(
+            serde_json::json!({ "name": "Erin", "email": "e@test.com" }),
+            None,
+        );
+        batch.collection("posts").insert(
+            serde_json::json!({
+                "title": "Orphan", "author_id": "nobody", "date": "2026-02-14", "status": "draft"
+            }),
+            None,
+        );
+        let err = batch.execute().unwrap_err();
+        assert!(matches!(err, GroundDbError::ReferentialIntegrity(_)), "{err}");
+        assert!(err.to_string().contains("'nobody'"), "{err}");
+        assert!(store.collection("users").unwrap().get("erin").is_err());
+        assert_eq!(store.collection("posts").unwrap().list().unwrap().len(), 1);
+    }
+
     // ── Phase 5: Integration tests ──
 
     fn setup_store_with_views() -> (TempDir, Store) {
@@ -2559,6 +6086,97 @@ views:
         assert_eq!(rows.len(), 2);
     }
 
+    #[test]
+    fn test_view_sorted_by_content_length() {
+        let tmp = TempDir::new().unwrap();
+        let schema = r#"
+collections:
+  posts:
+    path: "posts/{title}.md"
+    content: true
+    fields:
+      title: { type: string, required: true }
+
+views:
+  longest:
+    query: "SELECT title, content_length FROM posts ORDER BY content_length DESC, title"
+    materialize: false
+  empty:
+    query: "SELECT title FROM posts WHERE content_length = 0 ORDER BY title"
+    materialize: false
+"#;
+        std::fs::write(tmp.path().join("schema.yaml"), schema).unwrap();
+        let store = Store::open(tmp.path().to_str().unwrap()).unwrap();
+        let posts = store.collection("posts").unwrap();
+        posts.insert(serde_yaml::from_str("title: short").unwrap(), Some("Hi")).unwrap();
+        posts.insert(serde_yaml::from_str("title: long").unwrap(), Some("A much longer body")).unwrap();
+        posts.insert(serde_yaml::from_str("title: blank").unwrap(), None).unwrap();
+
+        let rows = store.view_dynamic("longest").unwrap();
+        let titles: Vec<&str> = rows
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|r| r["title"].as_str().unwrap())
+            .collect();
+        assert_eq!(titles, ["long", "short", "blank"]);
+        assert_eq!(rows[2]["content_length"], serde_json::json!(0));
+        assert_eq!(store.view_dynamic("empty").unwrap(), serde_json::json!([{ "title": "blank" }]));
+    }
+
+    #[test]
+    fn test_view_execution_union_all() {
+        let tmp = TempDir::new().unwrap();
+        let schema = r#"
+collections:
+  posts:
+    path: "posts/{title}.md"
+    fields:
+      title: { type: string, required: true }
+  comments:
+    path: "comments/{id}.md"
+    id: { auto: ulid }
+    fields:
+      body: { type: string, required: true }
+
+views:
+  activity:
+    query: |
+      SELECT id, 'post' AS kind FROM posts
+      UNION ALL
+      SELECT id, 'comment' FROM comments
+      ORDER BY kind DESC, id
+    materialize: false
+"#;
+        std::fs::write(tmp.path().join("schema.yaml"), schema).unwrap();
+        let store = Store::open(tmp.path().to_str().unwrap()).unwrap();
+
+        let view = store.view_engine.get_view("activity").unwrap();
+        assert_eq!(
+            view.referenced_collections(),
+            HashSet::from(["posts".to_string(), "comments".to_string()])
+        );
+
+        let posts = store.collection("posts").unwrap();
+        posts.insert(serde_yaml::from_str("title: alpha").unwrap(), None).unwrap();
+        posts.insert(serde_yaml::from_str("title: beta").unwrap(), None).unwrap();
+        let comment_id = store
+            .collection("comments")
+            .unwrap()
+            .insert(serde_yaml::from_str("body: Nice").unwrap(), None)
+            .unwrap();
+
+        let result = store.view_dynamic("activity").unwrap();
+        assert_eq!(
+            result,
+            serde_json::json!([
+                { "id": "alpha", "kind": "post" },
+                { "id": "beta", "kind": "post" },
+                { "id": comment_id, "kind": "comment" },
+            ])
+        );
+    }
+
     #[test]
     fn test_view_materialization() {
         let (tmp, store) = setup_store_with_views();
@@ -2576,6 +6194,112 @@ views:
         assert!(!content.contains("Draft Post"));
     }
 
+    #[test]
+    fn test_prune_materialized_removes_stale_files() {
+        let (tmp, store) = setup_store_with_views();
+        seed_view_data(&store);
+
+        let views_dir = tmp.path().join("views");
+        let live = views_dir.join("post_feed.yaml");
+        assert!(live.exists());
+        let stale = views_dir.join("old_view.yaml");
+        std::fs::write(&stale, "- id: gone\n").unwrap();
+
+        let removed = store.prune_materialized().unwrap();
+        assert_eq!(removed, vec![stale.clone()]);
+        assert!(!stale.exists());
+        assert!(live.exists());
+    }
+
+    #[test]
+    fn test_materialize_all_regenerates_files() {
+        let (tmp, store) = setup_store_with_views();
+        seed_view_data(&store);
+
+        let materialized = tmp.path().join("views/post_feed.yaml");
+        let expected = std::fs::read_to_string(&materialized).unwrap();
+        std::fs::remove_dir_all(tmp.path().join("views")).unwrap();
+
+        store.materialize_all().unwrap();
+        assert_eq!(std::fs::read_to_string(&materialized).unwrap(), expected);
+        assert!(expected.contains("First Post"));
+        // Only materialized views get a file
+        assert_eq!(std::fs::read_dir(tmp.path().join("views")).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_view_row_transform_adds_column() {
+        let (tmp, store) = setup_store_with_views();
+        seed_view_data(&store);
+        store
+            .on_view_rows("post_feed", |rows| {
+                for row in rows.iter_mut() {
+                    let slug = path_template::slugify(row["title"].as_str().unwrap_or_default());
+                    row["slug"] = serde_json::Value::String(slug);
+                }
+            })
+            .unwrap();
+
+        // Registering rebuilt the view through the transform
+        let rows = store.view_dynamic("post_feed").unwrap();
+        assert_eq!(rows[0]["slug"], "second-post");
+        let cached = store.db.get_view_data("post_feed").unwrap().unwrap();
+        assert!(cached.contains("\"slug\":\"first-post\""), "{cached}");
+        let materialized = tmp.path().join("views/post_feed.yaml");
+        let materialized = std::fs::read_to_string(materialized).unwrap();
+        assert!(materialized.contains("slug: first-post"), "{materialized}");
+
+        // Later rebuilds keep applying it
+        let posts = store.collection("posts").unwrap();
+        posts
+            .insert(
+                serde_yaml::from_str("title: Third Post\nauthor_id: bob\ndate: '2026-02-01'\nstatus: published").unwrap(),
+                None,
+            )
+            .unwrap();
+        let rows = store.view_dynamic("post_feed").unwrap();
+        assert_eq!(rows[0]["slug"], "third-post");
+
+        assert!(store.on_view_rows("missing", |_| {}).is_err());
+    }
+
+    #[test]
+    fn test_unordered_materialized_view_is_stable() {
+        let schema = r#"
+collections:
+  tasks:
+    path: "tasks/{title}.md"
+    fields:
+      title: { type: string, required: true }
+      rank: { type: number }
+
+views:
+  task_ranks:
+    query: |
+      SELECT t.rank, t.title
+      FROM tasks t
+    materialize: true
+"#;
+        // The same documents, written in different orders
+        let materialize = |titles: &[&str]| {
+            let tmp = TempDir::new().unwrap();
+            std::fs::write(tmp.path().join("schema.yaml"), schema).unwrap();
+            let store = Store::open(tmp.path().to_str().unwrap()).unwrap();
+            let tasks = store.collection("tasks").unwrap();
+            for title in titles {
+                let rank = title.len() % 3;
+                let data: serde_yaml::Value =
+                    serde_yaml::from_str(&format!("title: {title}\nrank: {rank}")).unwrap();
+                tasks.insert(data, None).unwrap();
+            }
+            std::fs::read(tmp.path().join("views/task_ranks.yaml")).unwrap()
+        };
+
+        let first = materialize(&["write", "review", "ship", "plan", "test"]);
+        let second = materialize(&["test", "ship", "plan", "review", "write"]);
+        assert_eq!(String::from_utf8(first).unwrap(), String::from_utf8(second).unwrap());
+    }
+
     #[test]
     fn test_view_buffer_multiplier() {
         let tmp = TempDir::new().unwrap();
@@ -2685,90 +6409,708 @@ views:
     }
 
     #[test]
-    fn test_subscription_on_delete() {
-        let (_tmp, store) = setup_test_store();
-
-        let received = Arc::new(Mutex::new(Vec::<ChangeEvent>::new()));
-        let received_clone = received.clone();
+    fn test_touch_bumps_modified_at() {
+        let (tmp, store) = setup_test_store();
+        let users = store.collection("users").unwrap();
+        let data: serde_yaml::Value =
+            serde_yaml::from_str("name: Alice\nemail: alice@test.com").unwrap();
+        users.insert(data, None).unwrap();
+
+        let received = Arc::new(Mutex::new(Vec::<ChangeEvent>::new()));
+        let received_clone = received.clone();
+        store.on_collection_change(
+            "users",
+            Box::new(move |event| {
+                received_clone.lock().unwrap().push(event);
+            }),
+        );
+
+        let path = tmp.path().join("users/alice.md");
+        let bytes_before = std::fs::read(&path).unwrap();
+        let before = store.get_dynamic("users", "alice").unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(20));
+
+        users.touch("alice").unwrap();
+
+        let after = store.get_dynamic("users", "alice").unwrap();
+        let parse = |v: &serde_json::Value| {
+            chrono::DateTime::parse_from_rfc3339(v["modified_at"].as_str().unwrap()).unwrap()
+        };
+        assert!(parse(&after) > parse(&before));
+        assert_eq!(std::fs::read(&path).unwrap(), bytes_before);
+        assert_eq!(after["email"], before["email"]);
+
+        let indexed = store
+            .query("users")
+            .filter("id", "alice")
+            .run()
+            .unwrap();
+        assert_eq!(indexed[0]["modified_at"], after["modified_at"]);
+
+        let events = received.lock().unwrap();
+        assert_eq!(events.len(), 1);
+        match &events[0] {
+            ChangeEvent::Updated { id, .. } => assert_eq!(id, "alice"),
+            other => panic!("Expected Updated event, got {:?}", other),
+        }
+        drop(events);
+        assert!(users.touch("nobody").is_err());
+    }
+
+    #[test]
+    fn test_update_content_keeps_front_matter() {
+        let (_tmp, store) = setup_test_store();
+        let users = store.collection("users").unwrap();
+        let data: serde_yaml::Value =
+            serde_yaml::from_str("name: Alice\nemail: alice@test.com").unwrap();
+        users.insert(data, None).unwrap();
+        let posts = store.collection("posts").unwrap();
+        let data: serde_yaml::Value = serde_yaml::from_str(
+            "title: Hello\nauthor_id: alice\ndate: 2026-02-01\ntags: [intro]\nstatus: published",
+        )
+        .unwrap();
+        let id = posts.insert(data, Some("Old body.\n")).unwrap();
+        let before = posts.get(&id).unwrap();
+
+        let received = Arc::new(Mutex::new(Vec::<ChangeEvent>::new()));
+        let received_clone = received.clone();
+        store.on_collection_change(
+            "posts",
+            Box::new(move |event| {
+                received_clone.lock().unwrap().push(event);
+            }),
+        );
+        std::thread::sleep(std::time::Duration::from_millis(20));
+
+        posts.update_content(&id, "New body.\n").unwrap();
+
+        let after = posts.get(&id).unwrap();
+        assert_eq!(after.data, before.data);
+        assert_eq!(after.content.unwrap().trim(), "New body.");
+        assert!(after.modified_at > before.modified_at);
+        let events = received.lock().unwrap();
+        assert!(matches!(
+            &events[..],
+            [ChangeEvent::Updated { id: updated, .. }] if *updated == id
+        ));
+        drop(events);
+
+        assert!(posts.update_content("missing", "x").is_err());
+        assert!(matches!(
+            users.update_content("alice", "x"),
+            Err(GroundDbError::Validation(_))
+        ));
+    }
+
+    #[test]
+    fn test_subscription_on_delete() {
+        let (_tmp, store) = setup_test_store();
+
+        let received = Arc::new(Mutex::new(Vec::<ChangeEvent>::new()));
+        let received_clone = received.clone();
+
+        store.on_collection_change(
+            "users",
+            Box::new(move |event| {
+                received_clone.lock().unwrap().push(event);
+            }),
+        );
+
+        // Insert then delete
+        let users = store.collection("users").unwrap();
+        let data: serde_yaml::Value =
+            serde_yaml::from_str("name: Alice\nemail: alice@test.com").unwrap();
+        users.insert(data, None).unwrap();
+        users.delete("alice").unwrap();
+
+        let events = received.lock().unwrap();
+        assert_eq!(events.len(), 2);
+        match &events[1] {
+            ChangeEvent::Deleted { id } => assert_eq!(id, "alice"),
+            other => panic!("Expected Deleted event, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_subscription_unsubscribe() {
+        let (_tmp, store) = setup_test_store();
+
+        let received = Arc::new(Mutex::new(Vec::<ChangeEvent>::new()));
+        let received_clone = received.clone();
+
+        let sub_id = store.on_collection_change(
+            "users",
+            Box::new(move |event| {
+                received_clone.lock().unwrap().push(event);
+            }),
+        );
+
+        // Insert then unsubscribe
+        let users = store.collection("users").unwrap();
+        let data: serde_yaml::Value =
+            serde_yaml::from_str("name: Alice\nemail: alice@test.com").unwrap();
+        users.insert(data, None).unwrap();
+
+        store.unsubscribe(sub_id);
+
+        // This should NOT trigger the callback
+        let data2: serde_yaml::Value =
+            serde_yaml::from_str("name: Bob\nemail: bob@test.com").unwrap();
+        users.insert(data2, None).unwrap();
+
+        let events = received.lock().unwrap();
+        assert_eq!(events.len(), 1, "Should only have 1 event after unsubscribe");
+    }
+
+    #[test]
+    fn test_view_subscription() {
+        let (_tmp, store) = setup_store_with_views();
+
+        let received = Arc::new(Mutex::new(Vec::<Vec<serde_json::Value>>::new()));
+        let received_clone = received.clone();
+
+        store.on_view_change(
+            "user_lookup",
+            Box::new(move |data| {
+                received_clone.lock().unwrap().push(data.to_vec());
+            }),
+        );
+
+        // Insert a user — should trigger view rebuild and notify subscribers
+        let users = store.collection("users").unwrap();
+        let data: serde_yaml::Value =
+            serde_yaml::from_str("name: Alice\nemail: alice@test.com").unwrap();
+        users.insert(data, None).unwrap();
+
+        let events = received.lock().unwrap();
+        assert!(!events.is_empty(), "View subscriber should have been notified");
+        // The most recent view data should contain Alice
+        let latest = events.last().unwrap();
+        assert!(latest.iter().any(|row| row["name"] == "Alice"));
+    }
+
+    #[test]
+    fn test_get_by_path() {
+        let (tmp, store) = setup_store_with_views();
+        let users = store.collection("users").unwrap();
+        let data: serde_yaml::Value =
+            serde_yaml::from_str("name: Alice\nemail: alice@test.com").unwrap();
+        users.insert(data, None).unwrap();
+
+        let doc = store.get_by_path("users/alice.md").unwrap();
+        assert_eq!(doc["id"], serde_json::json!("alice"));
+        assert_eq!(doc["email"], serde_json::json!("alice@test.com"));
+
+        let abs = tmp.path().join("users/alice.md");
+        let doc = store.get_by_path(abs.to_str().unwrap()).unwrap();
+        assert_eq!(doc["name"], serde_json::json!("Alice"));
+
+        assert!(matches!(
+            store.get_by_path("users/nobody.md"),
+            Err(GroundDbError::NotFound { .. })
+        ));
+        assert!(store.get_by_path("elsewhere/alice.md").is_err());
+        assert!(store.get_by_path("/tmp/outside.md").is_err());
+    }
+
+    #[test]
+    fn test_get_raw_matches_file_on_disk() {
+        let (tmp, store) = setup_store_with_views();
+        seed_view_data(&store);
+        let posts = store.collection("posts").unwrap();
+        let id = posts.list().unwrap()[0].id.clone();
+        let record = store.db.get_document("posts", &id).unwrap().unwrap();
+        let path = tmp.path().join(&record.path);
+
+        // Hand-edited formatting that reserializing would lose
+        let file = "---\n# edited by hand\ntitle:   'First Post'\nauthor_id: alice\ndate: \"2026-01-10\"\nstatus: published\n---\n\nBody  with trailing spaces  \n";
+        std::fs::write(&path, file).unwrap();
+
+        let (front_matter, body, full) = posts.get_raw(&id).unwrap();
+        assert_eq!(full.as_bytes(), std::fs::read(&path).unwrap());
+        assert_eq!(
+            front_matter,
+            "# edited by hand\ntitle:   'First Post'\nauthor_id: alice\ndate: \"2026-01-10\"\nstatus: published\n"
+        );
+        assert_eq!(body.as_deref(), Some("\nBody  with trailing spaces  \n"));
+
+        // A byte order mark stays in the file text, not in the split parts
+        let with_bom = format!("\u{feff}{file}");
+        std::fs::write(&path, &with_bom).unwrap();
+        let (bom_front_matter, bom_body, bom_full) = posts.get_raw(&id).unwrap();
+        assert_eq!(bom_full.as_bytes(), std::fs::read(&path).unwrap());
+        assert_eq!(bom_front_matter, front_matter);
+        assert_eq!(bom_body, body);
+
+        let doc = posts.get(&id).unwrap();
+        assert_eq!(doc.data["title"], serde_yaml::Value::from("First Post"));
+        assert!(matches!(
+            posts.get_raw("missing"),
+            Err(GroundDbError::NotFound { .. })
+        ));
+    }
+
+    #[test]
+    fn test_long_title_gets_shortened_filename() {
+        let (tmp, store) = setup_test_store();
+        store
+            .collection("users")
+            .unwrap()
+            .insert(
+                serde_yaml::from_str("name: alice\nemail: alice@test.com").unwrap(),
+                None,
+            )
+            .unwrap();
+        let posts = store.collection("posts").unwrap();
+        let title = "An extremely long title that keeps going ".repeat(12);
+        let data: serde_yaml::Value = serde_yaml::from_str(&format!(
+            "title: {title}\nauthor_id: alice\ndate: 2026-03-01"
+        ))
+        .unwrap();
+
+        let first = posts.insert(data.clone(), Some("first")).unwrap();
+        let second = posts.insert(data, Some("second")).unwrap();
+        assert_ne!(first, second);
+        for id in [&first, &second] {
+            let record = store.db.get_document("posts", id).unwrap().unwrap();
+            let name = record.path.strip_prefix("posts/draft/").unwrap();
+            assert!(name.len() <= 255, "{name}");
+            assert!(name.starts_with("2026-03-01-an-extremely-long-title"));
+            assert_eq!(name, format!("{id}.md"));
+            assert!(tmp.path().join(&record.path).exists());
+        }
+        assert_eq!(
+            posts.get(&first).unwrap().data["title"],
+            serde_yaml::Value::from(title.trim())
+        );
+        assert_eq!(posts.get(&second).unwrap().content.unwrap().trim(), "second");
+
+        // The limit is configurable
+        let options = StoreOptions {
+            max_filename_bytes: 20,
+            ..Default::default()
+        };
+        drop(store);
+        let store = Store::open_with_options(tmp.path().to_str().unwrap(), options).unwrap();
+        let data = serde_yaml::from_str(&format!(
+            "title: {title}\nauthor_id: alice\ndate: 2026-03-02"
+        ))
+        .unwrap();
+        let id = store.collection("posts").unwrap().insert(data, None).unwrap();
+        assert!(id.len() <= 17, "{id}");
+    }
+
+    #[test]
+    fn test_custom_slug_strategy() {
+        let tmp = TempDir::new().unwrap();
+        let schema = r#"
+collections:
+  notes:
+    path: "notes/{folder}/{title}.md"
+    fields:
+      title: { type: string, required: true }
+      folder: { type: string, required: true }
+"#;
+        std::fs::write(tmp.path().join("schema.yaml"), schema).unwrap();
+        let keep_underscores = |input: &str| {
+            input
+                .split('_')
+                .map(path_template::slugify)
+                .collect::<Vec<_>>()
+                .join("_")
+        };
+        let options = StoreOptions {
+            slug_strategy: Arc::new(keep_underscores),
+            ..Default::default()
+        };
+        let store = Store::open_with_options(tmp.path().to_str().unwrap(), options).unwrap();
+        let notes = store.collection("notes").unwrap();
+        let data = serde_yaml::from_str("title: Meeting_Notes Draft\nfolder: Team_A").unwrap();
+        let id = notes.insert(data, None).unwrap();
+
+        assert_eq!(id, "meeting_notes-draft");
+        assert!(tmp.path().join("notes/team_a/meeting_notes-draft.md").exists());
+        assert_eq!(
+            notes.get(&id).unwrap().data["folder"],
+            serde_yaml::Value::from("Team_A")
+        );
+
+        // Enum values in the path only need to stay apart under the store's strategy
+        let schema = r#"
+collections:
+  tasks:
+    path: "tasks/{stage}/{title}.md"
+    fields:
+      title: { type: string, required: true }
+      stage: { type: string, enum: [in_review, in-review] }
+"#;
+        std::fs::write(tmp.path().join("schema.yaml"), schema).unwrap();
+        drop(store);
+        assert!(Store::open(tmp.path().to_str().unwrap()).is_err());
+        let options = StoreOptions {
+            slug_strategy: Arc::new(keep_underscores),
+            ..Default::default()
+        };
+        let store = Store::open_with_options(tmp.path().to_str().unwrap(), options).unwrap();
+        let tasks = store.collection("tasks").unwrap();
+        let data = serde_yaml::from_str("title: Audit\nstage: in_review").unwrap();
+        tasks.insert(data, None).unwrap();
+        assert!(tmp.path().join("tasks/in_review/audit.md").exists());
+    }
+
+    #[test]
+    fn test_find_documents_returns_typed_matches() {
+        #[derive(Debug, serde::Deserialize)]
+        struct User {
+            name: String,
+            email: String,
+            role: String,
+        }
+
+        let (_tmp, store) = setup_store_with_views();
+        let users = store.collection("users").unwrap();
+        for (name, role) in [("Alice", "admin"), ("Bob", "member"), ("Carol", "admin")] {
+            let email = format!("{}@test.com", name.to_lowercase());
+            let yaml = format!("name: {name}\nemail: {email}\nrole: {role}");
+            users.insert(serde_yaml::from_str(&yaml).unwrap(), None).unwrap();
+        }
+
+        let admins = store.find_documents::<User>("users", "role", "admin").unwrap();
+        let names: Vec<&str> = admins.iter().map(|d| d.data.name.as_str()).collect();
+        assert_eq!(names, ["Alice", "Carol"]);
+        assert_eq!(admins[0].id, "alice");
+        assert_eq!(admins[1].data.email, "carol@test.com");
+        assert!(admins.iter().all(|d| d.data.role == "admin"));
+
+        let guests = store.find_documents::<User>("users", "role", "guest").unwrap();
+        assert!(guests.is_empty());
+        assert!(store.find_documents::<User>("nope", "role", "admin").is_err());
+    }
+
+    #[test]
+    fn test_read_view_stream_shares_cached_rows() {
+        #[derive(Debug, PartialEq, serde::Deserialize)]
+        struct UserRow {
+            id: String,
+            name: String,
+            email: String,
+        }
+
+        let (_tmp, store) = setup_store_with_views();
+        let records = (0..2000).map(|i| {
+            serde_json::json!({ "name": format!("user{i:04}"), "email": format!("u{i}@test.com") })
+        });
+        store.bulk_insert("users", records).unwrap();
+
+        let stream = store.read_view_stream::<UserRow>("user_lookup").unwrap();
+        // The stream holds the cached rows rather than a copy of them
+        let cached = store.view_engine.view_rows("user_lookup").unwrap();
+        assert_eq!(Arc::strong_count(&cached), 3);
+
+        let expected: Vec<UserRow> = store.read_view("user_lookup").unwrap();
+        let mut count = 0;
+        for (row, expected) in stream.zip(&expected) {
+            assert_eq!(&row.unwrap(), expected);
+            count += 1;
+        }
+        assert_eq!(count, 2000);
+        assert_eq!(expected[1999].name, "user1999");
+
+        assert!(store.read_view_stream::<UserRow>("missing").is_err());
+    }
+
+    #[test]
+    fn test_typed_view_subscription() {
+        #[derive(serde::Deserialize)]
+        struct UserRow {
+            name: String,
+        }
+
+        let (_tmp, store) = setup_store_with_views();
+        let names = Arc::new(Mutex::new(Vec::<String>::new()));
+        let names_clone = names.clone();
+        store.on_typed_view_change("user_lookup", move |rows: &[UserRow]| {
+            *names_clone.lock().unwrap() = rows.iter().map(|r| r.name.clone()).collect();
+        });
+
+        let users = store.collection("users").unwrap();
+        let data: serde_yaml::Value =
+            serde_yaml::from_str("name: Alice\nemail: alice@test.com").unwrap();
+        users.insert(data, None).unwrap();
+
+        assert_eq!(*names.lock().unwrap(), vec!["Alice".to_string()]);
+    }
+
+    #[test]
+    fn test_update_skips_views_not_reading_changed_fields() {
+        let (_tmp, store) = setup_store_with_views();
+        seed_view_data(&store);
+
+        let rebuilds = Arc::new(Mutex::new(0));
+        let counter = rebuilds.clone();
+        store.on_view_change("post_feed", Box::new(move |_| {
+            *counter.lock().unwrap() += 1;
+        }));
+
+        // post_feed never mentions tags: no rebuild
+        let posts = store.collection("posts").unwrap();
+        let partial: serde_yaml::Value = serde_yaml::from_str("tags: [rust]").unwrap();
+        let id = posts.list().unwrap()[0].id.clone();
+        posts.update_partial(&id, partial, None).unwrap();
+        assert_eq!(*rebuilds.lock().unwrap(), 0);
+
+        // title is selected by post_feed: rebuild
+        let partial: serde_yaml::Value = serde_yaml::from_str("title: Renamed").unwrap();
+        let id = posts.list().unwrap()[0].id.clone();
+        posts.update_partial(&id, partial, None).unwrap();
+        assert_eq!(*rebuilds.lock().unwrap(), 1);
+    }
+
+    #[test]
+    fn test_view_stats_count_rebuilds() {
+        let (_tmp, store) = setup_store_with_views();
+        let before = store.view_stats();
+        let user_builds = before["user_lookup"]["build_count"].as_u64().unwrap();
+
+        let users = store.collection("users").unwrap();
+        for name in ["Alice", "Bob", "Carol"] {
+            let data: serde_yaml::Value =
+                serde_yaml::from_str(&format!("name: {name}\nemail: {name}@test.com")).unwrap();
+            users.insert(data, None).unwrap();
+        }
+
+        let stats = store.view_stats();
+        assert_eq!(stats["user_lookup"]["build_count"].as_u64().unwrap(), user_builds + 3);
+        assert_eq!(stats["user_lookup"]["last_row_count"], 3);
+        assert!(stats["user_lookup"]["last_build_ms"].as_f64().unwrap() >= 0.0);
+        // Views over other collections are untouched by user writes
+        assert_eq!(stats["all_posts"]["build_count"], before["all_posts"]["build_count"]);
+        assert_eq!(store.status().unwrap()["view_stats"], stats);
+    }
+
+    #[test]
+    fn test_reopen_skips_rebuilding_unchanged_views() {
+        let (tmp, store) = setup_store_with_views();
+        seed_view_data(&store);
+        let feed = store.view_dynamic("post_feed").unwrap();
+        drop(store);
+
+        // Nothing changed on disk: every static view keeps its cached rows
+        let store = Store::open(tmp.path().to_str().unwrap()).unwrap();
+        let stats = store.view_stats();
+        for (name, s) in stats.as_object().unwrap() {
+            assert_eq!(s["build_count"], 0, "{name} was rebuilt");
+        }
+        assert_eq!(store.view_dynamic("post_feed").unwrap(), feed);
+        drop(store);
+
+        // A new user changes the users directory, so only views over users rebuild
+        std::fs::write(
+            tmp.path().join("users/zed.md"),
+            "---\nname: Zed\nemail: zed@test.com\n---\n",
+        )
+        .unwrap();
+        let store = Store::open(tmp.path().to_str().unwrap()).unwrap();
+        let stats = store.view_stats();
+        assert_eq!(stats["user_lookup"]["build_count"], 1);
+        assert_eq!(stats["post_feed"]["build_count"], 1);
+        assert_eq!(stats["all_posts"]["build_count"], 0);
+        assert_eq!(store.view_dynamic("user_lookup").unwrap().as_array().unwrap().len(), 3);
+
+        // An explicit rebuild re-runs every view
+        store.rebuild(None).unwrap();
+        assert_eq!(store.view_stats()["all_posts"]["build_count"], 1);
+    }
+
+    #[test]
+    fn test_changed_view_sql_rebuilds_only_that_view() {
+        let (tmp, store) = setup_store_with_views();
+        seed_view_data(&store);
+        drop(store);
 
-        store.on_collection_change(
-            "users",
-            Box::new(move |event| {
-                received_clone.lock().unwrap().push(event);
-            }),
+        let schema_path = tmp.path().join("schema.yaml");
+        let schema = std::fs::read_to_string(&schema_path).unwrap();
+        let edited = schema.replace(
+            "SELECT id, name, email, role\n      FROM users\n      ORDER BY name ASC",
+            "SELECT id, name\n      FROM users\n      ORDER BY name DESC",
         );
+        assert_ne!(edited, schema);
+        std::fs::write(&schema_path, edited).unwrap();
 
-        // Insert then delete
-        let users = store.collection("users").unwrap();
-        let data: serde_yaml::Value =
-            serde_yaml::from_str("name: Alice\nemail: alice@test.com").unwrap();
-        users.insert(data, None).unwrap();
-        users.delete("alice").unwrap();
-
-        let events = received.lock().unwrap();
-        assert_eq!(events.len(), 2);
-        match &events[1] {
-            ChangeEvent::Deleted { id } => assert_eq!(id, "alice"),
-            other => panic!("Expected Deleted event, got {:?}", other),
-        }
+        let store = Store::open(tmp.path().to_str().unwrap()).unwrap();
+        let stats = store.view_stats();
+        assert_eq!(stats["user_lookup"]["build_count"], 1);
+        assert_eq!(stats["post_feed"]["build_count"], 0);
+        assert_eq!(stats["all_posts"]["build_count"], 0);
+        let rows = store.view_dynamic("user_lookup").unwrap();
+        assert!(rows[0].get("email").is_none(), "{rows}");
     }
 
     #[test]
-    fn test_subscription_unsubscribe() {
-        let (_tmp, store) = setup_test_store();
+    fn test_open_without_boot_view_rebuild_serves_cached_rows() {
+        let (tmp, store) = setup_store_with_views();
+        seed_view_data(&store);
+        let users = store.view_dynamic("user_lookup").unwrap();
+        drop(store);
 
-        let received = Arc::new(Mutex::new(Vec::<ChangeEvent>::new()));
-        let received_clone = received.clone();
+        // An external edit would normally make views over users rebuild on open
+        std::fs::write(
+            tmp.path().join("users/zed.md"),
+            "---\nname: Zed\nemail: zed@test.com\n---\n",
+        )
+        .unwrap();
+        let options = StoreOptions {
+            rebuild_views_on_boot: false,
+            ..Default::default()
+        };
+        let store = Store::open_with_options(tmp.path().to_str().unwrap(), options).unwrap();
+        for (name, s) in store.view_stats().as_object().unwrap() {
+            assert_eq!(s["build_count"], 0, "{name} was rebuilt");
+        }
+        assert_eq!(store.view_dynamic("user_lookup").unwrap(), users);
+        // The scan still indexed the new file
+        assert!(store.collection("users").unwrap().get("zed").is_ok());
+    }
 
-        let sub_id = store.on_collection_change(
-            "users",
-            Box::new(move |event| {
-                received_clone.lock().unwrap().push(event);
-            }),
-        );
+    #[test]
+    fn test_coalesce_view_rebuilds_during_write_burst() {
+        let (tmp, store) = setup_store_with_views();
+        drop(store);
+        let options = StoreOptions {
+            coalesce_view_rebuilds: Some(std::time::Duration::from_secs(60)),
+            ..Default::default()
+        };
+        let store = Store::open_with_options(tmp.path().to_str().unwrap(), options).unwrap();
+        let builds = |store: &Store| {
+            store.view_stats()["user_lookup"]["build_count"]
+                .as_u64()
+                .unwrap()
+        };
+        let before = builds(&store);
 
-        // Insert then unsubscribe
         let users = store.collection("users").unwrap();
-        let data: serde_yaml::Value =
-            serde_yaml::from_str("name: Alice\nemail: alice@test.com").unwrap();
-        users.insert(data, None).unwrap();
+        for i in 0..100 {
+            let yaml = format!("name: user{i:03}\nemail: u{i}@test.com");
+            let data = serde_yaml::from_str(&yaml).unwrap();
+            users.insert(data, None).unwrap();
+        }
+        // Only the first write of the window rebuilds
+        assert_eq!(builds(&store), before + 1);
+
+        // Reading flushes the rest in one rebuild
+        let rows = store.view_dynamic("user_lookup").unwrap();
+        assert_eq!(rows.as_array().unwrap().len(), 100);
+        assert_eq!(builds(&store), before + 2);
+        store.view_dynamic("user_lookup").unwrap();
+        assert_eq!(builds(&store), before + 2);
+    }
 
-        store.unsubscribe(sub_id);
+    #[test]
+    fn test_view_flusher_rebuilds_after_the_window() {
+        let (tmp, store) = setup_store_with_views();
+        drop(store);
+        let options = StoreOptions {
+            coalesce_view_rebuilds: Some(std::time::Duration::from_millis(100)),
+            ..Default::default()
+        };
+        let store = Arc::new(Store::open_with_options(tmp.path().to_str().unwrap(), options).unwrap());
+        let flusher = store.spawn_view_flusher().unwrap();
+        let builds = |store: &Store| {
+            store.view_stats()["user_lookup"]["build_count"]
+                .as_u64()
+                .unwrap()
+        };
 
-        // This should NOT trigger the callback
-        let data2: serde_yaml::Value =
-            serde_yaml::from_str("name: Bob\nemail: bob@test.com").unwrap();
-        users.insert(data2, None).unwrap();
+        let users = store.collection("users").unwrap();
+        for i in 0..3 {
+            let data = serde_yaml::from_str(&format!("name: user{i}\nemail: u{i}@test.com")).unwrap();
+            users.insert(data, None).unwrap();
+        }
+        let after_burst = builds(&store);
 
-        let events = received.lock().unwrap();
-        assert_eq!(events.len(), 1, "Should only have 1 event after unsubscribe");
+        // The dirty view is rebuilt without a read or another write
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+        while builds(&store) == after_burst && std::time::Instant::now() < deadline {
+            std::thread::sleep(std::time::Duration::from_millis(20));
+        }
+        assert_eq!(builds(&store), after_burst + 1);
+        let rows = store.db.get_view_data("user_lookup").unwrap().unwrap();
+        assert_eq!(serde_json::from_str::<serde_json::Value>(&rows).unwrap().as_array().unwrap().len(), 3);
+
+        // Dropping the store stops the thread
+        drop(users);
+        drop(store);
+        flusher.join().unwrap();
     }
 
     #[test]
-    fn test_view_subscription() {
-        let (_tmp, store) = setup_store_with_views();
+    fn test_rebuild_views_only_leaves_files_alone() {
+        let (tmp, store) = setup_store_with_views();
+        seed_view_data(&store);
+        let feed = store.view_dynamic("post_feed").unwrap();
+
+        let mtimes = || -> Vec<(PathBuf, std::time::SystemTime)> {
+            let pattern = format!("{}/*/**/*.md", tmp.path().display());
+            glob::glob(&pattern)
+                .unwrap()
+                .filter_map(|p| p.ok())
+                .filter(|p| !p.starts_with(tmp.path().join("views")))
+                .map(|p| {
+                    let mtime = std::fs::metadata(&p).unwrap().modified().unwrap();
+                    (p, mtime)
+                })
+                .collect()
+        };
+        let before = mtimes();
+        assert!(!before.is_empty());
 
-        let received = Arc::new(Mutex::new(Vec::<Vec<serde_json::Value>>::new()));
-        let received_clone = received.clone();
+        for name in store.schema().views.keys() {
+            store.db.set_view_data(name, "[]").unwrap();
+            store.view_engine.set_view_data(name, Vec::new());
+        }
+        assert_eq!(store.view_dynamic("post_feed").unwrap(), serde_json::json!([]));
 
-        store.on_view_change(
-            "user_lookup",
-            Box::new(move |data| {
-                received_clone.lock().unwrap().push(data.to_vec());
-            }),
-        );
+        store.rebuild_views_only().unwrap();
+        assert_eq!(store.view_dynamic("post_feed").unwrap(), feed);
+        assert_eq!(store.view_dynamic("user_lookup").unwrap().as_array().unwrap().len(), 2);
+        assert_eq!(mtimes(), before);
+    }
 
-        // Insert a user — should trigger view rebuild and notify subscribers
-        let users = store.collection("users").unwrap();
-        let data: serde_yaml::Value =
-            serde_yaml::from_str("name: Alice\nemail: alice@test.com").unwrap();
-        users.insert(data, None).unwrap();
+    #[test]
+    fn test_views_lists_configuration_and_freshness() {
+        let (tmp, store) = setup_store_with_views();
+        seed_view_data(&store);
 
-        let events = received.lock().unwrap();
-        assert!(!events.is_empty(), "View subscriber should have been notified");
-        // The most recent view data should contain Alice
-        let latest = events.last().unwrap();
-        assert!(latest.iter().any(|row| row["name"] == "Alice"));
+        let views = store.views().unwrap();
+        let names: Vec<&str> = views.iter().map(|v| v.name.as_str()).collect();
+        let mut sorted = names.clone();
+        sorted.sort();
+        assert_eq!(names, sorted);
+        assert_eq!(views.len(), store.schema().views.len());
+
+        let feed = views.iter().find(|v| v.name == "post_feed").unwrap();
+        assert_eq!(feed.collections, vec!["posts".to_string(), "users".to_string()]);
+        assert_eq!(feed.buffer_multiplier, 2.0);
+        assert!(feed.materialize);
+        assert!(!feed.is_query_template);
+        assert_eq!(feed.cached_rows, store.view_engine.get_view_data("post_feed").map(|r| r.len()));
+        let built = feed.last_built.as_deref().unwrap();
+        assert!(chrono::DateTime::parse_from_rfc3339(built).is_ok());
+        assert!(!feed.stale);
+
+        let lookup = views.iter().find(|v| v.name == "user_lookup").unwrap();
+        assert_eq!(lookup.collections, vec!["users".to_string()]);
+        assert_eq!(lookup.buffer_multiplier, 1.0);
+        assert!(!lookup.materialize);
+
+        // A file written behind the store's back makes views over it stale
+        std::fs::write(
+            tmp.path().join("users/zed.md"),
+            "---\nname: Zed\nemail: zed@test.com\n---\n",
+        )
+        .unwrap();
+        let views = store.views().unwrap();
+        assert!(views.iter().find(|v| v.name == "post_feed").unwrap().stale);
     }
 
     #[test]
@@ -2793,6 +7135,71 @@ views:
         assert_eq!(rows[0]["name"], "Bob");
     }
 
+    #[test]
+    fn test_list_dynamic_fields_projection() {
+        let (_tmp, store) = setup_store_with_views();
+        seed_view_data(&store);
+
+        let mut filters = HashMap::new();
+        filters.insert("role".to_string(), "admin".to_string());
+
+        // Filter on a field that isn't projected
+        let result = store
+            .list_dynamic_fields("users", &filters, &["name"])
+            .unwrap();
+        let rows = result.as_array().unwrap();
+        assert_eq!(rows.len(), 1);
+        let obj = rows[0].as_object().unwrap();
+        let mut keys: Vec<&str> = obj.keys().map(|k| k.as_str()).collect();
+        keys.sort();
+        assert_eq!(keys, vec!["id", "name"]);
+        assert_eq!(obj["name"], "Alice");
+
+        let doc = store
+            .get_dynamic_fields("users", "alice", &["email", "role"])
+            .unwrap();
+        let obj = doc.as_object().unwrap();
+        assert_eq!(obj.len(), 3);
+        assert_eq!(obj["id"], "alice");
+        assert_eq!(obj["role"], "admin");
+        assert!(obj.contains_key("email"));
+    }
+
+    #[test]
+    fn test_views_read_non_identifier_field_names() {
+        let tmp = TempDir::new().unwrap();
+        let schema = r#"
+collections:
+  posts:
+    path: "posts/{title}.md"
+    fields:
+      title: { type: string, required: true }
+      published-at: { type: string }
+views:
+  timeline:
+    query: |
+      SELECT title, "published-at"
+      FROM posts
+      ORDER BY "published-at"
+"#;
+        std::fs::write(tmp.path().join("schema.yaml"), schema).unwrap();
+        let store = Store::open(tmp.path().to_str().unwrap()).unwrap();
+        for (title, at) in [("later", "2024-02-01"), ("first", "2024-01-01")] {
+            store
+                .insert_dynamic("posts", serde_json::json!({ "title": title, "published-at": at }), None)
+                .unwrap();
+        }
+
+        let rows = store.view_dynamic("timeline").unwrap();
+        assert_eq!(
+            rows,
+            serde_json::json!([
+                { "title": "first", "published-at": "2024-01-01" },
+                { "title": "later", "published-at": "2024-02-01" },
+            ])
+        );
+    }
+
     #[test]
     fn test_rebuild_also_rebuilds_views() {
         let (_tmp, store) = setup_store_with_views();
@@ -2823,6 +7230,331 @@ views:
         assert_eq!(result["is_query_template"], false);
     }
 
+    #[test]
+    fn test_explain_query_binds_params() {
+        let tmp = TempDir::new().unwrap();
+        let schema = r#"
+collections:
+  users:
+    path: "users/{name}.md"
+    fields:
+      name: { type: string, required: true }
+  comments:
+    path: "comments/{id}.md"
+    id: { auto: ulid }
+    fields:
+      user: { type: ref, target: users, required: true }
+      post: { type: string, required: true }
+
+views:
+  post_comments:
+    type: query
+    query: |
+      SELECT c.id, u.name AS commenter_name
+      FROM comments c
+      JOIN users u ON c.user = u.id
+      WHERE c.post = :post_id
+      ORDER BY c.created_at ASC
+    params:
+      post_id: { type: string }
+"#;
+        std::fs::write(tmp.path().join("schema.yaml"), schema).unwrap();
+        let store = Store::open(tmp.path().to_str().unwrap()).unwrap();
+
+        let params = HashMap::from([("post_id".to_string(), "it's-live".to_string())]);
+        let result = store.explain_query("post_comments", &params).unwrap();
+        let bound = result["bound_sql"].as_str().unwrap();
+        assert!(bound.contains("c.post = 'it''s-live'"), "{bound}");
+        assert!(!bound.contains(":post_id"), "{bound}");
+        assert_eq!(result["params"], serde_json::json!({ "post_id": "it's-live" }));
+
+        let plan: Vec<&str> = result["plan"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|step| step.as_str().unwrap())
+            .collect();
+        assert!(plan.iter().any(|step| step.contains("documents")), "{plan:#?}");
+
+        assert!(matches!(
+            store.explain_query("post_comments", &HashMap::new()),
+            Err(GroundDbError::Validation(_))
+        ));
+    }
+
+    #[test]
+    fn test_query_params_bind_by_declared_type() {
+        let tmp = TempDir::new().unwrap();
+        let schema = r#"
+collections:
+  events:
+    path: "events/{name}.md"
+    fields:
+      name: { type: string, required: true }
+      level: { type: number }
+      day: { type: date }
+      at: { type: datetime }
+
+views:
+  events_above:
+    type: query
+    query: |
+      SELECT name FROM events WHERE level >= :min ORDER BY level
+    params:
+      min: { type: number }
+
+  events_between:
+    type: query
+    query: |
+      SELECT name FROM events WHERE day >= :from AND day <= :to ORDER BY day
+    params:
+      from: { type: date }
+      to: { type: date }
+
+  events_until:
+    type: query
+    query: |
+      SELECT name FROM events WHERE at <= :until ORDER BY at
+    params:
+      until: { type: datetime }
+"#;
+        std::fs::write(tmp.path().join("schema.yaml"), schema).unwrap();
+        let store = Store::open(tmp.path().to_str().unwrap()).unwrap();
+        for (name, level, day) in [
+            ("small", 9, "2026-01-05"),
+            ("big", 100, "2026-02-10"),
+            ("huge", 1000, "2026-03-15"),
+        ] {
+            let at = format!("{day}T12:00:00Z");
+            let data = serde_json::json!({ "name": name, "level": level, "day": day, "at": at });
+            store.insert_dynamic("events", data, None).unwrap();
+        }
+        let names = |rows: serde_json::Value| -> Vec<String> {
+            rows.as_array()
+                .unwrap()
+                .iter()
+                .map(|r| r["name"].as_str().unwrap().to_string())
+                .collect()
+        };
+
+        // Bound as a number, so 9 is below 100
+        let params = HashMap::from([("min".to_string(), "100".to_string())]);
+        let rows = store.query_dynamic("events_above", &params).unwrap();
+        assert_eq!(names(rows), ["big", "huge"]);
+
+        // Non-ISO input is canonicalized before comparing
+        let params = HashMap::from([
+            ("from".to_string(), "2026/1/6".to_string()),
+            ("to".to_string(), "2026-03-15T12:00:00Z".to_string()),
+        ]);
+        let rows = store.query_dynamic("events_between", &params).unwrap();
+        assert_eq!(names(rows), ["big", "huge"]);
+
+        // A datetime bound at exactly a stored instant includes it
+        let params = HashMap::from([("until".to_string(), "2026-02-10T13:00:00+01:00".to_string())]);
+        let rows = store.query_dynamic("events_until", &params).unwrap();
+        assert_eq!(names(rows), ["small", "big"]);
+
+        let params = HashMap::from([("min".to_string(), "100".to_string())]);
+        let explained = store.explain_query("events_above", &params).unwrap();
+        assert!(explained["bound_sql"].as_str().unwrap().contains(">= 100"));
+
+        let params = HashMap::from([("min".to_string(), "lots".to_string())]);
+        assert!(matches!(
+            store.query_dynamic("events_above", &params),
+            Err(GroundDbError::Validation(_))
+        ));
+    }
+
+    #[test]
+    fn test_document_meta_survives_reindex_and_stays_out_of_file() {
+        let (tmp, store) = setup_test_store();
+        let data: serde_yaml::Value =
+            serde_yaml::from_str("name: Alice\nemail: alice@test.com").unwrap();
+        let id = store.collection("users").unwrap().insert(data, None).unwrap();
+
+        let cursor = serde_json::json!({ "cursor": 42, "source": "crm" });
+        store.set_meta("users", &id, "sync", cursor.clone()).unwrap();
+        assert_eq!(store.get_meta("users", &id, "sync").unwrap(), Some(cursor.clone()));
+        assert_eq!(store.get_meta("users", &id, "other").unwrap(), None);
+
+        store.rebuild(None).unwrap();
+        assert_eq!(store.get_meta("users", &id, "sync").unwrap(), Some(cursor));
+        let file = std::fs::read_to_string(tmp.path().join("users/alice.md")).unwrap();
+        assert!(!file.contains("sync") && !file.contains("cursor"), "{file}");
+
+        assert!(matches!(
+            store.set_meta("users", "nobody", "sync", serde_json::json!(1)),
+            Err(GroundDbError::NotFound { .. })
+        ));
+        store.delete_dynamic("users", &id).unwrap();
+        assert_eq!(store.get_meta("users", &id, "sync").unwrap(), None);
+    }
+
+    #[test]
+    fn test_enum_ordinals_follow_declaration_order() {
+        let tmp = TempDir::new().unwrap();
+        let schema = r#"
+collections:
+  tickets:
+    path: "tickets/{title}.md"
+    enum_ordinals: true
+    fields:
+      title: { type: string, required: true }
+      status: { type: string, enum: [open, in_progress, review, done], enum_case_insensitive: true }
+
+views:
+  late_tickets:
+    query: |
+      SELECT title, status, status_ordinal
+      FROM tickets
+      WHERE status_ordinal >= 2
+      ORDER BY status_ordinal DESC, title ASC
+"#;
+        std::fs::write(tmp.path().join("schema.yaml"), schema).unwrap();
+        let store = Store::open(tmp.path().to_str().unwrap()).unwrap();
+        let tickets = store.collection("tickets").unwrap();
+        for (title, status) in [("a", "done"), ("b", "open"), ("c", "Review"), ("d", "in_progress"), ("e", "review")] {
+            let data = serde_yaml::from_str(&format!("title: {title}\nstatus: {status}")).unwrap();
+            tickets.insert(data, None).unwrap();
+        }
+
+        let rows = store.view_dynamic("late_tickets").unwrap();
+        assert_eq!(
+            rows,
+            serde_json::json!([
+                { "title": "a", "status": "done", "status_ordinal": 3 },
+                { "title": "c", "status": "review", "status_ordinal": 2 },
+                { "title": "e", "status": "review", "status_ordinal": 2 },
+            ])
+        );
+
+        // Moving a ticket along its workflow updates the ordinal
+        tickets
+            .update_partial("b", serde_yaml::from_str("status: done").unwrap(), None)
+            .unwrap();
+        let rows = store.view_dynamic("late_tickets").unwrap();
+        assert_eq!(rows[1], serde_json::json!({ "title": "b", "status": "done", "status_ordinal": 3 }));
+
+        // The ordinal filter is served by its index
+        let sql = store.explain_view("late_tickets").unwrap()["rewritten_sql"].clone();
+        let plan = store
+            .db
+            .query_documents_sql::<String>(
+                &format!("EXPLAIN QUERY PLAN {}", sql.as_str().unwrap()),
+                &HashMap::new(),
+            )
+            .unwrap();
+        assert!(
+            plan.iter().any(|step| step["detail"]
+                .as_str()
+                .is_some_and(|detail| detail.contains("idx_ordinal_tickets_status"))),
+            "{plan:?}"
+        );
+    }
+
+    #[test]
+    fn test_view_diff_against_baseline() {
+        let (_tmp, store) = setup_store_with_views();
+        seed_view_data(&store);
+        let baseline = store.view_dynamic("user_lookup").unwrap();
+
+        let users = store.collection("users").unwrap();
+        users
+            .insert(serde_yaml::from_str("name: Cara\nemail: cara@test.com").unwrap(), None)
+            .unwrap();
+        users
+            .update_partial("alice", serde_yaml::from_str("role: guest").unwrap(), None)
+            .unwrap();
+        users.delete("bob").unwrap();
+
+        let diff = store.view_diff("user_lookup", &baseline).unwrap();
+        let ids = |key: &str| -> Vec<String> {
+            diff[key]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|row| row["id"].as_str().unwrap().to_string())
+                .collect()
+        };
+        assert_eq!(ids("added"), ["cara"]);
+        assert_eq!(ids("removed"), ["bob"]);
+        assert_eq!(ids("changed"), ["alice"]);
+        assert_eq!(diff["changed"][0]["before"]["role"], "admin");
+        assert_eq!(diff["changed"][0]["after"]["role"], "guest");
+
+        let same = store.view_dynamic("user_lookup").unwrap();
+        let diff = store.view_diff("user_lookup", &same).unwrap();
+        assert_eq!(diff, serde_json::json!({ "added": [], "removed": [], "changed": [] }));
+        assert!(store.view_diff("user_lookup", &serde_json::json!({})).is_err());
+    }
+
+    #[test]
+    fn test_diff_rows_without_ids_compares_whole_rows() {
+        let before = [serde_json::json!({ "title": "a" }), serde_json::json!({ "title": "b" })];
+        let after = [serde_json::json!({ "title": "b" }), serde_json::json!({ "title": "c" })];
+        assert_eq!(
+            diff_rows(&before, &after),
+            serde_json::json!({
+                "added": [{ "title": "c" }],
+                "removed": [{ "title": "a" }],
+                "changed": [],
+            })
+        );
+    }
+
+    #[test]
+    fn test_view_filters_list_field_with_json_each() {
+        let tmp = TempDir::new().unwrap();
+        let schema = r#"
+collections:
+  posts:
+    path: "posts/{title}.md"
+    fields:
+      title: { type: string, required: true }
+      tags: { type: list, items: string }
+
+views:
+  rust_posts:
+    query: |
+      SELECT p.title, p.tags
+      FROM posts p
+      WHERE EXISTS (SELECT 1 FROM json_each(p.tags) WHERE value = 'rust')
+      ORDER BY p.title ASC
+"#;
+        std::fs::write(tmp.path().join("schema.yaml"), schema).unwrap();
+        let store = Store::open(tmp.path().to_str().unwrap()).unwrap();
+        let posts = store.collection("posts").unwrap();
+        for data in [
+            "title: Borrowing\ntags: [rust, memory]",
+            "title: Goroutines\ntags: [go]",
+            "title: Untagged",
+            "title: Traits\ntags: [rust]",
+        ] {
+            posts.insert(serde_yaml::from_str(data).unwrap(), None).unwrap();
+        }
+
+        let rows = store.view_dynamic("rust_posts").unwrap();
+        let rows = rows.as_array().unwrap();
+        let titles: Vec<&str> = rows.iter().map(|r| r["title"].as_str().unwrap()).collect();
+        assert_eq!(titles, ["Borrowing", "Traits"]);
+        // List fields come back as JSON array text, not a stringified value
+        let tags: Vec<String> = serde_json::from_str(rows[0]["tags"].as_str().unwrap()).unwrap();
+        assert_eq!(tags, ["rust", "memory"]);
+    }
+
+    #[test]
+    fn test_inline_params_skips_string_literals() {
+        let params = HashMap::from([(
+            "id".to_string(),
+            rusqlite::types::Value::Text("a".into()),
+        )]);
+        assert_eq!(
+            inline_params("SELECT ':id', x FROM t WHERE y = :id AND z = :idx", &params),
+            "SELECT ':id', x FROM t WHERE y = 'a' AND z = :idx"
+        );
+    }
+
     #[test]
     fn test_strip_limit_basic() {
         assert_eq!(strip_limit("SELECT * FROM t LIMIT 10"), "SELECT * FROM t");
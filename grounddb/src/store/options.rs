@@ -0,0 +1,112 @@
+//! Runtime options for opening a store.
+
+/// Options that tune how a [`Store`](super::Store) reads and writes its data
+/// directory. Pass to [`Store::open_with_options`](super::Store::open_with_options);
+/// the other constructors use `StoreOptions::default()`.
+#[derive(Debug, Clone)]
+pub struct StoreOptions {
+    /// Files larger than this are skipped (with a warning) when scanning a
+    /// collection instead of being read and indexed. They are listed under
+    /// `skipped` in `validate_all()` output. `None` means no limit.
+    pub max_document_bytes: Option<u64>,
+    /// Write files so updates produce minimal git diffs: front matter keys keep
+    /// their on-disk order and bodies end with exactly one newline.
+    pub git_friendly: bool,
+    /// What an update does when the document's file was modified on disk
+    /// after it was last indexed (e.g. an external edit the watcher has not
+    /// processed yet).
+    pub conflict_policy: ConflictPolicy,
+    /// Don't take the `_system.lock` advisory lock on the data directory. Only
+    /// safe when nothing else can open the same store.
+    pub no_lock: bool,
+    /// How long opening waits for another process to release the lock before
+    /// failing with [`GroundDbError::Locked`](crate::GroundDbError::Locked).
+    /// `None` fails immediately.
+    pub lock_timeout: Option<std::time::Duration>,
+    /// Whether file paths that differ only in letter case name the same file,
+    /// as on default macOS and Windows filesystems. When set, an insert whose
+    /// path matches an indexed document's path ignoring case and Unicode
+    /// normalization form is a conflict.
+    /// `None` detects it when the store is opened.
+    pub case_insensitive_paths: Option<bool>,
+    /// Fail on the first document that can't be read or parsed, with
+    /// [`GroundDbError::Parse`](crate::GroundDbError::Parse) naming its path.
+    /// By default such documents are logged and skipped: scans list them under
+    /// `skipped` in `validate_all()` output, and reads like `Collection::list`
+    /// leave them out.
+    pub strict_parse: bool,
+    /// Read front matter that strict YAML rejects when it can be repaired:
+    /// tabs used for indentation become spaces and a duplicate key keeps its
+    /// last value. Each repair is logged as a warning naming the file.
+    pub lenient_yaml: bool,
+    /// Rebuild static views while opening the store when their cached rows
+    /// may be out of date. Set to `false` for faster startup on large stores:
+    /// cached rows in `_system.db` are served as-is and views are only rebuilt
+    /// by later writes, except views that have no cache yet. On by default.
+    pub rebuild_views_on_boot: bool,
+    /// Rebuild views affected by writes at most once per this window instead
+    /// of after every write. Writes within the window only mark views dirty;
+    /// reading a view, [`Store::flush_view_rebuilds`](super::Store::flush_view_rebuilds),
+    /// the next write after the window, or `process_watcher_events` after it
+    /// rebuilds them, as does [`Store::spawn_view_flusher`](super::Store::spawn_view_flusher)
+    /// when the window closes. `None` rebuilds after every write.
+    pub coalesce_view_rebuilds: Option<std::time::Duration>,
+    /// Longest file or directory name, in bytes, that rendering a path
+    /// template produces. Longer names (e.g. from a very long title) are cut
+    /// and given a short hash suffix so they stay unique; the shortened
+    /// filename stem becomes the document's id. Defaults to 255.
+    pub max_filename_bytes: usize,
+    /// How field values are turned into path segments when rendering path
+    /// templates. Defaults to [`DefaultSlug`](crate::path_template::DefaultSlug).
+    pub slug_strategy: std::sync::Arc<dyn crate::path_template::SlugStrategy>,
+}
+
+impl Default for StoreOptions {
+    fn default() -> Self {
+        Self {
+            max_document_bytes: None,
+            git_friendly: false,
+            conflict_policy: ConflictPolicy::default(),
+            no_lock: false,
+            lock_timeout: None,
+            case_insensitive_paths: None,
+            strict_parse: false,
+            lenient_yaml: false,
+            rebuild_views_on_boot: true,
+            coalesce_view_rebuilds: None,
+            max_filename_bytes: crate::path_template::DEFAULT_MAX_COMPONENT_BYTES,
+            slug_strategy: std::sync::Arc::new(crate::path_template::DefaultSlug),
+        }
+    }
+}
+
+/// Probe whether the filesystem holding `root` treats names differing only in
+/// case as the same file. Assumes case-sensitive if the probe can't be written.
+pub(super) fn detect_case_insensitive(root: &std::path::Path) -> bool {
+    let Ok(probe) = tempfile::Builder::new().prefix(".case-probe-").tempfile_in(root) else {
+        return false;
+    };
+    let Some(name) = probe.path().file_name().and_then(|n| n.to_str()) else {
+        return false;
+    };
+    let flipped: String = name
+        .chars()
+        .map(|c| if c.is_ascii_lowercase() { c.to_ascii_uppercase() } else { c.to_ascii_lowercase() })
+        .collect();
+    flipped != name && root.join(flipped).exists()
+}
+
+/// How an update resolves a document that changed on disk since it was
+/// indexed. See [`StoreOptions::conflict_policy`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ConflictPolicy {
+    /// Write the update as given, discarding the external edit.
+    #[default]
+    LastWriteWins,
+    /// Reject the update with [`GroundDbError::Conflict`](crate::GroundDbError::Conflict).
+    Error,
+    /// Three-way merge per top-level field (and the body) against the indexed
+    /// version: external changes to fields the update left alone are kept.
+    /// Fields changed differently on both sides are a `Conflict`.
+    Merge,
+}
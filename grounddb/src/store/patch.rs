@@ -0,0 +1,266 @@
+//! JSON Patch (RFC 6902) support for document data.
+//!
+//! [`Store::apply_json_patch`] applies an operation array to a document's front
+//! matter and writes the result through the regular update path, so the patched
+//! data is validated and the index, views and subscribers are updated as usual.
+
+use super::Store;
+use crate::error::{GroundDbError, Result};
+use crate::util::json_to_yaml;
+use serde_json::Value;
+
+impl Store {
+    /// Apply an RFC 6902 JSON Patch (`add`, `remove`, `replace`, `move`, `copy`,
+    /// `test`) to a document's data, then validate and write it. The patch is
+    /// all-or-nothing: if any operation fails (including a `test`), nothing is
+    /// written. The document body is left unchanged.
+    pub fn apply_json_patch(&self, collection: &str, id: &str, patch: Value) -> Result<()> {
+        let col = self.collection(collection)?;
+        self.with_document_lock(collection, id, || {
+            let existing = col.get(id)?;
+            let mut data = serde_json::to_value(&existing.data)?;
+            apply_patch(&mut data, &patch)?;
+            col.update_locked(id, json_to_yaml(&data), existing.content.as_deref())
+                .map(|_| ())
+        })
+    }
+}
+
+/// Apply every operation in `patch` to `doc`. On error `doc` is left untouched.
+fn apply_patch(doc: &mut Value, patch: &Value) -> Result<()> {
+    let ops = patch
+        .as_array()
+        .ok_or_else(|| patch_error("patch must be an array of operations"))?;
+
+    let mut patched = doc.clone();
+    for (i, op) in ops.iter().enumerate() {
+        apply_operation(&mut patched, op)
+            .map_err(|e| patch_error(&format!("operation {i}: {e}")))?;
+    }
+    *doc = patched;
+    Ok(())
+}
+
+fn apply_operation(doc: &mut Value, op: &Value) -> std::result::Result<(), String> {
+    let name = string_member(op, "op")?;
+    let path = string_member(op, "path")?;
+
+    match name {
+        "add" => add(doc, path, value_member(op)?.clone()),
+        "remove" => remove(doc, path).map(|_| ()),
+        "replace" => {
+            let target = doc
+                .pointer_mut(path)
+                .ok_or_else(|| format!("path '{path}' does not exist"))?;
+            *target = value_member(op)?.clone();
+            Ok(())
+        }
+        "move" => {
+            let from = string_member(op, "from")?;
+            if path.starts_with(&format!("{from}/")) {
+                return Err(format!("cannot move '{from}' into its own child '{path}'"));
+            }
+            let value = remove(doc, from)?;
+            add(doc, path, value)
+        }
+        "copy" => {
+            let from = string_member(op, "from")?;
+            let value = doc
+                .pointer(from)
+                .cloned()
+                .ok_or_else(|| format!("path '{from}' does not exist"))?;
+            add(doc, path, value)
+        }
+        "test" => {
+            let expected = value_member(op)?;
+            match doc.pointer(path) {
+                Some(actual) if actual == expected => Ok(()),
+                Some(actual) => Err(format!("test failed: '{path}' is {actual}, expected {expected}")),
+                None => Err(format!("test failed: path '{path}' does not exist")),
+            }
+        }
+        other => Err(format!("unknown op '{other}'")),
+    }
+}
+
+fn add(doc: &mut Value, path: &str, value: Value) -> std::result::Result<(), String> {
+    if path.is_empty() {
+        *doc = value;
+        return Ok(());
+    }
+    let (parent, key) = split_pointer(path)?;
+    match doc.pointer_mut(parent) {
+        Some(Value::Object(map)) => {
+            map.insert(key, value);
+            Ok(())
+        }
+        Some(Value::Array(items)) => {
+            let index = if key == "-" {
+                items.len()
+            } else {
+                array_index(&key, items.len() + 1)?
+            };
+            items.insert(index, value);
+            Ok(())
+        }
+        Some(_) => Err(format!("parent of '{path}' is not an object or array")),
+        None => Err(format!("parent of '{path}' does not exist")),
+    }
+}
+
+fn remove(doc: &mut Value, path: &str) -> std::result::Result<Value, String> {
+    let (parent, key) = split_pointer(path)?;
+    let removed = match doc.pointer_mut(parent) {
+        Some(Value::Object(map)) => map.remove(&key),
+        Some(Value::Array(items)) => {
+            let index = array_index(&key, items.len())?;
+            Some(items.remove(index))
+        }
+        _ => None,
+    };
+    removed.ok_or_else(|| format!("path '{path}' does not exist"))
+}
+
+/// Split a JSON Pointer into its parent pointer and unescaped last token.
+fn split_pointer(path: &str) -> std::result::Result<(&str, String), String> {
+    let slash = path
+        .rfind('/')
+        .ok_or_else(|| format!("invalid JSON Pointer '{path}'"))?;
+    let key = path[slash + 1..].replace("~1", "/").replace("~0", "~");
+    Ok((&path[..slash], key))
+}
+
+/// Parse an array index token, which must be below `bound`.
+fn array_index(token: &str, bound: usize) -> std::result::Result<usize, String> {
+    match token.parse::<usize>() {
+        Ok(index) if index < bound && (token == "0" || !token.starts_with('0')) => Ok(index),
+        _ => Err(format!("invalid array index '{token}'")),
+    }
+}
+
+fn string_member<'a>(op: &'a Value, key: &str) -> std::result::Result<&'a str, String> {
+    op.get(key)
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| format!("missing string member '{key}'"))
+}
+
+fn value_member(op: &Value) -> std::result::Result<&Value, String> {
+    op.get("value").ok_or_else(|| "missing member 'value'".to_string())
+}
+
+fn patch_error(message: &str) -> GroundDbError {
+    GroundDbError::Validation(format!("JSON Patch {message}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    fn setup_store() -> (TempDir, Store) {
+        let tmp = TempDir::new().unwrap();
+        let schema = r#"
+collections:
+  posts:
+    path: "posts/{title}.md"
+    fields:
+      title: { type: string, required: true }
+      status: { type: string, enum: [draft, published] }
+      tags: { type: list, items: string }
+    content: true
+"#;
+        std::fs::write(tmp.path().join("schema.yaml"), schema).unwrap();
+        let store = Store::open(tmp.path().to_str().unwrap()).unwrap();
+        let data: serde_yaml::Value =
+            serde_yaml::from_str("title: hello\nstatus: draft\ntags: [a, b]").unwrap();
+        store
+            .collection("posts")
+            .unwrap()
+            .insert(data, Some("Body text.\n"))
+            .unwrap();
+        (tmp, store)
+    }
+
+    #[test]
+    fn test_json_patch_replace_and_add() {
+        let (_tmp, store) = setup_store();
+        store
+            .apply_json_patch(
+                "posts",
+                "hello",
+                json!([
+                    { "op": "replace", "path": "/status", "value": "published" },
+                    { "op": "add", "path": "/tags/-", "value": "c" },
+                    { "op": "copy", "from": "/tags/0", "path": "/tags/0" }
+                ]),
+            )
+            .unwrap();
+
+        let doc = store.get_dynamic("posts", "hello").unwrap();
+        assert_eq!(doc["status"], json!("published"));
+        assert_eq!(doc["tags"], json!(["a", "a", "b", "c"]));
+        assert_eq!(doc["content"].as_str().map(str::trim), Some("Body text."));
+    }
+
+    #[test]
+    fn test_json_patch_remove() {
+        let (_tmp, store) = setup_store();
+        store
+            .apply_json_patch(
+                "posts",
+                "hello",
+                json!([
+                    { "op": "remove", "path": "/tags/0" },
+                    { "op": "remove", "path": "/status" }
+                ]),
+            )
+            .unwrap();
+
+        let doc = store.get_dynamic("posts", "hello").unwrap();
+        assert_eq!(doc["tags"], json!(["b"]));
+        assert!(doc.get("status").is_none());
+
+        let err = store
+            .apply_json_patch("posts", "hello", json!([{ "op": "remove", "path": "/status" }]))
+            .unwrap_err();
+        assert!(err.to_string().contains("does not exist"), "Unexpected: {err}");
+    }
+
+    #[test]
+    fn test_json_patch_failed_test_aborts() {
+        let (_tmp, store) = setup_store();
+        let err = store
+            .apply_json_patch(
+                "posts",
+                "hello",
+                json!([
+                    { "op": "replace", "path": "/status", "value": "published" },
+                    { "op": "test", "path": "/title", "value": "goodbye" }
+                ]),
+            )
+            .unwrap_err();
+        assert!(err.to_string().contains("test failed"), "Unexpected: {err}");
+
+        let doc = store.get_dynamic("posts", "hello").unwrap();
+        assert_eq!(doc["status"], json!("draft"));
+    }
+
+    #[test]
+    fn test_json_patch_move_and_invalid_ops() {
+        let mut doc = json!({ "a": { "b": 1 }, "list": [1, 2] });
+        apply_patch(&mut doc, &json!([{ "op": "move", "from": "/a/b", "path": "/c" }])).unwrap();
+        assert_eq!(doc, json!({ "a": {}, "c": 1, "list": [1, 2] }));
+
+        for bad in [
+            json!({ "op": "add" }),
+            json!([{ "op": "frobnicate", "path": "/a" }]),
+            json!([{ "op": "add", "path": "/list/5", "value": 0 }]),
+            json!([{ "op": "move", "from": "/a", "path": "/a/x" }]),
+        ] {
+            let before = doc.clone();
+            assert!(apply_patch(&mut doc, &bad).is_err(), "{bad} should fail");
+            assert_eq!(doc, before);
+        }
+    }
+}
@@ -0,0 +1,312 @@
+//! Fluent reads over the document index.
+//!
+//! [`Store::query`] returns a [`QueryBuilder`] that composes equality filters,
+//! ordering and pagination into a single SQLite statement against the
+//! `documents` table, so no document file has to be read.
+
+use super::Store;
+use crate::error::Result;
+use crate::schema::Collation;
+use crate::system_db::DATA_JSON;
+use std::collections::HashMap;
+
+/// Columns stored directly on the documents table rather than in `data_json`.
+const IMPLICIT_COLUMNS: &[&str] = &["id", "created_at", "modified_at"];
+
+/// Sort direction for [`QueryBuilder::order`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortOrder {
+    Asc,
+    Desc,
+}
+
+/// A filtered, sorted, paginated read over one collection.
+/// Built with [`Store::query`] and executed with [`QueryBuilder::run`].
+pub struct QueryBuilder<'a> {
+    store: &'a Store,
+    collection: String,
+    filters: Vec<(String, String)>,
+    order: Vec<(String, SortOrder)>,
+    limit: Option<usize>,
+    offset: Option<usize>,
+}
+
+impl Store {
+    /// Start a query over a collection. Unknown collections error on `run()`.
+    pub fn query(&self, collection: &str) -> QueryBuilder<'_> {
+        QueryBuilder {
+            store: self,
+            collection: collection.to_string(),
+            filters: Vec::new(),
+            order: Vec::new(),
+            limit: None,
+            offset: None,
+        }
+    }
+}
+
+impl<'a> QueryBuilder<'a> {
+    /// Only return documents whose `field` equals `value`. Numbers and booleans
+    /// are compared by their string form, as with `list_dynamic` filters.
+    pub fn filter(mut self, field: &str, value: &str) -> Self {
+        self.filters.push((field.to_string(), value.to_string()));
+        self
+    }
+
+    /// Apply every `key=value` pair in `filters`.
+    pub fn filters(mut self, filters: &HashMap<String, String>) -> Self {
+        let mut pairs: Vec<_> = filters.iter().collect();
+        pairs.sort();
+        for (field, value) in pairs {
+            self.filters.push((field.clone(), value.clone()));
+        }
+        self
+    }
+
+    /// Sort by `field`. Repeated calls add secondary sort keys; ties are
+    /// always broken by document id. A field's schema `collation` decides how
+    /// its values compare.
+    pub fn order(mut self, field: &str, order: SortOrder) -> Self {
+        self.order.push((field.to_string(), order));
+        self
+    }
+
+    /// Return at most `limit` documents.
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Skip the first `offset` matching documents.
+    pub fn offset(mut self, offset: usize) -> Self {
+        self.offset = Some(offset);
+        self
+    }
+
+    /// Execute the query, returning a JSON array of documents shaped like
+    /// `list_dynamic` output (id, timestamps, data fields and content).
+    pub fn run(self) -> Result<serde_json::Value> {
+        let collection = self.store.collection(&self.collection)?;
+        let fields = &collection.definition().fields;
+
+        let mut params = HashMap::new();
+        params.insert(":collection".to_string(), self.collection.clone());
+
+        // Decompress data once in a subquery so filters and sorts read plain JSON
+        let mut sql = format!(
+            "SELECT id, created_at, modified_at, data_json, content_text FROM (\
+             SELECT id, created_at, modified_at, {DATA_JSON} AS data_json, content_text \
+             FROM documents WHERE collection = :collection)",
+        );
+        let mut conditions = Vec::new();
+        for (i, (field, value)) in self.filters.iter().enumerate() {
+            let value_param = format!(":v{i}");
+            let expr = filter_expr(field, &format!(":f{i}"), &mut params);
+            conditions.push(format!("{expr} = {value_param}"));
+            params.insert(value_param, value.clone());
+        }
+        if !conditions.is_empty() {
+            sql.push_str(&format!(" WHERE {}", conditions.join(" AND ")));
+        }
+
+        let mut order_terms = Vec::new();
+        for (i, (field, order)) in self.order.iter().enumerate() {
+            let mut expr = sort_expr(field, &format!(":o{i}"), &mut params);
+            if !IMPLICIT_COLUMNS.contains(&field.as_str()) {
+                expr = collate(expr, fields.get(field).and_then(|f| f.collation));
+            }
+            let dir = match order {
+                SortOrder::Asc => "ASC",
+                SortOrder::Desc => "DESC",
+            };
+            order_terms.push(format!("{expr} {dir}"));
+        }
+        order_terms.push("id ASC".to_string());
+        sql.push_str(&format!(" ORDER BY {}", order_terms.join(", ")));
+
+        if self.limit.is_some() || self.offset.is_some() {
+            // SQLite only accepts OFFSET after a LIMIT; -1 means unbounded
+            let limit = self.limit.map(|n| n as i64).unwrap_or(-1);
+            sql.push_str(&format!(" LIMIT {limit} OFFSET {}", self.offset.unwrap_or(0)));
+        }
+
+        let rows = self.store.db.query_documents_sql(&sql, &params)?;
+        rows.into_iter().map(row_to_json).collect::<Result<Vec<_>>>().map(serde_json::Value::Array)
+    }
+}
+
+/// SQL expression yielding the value of `field` for sorting. Data fields bind
+/// their JSON path as `param`.
+fn sort_expr(field: &str, param: &str, params: &mut HashMap<String, String>) -> String {
+    if IMPLICIT_COLUMNS.contains(&field) {
+        return field.to_string();
+    }
+    params.insert(param.to_string(), format!("$.\"{}\"", field.replace('"', "")));
+    format!("json_extract(data_json, {param})")
+}
+
+/// Wrap a sort expression so it compares according to `collation`.
+fn collate(expr: String, collation: Option<Collation>) -> String {
+    match collation {
+        Some(Collation::Nocase) => format!("{expr} COLLATE NOCASE"),
+        Some(Collation::Numeric) => format!("CAST({expr} AS REAL)"),
+        None => expr,
+    }
+}
+
+/// SQL expression yielding `field` as text for equality filters, with
+/// booleans rendered as `true`/`false` rather than SQLite's 1/0.
+fn filter_expr(field: &str, param: &str, params: &mut HashMap<String, String>) -> String {
+    let value = sort_expr(field, param, params);
+    if IMPLICIT_COLUMNS.contains(&field) {
+        return value;
+    }
+    format!(
+        "(CASE json_type(data_json, {param}) WHEN 'true' THEN 'true' WHEN 'false' THEN 'false' \
+         ELSE CAST({value} AS TEXT) END)"
+    )
+}
+
+/// Flatten an index row into the dynamic API's document shape.
+pub(super) fn row_to_json(row: serde_json::Value) -> Result<serde_json::Value> {
+    let mut obj = serde_json::Map::new();
+    for key in IMPLICIT_COLUMNS {
+        obj.insert(key.to_string(), row[*key].clone());
+    }
+    let data_json = row["data_json"].as_str().unwrap_or("{}");
+    if let serde_json::Value::Object(fields) = serde_json::from_str(data_json)? {
+        obj.extend(fields);
+    }
+    if let Some(content) = row["content_text"].as_str() {
+        obj.insert("content".into(), serde_json::Value::String(content.to_string()));
+    }
+    Ok(serde_json::Value::Object(obj))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn setup_store() -> (TempDir, Store) {
+        let tmp = TempDir::new().unwrap();
+        let schema = r#"
+collections:
+  posts:
+    path: "posts/{title}.md"
+    fields:
+      title: { type: string, required: true }
+      status: { type: string, enum: [draft, published] }
+      rank: { type: number }
+      featured: { type: boolean }
+"#;
+        std::fs::write(tmp.path().join("schema.yaml"), schema).unwrap();
+        let store = Store::open(tmp.path().to_str().unwrap()).unwrap();
+        let posts = store.collection("posts").unwrap();
+        for (title, status, rank, featured) in [
+            ("alpha", "published", 3, false),
+            ("beta", "draft", 1, false),
+            ("gamma", "published", 10, true),
+            ("delta", "published", 2, false),
+            ("epsilon", "published", 7, true),
+        ] {
+            let data: serde_yaml::Value = serde_yaml::from_str(&format!(
+                "title: {title}\nstatus: {status}\nrank: {rank}\nfeatured: {featured}"
+            ))
+            .unwrap();
+            posts.insert(data, Some("Body\n")).unwrap();
+        }
+        (tmp, store)
+    }
+
+    fn ids(rows: &serde_json::Value) -> Vec<&str> {
+        rows.as_array()
+            .unwrap()
+            .iter()
+            .map(|r| r["id"].as_str().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_filter_order_limit() {
+        let (_tmp, store) = setup_store();
+
+        let rows = store
+            .query("posts")
+            .filter("status", "published")
+            .order("rank", SortOrder::Desc)
+            .limit(3)
+            .run()
+            .unwrap();
+        assert_eq!(ids(&rows), vec!["gamma", "epsilon", "alpha"]);
+        assert_eq!(rows[0]["rank"], serde_json::json!(10));
+        assert_eq!(rows[0]["content"], serde_json::json!("Body\n"));
+        assert!(rows[0]["created_at"].is_string());
+
+        let page2 = store
+            .query("posts")
+            .filter("status", "published")
+            .order("rank", SortOrder::Desc)
+            .limit(3)
+            .offset(3)
+            .run()
+            .unwrap();
+        assert_eq!(ids(&page2), vec!["delta"]);
+    }
+
+    #[test]
+    fn test_filter_by_number_and_bool() {
+        let (_tmp, store) = setup_store();
+
+        let rows = store.query("posts").filter("featured", "true").run().unwrap();
+        assert_eq!(ids(&rows), vec!["epsilon", "gamma"]);
+
+        let rows = store.query("posts").filter("rank", "1").run().unwrap();
+        assert_eq!(ids(&rows), vec!["beta"]);
+
+        let rows = store.query("posts").offset(4).run().unwrap();
+        assert_eq!(ids(&rows), vec!["gamma"]);
+
+        assert!(store.query("missing").run().is_err());
+    }
+
+    #[test]
+    fn test_order_uses_field_collation() {
+        let tmp = TempDir::new().unwrap();
+        let schema = r#"
+collections:
+  notes:
+    path: "notes/{slug}.md"
+    fields:
+      slug: { type: string, required: true }
+      title: { type: string, collation: nocase }
+      raw_title: { type: string }
+      version: { type: string, collation: numeric }
+"#;
+        std::fs::write(tmp.path().join("schema.yaml"), schema).unwrap();
+        let store = Store::open(tmp.path().to_str().unwrap()).unwrap();
+        let notes = store.collection("notes").unwrap();
+        for (slug, title, version) in [("a", "Banana", "10"), ("b", "apple", "9"), ("c", "cherry", "100")] {
+            let data: serde_yaml::Value = serde_yaml::from_str(&format!(
+                "slug: {slug}\ntitle: {title}\nraw_title: {title}\nversion: \"{version}\""
+            ))
+            .unwrap();
+            notes.insert(data, None).unwrap();
+        }
+
+        let titles = |field: &str, order: SortOrder| -> Vec<String> {
+            let rows = store.query("notes").order(field, order).run().unwrap();
+            rows.as_array()
+                .unwrap()
+                .iter()
+                .map(|r| r["title"].as_str().unwrap().to_string())
+                .collect()
+        };
+        assert_eq!(titles("title", SortOrder::Asc), vec!["apple", "Banana", "cherry"]);
+        assert_eq!(titles("title", SortOrder::Desc), vec!["cherry", "Banana", "apple"]);
+        // Without a collation, uppercase sorts before lowercase
+        assert_eq!(titles("raw_title", SortOrder::Asc), vec!["Banana", "apple", "cherry"]);
+        // Numeric collation compares "9" < "10" < "100"
+        assert_eq!(titles("version", SortOrder::Asc), vec!["apple", "Banana", "cherry"]);
+    }
+}
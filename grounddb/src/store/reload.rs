@@ -0,0 +1,132 @@
+//! Reloading `schema.yaml` into an open store.
+//!
+//! [`Store::reload_schema`] runs the same lifecycle as [`Store::open`] against
+//! the already-open system database: migrate from the last recorded schema,
+//! rescan collections and rebuild views.
+
+use super::{storage_templates, Store};
+use crate::error::{GroundDbError, Result};
+use crate::schema::parse_schema_str;
+use crate::view::ViewEngine;
+
+impl Store {
+    /// Re-read `schema.yaml` from the data directory and apply it in place:
+    /// re-parse path templates and views, migrate from the previous schema,
+    /// rescan collections and rebuild views.
+    ///
+    /// Takes `&mut self`, so no collection handle or other call can observe a
+    /// half-applied schema; a store shared between threads should sit behind
+    /// an `RwLock`. If the new schema doesn't parse, or its migration or boot
+    /// fails, the previous schema stays in effect and the error is returned.
+    /// A running file watcher is restarted to cover the new collections.
+    pub fn reload_schema(&mut self) -> Result<()> {
+        let schema_path = self.root.join("schema.yaml");
+        if !schema_path.exists() {
+            return Err(GroundDbError::Schema(format!(
+                "schema.yaml not found in {}",
+                self.root.display()
+            )));
+        }
+        let schema_yaml = std::fs::read_to_string(&schema_path)?;
+        let schema = parse_schema_str(&schema_yaml)?;
+        let path_templates = storage_templates(&schema, &self.options)?;
+        let view_engine = ViewEngine::new(&schema)?;
+
+        let previous = (
+            std::mem::replace(&mut self.schema, schema),
+            std::mem::replace(&mut self.schema_yaml, schema_yaml),
+            std::mem::replace(&mut self.path_templates, path_templates),
+            std::mem::replace(&mut self.view_engine, view_engine),
+        );
+        if let Err(e) = self.boot() {
+            (self.schema, self.schema_yaml, self.path_templates, self.view_engine) = previous;
+            self.sync_compressed_collections();
+            return Err(e);
+        }
+        self.booted_at = chrono::Utc::now();
+
+        let watching = self._watcher.lock().unwrap().is_some();
+        if watching {
+            self.watch()?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const SCHEMA: &str = r#"
+collections:
+  notes:
+    path: "notes/{title}.md"
+    fields:
+      title: { type: string, required: true }
+"#;
+
+    #[test]
+    fn test_reload_schema_adds_collection() {
+        let tmp = TempDir::new().unwrap();
+        std::fs::write(tmp.path().join("schema.yaml"), SCHEMA).unwrap();
+        let mut store = Store::open(tmp.path().to_str().unwrap()).unwrap();
+        store
+            .collection("notes")
+            .unwrap()
+            .insert(serde_yaml::from_str("title: first").unwrap(), None)
+            .unwrap();
+        assert!(store.collection("tags").is_err());
+
+        let extended = format!(
+            r#"{SCHEMA}
+  tags:
+    path: "tags/{{name}}.md"
+    fields:
+      name: {{ type: string, required: true }}
+      color: {{ type: string, default: gray }}
+
+views:
+  tag_list:
+    query: "SELECT name, color FROM tags ORDER BY name"
+"#
+        );
+        std::fs::write(tmp.path().join("schema.yaml"), extended).unwrap();
+        store.reload_schema().unwrap();
+
+        let tags = store.collection("tags").unwrap();
+        tags.insert(serde_yaml::from_str("name: urgent").unwrap(), None).unwrap();
+        assert_eq!(
+            store.view_dynamic("tag_list").unwrap(),
+            serde_json::json!([{ "name": "urgent", "color": "gray" }])
+        );
+        // Existing documents are still indexed
+        assert!(store.get_dynamic("notes", "first").is_ok());
+    }
+
+    #[test]
+    fn test_reload_schema_keeps_previous_schema_on_error() {
+        let tmp = TempDir::new().unwrap();
+        std::fs::write(tmp.path().join("schema.yaml"), SCHEMA).unwrap();
+        let mut store = Store::open(tmp.path().to_str().unwrap()).unwrap();
+        store
+            .collection("notes")
+            .unwrap()
+            .insert(serde_yaml::from_str("title: first").unwrap(), None)
+            .unwrap();
+
+        // A new required field without a default can't be migrated
+        let broken = SCHEMA.replace(
+            "title: { type: string, required: true }",
+            "title: { type: string, required: true }\n      owner: { type: string, required: true }",
+        );
+        std::fs::write(tmp.path().join("schema.yaml"), broken).unwrap();
+        assert!(store.reload_schema().is_err());
+
+        let notes = store.collection("notes").unwrap();
+        notes
+            .insert(serde_yaml::from_str("title: second").unwrap(), None)
+            .unwrap();
+        assert_eq!(store.list_dynamic("notes", &Default::default()).unwrap().as_array().unwrap().len(), 2);
+    }
+}
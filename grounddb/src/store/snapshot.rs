@@ -0,0 +1,258 @@
+//! Point-in-time snapshots of a data directory.
+//!
+//! A snapshot copies every document (everything under the data directory except
+//! `.snapshots/` and the system database files) plus a consistent copy of
+//! `_system.db` into `.snapshots/<label>/`. Restoring replaces the data directory
+//! contents and the index with the snapshot's. Both hold the write gate
+//! exclusively, so no write lands halfway through.
+
+use super::Store;
+use crate::error::{GroundDbError, Result};
+use std::path::{Path, PathBuf};
+
+/// Directory (relative to the data root) holding all snapshots.
+const SNAPSHOTS_DIR: &str = ".snapshots";
+
+/// File name of the system database copy inside a snapshot.
+const SNAPSHOT_DB: &str = "_system.db";
+
+/// Directory (inside `.snapshots/`) a snapshot is copied into before `restore`
+/// swaps it in. Labels can't start with `.`, so it never clashes with one.
+const RESTORE_STAGING: &str = ".restoring";
+
+/// Directory (inside `.snapshots/`) the current documents are moved aside to
+/// while `restore` swaps the snapshot in.
+const RESTORE_REPLACED: &str = ".replaced";
+
+/// Identifies a snapshot taken with [`Store::snapshot`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SnapshotId(String);
+
+impl SnapshotId {
+    /// The label the snapshot was taken under.
+    pub fn label(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Store {
+    /// Copy all documents and the system database into `.snapshots/<label>/`.
+    /// Fails if a snapshot with this label already exists.
+    pub fn snapshot(&self, label: &str) -> Result<SnapshotId> {
+        // No write may land between copying the files and copying the index
+        let _gate = self.write_gate.enter_exclusive();
+        let dir = self.snapshot_dir(label)?;
+        if dir.exists() {
+            return Err(GroundDbError::Other(format!(
+                "Snapshot '{label}' already exists"
+            )));
+        }
+        std::fs::create_dir_all(&dir)?;
+
+        let result = copy_data_tree(&self.root, &dir)
+            .and_then(|_| self.db.backup_to(&dir.join(SNAPSHOT_DB)));
+        if result.is_err() {
+            let _ = std::fs::remove_dir_all(&dir);
+        }
+        result?;
+
+        Ok(SnapshotId(label.to_string()))
+    }
+
+    /// Roll the data directory and index back to the snapshot taken under `label`.
+    ///
+    /// `schema.yaml` is restored too; if it differs from the running schema,
+    /// reopen the store to pick it up.
+    pub fn restore(&self, label: &str) -> Result<()> {
+        let _gate = self.write_gate.enter_exclusive();
+        let dir = self.snapshot_dir(label)?;
+        let snapshot_db = dir.join(SNAPSHOT_DB);
+        if !snapshot_db.exists() {
+            return Err(GroundDbError::Other(format!(
+                "Snapshot '{label}' not found"
+            )));
+        }
+
+        // Copy the snapshot into a staging directory first, so a failed copy
+        // leaves the current documents untouched, then swap it in
+        let staging = self.root.join(SNAPSHOTS_DIR).join(RESTORE_STAGING);
+        let replaced = self.root.join(SNAPSHOTS_DIR).join(RESTORE_REPLACED);
+        remove_if_exists(&staging)?;
+        remove_if_exists(&replaced)?;
+        if let Err(e) = copy_data_tree(&dir, &staging) {
+            let _ = std::fs::remove_dir_all(&staging);
+            return Err(e);
+        }
+
+        std::fs::create_dir_all(&replaced)?;
+        let swapped = move_entries(&self.root, &replaced)
+            .and_then(|_| move_entries(&staging, &self.root));
+        if let Err(e) = swapped {
+            // Put the original documents back before reporting the failure
+            let _ = move_entries(&self.root, &staging);
+            let _ = move_entries(&replaced, &self.root);
+            let _ = std::fs::remove_dir_all(&staging);
+            let _ = std::fs::remove_dir_all(&replaced);
+            return Err(e);
+        }
+        let _ = std::fs::remove_dir_all(&staging);
+        let _ = std::fs::remove_dir_all(&replaced);
+
+        self.db.restore_from(&snapshot_db)?;
+        self.view_engine.load_from_db(&self.db)?;
+        self.rebuild_all_static_views(false)
+    }
+
+    fn snapshot_dir(&self, label: &str) -> Result<PathBuf> {
+        if label.is_empty() || label.starts_with('.') || label.contains(['/', '\\']) {
+            return Err(GroundDbError::Other(format!(
+                "Invalid snapshot label '{label}'"
+            )));
+        }
+        Ok(self.root.join(SNAPSHOTS_DIR).join(label))
+    }
+}
+
+/// Whether a top-level entry of `root` is left out of snapshots: the snapshots
+/// themselves, the live system database (which is copied via SQLite instead)
+/// and the open store's lock file.
+fn is_excluded(root: &Path, path: &Path) -> bool {
+    if path.parent() != Some(root) {
+        return false;
+    }
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    name == SNAPSHOTS_DIR || name.starts_with(SNAPSHOT_DB) || name == super::lock::LOCK_FILE
+}
+
+/// Recursively copy the contents of `from` into `to`, skipping excluded entries.
+fn copy_data_tree(from: &Path, to: &Path) -> Result<()> {
+    copy_dir(from, from, to)
+}
+
+/// Move every non-excluded top-level entry of `from` into `to`.
+fn move_entries(from: &Path, to: &Path) -> Result<()> {
+    for entry in std::fs::read_dir(from)? {
+        let path = entry?.path();
+        if is_excluded(from, &path) {
+            continue;
+        }
+        std::fs::rename(&path, to.join(path.file_name().unwrap_or_default()))?;
+    }
+    Ok(())
+}
+
+fn remove_if_exists(dir: &Path) -> Result<()> {
+    if dir.exists() {
+        std::fs::remove_dir_all(dir)?;
+    }
+    Ok(())
+}
+
+fn copy_dir(root: &Path, from: &Path, to: &Path) -> Result<()> {
+    std::fs::create_dir_all(to)?;
+    for entry in std::fs::read_dir(from)? {
+        let path = entry?.path();
+        if is_excluded(root, &path) {
+            continue;
+        }
+        let target = to.join(path.file_name().unwrap_or_default());
+        if path.is_dir() {
+            copy_dir(root, &path, &target)?;
+        } else {
+            std::fs::copy(&path, &target)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn setup_store() -> (TempDir, Store) {
+        let tmp = TempDir::new().unwrap();
+        let schema = r#"
+collections:
+  users:
+    path: "users/{name}.md"
+    fields:
+      name: { type: string, required: true }
+      email: { type: string, required: true }
+"#;
+        std::fs::write(tmp.path().join("schema.yaml"), schema).unwrap();
+        let store = Store::open(tmp.path().to_str().unwrap()).unwrap();
+        (tmp, store)
+    }
+
+    #[test]
+    fn test_snapshot_and_restore() {
+        let (tmp, store) = setup_store();
+        let users = store.collection("users").unwrap();
+        let data: serde_yaml::Value =
+            serde_yaml::from_str("name: Alice\nemail: alice@test.com").unwrap();
+        users.insert(data, None).unwrap();
+
+        let id = store.snapshot("before").unwrap();
+        assert_eq!(id.label(), "before");
+        assert!(tmp.path().join(".snapshots/before/users/alice.md").exists());
+        assert!(store.snapshot("before").is_err());
+
+        // Mutate after the snapshot
+        let data: serde_yaml::Value =
+            serde_yaml::from_str("name: Alice\nemail: alice@new.com").unwrap();
+        users.update("alice", data, None).unwrap();
+        let data: serde_yaml::Value =
+            serde_yaml::from_str("name: Bob\nemail: bob@test.com").unwrap();
+        users.insert(data, None).unwrap();
+
+        store.restore("before").unwrap();
+
+        let alice = users.get("alice").unwrap();
+        assert_eq!(
+            alice.data["email"],
+            serde_yaml::Value::String("alice@test.com".into())
+        );
+        assert!(users.get("bob").is_err());
+        assert!(!tmp.path().join("users/bob.md").exists());
+        assert_eq!(users.list().unwrap().len(), 1);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_failed_restore_keeps_current_documents() {
+        let (tmp, store) = setup_store();
+        let users = store.collection("users").unwrap();
+        let data: serde_yaml::Value =
+            serde_yaml::from_str("name: Alice\nemail: alice@test.com").unwrap();
+        users.insert(data, None).unwrap();
+        store.snapshot("broken").unwrap();
+
+        // A dangling link in the snapshot makes copying it back fail partway
+        std::os::unix::fs::symlink(
+            tmp.path().join("missing.md"),
+            tmp.path().join(".snapshots/broken/users/zed.md"),
+        )
+        .unwrap();
+        let data: serde_yaml::Value =
+            serde_yaml::from_str("name: Bob\nemail: bob@test.com").unwrap();
+        users.insert(data, None).unwrap();
+
+        assert!(store.restore("broken").is_err());
+        assert!(tmp.path().join("users/alice.md").exists());
+        assert!(tmp.path().join("users/bob.md").exists());
+        assert!(tmp.path().join("schema.yaml").exists());
+        assert!(!tmp.path().join(".snapshots/.restoring").exists());
+        assert_eq!(users.list().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_restore_unknown_snapshot() {
+        let (_tmp, store) = setup_store();
+        assert!(store.restore("missing").is_err());
+        assert!(store.snapshot("../escape").is_err());
+    }
+}
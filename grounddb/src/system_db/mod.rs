@@ -1,37 +1,64 @@
 use crate::error::{GroundDbError, Result};
 use crate::util::json_to_yaml;
+use crate::trace::OpSpan;
 use rusqlite::{params, Connection, OptionalExtension};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::Path;
 use std::sync::Mutex;
+use unicode_normalization::UnicodeNormalization;
+
+/// A document's data as JSON text, whether it's stored as plain JSON or as a
+/// zstd-compressed blob (`data_compressed = 1`). Use in place of `data_json`
+/// wherever SQL reads the data.
+pub(crate) const DATA_JSON: &str =
+    "(CASE WHEN data_compressed THEN grounddb_decompress(data_json) ELSE data_json END)";
 
 /// The system database that manages document index, schema state, and view cache.
 /// Uses a Mutex around the connection so Store can be Send + Sync.
 pub struct SystemDb {
     conn: Mutex<Connection>,
+    /// Collections whose document data is stored zstd-compressed
+    compressed: Mutex<HashSet<String>>,
 }
 
 impl SystemDb {
     /// Open or create the system database at the given path.
     pub fn open(path: &Path) -> Result<Self> {
-        let conn = Connection::open(path)?;
-        let db = SystemDb { conn: Mutex::new(conn) };
-        db.initialize_tables()?;
-        Ok(db)
+        Self::with_connection(Connection::open(path)?)
     }
 
     /// Open an in-memory system database (for testing).
     pub fn open_in_memory() -> Result<Self> {
-        let conn = Connection::open_in_memory()?;
-        let db = SystemDb { conn: Mutex::new(conn) };
+        Self::with_connection(Connection::open_in_memory()?)
+    }
+
+    fn with_connection(conn: Connection) -> Result<Self> {
+        register_functions(&conn)?;
+        let db = SystemDb {
+            conn: Mutex::new(conn),
+            compressed: Mutex::new(HashSet::new()),
+        };
         db.initialize_tables()?;
         Ok(db)
     }
 
+    /// Set which collections store their document data compressed. Applies
+    /// to documents upserted from now on; already indexed rows keep their
+    /// format until they are next written, and are readable either way.
+    pub fn set_compressed_collections(&self, collections: impl IntoIterator<Item = String>) {
+        *self.compressed.lock().unwrap() = collections.into_iter().collect();
+    }
+
     fn conn(&self) -> std::sync::MutexGuard<'_, Connection> {
         self.conn.lock().unwrap()
     }
 
+    /// Run a trivial query to check the database is usable.
+    pub fn ping(&self) -> Result<()> {
+        self.conn().query_row("SELECT 1", [], |_| Ok(()))?;
+        Ok(())
+    }
+
     fn initialize_tables(&self) -> Result<()> {
         // First create all tables, then migrate existing ones if needed
         self.conn().execute_batch(
@@ -57,6 +84,9 @@ impl SystemDb {
                 created_at TEXT,
                 modified_at TEXT,
                 content_text TEXT,
+                derived_json TEXT,
+                content_length INTEGER NOT NULL DEFAULT 0,
+                data_compressed INTEGER NOT NULL DEFAULT 0,
                 PRIMARY KEY (collection, id)
             );
 
@@ -80,6 +110,21 @@ impl SystemDb {
                 hash TEXT NOT NULL,
                 updated_at TEXT NOT NULL DEFAULT (datetime('now'))
             );
+
+            CREATE TABLE IF NOT EXISTS deletions (
+                id TEXT NOT NULL,
+                collection TEXT NOT NULL,
+                deleted_at TEXT NOT NULL,
+                PRIMARY KEY (collection, id)
+            );
+
+            CREATE TABLE IF NOT EXISTS document_meta (
+                collection TEXT NOT NULL,
+                id TEXT NOT NULL,
+                key TEXT NOT NULL,
+                value_json TEXT NOT NULL,
+                PRIMARY KEY (collection, id, key)
+            );
             "
         )?;
         // Migrate existing documents table: add columns if missing
@@ -93,6 +138,9 @@ impl SystemDb {
         let mut has_created_at = false;
         let mut has_modified_at = false;
         let mut has_content_text = false;
+        let mut has_derived_json = false;
+        let mut has_content_length = false;
+        let mut has_data_compressed = false;
 
         let mut stmt = conn.prepare("PRAGMA table_info(documents)")?;
         let rows = stmt.query_map([], |row| {
@@ -104,6 +152,9 @@ impl SystemDb {
                 "created_at" => has_created_at = true,
                 "modified_at" => has_modified_at = true,
                 "content_text" => has_content_text = true,
+                "derived_json" => has_derived_json = true,
+                "content_length" => has_content_length = true,
+                "data_compressed" => has_data_compressed = true,
                 _ => {}
             }
         }
@@ -118,6 +169,20 @@ impl SystemDb {
         if !has_content_text {
             conn.execute_batch("ALTER TABLE documents ADD COLUMN content_text TEXT")?;
         }
+        if !has_derived_json {
+            conn.execute_batch("ALTER TABLE documents ADD COLUMN derived_json TEXT")?;
+        }
+        if !has_content_length {
+            conn.execute_batch(
+                "ALTER TABLE documents ADD COLUMN content_length INTEGER NOT NULL DEFAULT 0;
+                 UPDATE documents SET content_length = length(content_text) WHERE content_text IS NOT NULL;",
+            )?;
+        }
+        if !has_data_compressed {
+            conn.execute_batch(
+                "ALTER TABLE documents ADD COLUMN data_compressed INTEGER NOT NULL DEFAULT 0",
+            )?;
+        }
 
         Ok(())
     }
@@ -146,6 +211,26 @@ impl SystemDb {
         Ok(result)
     }
 
+    /// Get the YAML of a recorded schema, by version (the `schema_history` row
+    /// id) or by hash. A hash recorded more than once resolves to its latest entry.
+    pub fn get_schema_yaml(&self, version_or_hash: &str) -> Result<Option<String>> {
+        let conn = self.conn();
+        let result = match version_or_hash.parse::<i64>() {
+            Ok(version) => conn.query_row(
+                "SELECT schema_yaml FROM schema_history WHERE id = ?1",
+                params![version],
+                |row| row.get(0),
+            ),
+            Err(_) => conn.query_row(
+                "SELECT schema_yaml FROM schema_history WHERE hash = ?1 ORDER BY id DESC LIMIT 1",
+                params![version_or_hash],
+                |row| row.get(0),
+            ),
+        }
+        .optional()?;
+        Ok(result)
+    }
+
     /// Record a new schema version.
     pub fn record_schema(&self, hash: &str, yaml: &str) -> Result<()> {
         self.conn().execute(
@@ -166,7 +251,10 @@ impl SystemDb {
 
     // ── Document Index ───────────────────────────────────────────────
 
-    /// Upsert a document into the index.
+    /// Upsert a document into the index. `content_length` is the body's length
+    /// in characters, 0 for documents without one. Data of a compressed
+    /// collection (see [`SystemDb::set_compressed_collections`]) is stored as
+    /// a zstd blob.
     pub fn upsert_document(
         &self,
         id: &str,
@@ -178,9 +266,37 @@ impl SystemDb {
         content_text: Option<&str>,
     ) -> Result<()> {
         let data_json = serde_json::to_string(data)?;
+        let content_length = content_text.map_or(0, |c| c.chars().count() as i64);
+        let stored = if self.compressed.lock().unwrap().contains(collection) {
+            let blob = zstd::encode_all(data_json.as_bytes(), 0)?;
+            (rusqlite::types::Value::Blob(blob), true)
+        } else {
+            (rusqlite::types::Value::Text(data_json), false)
+        };
+        let conn = self.conn();
+        conn.execute(
+            "INSERT OR REPLACE INTO documents (id, collection, path, data_json, created_at, modified_at, content_text, content_length, data_compressed) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![id, collection, path, stored.0, created_at, modified_at, content_text, content_length, stored.1],
+        )?;
+        conn.execute(
+            "DELETE FROM deletions WHERE collection = ?1 AND id = ?2",
+            params![collection, id],
+        )?;
+        Ok(())
+    }
+
+    /// Store fields derived from a document's body (e.g. `_title`, `_links`).
+    /// Must be called after `upsert_document`, which clears them.
+    pub fn set_document_derived(
+        &self,
+        collection: &str,
+        id: &str,
+        derived: &serde_json::Value,
+    ) -> Result<()> {
+        let derived_json = serde_json::to_string(derived)?;
         self.conn().execute(
-            "INSERT OR REPLACE INTO documents (id, collection, path, data_json, created_at, modified_at, content_text) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
-            params![id, collection, path, data_json, created_at, modified_at, content_text],
+            "UPDATE documents SET derived_json = ?1 WHERE collection = ?2 AND id = ?3",
+            params![derived_json, collection, id],
         )?;
         Ok(())
     }
@@ -189,7 +305,7 @@ impl SystemDb {
     pub fn get_document(&self, collection: &str, id: &str) -> Result<Option<DocumentRecord>> {
         let conn = self.conn();
         let result = conn.query_row(
-            "SELECT id, collection, path, data_json FROM documents WHERE collection = ?1 AND id = ?2",
+            &format!("SELECT id, collection, path, {DATA_JSON} FROM documents WHERE collection = ?1 AND id = ?2"),
             params![collection, id],
             |row| {
                 Ok(DocumentRecord {
@@ -203,12 +319,49 @@ impl SystemDb {
         Ok(result)
     }
 
+    /// Get a document record by its path relative to the data directory.
+    pub fn get_by_path(&self, collection: &str, path: &str) -> Result<Option<DocumentRecord>> {
+        let conn = self.conn();
+        let result = conn.query_row(
+            &format!("SELECT id, collection, path, {DATA_JSON} FROM documents WHERE collection = ?1 AND path = ?2"),
+            params![collection, path],
+            |row| {
+                Ok(DocumentRecord {
+                    id: row.get(0)?,
+                    collection: row.get(1)?,
+                    path: row.get(2)?,
+                    data_json: row.get(3)?,
+                })
+            },
+        ).optional()?;
+        Ok(result)
+    }
+
+    /// Get a document record whose path matches `path` ignoring case (with
+    /// Unicode case folding) and Unicode normalization form.
+    pub fn get_by_path_nocase(&self, collection: &str, path: &str) -> Result<Option<DocumentRecord>> {
+        let conn = self.conn();
+        let result = conn.query_row(
+            &format!("SELECT id, collection, path, {DATA_JSON} FROM documents WHERE collection = ?1 AND grounddb_fold_path(path) = grounddb_fold_path(?2)"),
+            params![collection, path],
+            |row| {
+                Ok(DocumentRecord {
+                    id: row.get(0)?,
+                    collection: row.get(1)?,
+                    path: row.get(2)?,
+                    data_json: row.get(3)?,
+                })
+            },
+        ).optional()?;
+        Ok(result)
+    }
+
     /// List all documents in a collection.
     pub fn list_documents(&self, collection: &str) -> Result<Vec<DocumentRecord>> {
         let conn = self.conn();
-        let mut stmt = conn.prepare(
-            "SELECT id, collection, path, data_json FROM documents WHERE collection = ?1 ORDER BY id",
-        )?;
+        let mut stmt = conn.prepare(&format!(
+            "SELECT id, collection, path, {DATA_JSON} FROM documents WHERE collection = ?1 ORDER BY id",
+        ))?;
         let rows = stmt.query_map(params![collection], |row| {
             Ok(DocumentRecord {
                 id: row.get(0)?,
@@ -225,15 +378,133 @@ impl SystemDb {
         Ok(docs)
     }
 
-    /// Delete a document from the index.
+    /// List documents in a collection with only the given data fields extracted
+    /// in SQL, so the full `data_json` never has to be parsed.
+    pub fn list_document_fields(
+        &self,
+        collection: &str,
+        fields: &[&str],
+    ) -> Result<Vec<ProjectedRecord>> {
+        self.query_projected(collection, None, fields)
+    }
+
+    /// Get a single document with only the given data fields extracted.
+    pub fn get_document_fields(
+        &self,
+        collection: &str,
+        id: &str,
+        fields: &[&str],
+    ) -> Result<Option<ProjectedRecord>> {
+        Ok(self.query_projected(collection, Some(id), fields)?.into_iter().next())
+    }
+
+    fn query_projected(
+        &self,
+        collection: &str,
+        id: Option<&str>,
+        fields: &[&str],
+    ) -> Result<Vec<ProjectedRecord>> {
+        let mut bindings: Vec<String> = vec![collection.to_string()];
+        let extracts: Vec<String> = fields
+            .iter()
+            .map(|field| {
+                bindings.push(format!("$.\"{}\"", field.replace('"', "")));
+                // `->` keeps JSON types (json_extract turns true into 1)
+                format!("({DATA_JSON} -> ?{})", bindings.len())
+            })
+            .collect();
+        let mut sql = format!(
+            "SELECT id, path, created_at, modified_at, json_array({}) FROM documents WHERE collection = ?1",
+            extracts.join(", ")
+        );
+        if let Some(id) = id {
+            bindings.push(id.to_string());
+            sql.push_str(&format!(" AND id = ?{}", bindings.len()));
+        }
+        sql.push_str(" ORDER BY id");
+
+        let conn = self.conn();
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map(rusqlite::params_from_iter(bindings.iter()), |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, Option<String>>(2)?,
+                row.get::<_, Option<String>>(3)?,
+                row.get::<_, String>(4)?,
+            ))
+        })?;
+
+        let mut records = Vec::new();
+        for row in rows {
+            let (id, path, created_at, modified_at, values_json) = row?;
+            let values: Vec<serde_json::Value> = serde_json::from_str(&values_json)?;
+            let mut data = serde_json::Map::new();
+            for (field, value) in fields.iter().zip(values) {
+                // Absent fields extract as NULL; leave them out
+                if !value.is_null() {
+                    data.insert(field.to_string(), value);
+                }
+            }
+            records.push(ProjectedRecord {
+                id,
+                path,
+                created_at,
+                modified_at,
+                fields: data,
+            });
+        }
+        Ok(records)
+    }
+
+    /// Get the indexed body text of a document, if it has one.
+    pub fn get_document_content(&self, collection: &str, id: &str) -> Result<Option<String>> {
+        let conn = self.conn();
+        let result = conn
+            .query_row(
+                "SELECT content_text FROM documents WHERE collection = ?1 AND id = ?2",
+                params![collection, id],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(result.flatten())
+    }
+
+    /// Delete a document from the index, leaving a tombstone (see
+    /// [`SystemDb::deletions_since`]) if it was indexed.
     pub fn delete_document(&self, collection: &str, id: &str) -> Result<()> {
-        self.conn().execute(
+        let conn = self.conn();
+        let removed = conn.execute(
             "DELETE FROM documents WHERE collection = ?1 AND id = ?2",
             params![collection, id],
         )?;
+        if removed > 0 {
+            conn.execute(
+                "INSERT OR REPLACE INTO deletions (id, collection, deleted_at) VALUES (?1, ?2, ?3)",
+                params![id, collection, chrono::Utc::now().to_rfc3339()],
+            )?;
+        }
         Ok(())
     }
 
+    /// Ids and deletion times (RFC 3339, UTC) of documents deleted from
+    /// `collection` after `since`, oldest first. `since` must be an RFC 3339
+    /// UTC timestamp as produced by `to_rfc3339()`.
+    pub fn deletions_since(&self, collection: &str, since: &str) -> Result<Vec<(String, String)>> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare(
+            "SELECT id, deleted_at FROM deletions
+             WHERE collection = ?1 AND deleted_at > ?2
+             ORDER BY deleted_at, id",
+        )?;
+        let rows = stmt.query_map(params![collection, since], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        let mut deletions = Vec::new();
+        for row in rows {
+            deletions.push(row?);
+        }
+        Ok(deletions)
+    }
+
     /// Find all documents that reference a given target document.
     pub fn find_references(
         &self,
@@ -243,10 +514,13 @@ impl SystemDb {
         let pattern = format!("%\"{}\"%" , target_id);
         let conn = self.conn();
 
-        let mut stmt = conn.prepare(
-            "SELECT id, collection, path, data_json FROM documents
-             WHERE collection != ?1 AND data_json LIKE ?2",
-        )?;
+        let mut stmt = conn.prepare(&format!(
+            "SELECT id, collection, path, data_json FROM (
+                 SELECT id, collection, path, {DATA_JSON} AS data_json FROM documents
+                 WHERE collection != ?1
+             )
+             WHERE data_json LIKE ?2",
+        ))?;
         let rows = stmt.query_map(params![target_collection, pattern], |row| {
             Ok(DocumentRecord {
                 id: row.get(0)?,
@@ -263,6 +537,56 @@ impl SystemDb {
         Ok(docs)
     }
 
+    /// Find documents in `source_collection` whose ref field `field` points at
+    /// `target_collection/target_id`. Matches plain string refs and polymorphic
+    /// `{ type, id }` refs (whose `type`, when present, must be the target),
+    /// either as the field's value or as an element of a list of refs.
+    pub fn find_field_references(
+        &self,
+        source_collection: &str,
+        field: &str,
+        target_collection: &str,
+        target_id: &str,
+    ) -> Result<Vec<DocumentRecord>> {
+        let path = format!("$.\"{}\"", field.replace('"', ""));
+        let conn = self.conn();
+
+        let mut stmt = conn.prepare(&format!(
+            "SELECT id, collection, path, data_json FROM (
+                 SELECT id, collection, path, {DATA_JSON} AS data_json FROM documents
+                 WHERE collection = ?1
+             )
+             WHERE (json_extract(data_json, ?2) = ?4
+                    OR (json_extract(data_json, ?2 || '.id') = ?4
+                        AND coalesce(json_extract(data_json, ?2 || '.type'), ?3) = ?3)
+                    OR (json_type(data_json, ?2) = 'array'
+                        AND EXISTS (
+                            SELECT 1 FROM json_each(data_json, ?2) AS item
+                            WHERE (item.type = 'text' AND item.value = ?4)
+                               OR (item.type = 'object'
+                                   AND json_extract(item.value, '$.id') = ?4
+                                   AND coalesce(json_extract(item.value, '$.type'), ?3) = ?3))))
+             ORDER BY id",
+        ))?;
+        let rows = stmt.query_map(
+            params![source_collection, path, target_collection, target_id],
+            |row| {
+                Ok(DocumentRecord {
+                    id: row.get(0)?,
+                    collection: row.get(1)?,
+                    path: row.get(2)?,
+                    data_json: row.get(3)?,
+                })
+            },
+        )?;
+
+        let mut docs = Vec::new();
+        for row in rows {
+            docs.push(row?);
+        }
+        Ok(docs)
+    }
+
     /// Delete all documents in a collection from the index.
     pub fn delete_collection_documents(&self, collection: &str) -> Result<()> {
         self.conn().execute(
@@ -272,6 +596,37 @@ impl SystemDb {
         Ok(())
     }
 
+    // ── Document Metadata ────────────────────────────────────────────
+
+    /// Get one sidecar metadata value (JSON text) for a document.
+    pub fn get_document_meta(&self, collection: &str, id: &str, key: &str) -> Result<Option<String>> {
+        let conn = self.conn();
+        let result = conn.query_row(
+            "SELECT value_json FROM document_meta WHERE collection = ?1 AND id = ?2 AND key = ?3",
+            params![collection, id, key],
+            |row| row.get(0),
+        ).optional()?;
+        Ok(result)
+    }
+
+    /// Store one sidecar metadata value (JSON text) for a document.
+    pub fn set_document_meta(&self, collection: &str, id: &str, key: &str, value_json: &str) -> Result<()> {
+        self.conn().execute(
+            "INSERT OR REPLACE INTO document_meta (collection, id, key, value_json) VALUES (?1, ?2, ?3, ?4)",
+            params![collection, id, key, value_json],
+        )?;
+        Ok(())
+    }
+
+    /// Remove all sidecar metadata for a document.
+    pub fn delete_document_meta(&self, collection: &str, id: &str) -> Result<()> {
+        self.conn().execute(
+            "DELETE FROM document_meta WHERE collection = ?1 AND id = ?2",
+            params![collection, id],
+        )?;
+        Ok(())
+    }
+
     // ── Directory Hashes ─────────────────────────────────────────────
 
     /// Get the stored directory hash for a collection.
@@ -341,6 +696,41 @@ impl SystemDb {
         Ok(())
     }
 
+    /// Delete a view's cached rows and metadata.
+    pub fn delete_view(&self, view_name: &str) -> Result<()> {
+        let conn = self.conn();
+        for table in ["view_data", "view_metadata"] {
+            conn.execute(
+                &format!("DELETE FROM {table} WHERE view_name = ?1"),
+                params![view_name],
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Move a view's cached rows and metadata to a new name, replacing any
+    /// already stored under it.
+    pub fn rename_view(&self, from: &str, to: &str) -> Result<()> {
+        let conn = self.conn();
+        for table in ["view_data", "view_metadata"] {
+            conn.execute(
+                &format!("UPDATE OR REPLACE {table} SET view_name = ?2 WHERE view_name = ?1"),
+                params![from, to],
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Create (if missing) an index named `name` on `expression` over one
+    /// collection's documents.
+    pub fn create_expression_index(&self, name: &str, expression: &str) -> Result<()> {
+        let name = name.replace('"', "\"\"");
+        self.conn().execute_batch(&format!(
+            "CREATE INDEX IF NOT EXISTS \"{name}\" ON documents(collection, {expression})"
+        ))?;
+        Ok(())
+    }
+
     // ── Transaction Support ──────────────────────────────────────────
 
     /// Begin a transaction.
@@ -361,17 +751,62 @@ impl SystemDb {
         Ok(())
     }
 
+    // ── Snapshots ────────────────────────────────────────────────────
+
+    /// Write a consistent copy of the whole database to `path`.
+    pub fn backup_to(&self, path: &Path) -> Result<()> {
+        self.conn()
+            .execute("VACUUM INTO ?1", params![path.to_string_lossy()])?;
+        Ok(())
+    }
+
+    /// Replace the contents of every table with those of the database at `path`
+    /// (typically written by `backup_to`). Runs in a single transaction.
+    pub fn restore_from(&self, path: &Path) -> Result<()> {
+        let conn = self.conn();
+        conn.execute("ATTACH DATABASE ?1 AS snapshot", params![path.to_string_lossy()])?;
+        let result = conn.execute_batch(
+            "
+            BEGIN;
+            DELETE FROM main.schema_history;
+            INSERT INTO main.schema_history SELECT * FROM snapshot.schema_history;
+            DELETE FROM main.migrations;
+            INSERT INTO main.migrations SELECT * FROM snapshot.migrations;
+            DELETE FROM main.documents;
+            INSERT INTO main.documents SELECT * FROM snapshot.documents;
+            DELETE FROM main.view_data;
+            INSERT INTO main.view_data SELECT * FROM snapshot.view_data;
+            DELETE FROM main.view_metadata;
+            INSERT INTO main.view_metadata SELECT * FROM snapshot.view_metadata;
+            DELETE FROM main.directory_hashes;
+            INSERT INTO main.directory_hashes SELECT * FROM snapshot.directory_hashes;
+            DELETE FROM main.deletions;
+            INSERT INTO main.deletions SELECT * FROM snapshot.deletions;
+            DELETE FROM main.document_meta;
+            INSERT INTO main.document_meta SELECT * FROM snapshot.document_meta;
+            COMMIT;
+            ",
+        );
+        if result.is_err() {
+            let _ = conn.execute_batch("ROLLBACK");
+        }
+        conn.execute_batch("DETACH DATABASE snapshot")?;
+        result?;
+        Ok(())
+    }
+
     // ── SQL Query Execution (for views) ──────────────────────────────
 
     /// Execute a SQL query against the documents table, returning results as
     /// a list of JSON objects. This powers the view engine.
     ///
     /// `params` is a list of `(":name", value)` pairs for named parameter binding.
-    pub fn query_documents_sql(
+    pub fn query_documents_sql<V: rusqlite::types::ToSql>(
         &self,
         sql: &str,
-        params_map: &HashMap<String, String>,
+        params_map: &HashMap<String, V>,
     ) -> Result<Vec<serde_json::Value>> {
+        let span = OpSpan::query(sql);
         let conn = self.conn();
         let mut stmt = conn.prepare(sql)
             .map_err(|e| GroundDbError::SqlParse(format!("Failed to prepare SQL: {e}")))?;
@@ -382,7 +817,7 @@ impl SystemDb {
             .collect();
 
         // Build named parameter bindings for rusqlite
-        let named_params: Vec<(String, String)> = params_map
+        let named_params: Vec<(String, &V)> = params_map
             .iter()
             .map(|(k, v)| {
                 let key = if k.starts_with(':') {
@@ -390,12 +825,12 @@ impl SystemDb {
                 } else {
                     format!(":{k}")
                 };
-                (key, v.clone())
+                (key, v)
             })
             .collect();
         let param_refs: Vec<(&str, &dyn rusqlite::types::ToSql)> = named_params
             .iter()
-            .map(|(k, v)| (k.as_str(), v as &dyn rusqlite::types::ToSql))
+            .map(|(k, v)| (k.as_str(), *v as &dyn rusqlite::types::ToSql))
             .collect();
 
         let rows = stmt.query_map(param_refs.as_slice(), |row| {
@@ -424,10 +859,41 @@ impl SystemDb {
         for row in rows {
             results.push(row?);
         }
+        span.finish(results.len());
         Ok(results)
     }
 }
 
+/// Register the SQL functions that queries over the documents table rely on.
+fn register_functions(conn: &Connection) -> Result<()> {
+    use rusqlite::functions::FunctionFlags;
+    conn.create_scalar_function(
+        "grounddb_decompress",
+        1,
+        FunctionFlags::SQLITE_UTF8 | FunctionFlags::SQLITE_DETERMINISTIC,
+        |ctx| {
+            let blob = ctx.get_raw(0).as_blob()?;
+            let bytes = zstd::decode_all(blob)
+                .map_err(|e| rusqlite::Error::UserFunctionError(Box::new(e)))?;
+            String::from_utf8(bytes).map_err(|e| rusqlite::Error::UserFunctionError(Box::new(e)))
+        },
+    )?;
+    conn.create_scalar_function(
+        "grounddb_fold_path",
+        1,
+        FunctionFlags::SQLITE_UTF8 | FunctionFlags::SQLITE_DETERMINISTIC,
+        |ctx| Ok(fold_path(ctx.get_raw(0).as_str()?)),
+    )?;
+    Ok(())
+}
+
+/// The form two paths share when a case-insensitive filesystem treats them
+/// as the same file: case-folded (`Straße` and `STRASSE` match) and NFC
+/// normalized (a decomposed `é` matches the precomposed one).
+fn fold_path(path: &str) -> String {
+    path.to_uppercase().to_lowercase().nfc().collect()
+}
+
 /// A record from the documents table
 #[derive(Debug, Clone)]
 pub struct DocumentRecord {
@@ -446,6 +912,16 @@ impl DocumentRecord {
     }
 }
 
+/// A record from the documents table with only selected data fields extracted
+#[derive(Debug, Clone)]
+pub struct ProjectedRecord {
+    pub id: String,
+    pub path: String,
+    pub created_at: Option<String>,
+    pub modified_at: Option<String>,
+    pub fields: serde_json::Map<String, serde_json::Value>,
+}
+
 /// Compute a directory hash from a list of (filename, mtime) pairs.
 /// Used for change detection during boot.
 pub fn compute_directory_hash(entries: &[(String, u64)]) -> String {
@@ -545,6 +1021,23 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_get_schema_yaml() {
+        let db = SystemDb::open_in_memory().unwrap();
+        db.record_schema("abc123", "collections: {}").unwrap();
+        db.record_schema("def456", "collections: { users: {} }").unwrap();
+
+        assert_eq!(
+            db.get_schema_yaml("1").unwrap(),
+            Some("collections: {}".to_string())
+        );
+        assert_eq!(
+            db.get_schema_yaml("def456").unwrap(),
+            Some("collections: { users: {} }".to_string())
+        );
+        assert!(db.get_schema_yaml("missing").unwrap().is_none());
+    }
+
     #[test]
     fn test_directory_hashes() {
         let db = SystemDb::open_in_memory().unwrap();
@@ -569,6 +1062,30 @@ mod tests {
         assert!(data.contains("test"));
     }
 
+    #[test]
+    fn test_rename_view() {
+        let db = SystemDb::open_in_memory().unwrap();
+        db.set_view_data("post_feed", "[{\"title\": \"test\"}]").unwrap();
+        db.set_view_metadata("post_feed", "2026-01-01T00:00:00Z", "{}").unwrap();
+        db.set_view_data("feed", "[]").unwrap();
+
+        db.rename_view("post_feed", "feed").unwrap();
+        assert!(db.get_view_data("post_feed").unwrap().is_none());
+        assert!(db.get_view_data("feed").unwrap().unwrap().contains("test"));
+        assert!(db.get_view_metadata("feed").unwrap().is_some());
+    }
+
+    #[test]
+    fn test_delete_view() {
+        let db = SystemDb::open_in_memory().unwrap();
+        db.set_view_data("feed", "[]").unwrap();
+        db.set_view_metadata("feed", "2026-01-01T00:00:00Z", "{}").unwrap();
+
+        db.delete_view("feed").unwrap();
+        assert!(db.get_view_data("feed").unwrap().is_none());
+        assert!(db.get_view_metadata("feed").unwrap().is_none());
+    }
+
     #[test]
     fn test_find_references() {
         let db = SystemDb::open_in_memory().unwrap();
@@ -585,6 +1102,69 @@ mod tests {
         assert_eq!(refs[0].id, "test-post");
     }
 
+    #[test]
+    fn test_compressed_document_round_trip() {
+        let db = SystemDb::open_in_memory().unwrap();
+        db.set_compressed_collections(["posts".to_string()]);
+
+        let body = "All work and no play makes Jack a dull boy. ".repeat(500);
+        let mut data = serde_yaml::Mapping::new();
+        data.insert("title".into(), "Large".into());
+        data.insert("author_id".into(), "alice".into());
+        data.insert("notes".into(), body.into());
+        let data = serde_yaml::Value::Mapping(data);
+        db.upsert_document("large", "posts", "posts/large.md", &data, None, None, None).unwrap();
+
+        let (compressed, stored_len): (bool, usize) = db
+            .conn()
+            .query_row(
+                "SELECT data_compressed, length(data_json) FROM documents WHERE id = 'large'",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert!(compressed);
+        assert!(stored_len < serde_json::to_string(&data).unwrap().len() / 10);
+
+        let doc = db.get_document("posts", "large").unwrap().unwrap();
+        assert_eq!(doc.parse_data().unwrap(), data);
+        // JSON queries see the decompressed data too
+        let refs = db.find_field_references("posts", "author_id", "users", "alice").unwrap();
+        assert_eq!(refs.len(), 1);
+        let projected = db.get_document_fields("posts", "large", &["title"]).unwrap().unwrap();
+        assert_eq!(projected.fields["title"], serde_json::json!("Large"));
+    }
+
+    #[test]
+    fn test_path_lookup_folds_unicode_case_and_normalization() {
+        let db = SystemDb::open_in_memory().unwrap();
+        let data: serde_yaml::Value = serde_yaml::from_str("title: x").unwrap();
+        db.upsert_document("café", "notes", "notes/Caf\u{e9}.md", &data, None, None, None).unwrap();
+        db.upsert_document("straße", "notes", "notes/Straße.md", &data, None, None, None).unwrap();
+
+        let found = |path: &str| db.get_by_path_nocase("notes", path).unwrap().map(|r| r.id);
+        assert_eq!(found("notes/CAFE\u{301}.md").as_deref(), Some("café"));
+        assert_eq!(found("notes/STRASSE.md").as_deref(), Some("straße"));
+        assert_eq!(found("notes/cafe.md"), None);
+    }
+
+    #[test]
+    fn test_projected_fields_keep_json_types() {
+        let db = SystemDb::open_in_memory().unwrap();
+        let data: serde_yaml::Value =
+            serde_yaml::from_str("title: Hi\nactive: true\nrank: 2\ntags: [a, b]").unwrap();
+        db.upsert_document("hi", "posts", "posts/hi.md", &data, None, None, None).unwrap();
+
+        let projected = db
+            .get_document_fields("posts", "hi", &["active", "rank", "tags", "missing"])
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            serde_json::Value::Object(projected.fields),
+            serde_json::json!({ "active": true, "rank": 2, "tags": ["a", "b"] })
+        );
+    }
+
     #[test]
     fn test_compute_directory_hash() {
         let entries = vec![
@@ -0,0 +1,180 @@
+//! `tracing` spans around slow operations (the `tracing` feature).
+//!
+//! Scans, view rebuilds and SQL queries each run inside an [`OpSpan`] named
+//! `grounddb.<operation>`, carrying the collection or view name and, once the
+//! operation finishes, `rows` and `duration_ms`. Without the feature the spans
+//! compile to nothing.
+
+/// An entered span for one operation, closed when dropped
+pub(crate) struct OpSpan {
+    #[cfg(feature = "tracing")]
+    span: tracing::span::EnteredSpan,
+    #[cfg(feature = "tracing")]
+    started: std::time::Instant,
+}
+
+/// Enter a span named `$name` with the field `$field` (a `&str` variable of
+/// that name) and empty `rows` and `duration_ms` fields
+#[cfg(feature = "tracing")]
+macro_rules! op_span {
+    ($name:literal, $field:ident) => {
+        OpSpan {
+            span: tracing::info_span!(
+                $name,
+                $field,
+                rows = tracing::field::Empty,
+                duration_ms = tracing::field::Empty
+            )
+            .entered(),
+            started: std::time::Instant::now(),
+        }
+    };
+}
+
+#[cfg(not(feature = "tracing"))]
+macro_rules! op_span {
+    ($name:literal, $field:ident) => {{
+        let _ = $field;
+        OpSpan {}
+    }};
+}
+
+impl OpSpan {
+    /// Scanning a collection's files into the index
+    pub(crate) fn scan(collection: &str) -> Self {
+        op_span!("grounddb.scan", collection)
+    }
+
+    /// Rebuilding a static view
+    pub(crate) fn view_rebuild(view: &str) -> Self {
+        op_span!("grounddb.view_rebuild", view)
+    }
+
+    /// Running SQL against the documents table
+    pub(crate) fn query(sql: &str) -> Self {
+        op_span!("grounddb.query", sql)
+    }
+
+    /// Record how many rows (or documents) the operation produced and how long
+    /// it took, then close the span.
+    pub(crate) fn finish(self, rows: usize) {
+        #[cfg(feature = "tracing")]
+        {
+            self.span.record("rows", rows as u64);
+            self.span
+                .record("duration_ms", self.started.elapsed().as_secs_f64() * 1000.0);
+        }
+        #[cfg(not(feature = "tracing"))]
+        let _ = rows;
+    }
+}
+
+#[cfg(all(test, feature = "tracing"))]
+mod tests {
+    use crate::Store;
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+    use tempfile::TempDir;
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::{Event, Metadata, Subscriber};
+
+    /// Span name and fields by span id
+    type Spans = Arc<Mutex<HashMap<u64, (String, HashMap<String, String>)>>>;
+
+    /// Records every span's name and fields
+    struct Capture {
+        spans: Spans,
+        next_id: Mutex<u64>,
+    }
+
+    struct Fields<'a>(&'a mut HashMap<String, String>);
+
+    impl Visit for Fields<'_> {
+        fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+            self.0.insert(field.name().to_string(), format!("{value:?}"));
+        }
+
+        fn record_str(&mut self, field: &Field, value: &str) {
+            self.0.insert(field.name().to_string(), value.to_string());
+        }
+    }
+
+    impl Subscriber for Capture {
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, attrs: &Attributes<'_>) -> Id {
+            let mut next_id = self.next_id.lock().unwrap();
+            *next_id += 1;
+            let mut fields = HashMap::new();
+            attrs.record(&mut Fields(&mut fields));
+            self.spans
+                .lock()
+                .unwrap()
+                .insert(*next_id, (attrs.metadata().name().to_string(), fields));
+            Id::from_u64(*next_id)
+        }
+
+        fn record(&self, span: &Id, values: &Record<'_>) {
+            if let Some((_, fields)) = self.spans.lock().unwrap().get_mut(&span.into_u64()) {
+                values.record(&mut Fields(fields));
+            }
+        }
+
+        fn record_follows_from(&self, _: &Id, _: &Id) {}
+        fn event(&self, _: &Event<'_>) {}
+        fn enter(&self, _: &Id) {}
+        fn exit(&self, _: &Id) {}
+    }
+
+    #[test]
+    fn test_view_rebuild_emits_span() {
+        let tmp = TempDir::new().unwrap();
+        let schema = r#"
+collections:
+  users:
+    path: "users/{name}.md"
+    fields:
+      name: { type: string, required: true }
+
+views:
+  user_names:
+    query: |
+      SELECT name FROM users ORDER BY name
+"#;
+        std::fs::write(tmp.path().join("schema.yaml"), schema).unwrap();
+        let spans = Spans::default();
+        let capture = Capture {
+            spans: spans.clone(),
+            next_id: Mutex::new(0),
+        };
+
+        tracing::subscriber::with_default(capture, || {
+            let store = Store::open(tmp.path().to_str().unwrap()).unwrap();
+            spans.lock().unwrap().clear();
+            for name in ["alice", "bob"] {
+                store
+                    .insert_dynamic("users", serde_json::json!({ "name": name }), None)
+                    .unwrap();
+            }
+        });
+
+        let spans = spans.lock().unwrap();
+        let mut rebuilds: Vec<&HashMap<String, String>> = spans
+            .values()
+            .filter(|(name, _)| name == "grounddb.view_rebuild")
+            .map(|(_, fields)| fields)
+            .collect();
+        rebuilds.sort_by_key(|fields| fields["rows"].clone());
+        assert_eq!(rebuilds.len(), 2, "{spans:#?}");
+        assert_eq!(rebuilds[0]["view"], "user_names");
+        assert_eq!(rebuilds[0]["rows"], "1");
+        assert_eq!(rebuilds[1]["rows"], "2");
+        assert!(rebuilds[1].contains_key("duration_ms"));
+        assert!(spans.values().any(|(name, fields)| {
+            name == "grounddb.query" && fields["sql"].contains("FROM documents")
+        }));
+    }
+}
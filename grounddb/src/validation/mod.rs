@@ -1,5 +1,24 @@
 use crate::error::{GroundDbError, Result};
 use crate::schema::{CollectionDefinition, FieldDefinition, FieldType, SchemaDefinition};
+use std::sync::Arc;
+
+/// A domain rule the schema can't express (e.g. "end after start"), registered
+/// with [`Store::add_validator`](crate::Store::add_validator). It runs on every
+/// write after the built-in checks; the messages it returns are errors that
+/// reject the write regardless of the collection's `strict` setting.
+pub trait Validator: Send + Sync {
+    /// Check `data` (with defaults applied) for a document in `collection`.
+    fn validate(&self, collection: &str, data: &serde_yaml::Value) -> Vec<String>;
+}
+
+impl<F> Validator for F
+where
+    F: Fn(&str, &serde_yaml::Value) -> Vec<String> + Send + Sync,
+{
+    fn validate(&self, collection: &str, data: &serde_yaml::Value) -> Vec<String> {
+        self(collection, data)
+    }
+}
 
 /// Result of validating a document
 #[derive(Debug, Clone)]
@@ -46,20 +65,29 @@ pub fn validate_document(
     for (field_name, field_def) in &collection.fields {
         let value = mapping.get(serde_yaml::Value::String(field_name.clone()));
 
-        if field_def.required && (value.is_none() || value == Some(&serde_yaml::Value::Null)) {
-            if field_def.default.is_none() {
-                add_issue(
-                    &mut result,
-                    collection.strict,
-                    format!("Required field '{field_name}' is missing"),
-                );
+        match value {
+            None => {
+                if field_def.required && field_def.default.is_none() {
+                    add_issue(
+                        &mut result,
+                        collection.strict,
+                        format!("Required field '{field_name}' is missing"),
+                    );
+                }
             }
-            continue;
-        }
-
-        if let Some(val) = value {
-            if *val != serde_yaml::Value::Null {
-                validate_field_value(schema, field_name, field_def, val, collection.strict, &mut result);
+            // Explicit null is distinct from absence: allowed only for nullable fields
+            Some(serde_yaml::Value::Null) => {
+                if !field_def.is_nullable() && field_def.default.is_none() {
+                    let message = if field_def.required {
+                        format!("Required field '{field_name}' is missing")
+                    } else {
+                        format!("Field '{field_name}' may not be null")
+                    };
+                    add_issue(&mut result, collection.strict, message);
+                }
+            }
+            Some(val) => {
+                validate_field_value(schema, field_name, field_def, val, collection, &mut result);
             }
         }
     }
@@ -68,7 +96,9 @@ pub fn validate_document(
     if !collection.additional_properties {
         for key in mapping.keys() {
             if let serde_yaml::Value::String(key_str) = key {
-                if !collection.fields.contains_key(key_str) {
+                if !collection.fields.contains_key(key_str)
+                    && key_str != crate::document::CREATED_AT_KEY
+                {
                     add_issue(
                         &mut result,
                         collection.strict,
@@ -82,8 +112,8 @@ pub fn validate_document(
     result
 }
 
-/// Apply default values to a document's data. Modifies the data in place.
-/// Returns the data with defaults applied.
+/// Apply default values to a document's data and canonicalize case-insensitive
+/// enum values to their declared variant. Modifies the data in place.
 pub fn apply_defaults(
     collection: &CollectionDefinition,
     data: &mut serde_yaml::Value,
@@ -95,27 +125,53 @@ pub fn apply_defaults(
 
     for (field_name, field_def) in &collection.fields {
         let key = serde_yaml::Value::String(field_name.clone());
+        // An explicit null on a field declared `nullable: true` is a value, not a gap to fill
         let has_value = mapping
             .get(&key)
-            .map(|v| *v != serde_yaml::Value::Null)
+            .map(|v| *v != serde_yaml::Value::Null || field_def.nullable == Some(true))
             .unwrap_or(false);
 
         if !has_value {
             if let Some(default) = &field_def.default {
                 mapping.insert(key, default.clone());
             }
+        } else if let Some(serde_yaml::Value::String(s)) = mapping.get(&key) {
+            if let Some(variant) = field_def.enum_variant(s, collection.enum_case_insensitive) {
+                if variant != s {
+                    let variant = variant.to_string();
+                    mapping.insert(key, serde_yaml::Value::String(variant));
+                }
+            }
         }
     }
 }
 
+/// Problems with a single value for `field_def`, e.g. a schema `default`.
+/// Reported regardless of the collection's `strict` setting.
+pub(crate) fn field_value_issues(
+    schema: &SchemaDefinition,
+    field_name: &str,
+    field_def: &FieldDefinition,
+    value: &serde_yaml::Value,
+    collection: &CollectionDefinition,
+) -> Vec<String> {
+    let mut result = ValidationResult {
+        errors: Vec::new(),
+        warnings: Vec::new(),
+    };
+    validate_field_value(schema, field_name, field_def, value, collection, &mut result);
+    result.errors.into_iter().chain(result.warnings).collect()
+}
+
 fn validate_field_value(
     schema: &SchemaDefinition,
     field_name: &str,
     field_def: &FieldDefinition,
     value: &serde_yaml::Value,
-    strict: bool,
+    collection: &CollectionDefinition,
     result: &mut ValidationResult,
 ) {
+    let strict = collection.strict;
     match &field_def.field_type {
         FieldType::String => {
             if !value.is_string() {
@@ -127,21 +183,7 @@ fn validate_field_value(
                 return;
             }
 
-            // Check enum values
-            if let Some(enum_values) = &field_def.enum_values {
-                if let Some(s) = value.as_str() {
-                    if !enum_values.contains(&s.to_string()) {
-                        add_issue(
-                            result,
-                            strict,
-                            format!(
-                                "Field '{field_name}' value '{}' is not in enum: {:?}",
-                                s, enum_values
-                            ),
-                        );
-                    }
-                }
-            }
+            check_enum(field_name, field_def, value, collection, result);
         }
         FieldType::Number => {
             if !value.is_number() {
@@ -150,7 +192,9 @@ fn validate_field_value(
                     strict,
                     format!("Field '{field_name}' expected number, got {}", type_name(value)),
                 );
+                return;
             }
+            check_enum(field_name, field_def, value, collection, result);
         }
         FieldType::Boolean => {
             if !value.is_bool() {
@@ -159,7 +203,9 @@ fn validate_field_value(
                     strict,
                     format!("Field '{field_name}' expected boolean, got {}", type_name(value)),
                 );
+                return;
             }
+            check_enum(field_name, field_def, value, collection, result);
         }
         FieldType::Date | FieldType::Datetime => {
             // Dates are stored as strings in YAML
@@ -179,7 +225,12 @@ fn validate_field_value(
                     format!("Field '{field_name}' expected list, got {}", type_name(value)),
                 );
             }
-            // Could validate items here but keeping it simple for v1
+            // Items of a list of refs must each be a valid ref
+            if let (Some(ref_def), Some(items)) = (field_def.ref_definition(), value.as_sequence()) {
+                for (i, item) in items.iter().enumerate() {
+                    check_ref_shape(&format!("{field_name}[{i}]"), ref_def, item, result, strict);
+                }
+            }
         }
         FieldType::Object => {
             if !value.is_mapping() {
@@ -190,39 +241,7 @@ fn validate_field_value(
                 );
             }
         }
-        FieldType::Ref => {
-            // Refs can be strings (single target) or mappings (polymorphic)
-            match &field_def.target {
-                Some(crate::schema::RefTarget::Single(_)) => {
-                    if !value.is_string() {
-                        add_issue(
-                            result,
-                            strict,
-                            format!(
-                                "Field '{field_name}' (ref) expected string ID, got {}",
-                                type_name(value)
-                            ),
-                        );
-                    }
-                }
-                Some(crate::schema::RefTarget::Multiple(_)) => {
-                    // Polymorphic ref: either a string or a mapping with type+id
-                    if !value.is_string() && !value.is_mapping() {
-                        add_issue(
-                            result,
-                            strict,
-                            format!(
-                                "Field '{field_name}' (polymorphic ref) expected string or {{type, id}} mapping, got {}",
-                                type_name(value)
-                            ),
-                        );
-                    }
-                }
-                None => {
-                    // Already caught by schema validation, but be defensive
-                }
-            }
-        }
+        FieldType::Ref => check_ref_shape(field_name, field_def, value, result, strict),
         FieldType::Custom(type_name_str) => {
             // Validate against reusable type definition
             if let Some(type_fields) = schema.get_custom_type(type_name_str) {
@@ -259,6 +278,86 @@ fn validate_field_value(
     }
 }
 
+/// Check that `value` has the shape of a ref described by `ref_def`: a string
+/// ID, or for polymorphic refs a string or `{type, id}` mapping.
+fn check_ref_shape(
+    field_name: &str,
+    ref_def: &FieldDefinition,
+    value: &serde_yaml::Value,
+    result: &mut ValidationResult,
+    strict: bool,
+) {
+    // Refs can be strings (single target) or mappings (polymorphic)
+    match &ref_def.target {
+        Some(crate::schema::RefTarget::Single(_)) if ref_def.denormalize.is_some() => {
+            // Denormalized ref: `{ id, <copied fields> }`, or a bare id before expansion
+            let has_id = value.get("id").is_some_and(|id| id.is_string());
+            if !value.is_string() && !has_id {
+                add_issue(
+                    result,
+                    strict,
+                    format!(
+                        "Field '{field_name}' (denormalized ref) expected string ID or {{id, ...}} mapping, got {}",
+                        type_name(value)
+                    ),
+                );
+            }
+        }
+        Some(crate::schema::RefTarget::Single(_)) => {
+            if !value.is_string() {
+                add_issue(
+                    result,
+                    strict,
+                    format!(
+                        "Field '{field_name}' (ref) expected string ID, got {}",
+                        type_name(value)
+                    ),
+                );
+            }
+        }
+        Some(crate::schema::RefTarget::Multiple(_)) => {
+            // Polymorphic ref: either a string or a mapping with type+id
+            if !value.is_string() && !value.is_mapping() {
+                add_issue(
+                    result,
+                    strict,
+                    format!(
+                        "Field '{field_name}' (polymorphic ref) expected string or {{type, id}} mapping, got {}",
+                        type_name(value)
+                    ),
+                );
+            }
+        }
+        None => {
+            // Already caught by schema validation, but be defensive
+        }
+    }
+}
+
+/// Report a scalar value that isn't one of the field's enum values
+fn check_enum(
+    field_name: &str,
+    field_def: &FieldDefinition,
+    value: &serde_yaml::Value,
+    collection: &CollectionDefinition,
+    result: &mut ValidationResult,
+) {
+    let Some(enum_values) = &field_def.enum_values else {
+        return;
+    };
+    if !field_def.enum_allows(value, collection.enum_case_insensitive) {
+        let shown = match value {
+            serde_yaml::Value::String(s) => s.clone(),
+            other => serde_yaml::to_string(other).unwrap_or_default().trim().to_string(),
+        };
+        add_issue(
+            result,
+            collection.strict,
+            format!("Field '{field_name}' value '{shown}' is not in enum: {enum_values:?}"),
+        );
+    }
+}
+
 fn add_issue(result: &mut ValidationResult, strict: bool, message: String) {
     if strict {
         result.errors.push(message);
@@ -284,9 +383,24 @@ pub fn validate_and_prepare(
     schema: &SchemaDefinition,
     collection: &CollectionDefinition,
     data: &mut serde_yaml::Value,
+) -> Result<Vec<String>> {
+    validate_and_prepare_with(schema, "", collection, data, &[])
+}
+
+/// Like [`validate_and_prepare`], then runs `validators` for the collection
+/// named `collection_name`; any message they return fails the validation.
+pub fn validate_and_prepare_with(
+    schema: &SchemaDefinition,
+    collection_name: &str,
+    collection: &CollectionDefinition,
+    data: &mut serde_yaml::Value,
+    validators: &[Arc<dyn Validator>],
 ) -> Result<Vec<String>> {
     apply_defaults(collection, data);
-    let result = validate_document(schema, collection, data);
+    let mut result = validate_document(schema, collection, data);
+    for validator in validators {
+        result.errors.extend(validator.validate(collection_name, data));
+    }
 
     if !result.is_ok() {
         return Err(GroundDbError::Validation(format!(
@@ -331,6 +445,7 @@ collections:
       author_id: { type: ref, target: users, required: true }
       date: { type: date, required: true }
       tags: { type: list, items: string }
+      contributors: { type: list, items: ref, target: users }
       status: { type: string, enum: [draft, published, archived], default: draft }
     content: true
     additional_properties: false
@@ -374,6 +489,95 @@ collections:
         assert!(result.errors.iter().any(|e| e.contains("email")));
     }
 
+    #[test]
+    fn test_nullable_distinct_from_required() {
+        let schema = parse_schema_str(
+            r#"
+collections:
+  users:
+    path: "users/{name}.md"
+    fields:
+      name: { type: string, required: true }
+  posts:
+    path: "posts/{title}.md"
+    fields:
+      title: { type: string, required: true }
+      reviewer_id: { type: ref, target: users, required: true, nullable: true }
+      summary: { type: string, nullable: false }
+    strict: true
+"#,
+        )
+        .unwrap();
+        let collection = &schema.collections["posts"];
+
+        // Required-but-nullable: explicit null (as after nullify) is fine...
+        let data: serde_yaml::Value =
+            serde_yaml::from_str("title: Hello\nreviewer_id: null").unwrap();
+        let result = validate_document(&schema, collection, &data);
+        assert!(result.is_ok(), "Errors: {:?}", result.errors);
+
+        // ...but absence is not
+        let data: serde_yaml::Value = serde_yaml::from_str("title: Hello").unwrap();
+        let result = validate_document(&schema, collection, &data);
+        assert!(result.errors.iter().any(|e| e.contains("reviewer_id")));
+
+        // Required non-nullable rejects null
+        let data: serde_yaml::Value =
+            serde_yaml::from_str("title: null\nreviewer_id: alice").unwrap();
+        let result = validate_document(&schema, collection, &data);
+        assert!(result.errors.iter().any(|e| e.contains("title")));
+
+        // Optional non-nullable may be absent but not null
+        let data: serde_yaml::Value =
+            serde_yaml::from_str("title: Hello\nreviewer_id: alice\nsummary: null").unwrap();
+        let result = validate_document(&schema, collection, &data);
+        assert!(result.errors.iter().any(|e| e.contains("'summary' may not be null")));
+    }
+
+    #[test]
+    fn test_enum_case_insensitive() {
+        let schema = parse_schema_str(
+            r#"
+collections:
+  users:
+    path: "users/{name}.md"
+    fields:
+      name: { type: string, required: true }
+      role: { type: string, enum: [admin, member], enum_case_insensitive: true }
+      tier: { type: string, enum: [free, pro] }
+    strict: true
+  members:
+    path: "members/{name}.md"
+    fields:
+      name: { type: string, required: true }
+      role: { type: string, enum: [admin, member] }
+      tier: { type: string, enum: [free, pro], enum_case_insensitive: false }
+    enum_case_insensitive: true
+    strict: true
+"#,
+        )
+        .unwrap();
+
+        // Enabled on the field: accepted and canonicalized on write
+        let users = &schema.collections["users"];
+        let mut data: serde_yaml::Value = serde_yaml::from_str("name: Alice\nrole: Admin").unwrap();
+        validate_and_prepare(&schema, users, &mut data).unwrap();
+        assert_eq!(data["role"], serde_yaml::Value::String("admin".into()));
+
+        // Disabled (the default): still rejected
+        let mut data: serde_yaml::Value = serde_yaml::from_str("name: Alice\ntier: PRO").unwrap();
+        assert!(validate_and_prepare(&schema, users, &mut data).is_err());
+
+        // Collection default applies unless the field overrides it
+        let members = &schema.collections["members"];
+        let mut data: serde_yaml::Value =
+            serde_yaml::from_str("name: Bob\nrole: MEMBER\ntier: free").unwrap();
+        validate_and_prepare(&schema, members, &mut data).unwrap();
+        assert_eq!(data["role"], serde_yaml::Value::String("member".into()));
+        let data: serde_yaml::Value = serde_yaml::from_str("name: Bob\ntier: Free").unwrap();
+        assert!(!validate_document(&schema, members, &data).is_ok());
+    }
+
     #[test]
     fn test_invalid_enum_value() {
         let schema = test_schema();
@@ -388,6 +592,34 @@ collections:
         assert!(result.errors.iter().any(|e| e.contains("superadmin")));
     }
 
+    #[test]
+    fn test_number_and_boolean_enums() {
+        let schema = parse_schema_str(
+            r#"
+collections:
+  tasks:
+    path: "tasks/{id}.md"
+    fields:
+      level: { type: number, enum: [1, 2, 3] }
+      flagged: { type: boolean, enum: [false] }
+    strict: true
+"#,
+        )
+        .unwrap();
+        let tasks = &schema.collections["tasks"];
+
+        let data: serde_yaml::Value = serde_yaml::from_str("level: 2\nflagged: false").unwrap();
+        assert!(validate_document(&schema, tasks, &data).is_ok());
+        // Compared by value, not by text
+        let data: serde_yaml::Value = serde_yaml::from_str("level: 3.0").unwrap();
+        assert!(validate_document(&schema, tasks, &data).is_ok());
+
+        let data: serde_yaml::Value = serde_yaml::from_str("level: 4\nflagged: true").unwrap();
+        let result = validate_document(&schema, tasks, &data);
+        assert!(result.errors.iter().any(|e| e.contains("'level' value '4' is not in enum")));
+        assert!(result.errors.iter().any(|e| e.contains("'flagged' value 'true' is not in enum")));
+    }
+
     #[test]
     fn test_type_mismatch() {
         let schema = test_schema();
@@ -536,4 +768,27 @@ collections:
         assert!(!result.is_ok());
         assert!(result.errors.iter().any(|e| e.contains("tags")));
     }
+
+    #[test]
+    fn test_list_of_refs_validated_element_wise() {
+        let schema = test_schema();
+        let collection = &schema.collections["posts"];
+        let data: serde_yaml::Value = serde_yaml::from_str(
+            "title: Test\nauthor_id: alice\ndate: '2026-01-01'\ncontributors: [bob, carol]",
+        )
+        .unwrap();
+        let result = validate_document(&schema, collection, &data);
+        assert!(result.is_ok(), "Errors: {:?}", result.errors);
+
+        let data: serde_yaml::Value = serde_yaml::from_str(
+            "title: Test\nauthor_id: alice\ndate: '2026-01-01'\ncontributors: [bob, 42]",
+        )
+        .unwrap();
+        let result = validate_document(&schema, collection, &data);
+        assert!(
+            result.errors.iter().any(|e| e.contains("contributors[1]")),
+            "Errors: {:?}",
+            result.errors
+        );
+    }
 }
@@ -1,14 +1,17 @@
 use crate::error::{GroundDbError, Result};
-use crate::schema::{SchemaDefinition, ViewDefinition, ViewType};
-use crate::system_db::SystemDb;
+use crate::schema::{is_identifier, FieldType, SchemaDefinition, ViewDefinition, ViewType};
+use crate::system_db::{SystemDb, DATA_JSON};
 use sqlparser::ast::{
     Expr, Query, Select, SelectItem, SetExpr, Statement, TableFactor, TableWithJoins,
 };
 use sqlparser::dialect::GenericDialect;
 use sqlparser::parser::Parser;
+use sqlparser::tokenizer::{Token, Tokenizer};
+use std::cmp::Ordering;
 use std::collections::{HashMap, HashSet};
-use std::path::Path;
-use std::sync::Mutex;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 /// A reference to a table/collection in a FROM or JOIN clause, with optional alias.
 #[derive(Debug, Clone)]
@@ -29,6 +32,8 @@ pub struct ParsedView {
     pub columns: Vec<ViewColumn>,
     /// Whether the view has a LIMIT clause
     pub limit: Option<u64>,
+    /// Whether the view has an ORDER BY clause
+    pub has_order_by: bool,
     /// Buffer multiplier (e.g., 2.0 for "2x")
     pub buffer_multiplier: f64,
     /// Whether to materialize this view
@@ -37,6 +42,12 @@ pub struct ParsedView {
     pub is_query_template: bool,
     /// Parameter names for query templates
     pub param_names: Vec<String>,
+    /// Every identifier appearing anywhere in the SQL (lowercased). A superset of
+    /// the fields the view reads in SELECT/WHERE/JOIN/ORDER BY.
+    pub identifiers: HashSet<String>,
+    /// Whether the SQL contains `*` (wildcard select, `COUNT(*)`, arithmetic),
+    /// in which case any field may be read
+    pub uses_wildcard: bool,
 }
 
 impl ParsedView {
@@ -44,6 +55,15 @@ impl ParsedView {
     pub fn referenced_collections(&self) -> HashSet<String> {
         self.table_refs.iter().map(|r| r.collection.clone()).collect()
     }
+
+    /// Whether the view may read any of the given fields. Conservative: a field
+    /// counts as referenced if its name appears anywhere in the SQL.
+    pub fn references_any_field(&self, fields: &HashSet<String>) -> bool {
+        self.uses_wildcard
+            || fields
+                .iter()
+                .any(|f| self.identifiers.contains(&f.to_lowercase()))
+    }
 }
 
 /// A column in a view result
@@ -54,11 +74,43 @@ pub struct ViewColumn {
     pub source_field: Option<String>,
 }
 
+/// Build counters for a single view, updated on every rebuild.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ViewStats {
+    /// Number of rebuilds since the engine was created
+    pub build_count: u64,
+    /// Wall-clock duration of the most recent rebuild
+    pub last_build_duration: Duration,
+    /// Number of rows produced by the most recent rebuild
+    pub last_row_count: usize,
+}
+
+/// A view's configuration and cache state, as reported by `Store::views`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ViewStatus {
+    pub name: String,
+    pub materialize: bool,
+    /// Buffer multiplier (1.0 when the view has no `buffer`)
+    pub buffer_multiplier: f64,
+    pub is_query_template: bool,
+    /// Collections the view reads, sorted by name
+    pub collections: Vec<String>,
+    /// Rows in the in-memory cache; `None` for query templates and for static
+    /// views that haven't been built
+    pub cached_rows: Option<usize>,
+    /// RFC 3339 time of the last rebuild recorded in the system database
+    pub last_built: Option<String>,
+    /// Whether a static view's cache is missing or one of its collections has
+    /// changed on disk since it was indexed. Always `false` for query templates.
+    pub stale: bool,
+}
+
 /// The view engine maintains view state and rebuilds views from the document index.
 /// Uses Mutex on view_data so the cache can be updated from shared (&self) references.
 pub struct ViewEngine {
     views: HashMap<String, ParsedView>,
-    view_data: Mutex<HashMap<String, Vec<serde_json::Value>>>,
+    view_data: Mutex<HashMap<String, Arc<Vec<serde_json::Value>>>>,
+    stats: Mutex<HashMap<String, ViewStats>>,
 }
 
 impl ViewEngine {
@@ -68,12 +120,14 @@ impl ViewEngine {
 
         for (name, view_def) in &schema.views {
             let parsed = parse_view_query(name, view_def)?;
+            validate_view_columns(&parsed, schema)?;
             views.insert(name.clone(), parsed);
         }
 
         Ok(ViewEngine {
             views,
             view_data: Mutex::new(HashMap::new()),
+            stats: Mutex::new(HashMap::new()),
         })
     }
 
@@ -91,13 +145,31 @@ impl ViewEngine {
             .collect()
     }
 
+    /// Like `affected_views`, but for an in-place update that only changed
+    /// `changed_fields`: views over the collection that never mention any of
+    /// those fields are skipped, since their rows can't have changed.
+    pub fn affected_views_for_fields(
+        &self,
+        collection: &str,
+        changed_fields: &HashSet<String>,
+    ) -> Vec<&str> {
+        self.views
+            .iter()
+            .filter(|(_, v)| {
+                v.referenced_collections().contains(collection)
+                    && v.references_any_field(changed_fields)
+            })
+            .map(|(name, _)| name.as_str())
+            .collect()
+    }
+
     /// Load cached view data from the system database
     pub fn load_from_db(&self, db: &SystemDb) -> Result<()> {
         let mut cache = self.view_data.lock().unwrap();
         for name in self.views.keys() {
             if let Some(json_str) = db.get_view_data(name)? {
                 let data: Vec<serde_json::Value> = serde_json::from_str(&json_str)?;
-                cache.insert(name.clone(), data);
+                cache.insert(name.clone(), Arc::new(data));
             }
         }
         Ok(())
@@ -107,7 +179,7 @@ impl ViewEngine {
     pub fn save_to_db(&self, db: &SystemDb) -> Result<()> {
         let cache = self.view_data.lock().unwrap();
         for (name, data) in cache.iter() {
-            let json_str = serde_json::to_string(data)?;
+            let json_str = serde_json::to_string(data.as_slice())?;
             db.set_view_data(name, &json_str)?;
         }
         Ok(())
@@ -115,6 +187,12 @@ impl ViewEngine {
 
     /// Get a clone of the current data for a static view
     pub fn get_view_data(&self, name: &str) -> Option<Vec<serde_json::Value>> {
+        self.view_rows(name).map(|rows| rows.to_vec())
+    }
+
+    /// The current rows of a static view, shared rather than cloned. A
+    /// rebuild replaces the cached rows, leaving earlier handles unchanged.
+    pub fn view_rows(&self, name: &str) -> Option<Arc<Vec<serde_json::Value>>> {
         let cache = self.view_data.lock().unwrap();
         cache.get(name).cloned()
     }
@@ -122,7 +200,22 @@ impl ViewEngine {
     /// Update the cached data for a view
     pub fn set_view_data(&self, name: &str, data: Vec<serde_json::Value>) {
         let mut cache = self.view_data.lock().unwrap();
-        cache.insert(name.to_string(), data);
+        cache.insert(name.to_string(), Arc::new(data));
+    }
+
+    /// Record a completed rebuild of a view
+    pub fn record_build(&self, name: &str, duration: Duration, row_count: usize) {
+        let mut stats = self.stats.lock().unwrap();
+        let entry = stats.entry(name.to_string()).or_default();
+        entry.build_count += 1;
+        entry.last_build_duration = duration;
+        entry.last_row_count = row_count;
+    }
+
+    /// Get the build counters for a view (default/zero if it was never built)
+    pub fn view_stats(&self, name: &str) -> ViewStats {
+        let stats = self.stats.lock().unwrap();
+        stats.get(name).cloned().unwrap_or_default()
     }
 
     /// Materialize a single view to the views/ directory as a YAML file.
@@ -138,11 +231,18 @@ impl ViewEngine {
             std::fs::create_dir_all(&views_dir)?;
             let output_path = views_dir.join(format!("{view_name}.yaml"));
 
+            // Without an ORDER BY, SQLite's row order can change between
+            // rebuilds; sort so identical data always yields an identical file
+            let mut rows: Vec<&serde_json::Value> = data.iter().collect();
+            if !parsed.has_order_by {
+                rows.sort_by(|a, b| compare_rows(a, b));
+            }
+
             // Apply limit for materialized output (buffer has more data)
             let limited_data: Vec<&serde_json::Value> = if let Some(limit) = parsed.limit {
-                data.iter().take(limit as usize).collect()
+                rows.into_iter().take(limit as usize).collect()
             } else {
-                data.iter().collect()
+                rows
             };
 
             let yaml = serde_yaml::to_string(&limited_data)?;
@@ -152,6 +252,37 @@ impl ViewEngine {
         Ok(())
     }
 
+    /// Delete files in the views/ directory that don't belong to a current
+    /// materialized view (e.g. left behind after a view was removed from the
+    /// schema or stopped materializing). Returns the removed paths.
+    pub fn prune_materialized(&self, root: &Path) -> Result<Vec<PathBuf>> {
+        let views_dir = root.join("views");
+        let mut removed = Vec::new();
+        if !views_dir.exists() {
+            return Ok(removed);
+        }
+
+        for entry in std::fs::read_dir(&views_dir)? {
+            let path = entry?.path();
+            if !path.is_file() || path.extension().and_then(|e| e.to_str()) != Some("yaml") {
+                continue;
+            }
+            let live = path
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .and_then(|name| self.views.get(name))
+                .map(|v| v.materialize)
+                .unwrap_or(false);
+            if !live {
+                std::fs::remove_file(&path)?;
+                removed.push(path);
+            }
+        }
+
+        removed.sort();
+        Ok(removed)
+    }
+
     /// Materialize all materialized views to the views/ directory as YAML files.
     pub fn materialize_views(&self, root: &Path) -> Result<()> {
         let view_names: Vec<String> = self.views.keys().cloned().collect();
@@ -162,6 +293,48 @@ impl ViewEngine {
     }
 }
 
+/// Stable order for view rows: by `id` when present, then by every column.
+fn compare_rows(a: &serde_json::Value, b: &serde_json::Value) -> Ordering {
+    compare_json(&a["id"], &b["id"]).then_with(|| compare_json(a, b))
+}
+
+/// Total order over JSON values: null < bool < number < string < array <
+/// object, comparing like values by content (objects key by key).
+fn compare_json(a: &serde_json::Value, b: &serde_json::Value) -> Ordering {
+    use serde_json::Value;
+    fn rank(v: &Value) -> u8 {
+        match v {
+            Value::Null => 0,
+            Value::Bool(_) => 1,
+            Value::Number(_) => 2,
+            Value::String(_) => 3,
+            Value::Array(_) => 4,
+            Value::Object(_) => 5,
+        }
+    }
+    match (a, b) {
+        (Value::Bool(x), Value::Bool(y)) => x.cmp(y),
+        (Value::Number(x), Value::Number(y)) => {
+            let (x, y) = (x.as_f64().unwrap_or(0.0), y.as_f64().unwrap_or(0.0));
+            x.total_cmp(&y)
+        }
+        (Value::String(x), Value::String(y)) => x.cmp(y),
+        (Value::Array(x), Value::Array(y)) => x
+            .iter()
+            .zip(y)
+            .map(|(x, y)| compare_json(x, y))
+            .find(|o| o.is_ne())
+            .unwrap_or_else(|| x.len().cmp(&y.len())),
+        (Value::Object(x), Value::Object(y)) => x
+            .iter()
+            .zip(y)
+            .map(|((xk, xv), (yk, yv))| xk.cmp(yk).then_with(|| compare_json(xv, yv)))
+            .find(|o| o.is_ne())
+            .unwrap_or_else(|| x.len().cmp(&y.len())),
+        _ => rank(a).cmp(&rank(b)),
+    }
+}
+
 /// Rewritten SQL query ready for execution against the documents table.
 #[derive(Debug, Clone)]
 pub struct RewrittenQuery {
@@ -185,9 +358,14 @@ pub fn rewrite_view_sql(
     schema: &SchemaDefinition,
 ) -> Result<RewrittenQuery> {
     let mut cte_parts = Vec::new();
+    let mut seen = HashSet::new();
 
     for table_ref in &parsed.table_refs {
         let collection_name = &table_ref.collection;
+        // A collection read twice (self-join, both sides of a UNION) needs one CTE
+        if !seen.insert(collection_name) {
+            continue;
+        }
         let col_def = schema.collections.get(collection_name);
         if col_def.is_none() {
             return Err(GroundDbError::SqlParse(format!(
@@ -198,28 +376,62 @@ pub fn rewrite_view_sql(
         let col_def = col_def.unwrap();
 
         // Build SELECT columns for this CTE
-        let mut cte_columns = Vec::new();
-
-        // Implicit fields: id, created_at, modified_at are direct columns
-        cte_columns.push("id".to_string());
-        cte_columns.push("created_at".to_string());
-        cte_columns.push("modified_at".to_string());
+        // Implicit fields: id, created_at, modified_at, content_length are direct columns
+        let mut cte_columns: Vec<String> = ["id", "created_at", "modified_at", "content_length"]
+            .iter()
+            .map(|c| c.to_string())
+            .collect();
 
         // If collection has content: true, expose content_text as "content"
-        if col_def.content {
+        if col_def.has_content() {
             cte_columns.push("content_text AS content".to_string());
         }
 
-        // Schema-defined fields extracted via json_extract
-        for (field_name, _field_def) in &col_def.fields {
+        // Fields derived from the body live in derived_json
+        if let Some(extract) = &col_def.extract_content {
+            if extract.title_from_h1 {
+                cte_columns.push("json_extract(derived_json, '$._title') AS _title".to_string());
+            }
+            if extract.links {
+                cte_columns.push("json_extract(derived_json, '$._links') AS _links".to_string());
+            }
+        }
+
+        // Indexed enum ordinals (`enum_ordinals: true`) also live in derived_json
+        for (field_name, _) in col_def.ordinal_fields() {
             cte_columns.push(format!(
-                "json_extract(data_json, '$.{field_name}') AS {field_name}"
+                "{} AS {}",
+                ordinal_sql(field_name),
+                column_name(&format!("{field_name}_ordinal"))
             ));
         }
 
+        // Schema-defined fields extracted via json_extract. List fields are
+        // only passed through when they hold an array, so user SQL can always
+        // hand them to `json_each()`.
+        // Fields are sorted so the same schema always produces the same SQL
+        let data = if col_def.compress { DATA_JSON } else { "data_json" };
+        let mut fields: Vec<_> = col_def.fields.iter().collect();
+        fields.sort_by_key(|(name, _)| *name);
+        for (field_name, field_def) in fields {
+            let path = json_key_path(field_name);
+            let column = column_name(field_name);
+            let extract = format!("json_extract({data}, '{path}')");
+            cte_columns.push(if field_def.field_type == FieldType::List {
+                format!(
+                    "CASE json_type({data}, '{path}') WHEN 'array' THEN {extract} END AS {column}"
+                )
+            } else {
+                format!("{extract} AS {column}")
+            });
+        }
+
         let columns_sql = cte_columns.join(",\n      ");
+        // Schema parsing only admits identifier names, but never let one
+        // break out of the string literal
+        let collection_literal = collection_name.replace('\'', "''");
         let cte = format!(
-            "{collection_name} AS (\n    SELECT\n      {columns_sql}\n    FROM documents\n    WHERE collection = '{collection_name}'\n  )"
+            "{collection_name} AS (\n    SELECT\n      {columns_sql}\n    FROM documents\n    WHERE collection = '{collection_literal}'\n  )"
         );
         cte_parts.push(cte);
     }
@@ -252,6 +464,73 @@ pub fn rewrite_view_sql(
     })
 }
 
+/// A field name as a CTE column: bare when it's a plain identifier, otherwise
+/// double-quoted (`"published-at"`).
+fn column_name(field: &str) -> String {
+    if is_identifier(field) {
+        field.to_string()
+    } else {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    }
+}
+
+/// The JSON path to a top-level key, for use inside a SQL string literal:
+/// `$.title`, or `$."published-at"` for keys that aren't plain identifiers.
+fn json_key_path(key: &str) -> String {
+    if is_identifier(key) {
+        format!("$.{key}")
+    } else {
+        format!("$.\"{}\"", key.replace('\'', "''"))
+    }
+}
+
+/// The SQL reading an enum field's indexed ordinal from `derived_json`. Views
+/// select it with exactly the expression the ordinal's index is built on, so
+/// SQLite can use the index for filters and sorts on `<field>_ordinal`.
+pub(crate) fn ordinal_sql(field_name: &str) -> String {
+    format!(
+        "json_extract(derived_json, '{}')",
+        json_key_path(&format!("{field_name}_ordinal"))
+    )
+}
+
+/// Check one view against the schema without executing it: its SQL parses,
+/// the columns it selects exist, and every table it reads is a collection.
+pub fn check_view(name: &str, view_def: &ViewDefinition, schema: &SchemaDefinition) -> Result<()> {
+    let parsed = parse_view_query(name, view_def)?;
+    validate_view_columns(&parsed, schema)?;
+    rewrite_view_sql(&parsed, schema).map(|_| ())
+}
+
+/// The result column names of a view, in SELECT order.
+pub(crate) fn view_columns(name: &str, view_def: &ViewDefinition) -> Result<Vec<String>> {
+    let parsed = parse_view_query(name, view_def)?;
+    Ok(parsed.columns.into_iter().map(|c| c.name).collect())
+}
+
+/// Result column names that more than one SELECT item produces, such as
+/// unaliased `p.id` and `u.id` in a join, each with the expressions that
+/// produce it in SELECT order.
+pub(crate) fn duplicate_columns(
+    name: &str,
+    view_def: &ViewDefinition,
+) -> Result<Vec<(String, Vec<String>)>> {
+    let parsed = parse_view_query(name, view_def)?;
+    let mut by_name: Vec<(String, Vec<String>)> = Vec::new();
+    for column in parsed.columns.iter().filter(|c| c.name != "*") {
+        let source = match (&column.source_collection, &column.source_field) {
+            (Some(qualifier), Some(field)) => format!("{qualifier}.{field}"),
+            _ => column.name.clone(),
+        };
+        match by_name.iter_mut().find(|(n, _)| *n == column.name) {
+            Some((_, sources)) => sources.push(source),
+            None => by_name.push((column.name.clone(), vec![source])),
+        }
+    }
+    by_name.retain(|(_, sources)| sources.len() > 1);
+    Ok(by_name)
+}
+
 /// Parse a SQL view query to extract metadata (referenced collections, columns, etc.)
 fn parse_view_query(name: &str, view_def: &ViewDefinition) -> Result<ParsedView> {
     // Replace :param placeholders with NULL for parsing purposes
@@ -272,9 +551,27 @@ fn parse_view_query(name: &str, view_def: &ViewDefinition) -> Result<ParsedView>
     let mut table_refs = Vec::new();
     let mut columns = Vec::new();
     let mut limit = None;
+    let mut has_order_by = false;
 
     if let Statement::Query(query) = stmt {
         extract_from_query(query, &mut table_refs, &mut columns, &mut limit);
+        has_order_by = !query.order_by.is_empty();
+    }
+
+    // Collect identifiers for field-level change tracking
+    let mut identifiers = HashSet::new();
+    let mut uses_wildcard = false;
+    let tokens = Tokenizer::new(&dialect, &clean_sql)
+        .tokenize()
+        .map_err(|e| GroundDbError::SqlParse(format!("View '{name}': {e}")))?;
+    for token in tokens {
+        match token {
+            Token::Word(word) => {
+                identifiers.insert(word.value.to_lowercase());
+            }
+            Token::Mul => uses_wildcard = true,
+            _ => {}
+        }
     }
 
     // Parse buffer multiplier
@@ -301,13 +598,54 @@ fn parse_view_query(name: &str, view_def: &ViewDefinition) -> Result<ParsedView>
         table_refs,
         columns,
         limit,
+        has_order_by,
         buffer_multiplier,
         materialize: view_def.materialize,
         is_query_template,
         param_names,
+        identifiers,
+        uses_wildcard,
     })
 }
 
+/// Check that every qualified column a view selects (`alias.field`) exists in the
+/// CTE generated for its collection: a declared field or an implicit column.
+/// Catches typos at open instead of as an opaque SQLite error at execution.
+fn validate_view_columns(parsed: &ParsedView, schema: &SchemaDefinition) -> Result<()> {
+    for column in &parsed.columns {
+        let (Some(qualifier), Some(field)) = (&column.source_collection, &column.source_field)
+        else {
+            continue;
+        };
+        // Qualifiers that aren't a FROM/JOIN collection (e.g. subqueries) are left to SQLite
+        let Some(table_ref) = parsed.table_refs.iter().find(|t| {
+            t.alias.as_deref() == Some(qualifier.as_str())
+                || (t.alias.is_none() && t.collection == *qualifier)
+        }) else {
+            continue;
+        };
+        let Some(col_def) = schema.collections.get(&table_ref.collection) else {
+            continue;
+        };
+
+        let implicit = matches!(field.as_str(), "id" | "created_at" | "modified_at" | "content_length")
+            || (field == "content" && col_def.has_content())
+            || col_def.extract_content.as_ref().is_some_and(|e| {
+                (field == "_title" && e.title_from_h1) || (field == "_links" && e.links)
+            })
+            || field.strip_suffix("_ordinal").is_some_and(|enum_field| {
+                col_def.ordinal_fields().iter().any(|(name, _)| *name == enum_field)
+            });
+        if !implicit && !col_def.fields.contains_key(field) {
+            return Err(GroundDbError::SqlParse(format!(
+                "View '{}': column '{qualifier}.{field}' is not a field of collection '{}'",
+                parsed.name, table_ref.collection
+            )));
+        }
+    }
+    Ok(())
+}
+
 /// Replace :param placeholders in SQL with NULL for parsing
 fn replace_params(sql: &str) -> String {
     let mut result = String::new();
@@ -344,9 +682,7 @@ fn extract_from_query(
     columns: &mut Vec<ViewColumn>,
     limit: &mut Option<u64>,
 ) {
-    if let SetExpr::Select(select) = query.body.as_ref() {
-        extract_from_select(select, table_refs, columns);
-    }
+    extract_from_set_expr(&query.body, table_refs, columns);
 
     // Extract LIMIT
     if let Some(expr) = &query.limit {
@@ -358,6 +694,40 @@ fn extract_from_query(
     }
 }
 
+/// Extract metadata from a query body. For a set operation (`UNION ALL` etc.)
+/// tables come from every branch, but result columns are named by the first
+/// SELECT, as in SQL. Parenthesized queries are unwrapped.
+fn extract_from_set_expr(
+    body: &SetExpr,
+    table_refs: &mut Vec<TableRef>,
+    columns: &mut Vec<ViewColumn>,
+) {
+    match body {
+        SetExpr::Select(select) => extract_from_select(select, table_refs, columns),
+        SetExpr::SetOperation { left, right, .. } => {
+            extract_from_set_expr(left, table_refs, columns);
+            extract_from_set_expr(right, table_refs, &mut Vec::new());
+        }
+        SetExpr::Query(query) => extract_from_set_expr(&query.body, table_refs, columns),
+        // `TABLE posts` is shorthand for `SELECT * FROM posts`
+        SetExpr::Table(table) => {
+            if let Some(name) = &table.table_name {
+                table_refs.push(TableRef {
+                    collection: name.clone(),
+                    alias: None,
+                });
+                columns.push(ViewColumn {
+                    name: "*".to_string(),
+                    source_collection: None,
+                    source_field: None,
+                });
+            }
+        }
+        // VALUES reads no collections; INSERT/UPDATE aren't valid view bodies
+        SetExpr::Values(_) | SetExpr::Insert(_) | SetExpr::Update(_) => {}
+    }
+}
+
 /// Extract metadata from a SELECT clause
 fn extract_from_select(
     select: &Select,
@@ -412,20 +782,32 @@ fn extract_from_table_with_joins(
     }
 }
 
-/// Extract a table name and alias from a table factor
+/// Extract a table name and alias from a table factor. Collections read by
+/// subqueries in FROM (`FROM (SELECT ...) x`) count as referenced too.
 fn extract_table_name(
     factor: &TableFactor,
     table_refs: &mut Vec<TableRef>,
 ) {
-    if let TableFactor::Table { name, alias, .. } = factor {
-        let table_name = name.0.last().map(|i| i.value.clone()).unwrap_or_default();
-        if !table_name.is_empty() {
-            let alias_name = alias.as_ref().map(|a| a.name.value.clone());
-            table_refs.push(TableRef {
-                collection: table_name,
-                alias: alias_name,
-            });
+    match factor {
+        // Table-valued functions such as `json_each(p.tags)` read no collection
+        TableFactor::Table { args: Some(_), .. } => {}
+        TableFactor::Table { name, alias, .. } => {
+            let table_name = name.0.last().map(|i| i.value.clone()).unwrap_or_default();
+            if !table_name.is_empty() {
+                let alias_name = alias.as_ref().map(|a| a.name.value.clone());
+                table_refs.push(TableRef {
+                    collection: table_name,
+                    alias: alias_name,
+                });
+            }
+        }
+        TableFactor::Derived { subquery, .. } => {
+            extract_from_set_expr(&subquery.body, table_refs, &mut Vec::new());
+        }
+        TableFactor::NestedJoin { table_with_joins, .. } => {
+            extract_from_table_with_joins(table_with_joins, table_refs);
         }
+        _ => {}
     }
 }
 
@@ -528,6 +910,7 @@ views:
         assert!(feed_collections.contains("posts"));
         assert!(feed_collections.contains("users"));
         assert_eq!(feed.limit, Some(100));
+        assert!(feed.has_order_by);
         assert_eq!(feed.buffer_multiplier, 2.0);
         assert!(feed.materialize);
         assert!(!feed.is_query_template);
@@ -545,6 +928,7 @@ views:
         assert_eq!(lookup_collections.len(), 1);
         assert!(lookup.materialize);
         assert_eq!(lookup.limit, None);
+        assert!(lookup.has_order_by);
     }
 
     #[test]
@@ -672,6 +1056,83 @@ views:
         assert!(rewritten.param_names.contains(&"post_id".to_string()));
     }
 
+    #[test]
+    fn test_affected_views_for_fields() {
+        let schema = test_schema();
+        let engine = ViewEngine::new(&schema).unwrap();
+
+        let changed: HashSet<String> = ["email".to_string()].into();
+        let mut affected = engine.affected_views_for_fields("users", &changed);
+        affected.sort();
+        assert!(affected.contains(&"user_lookup"));
+        assert!(!affected.contains(&"post_feed"));
+
+        let changed: HashSet<String> = ["tags".to_string()].into();
+        assert!(!engine.affected_views_for_fields("posts", &changed).contains(&"post_feed"));
+        let changed: HashSet<String> = ["status".to_string()].into();
+        assert!(engine.affected_views_for_fields("posts", &changed).contains(&"post_feed"));
+    }
+
+    #[test]
+    fn test_union_view_discovers_both_collections() {
+        let schema = test_schema();
+        let view_def: ViewDefinition = serde_yaml::from_str(
+            r#"
+query: |
+  SELECT id, title AS label FROM posts
+  UNION
+  (SELECT u.id, u.name FROM users u)
+  UNION
+  SELECT x.id, x.id FROM (SELECT id FROM posts) x
+"#,
+        )
+        .unwrap();
+        let parsed = parse_view_query("labels", &view_def).unwrap();
+
+        assert_eq!(
+            parsed.referenced_collections(),
+            HashSet::from(["posts".to_string(), "users".to_string()])
+        );
+        // Result columns come from the first SELECT
+        let names: Vec<&str> = parsed.columns.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, ["id", "label"]);
+
+        let rewritten = rewrite_view_sql(&parsed, &schema).unwrap();
+        assert!(rewritten.sql.contains("posts AS ("));
+        assert!(rewritten.sql.contains("users AS ("));
+        assert_eq!(rewritten.sql.matches("posts AS (").count(), 1);
+    }
+
+    #[test]
+    fn test_json_each_join_reads_only_the_collection() {
+        let schema = parse_schema_str(
+            r#"
+collections:
+  posts:
+    path: "posts/{title}.md"
+    fields:
+      title: { type: string, required: true }
+      tags: { type: list, items: string }
+"#,
+        )
+        .unwrap();
+        let view_def: ViewDefinition = serde_yaml::from_str(
+            r#"
+query: |
+  SELECT p.title FROM posts p, json_each(p.tags) t WHERE t.value = 'rust'
+"#,
+        )
+        .unwrap();
+        let parsed = parse_view_query("rust_posts", &view_def).unwrap();
+        assert_eq!(parsed.referenced_collections(), HashSet::from(["posts".to_string()]));
+
+        let rewritten = rewrite_view_sql(&parsed, &schema).unwrap();
+        assert!(rewritten.sql.contains(
+            "CASE json_type(data_json, '$.tags') WHEN 'array' THEN json_extract(data_json, '$.tags') END AS tags"
+        ));
+        assert!(rewritten.sql.contains("json_extract(data_json, '$.title') AS title"));
+    }
+
     #[test]
     fn test_rewrite_unknown_collection_errors() {
         let schema = test_schema();
@@ -685,10 +1146,13 @@ views:
             }],
             columns: vec![],
             limit: None,
+            has_order_by: false,
             buffer_multiplier: 1.0,
             materialize: false,
             is_query_template: false,
             param_names: vec![],
+            identifiers: HashSet::new(),
+            uses_wildcard: true,
         };
 
         let result = rewrite_view_sql(&parsed, &schema);